@@ -1,4 +1,5 @@
 # Copyright(C) Facebook, Inc. and its affiliates.
+import secrets
 from os.path import join
 
 from benchmark.utils import PathMaker
@@ -30,20 +31,27 @@ class CommandMaker:
         return f'./genconfig --blocksize 100 --delay 100 --base_port {bport} --client_base_port {client_bport} --NumNodes {num_nodes} --target . --client_run_port {client_run_port} --local true'
 
     @staticmethod
-    def run_primary(key, protocol,batches,per, lin, opt, ibft,debug=False):
+    def run_primary(key, protocol,batches,per, lin, opt, ibft, session, fft='false',debug=False):
         assert isinstance(key, str)
+        assert isinstance(session, int)
         assert isinstance(debug, bool)
         #v = '-vvv' if debug else '-vv'
         return (f'ulimit -n 1000000; ./node --config {key} --ip ip_file '
-                f'--protocol {protocol} --syncer syncer --batches {batches} --per {per} --lin {lin} --opt {opt} --ibft {ibft}')
+                f'--protocol {protocol} --syncer syncer --batches {batches} --per {per} --lin {lin} --opt {opt} --ibft {ibft} --fft {fft} --session {session}')
     
     @staticmethod
-    def run_syncer(key,batches,per, lin, opt, ibft,debug=False):
+    def run_syncer(key,batches,per, lin, opt, ibft, session, fft='false',debug=False):
         assert isinstance(key, str)
+        assert isinstance(session, int)
         assert isinstance(debug, bool)
         #v = '-vvv' if debug else '-vv'
         return (f'ulimit -n 1000000; ./node --config {key} --ip ip_file '
-                f'--protocol sync --syncer syncer --batches {batches} --per {per} --lin {lin} --opt {opt} --ibft {ibft}')
+                f'--protocol sync --syncer syncer --batches {batches} --per {per} --lin {lin} --opt {opt} --ibft {ibft} --fft {fft} --session {session}')
+
+    @staticmethod
+    def fresh_session():
+        # Nonce of one run, shared by all nodes and the syncer so that messages of earlier runs are rejected
+        return secrets.randbits(63)
 
     @staticmethod
     def unzip_tkeys(fileloc, debug=False):
@@ -212,6 +212,7 @@ class BenchParameters:
             self.linear = str(json['linear']) if 'linear' in json else 'true'
             self.optimistic = str(json['optimistic']) if 'optimistic' in json else 'false'
             self.ACS_enabled = str(json['ACS_enabled']) if 'ACS_enabled' in json else 'true'
+            self.fft = str(json['fft']) if 'fft' in json else 'false'
 
             if 'collocate' in json:
                 self.collocate = bool(json['collocate'])
@@ -108,9 +108,11 @@ class LocalBench:
             #         self._background_run(cmd, log_file)
 
             # # Run the primaries (except the faulty ones).
+            session = CommandMaker.fresh_session()
             for i in range(nodes):
                 cmd = CommandMaker.run_primary(
                     PathMaker.key_file(i),
+                    session=session,
                     debug=debug
                 )
                 log_file = PathMaker.primary_log_file(i)
@@ -251,9 +251,11 @@ class Bench:
         lin = bench_parameters.linear
         opt= bench_parameters.optimistic
         ibft = bench_parameters.ACS_enabled
+        fft = bench_parameters.fft
 
         protocol = bench_parameters.protocol
 
+        session = CommandMaker.fresh_session()
         for i,ip in enumerate(hosts):
             #host = Committee.ip(address)
             if i == 0:
@@ -265,7 +267,9 @@ class Bench:
                     per_batch,
                     lin=lin,
                     opt=opt,
-                    ibft=ibft
+                    ibft=ibft,
+                    session=session,
+                    fft=fft
                 )
                 print(cmd)
                 log_file = PathMaker.syncer_log_file()
@@ -277,7 +281,9 @@ class Bench:
                 per_batch,
                 lin=lin,
                 opt=opt,
-                ibft=ibft
+                ibft=ibft,
+                session=session,
+                fft=fft
             )
             unzip_cmd = CommandMaker.unzip_tkeys('data.tar.gz')
             print(unzip_cmd)
@@ -369,7 +375,9 @@ class Bench:
         lin = bench_parameters.linear
         opt= bench_parameters.optimistic
         ibft = bench_parameters.ACS_enabled
+        fft = bench_parameters.fft
 
+        session = CommandMaker.fresh_session()
         for i,ip in enumerate(hosts):
             #host = Committee.ip(address)
             if i == 0:
@@ -381,7 +389,9 @@ class Bench:
                     per_batch,
                     lin=lin,
                     opt=opt,
-                    ibft=ibft
+                    ibft=ibft,
+                    session=session,
+                    fft=fft
                 )
                 print(cmd)
                 log_file = PathMaker.syncer_log_file()
@@ -393,7 +403,9 @@ class Bench:
                 per_batch,
                 lin=lin,
                 opt=opt,
-                ibft=ibft
+                ibft=ibft,
+                session=session,
+                fft=fft
             )
             log_file = PathMaker.primary_log_file(i)
             self._background_run(ip, cmd, log_file)
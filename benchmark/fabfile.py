@@ -16,6 +16,8 @@ num_secrets = 2300
 linear = 'true'
 optimistic = 'false'
 ACS_enabled = 'true'
+# Share at roots of unity with FFTs instead of the Vandermonde path (dpss only)
+fft = 'false'
 
 @task
 def local(ctx, debug=True):
@@ -138,6 +140,7 @@ def remote(ctx, debug=False):
         'linear': linear,
         'optimistic': optimistic,
         'ACS_enabled': ACS_enabled,
+        'fft': fft,
         'protocol': protocol,
         'workers': 1,
         'collocate': True,
@@ -170,6 +173,7 @@ def rerun(ctx, debug=False):
         'linear': linear,
         'optimistic': optimistic,
         'ACS_enabled': ACS_enabled,
+        'fft': fft,
         'protocol': protocol,
         'workers': 1,
         'collocate': True,
@@ -230,6 +234,7 @@ def logs(ctx):
         'linear': linear,
         'optimistic': optimistic,
         'ACS_enabled': ACS_enabled,
+        'fft': fft,
         'workers': 1,
         'collocate': True,
         'rate': [10_000, 110_000],
@@ -11,21 +11,23 @@ use fnv::FnvHashMap;
 use network::{plaintcp::{CancelHandler, TcpReceiver, TcpReliableSender}, Acknowledgement, Message};
 //use signal_hook::{iterator::Signals, consts::{SIGINT, SIGTERM}};
 use tokio::{sync::{
-    mpsc::{UnboundedReceiver, Sender, Receiver, channel, unbounded_channel},
+    mpsc::{Sender, Receiver},
     oneshot,
 }};
 // use tokio_util::time::DelayQueue;
 use types::{Replica, WrapperMsg};
 
-use consensus::{LargeFieldSSS, LargeFieldSer};
-use crypto::{aes_hash::HashState, LargeField, hash::Hash};
+use consensus::{LargeFieldSSS, LargeFieldSer, InstanceGC, gc_retention, ingress_channel, ingress_per_sender_capacity, service_channel, IngressReceiver, SessionMsg, SessionContext, session_id, RBCService, RBCBackend, ReliableBroadcast, rbc_backend, LargeField};
+use crypto::{aes_hash::HashState, hash::Hash};
 
-use crate::{msg::ProtMsg, Handler, protocol::ACSState};
+use asks::{ASKSOutput, ASKSService};
+
+use crate::{msg::{ProtMsg, RBCId}, Handler, protocol::{ACSState, split_vaba_instance_id}};
 
 pub struct Context {
     /// Networking context
-    pub net_send: TcpReliableSender<Replica, WrapperMsg<ProtMsg>, Acknowledgement>,
-    pub net_recv: UnboundedReceiver<WrapperMsg<ProtMsg>>,
+    pub net_send: TcpReliableSender<Replica, WrapperMsg<SessionMsg<ProtMsg>>, Acknowledgement>,
+    pub net_recv: IngressReceiver<WrapperMsg<SessionMsg<ProtMsg>>>,
     //pub sync_send: TcpReliableSender<Replica, SyncMsg, Acknowledgement>,
     //pub sync_recv: UnboundedReceiver<SyncMsg>,
     /// Data context
@@ -37,6 +39,8 @@ pub struct Context {
     pub large_field_shamir_ss: LargeFieldSSS,
     /// Secret Key map
     pub sec_key_map: HashMap<Replica, Vec<u8>>,
+    /// Session binding and replay protection
+    pub session: SessionContext,
 
     /// Hardware acceleration context
     pub hash_context: HashState,
@@ -56,8 +60,12 @@ pub struct Context {
     /// Constants for PRF seeding
     pub nonce_seed: usize,
 
-    ///// State for GatherState and ACS
-    pub acs_state: ACSState,
+    ///// State for GatherState and ACS, per ACS session
+    pub acs_states: HashMap<usize, ACSState>,
+    /// Lifecycle of terminated ACS sessions, pruned after the retention window
+    pub gc: InstanceGC<usize>,
+    /// Highest session started by a local input, peers can only open sessions up to `MAX_SESSIONS_AHEAD` beyond it
+    pub highest_local_session: usize,
     
     pub acss_map: HashMap<Replica, 
         HashMap<usize, 
@@ -67,7 +75,6 @@ pub struct Context {
         >,
 
     pub completed_batches: HashMap<Replica, HashSet<usize>>,
-    pub acs_input_set: HashSet<Replica>,
     /// Channels to interact with other services
 
     //pub acss_req: Sender<(usize, Vec<LargeFieldSer>)>,
@@ -76,11 +83,9 @@ pub struct Context {
     pub event_recv_channel: Receiver<(usize,usize, Vec<LargeFieldSer>)>,
     pub acs_out_channel: Sender<(usize,Vec<usize>)>,
 
-    pub asks_req: Sender<(usize, usize, bool, bool, Option<Vec<LargeField>>,Option<usize>)>,
-    pub asks_out_recv: Receiver<(usize, Replica, Option<Vec<LargeField>>)>,
+    pub asks: ASKSService,
 
-    pub ctrbc_req: Sender<Vec<u8>>,
-    pub ctrbc_out_recv: Receiver<(usize, usize, Vec<u8>)>,
+    pub rbc: RBCService<RBCId>,
 
     pub ra_req_send: Sender<(usize, usize, usize)>,
     pub ra_out_recv: Receiver<(usize, Replica, usize)>
@@ -135,8 +140,8 @@ impl Context {
         syncer_map.insert(0, config.client_addr);
 
         // Setup networking
-        let (tx_net_to_consensus, rx_net_to_consensus) = unbounded_channel();
-        TcpReceiver::<Acknowledgement, WrapperMsg<ProtMsg>, _>::spawn(
+        let (tx_net_to_consensus, rx_net_to_consensus) = ingress_channel(ingress_per_sender_capacity(), config.sk_map.clone());
+        TcpReceiver::<Acknowledgement, WrapperMsg<SessionMsg<ProtMsg>>, _>::spawn(
             my_address,
             Handler::new(tx_net_to_consensus),
         );
@@ -151,7 +156,7 @@ impl Context {
         //    SyncHandler::new(tx_net_to_client),
         //);
 
-        let consensus_net = TcpReliableSender::<Replica, WrapperMsg<ProtMsg>, Acknowledgement>::with_peers(
+        let consensus_net = TcpReliableSender::<Replica, WrapperMsg<SessionMsg<ProtMsg>>, Acknowledgement>::with_peers(
             consensus_addrs.clone(),
         );
         //let sync_net =
@@ -175,14 +180,18 @@ impl Context {
         //let (acss_req_send_channel, acss_req_recv_channel) = channel(10000);
         //let (acss_out_send_channel, acss_out_recv_channel) = channel(10000);
         // Prepare RBC config
-        let (ctrbc_req_send_channel, ctrbc_req_recv_channel) = channel(10000);
-        let (ctrbc_out_send_channel, ctrbc_out_recv_channel) = channel(10000);
+        let (rbc, _rbc_serv_status) = RBCService::spawn(
+            rbc_backend(RBCBackend::CTRBC),
+            rbc_config,
+            false
+        );
         
-        let (ra_req_send_channel, ra_req_recv_channel) = channel(10000);
-        let (ra_out_send_channel, ra_out_recv_channel) = channel(10000);
+        let (ra_req_send_channel, ra_req_recv_channel) = service_channel();
+        let (ra_out_send_channel, ra_out_recv_channel) = service_channel();
         
-        let (asks_req_send_channel, asks_req_recv_channel) = channel(10000);
-        let (asks_out_send_channel, asks_out_recv_channel) = channel(10000);
+        let (asks, _asks_serv_status) = ASKSService::spawn(asks_config, false);
+
+        let session = SessionContext::new(session_id(), "acs", config.id, &consensus_addrs);
 
         tokio::spawn(async move {
             let mut c = Context {
@@ -192,6 +201,7 @@ impl Context {
                 //sync_recv: rx_net_from_client,
                 num_nodes: config.num_nodes,
                 sec_key_map: HashMap::default(),
+                session: session,
                 hash_context: hashstate,
                 myid: config.id,
                 _byz: byz,
@@ -205,7 +215,9 @@ impl Context {
                 threshold: 10000,
 
                 max_id: rbc_start_id, 
-                acs_state: ACSState::new(),
+                acs_states: HashMap::default(),
+                gc: InstanceGC::new(gc_retention()),
+                highest_local_session: 0,
 
                 //num_batches: num_batches,
                 //per_batch: per_batch, 
@@ -213,8 +225,6 @@ impl Context {
                 acss_map: HashMap::default(),
                 completed_batches: HashMap::default(),
 
-                acs_input_set: HashSet::default(),
-
                 nonce_seed: 1,
 
                 //acss_req: acss_req_send_channel,
@@ -222,11 +232,9 @@ impl Context {
                 event_recv_channel: term_event_channel,
                 acs_out_channel: acs_out_channel,
 
-                ctrbc_req: ctrbc_req_send_channel,
-                ctrbc_out_recv: ctrbc_out_recv_channel,
+                rbc: rbc,
 
-                asks_req: asks_req_send_channel,
-                asks_out_recv: asks_out_recv_channel,
+                asks: asks,
 
                 ra_req_send: ra_req_send_channel,
                 ra_out_recv: ra_out_recv_channel
@@ -262,22 +270,8 @@ impl Context {
         // This is so that the inner contexts are not dropped by the compiler
         let mut statuses = Vec::new();
 
-        let _rbc_serv_status = ctrbc::Context::spawn(
-            rbc_config,
-            ctrbc_req_recv_channel, 
-            ctrbc_out_send_channel, 
-            false
-        );
-
         statuses.push(_rbc_serv_status);
 
-        let _asks_serv_status = asks::Context::spawn(
-            asks_config, 
-            asks_req_recv_channel, 
-            asks_out_send_channel,
-            false
-        );
-
         statuses.push(_asks_serv_status);
 
         let _ra_serv_status = ra::Context::spawn(
@@ -300,7 +294,7 @@ impl Context {
         let mut total_bytes = 0;
         let sec_key_map = self.sec_key_map.clone();
         for (replica, sec_key) in sec_key_map.into_iter() {
-            let wrapper_msg = WrapperMsg::new(protmsg.clone(), self.myid, &sec_key.as_slice());
+            let wrapper_msg = WrapperMsg::new(self.session.seal(replica, protmsg.clone()), self.myid, &sec_key.as_slice());
             total_bytes += Bytes::from(wrapper_msg.to_bytes()).len();
             let cancel_handler: CancelHandler<Acknowledgement> = self.net_send.send(replica, wrapper_msg).await;
             self.add_cancel_handler(cancel_handler);
@@ -312,17 +306,17 @@ impl Context {
     pub async fn broadcast(&mut self, protmsg: ProtMsg) {
         let sec_key_map = self.sec_key_map.clone();
         for (replica, sec_key) in sec_key_map.into_iter() {
-            let wrapper_msg = WrapperMsg::new(protmsg.clone(), self.myid, &sec_key.as_slice());
+            let wrapper_msg = WrapperMsg::new(self.session.seal(replica, protmsg.clone()), self.myid, &sec_key.as_slice());
             let cancel_handler: CancelHandler<Acknowledgement> = self.net_send.send(replica, wrapper_msg).await;
             self.add_cancel_handler(cancel_handler);
         }
     }
 
     pub fn add_cancel_handler(&mut self, canc: CancelHandler<Acknowledgement>) {
-        self.cancel_handlers.entry(0).or_default().push(canc);
+        consensus::add_cancel_handler_gc(&mut self.cancel_handlers, canc);
     }
 
-    pub async fn send(&mut self, replica: Replica, wrapper_msg: WrapperMsg<ProtMsg>) {
+    pub async fn send(&mut self, replica: Replica, wrapper_msg: WrapperMsg<SessionMsg<ProtMsg>>) {
         #[cfg(feature = "bandwidth")]
         log::info!("Network sending bytes: {:?}", Bytes::from(wrapper_msg.to_bytes()).len());
         let cancel_handler: CancelHandler<Acknowledgement> =
@@ -391,28 +385,33 @@ impl Context {
                         anyhow!("Networking layer has closed")
                     )?;
                     log::debug!("Received termination event: {:?}", term_event);
-                    // Process the termination event
-                    self.process_termination_event(term_event.1).await;
+                    // Process the termination event, the first element is the ACS session
+                    self.process_termination_event(term_event.0, term_event.1).await;
                 },
-                ctrbc_msg = self.ctrbc_out_recv.recv() => {
-                    let ctrbc_msg = ctrbc_msg.ok_or_else(||
+                rbc_msg = self.rbc.deliver() => {
+                    let rbc_msg = rbc_msg.ok_or_else(||
                         anyhow!("Networking layer has closed")
                     )?;
 
-                    log::debug!("Received message from CTRBC channel {:?}", ctrbc_msg);
-                    self.process_ctrbc_event(ctrbc_msg.1, ctrbc_msg.0, ctrbc_msg.2).await;
+                    log::debug!("Received message from RBC channel {:?}", rbc_msg);
+                    self.process_ctrbc_event(rbc_msg.broadcaster, rbc_msg.id, rbc_msg.payload).await;
                 },
-                asks_msg = self.asks_out_recv.recv() =>{
+                asks_msg = self.asks.next_output() =>{
                     let asks_msg = asks_msg.ok_or_else(||
                         anyhow!("Networking layer has closed")
                     )?;
 
                     log::debug!("Received message from ASKS channel {:?}", asks_msg);
-                    if asks_msg.2.is_none(){
-                        self.process_asks_termination(asks_msg.0, asks_msg.1, asks_msg.2).await;
-                    }
-                    else{
-                        self.process_asks_reconstruction_result(asks_msg.0, asks_msg.1, asks_msg.2.unwrap()).await;
+                    match asks_msg{
+                        ASKSOutput::Shared { id, dealer } => {
+                            let (session, instance) = split_vaba_instance_id(id);
+                            self.process_asks_termination(session, instance, dealer, None).await;
+                        },
+                        ASKSOutput::Reconstructed { id, dealer, results } => {
+                            let (session, instance) = split_vaba_instance_id(id);
+                            let secrets = results.iter().map(|result| result.secret_or_default()).collect();
+                            self.process_asks_reconstruction_result(session, instance, dealer, secrets).await;
+                        }
                     }
                 },
                 ra_msg = self.ra_out_recv.recv() => {
@@ -421,7 +420,8 @@ impl Context {
                     )?;
 
                     log::debug!("Received message from RA channel {:?}", ra_msg);
-                    self.process_ra_termination(ra_msg.1, ra_msg.0, ra_msg.2).await;
+                    let (session, instance) = split_vaba_instance_id(ra_msg.1);
+                    self.process_ra_termination(session, instance, ra_msg.0, ra_msg.2).await;
                 }
             };
         }
@@ -16,8 +16,10 @@ pub enum ProtMsg{
     // PubRecEcho2(Vec<LargeFieldSer>)
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct CTRBCInterface{
+/// Instance id of a reliable broadcast of an ACS session
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RBCId{
+    /// ACS session the broadcast belongs to
+    pub session: usize,
     pub id: usize,
-    pub msg: Vec<u8>
 }
\ No newline at end of file
@@ -1,30 +1,30 @@
 use std::collections::HashSet;
 
-use crypto::LargeField;
+use consensus::LargeField;
 use types::{Replica};
 
 use crate::Context;
 
-use super::VABAState;
+use super::{VABAState, vaba_instance_id};
 
 impl Context{
-    pub async fn process_asks_termination(&mut self, instance: usize, sender: Replica, value: Option<Vec<LargeField>>){
-        log::debug!("Processing ASKS termination for instance {} from sender {}", instance, sender);
-        if !self.acs_state.vaba_states.contains_key(&instance){
-            let vaba_context = VABAState::new_without_pre_justify();
-            self.acs_state.vaba_states.insert(instance, vaba_context);
+    pub async fn process_asks_termination(&mut self, session: usize, instance: usize, sender: Replica, value: Option<Vec<LargeField>>){
+        log::debug!("Processing ASKS termination for instance {} of session {} from sender {}", instance, session, sender);
+        if !self.init_session(session){
+            return;
+        }
+        let acs_state = self.acs_states.get_mut(&session).unwrap();
+        if !acs_state.vaba_states.contains_key(&instance){
+            let vaba_context = VABAState::new_without_pre_justify(instance);
+            acs_state.vaba_states.insert(instance, vaba_context);
         }
         
-        let vaba_context = self.acs_state.vaba_states.get_mut(&instance).unwrap();
+        let vaba_context = acs_state.vaba_states.get_mut(&instance).unwrap();
         
         if value.is_none(){
             vaba_context.term_asks_instances.insert(sender);
-            // Remove pending asks instances from the map of unvalidated pre_justify_votes
-            for (_rep, map) in vaba_context.unvalidated_pre_justify_votes.iter_mut(){
-                map.1.remove(&sender);
-            }
-            self.broadcast_pre(instance).await;
-            self.check_witness_pre_broadcast(instance).await;
+            self.broadcast_pre(session, instance).await;
+            self.check_witness_pre_broadcast(session, instance).await;
         }
         else{
             let value = value.unwrap()[0].clone();
@@ -32,10 +32,10 @@ impl Context{
         }
     }
 
-    pub async fn init_asks_reconstruction(&mut self, instance: usize){
+    pub async fn init_asks_reconstruction(&mut self, session: usize, instance: usize){
         // Generate list of all ASKS instances to be reconstructed
         // Reconstruct all received ASKS shares
-        let vaba_context = self.acs_state.vaba_states.get_mut(&instance).unwrap();
+        let vaba_context = self.acs_states.get_mut(&session).unwrap().vaba_states.get_mut(&instance).unwrap();
         if vaba_context.asks_reconstruction_started{
             return;
         }
@@ -51,6 +51,7 @@ impl Context{
                 union_set.insert(*index);
             }
         }
+        vaba_context.gather_output = union_set.clone();
         // Fetch list of asks instances specified by this party
         for rep in union_set{
             let (_,asks_instances,_) = vaba_context.pre_justify_votes.get(&rep).unwrap();
@@ -72,32 +73,36 @@ impl Context{
         }
 
         for rep in vaba_context.term_asks_instances.iter(){
-            let _status = self.asks_req.send((instance, 1, true, true, None, Some(*rep))).await;
+            let _status = self.asks.reconstruct(vaba_instance_id(session, instance), *rep, None, None).await;
         }
         // Reconstruction true
         vaba_context.asks_reconstruction_started = true;
         // Wait until receiving all results for ranks
-        self.check_reconstruction_phase_terminated(instance).await;
+        self.check_reconstruction_phase_terminated(session, instance).await;
     }
 
-    pub async fn process_asks_reconstruction_result(&mut self, instance: usize, secret_preparer_rep: usize, recon_result: Vec<LargeField>){
-        log::debug!("Received reconstruction result from ASKS for instance {} and Replica {}", instance, secret_preparer_rep);
+    pub async fn process_asks_reconstruction_result(&mut self, session: usize, instance: usize, secret_preparer_rep: usize, recon_result: Vec<LargeField>){
+        log::debug!("Received reconstruction result from ASKS for instance {} of session {} and Replica {}", instance, session, secret_preparer_rep);
         
         let recon_result = recon_result[0].clone();
         // Compute Rank of reconstruction
-        if !self.acs_state.vaba_states.contains_key(&instance){
-            let vaba_context = VABAState::new_without_pre_justify();
-            self.acs_state.vaba_states.insert(instance, vaba_context);
+        if !self.init_session(session){
+            return;
+        }
+        let acs_state = self.acs_states.get_mut(&session).unwrap();
+        if !acs_state.vaba_states.contains_key(&instance){
+            let vaba_context = VABAState::new_without_pre_justify(instance);
+            acs_state.vaba_states.insert(instance, vaba_context);
         }
         
-        let vaba_context = self.acs_state.vaba_states.get_mut(&instance).unwrap();
+        let vaba_context = acs_state.vaba_states.get_mut(&instance).unwrap();
 
         vaba_context.asks_reconstructed_values.insert(secret_preparer_rep, recon_result.clone());
-        self.check_reconstruction_phase_terminated(instance).await;
+        self.check_reconstruction_phase_terminated(session, instance).await;
     }
 
-    pub async fn check_reconstruction_phase_terminated(&mut self, instance: usize){
-        let vaba_context = self.acs_state.vaba_states.get_mut(&instance).unwrap();
+    pub async fn check_reconstruction_phase_terminated(&mut self, session: usize, instance: usize){
+        let vaba_context = self.acs_states.get_mut(&session).unwrap().vaba_states.get_mut(&instance).unwrap();
         
         if vaba_context.asks_reconstruction_started{
             let mut new_ranks_reconstructed_parties = Vec::new();
@@ -139,7 +144,7 @@ impl Context{
             log::debug!("Party with maximum rank {}, maximum rank {}", party_with_max_rank, max_rank);
             vaba_context.elected_leader = Some(party_with_max_rank.clone());
             // Start voting phase
-            self.start_vote_phase(instance, party_with_max_rank).await;
+            self.start_vote_phase(session, instance, party_with_max_rank).await;
             //self.terminate("Terminate".to_string()).await;   
         }
     }
@@ -2,131 +2,150 @@ use std::collections::{HashSet};
 
 use types::Replica;
 
-use crate::{Context, msg::CTRBCInterface};
+use consensus::ReliableBroadcast;
+
+use crate::{Context, msg::RBCId, protocol::{ViewBroadcast, view_broadcast}};
 
 impl Context{
-    pub async fn process_ctrbc_event(&mut self, broadcaster: usize, _instance: usize, value: Vec<u8>){
-        let deser_msg: CTRBCInterface = bincode::deserialize(value.as_slice()).unwrap();
-        let instance = deser_msg.id;
-        let value = deser_msg.msg;
+    pub async fn process_ctrbc_event(&mut self, broadcaster: usize, rbc_id: RBCId, value: Vec<u8>){
+        let session = rbc_id.session;
+        let instance = rbc_id.id;
+        if !self.init_session(session){
+            return;
+        }
         if instance == 1 {
             // First instance is for the RBC of the core ACS instance
             //let replicas_list: Vec<Replica> = bincode::deserialize(value.as_slice()).unwrap();
-            log::debug!("Received L1 CTRBC broadcast from party {}", broadcaster);
-            self.acs_state.broadcast_messages.insert(broadcaster , Vec::new());
-            
-            if self.acs_state.broadcast_messages.len() == self.num_nodes - self.num_faults{
+            log::debug!("Received L1 CTRBC broadcast from party {} in session {}", broadcaster, session);
+            let acs_state = self.acs_states.get_mut(&session).unwrap();
+            acs_state.broadcast_messages.insert(broadcaster , Vec::new());
+
+            if acs_state.broadcast_messages.len() == self.num_nodes - self.num_faults{
                 // Invoke CTRBC to broadcast list of indices
-                let key_set:Vec<Replica> = self.acs_state.broadcast_messages.keys().map(|key | key.clone()).collect();
+                let key_set:Vec<Replica> = acs_state.broadcast_messages.keys().map(|key | key.clone()).collect();
                 let ser_value = bincode::serialize(&key_set).unwrap();
 
-                let ctrbc_msg = CTRBCInterface{
-                    id: 2,
-                    msg: ser_value
+                let rbc_id = RBCId{
+                    session: session,
+                    id: 2
                 };
 
-                let ser_inst_id_val = bincode::serialize(&ctrbc_msg).unwrap();
-
                 log::debug!("Received n-f broadcasts of the initial value, broadcasting the list of broadcasts");
-                let _status = self.ctrbc_req.send(ser_inst_id_val).await;
+                let status = self.rbc.broadcast(rbc_id, ser_value).await;
+                if status.is_err(){
+                    log::error!("Error sending transaction to the RBC queue: {:?}", status.err().unwrap());
+                }
             }
-            self.check_witnesses_rbc_inst(broadcaster).await;
+            self.check_witnesses_rbc_inst(session, broadcaster).await;
         }
         else if instance == 2 {
-            log::debug!("Received L2 CTRBC broadcast from party {}", broadcaster);
+            log::debug!("Received L2 CTRBC broadcast from party {} in session {}", broadcaster, session);
             // Second RBC instance is for list of broadcasts
-            let replicas_list: Vec<Replica> = bincode::deserialize(value.as_slice()).unwrap();
-            self.acs_state.re_broadcast_messages.insert(broadcaster, replicas_list.clone());
-            self.check_witnesses_rbc_inst_single_party(broadcaster).await;
+            let replicas_list: Vec<Replica> = match bincode::deserialize(value.as_slice()){
+                Ok(replicas_list) => replicas_list,
+                Err(e) => {
+                    log::error!("Unable to deserialize list of broadcasts of party {}: {:?}", broadcaster, e);
+                    return;
+                }
+            };
+            self.acs_states.get_mut(&session).unwrap().re_broadcast_messages.insert(broadcaster, replicas_list.clone());
+            self.check_witnesses_rbc_inst_single_party(session, broadcaster).await;
         }
         else{
-            // Second instance RBC is for VABA instance
-            let true_inst_mod = instance - 2;
-            let tot_rbcs_per_vaba = 2;
-            log::debug!("Received L3 CTRBC broadcast from party {} for true_inst_mod {}", broadcaster, true_inst_mod);
-
-            if true_inst_mod % tot_rbcs_per_vaba == 1{
-                let vaba_index = (true_inst_mod/tot_rbcs_per_vaba) + 1;
-                // This broadcast corresponds to Broadcast termination of (pre_v, asks_v, justify_v)
-                self.process_pre_broadcast(vaba_index, broadcaster, value).await;
-            }
-            else if true_inst_mod % tot_rbcs_per_vaba == 0{
-                let vaba_index = true_inst_mod/tot_rbcs_per_vaba;
-                // This broadcast corresponds to a Vote instance
-                // This case has not been handled
-                self.process_vote(vaba_index, value, broadcaster).await;
+            // Remaining RBC instances belong to the views of VABA
+            log::debug!("Received L3 CTRBC broadcast {} from party {}", instance, broadcaster);
+            match view_broadcast(instance){
+                Some(ViewBroadcast::Pre(vaba_index)) => {
+                    // This broadcast corresponds to Broadcast termination of (pre_v, asks_v, justify_v)
+                    self.process_pre_broadcast(session, vaba_index, broadcaster, value).await;
+                },
+                Some(ViewBroadcast::Vote(vaba_index)) => {
+                    // This broadcast corresponds to a Vote instance
+                    self.process_vote(session, vaba_index, value, broadcaster).await;
+                },
+                None => {
+                    log::error!("Invalid CTRBC instance {} from party {} in session {}", instance, broadcaster, session);
+                }
             }
         }
     }
 
-    pub async fn check_witnesses_rbc_inst(&mut self, broadcaster: usize){
+    pub async fn check_witnesses_rbc_inst(&mut self, session: usize, broadcaster: usize){
+        let acs_state = self.acs_states.get_mut(&session).unwrap();
         let mut added_witnesses = Vec::new();
         // Check for witnesses after each accepted broadcast
-        for (rep_key, broadcast_list) in self.acs_state.broadcasts_left_to_be_accepted.iter_mut(){
+        for (rep_key, broadcast_list) in acs_state.broadcasts_left_to_be_accepted.iter_mut(){
             broadcast_list.remove(&broadcaster);
             if broadcast_list.len() == 0{
                 // Add party to witness list
-                log::debug!("Added party {} to list of first witnesses self.acs_state.accepted_witnesses", *rep_key);
+                log::debug!("Added party {} to list of first witnesses of session {}", *rep_key, session);
                 added_witnesses.push(*rep_key);
             }
         }
         for witness in added_witnesses.iter(){
-            self.acs_state.broadcasts_left_to_be_accepted.remove(&witness);
-            self.acs_state.accepted_witnesses.insert(*witness);
+            acs_state.broadcasts_left_to_be_accepted.remove(&witness);
+            acs_state.accepted_witnesses.insert(*witness);
         }
-        
+
         // Check if any new witnesses were added after this broadcast terminated
         if !added_witnesses.is_empty(){
-            self.check_witness_pre_broadcast(1).await;
+            self.check_witness_pre_broadcast_all_views(session).await;
         }
 
         // If this is the first witness accepted for the first time ever
-        if self.acs_state.accepted_witnesses.len() >= 1 && !self.acs_state.vaba_started{
+        let acs_state = self.acs_states.get_mut(&session).unwrap();
+        if acs_state.accepted_witnesses.len() >= 1 && !acs_state.vaba_started{
             // Start first phase of VABA
             // Start ASKS first
             let pre_i = broadcaster;
-            self.start_vaba(pre_i, Vec::new(), 1).await;
-            self.acs_state.vaba_started = true;
+            acs_state.vaba_started = true;
+            self.start_vaba(session, pre_i, Vec::new(), 1).await;
         }
     }
 
-    pub async fn check_witnesses_rbc_inst_single_party(&mut self, broadcaster: usize){
+    pub async fn check_witnesses_rbc_inst_single_party(&mut self, session: usize, broadcaster: usize){
+        let acs_state = self.acs_states.get_mut(&session).unwrap();
         // Check for witnesses
-        let replicas_list = self.acs_state.re_broadcast_messages.get(&broadcaster).unwrap();
+        let replicas_list = acs_state.re_broadcast_messages.get(&broadcaster).unwrap();
         let mut hashset_replicas: HashSet<usize> = HashSet::default();
         for rep in replicas_list.into_iter(){
-            if !self.acs_state.broadcast_messages.contains_key(&rep){
+            if !acs_state.broadcast_messages.contains_key(&rep){
                 hashset_replicas.insert(*rep);
             }
         }
-        
+
         if hashset_replicas.is_empty(){
             // Add witness to witness list
-            self.acs_state.accepted_witnesses.insert(broadcaster);
-            self.check_witness_pre_broadcast(1).await;
+            acs_state.accepted_witnesses.insert(broadcaster);
+            self.check_witness_pre_broadcast_all_views(session).await;
         }
         else {
-            self.acs_state.broadcasts_left_to_be_accepted.insert(broadcaster, hashset_replicas.clone());
+            acs_state.broadcasts_left_to_be_accepted.insert(broadcaster, hashset_replicas.clone());
         }
 
         // If this is the first witness accepted for the first time ever
-        if self.acs_state.accepted_witnesses.len() >= 1 && !self.acs_state.vaba_started{
+        let acs_state = self.acs_states.get_mut(&session).unwrap();
+        if acs_state.accepted_witnesses.len() >= 1 && !acs_state.vaba_started{
             // Start first phase of VABA
             // Start ASKS first
             let pre_i = broadcaster;
-            self.start_vaba( pre_i, Vec::new(), 1).await;
-            self.acs_state.vaba_started = true;
+            acs_state.vaba_started = true;
+            self.start_vaba(session, pre_i, Vec::new(), 1).await;
         }
     }
 
-    pub async fn process_termination_event(&mut self, replica: usize){
-        self.acs_input_set.insert(replica);
-        log::debug!("Completed sharing process for secrets originated by {}, adding to acs_set", replica);
-        let ctrbc_msg = CTRBCInterface{
-            id: 1,
-            msg: Vec::new()
+    pub async fn process_termination_event(&mut self, session: usize, replica: usize){
+        // Local inputs open the window of sessions peers can start
+        self.highest_local_session = std::cmp::max(self.highest_local_session, session);
+        if !self.init_session(session){
+            return;
+        }
+        self.acs_states.get_mut(&session).unwrap().acs_input_set.insert(replica);
+        log::debug!("Completed sharing process for secrets originated by {}, adding to acs_set of session {}", replica, session);
+        let rbc_id = RBCId{
+            session: session,
+            id: 1
         };
-        let ser_msg = bincode::serialize(&ctrbc_msg).unwrap();
-        self.process_ctrbc_event(replica, 1, ser_msg).await;
+        self.process_ctrbc_event(replica, rbc_id, Vec::new()).await;
     }
-}
\ No newline at end of file
+}
@@ -4,27 +4,31 @@ use types::Replica;
 
 use crate::{Context, msg::ProtMsg};
 
-use super::VABAState;
+use super::{VABAState, vaba_instance_id};
 
 impl Context{
-    pub async fn process_gather_echo(&mut self, gather_indices: Vec<Replica>, broadcaster: usize, inst: usize){
-        if !self.acs_state.vaba_states.contains_key(&inst){
-            let vaba_context = VABAState::new_without_pre_justify();
-            self.acs_state.vaba_states.insert(inst, vaba_context);
+    pub async fn process_gather_echo(&mut self, gather_indices: Vec<Replica>, broadcaster: usize, session: usize, inst: usize){
+        if !self.init_session(session){
+            return;
+        }
+        let acs_state = self.acs_states.get_mut(&session).unwrap();
+        if !acs_state.vaba_states.contains_key(&inst){
+            let vaba_context = VABAState::new_without_pre_justify(inst);
+            acs_state.vaba_states.insert(inst, vaba_context);
         }
 
-        let vaba_context = self.acs_state.vaba_states.get_mut(&inst).unwrap();
+        let vaba_context = acs_state.vaba_states.get_mut(&inst).unwrap();
         vaba_context.gather_state.received_gather_echos.insert(broadcaster , gather_indices.clone());
         let mut set_indices = HashSet::default();
         set_indices.extend(gather_indices);
         vaba_context.gather_state.unvalidated_gather_echos.insert(broadcaster, set_indices);
 
         // Check gather termination
-        self.check_gather_echo_new_party(inst, broadcaster).await;
+        self.check_gather_echo_new_party(session, inst, broadcaster).await;
     }
 
-    pub async fn check_gather_echo_termination(&mut self, inst: usize, terminated_rbcs: Vec<Replica>){
-        let vaba_context = self.acs_state.vaba_states.get_mut(&inst).unwrap();
+    pub async fn check_gather_echo_termination(&mut self, session: usize, inst: usize, terminated_rbcs: Vec<Replica>){
+        let vaba_context = self.acs_states.get_mut(&session).unwrap().vaba_states.get_mut(&inst).unwrap();
         let mut new_witnesses = Vec::new();
         
         for (rep, map) in vaba_context.gather_state.unvalidated_gather_echos.iter_mut(){
@@ -47,13 +51,13 @@ impl Context{
         
         // Upon collecting n-f ECHOs, broadcast this list again as ECHO2s. 
         if vaba_context.gather_state.validated_gather_echos.len() == self.num_nodes - self.num_faults{
-            self.init_gather_echo2(inst).await;
+            self.init_gather_echo2(session, inst).await;
         }
-        self.check_gather_echo2_termination(inst, terminated_rbcs).await;
+        self.check_gather_echo2_termination(session, inst, terminated_rbcs).await;
     }
 
-    pub async fn check_gather_echo_new_party(&mut self, inst: usize, sender: Replica){
-        let vaba_context = self.acs_state.vaba_states.get_mut(&inst).unwrap();
+    pub async fn check_gather_echo_new_party(&mut self, session: usize, inst: usize, sender: Replica){
+        let vaba_context = self.acs_states.get_mut(&session).unwrap().vaba_states.get_mut(&inst).unwrap();
         let gather_indices = vaba_context.gather_state.unvalidated_gather_echos.get_mut(&sender).unwrap();
         for index in gather_indices.clone().into_iter(){
             if vaba_context.validated_pre_justify_votes.contains(&index) && vaba_context.reliable_agreement.contains(&index){
@@ -68,12 +72,12 @@ impl Context{
         }
         // Upon collecting n-f ECHOs, broadcast this list again as ECHO2s. 
         if vaba_context.gather_state.validated_gather_echos.len() >= self.num_nodes - self.num_faults{
-            self.init_gather_echo2(inst).await;
+            self.init_gather_echo2(session, inst).await;
         }
     }
 
-    pub async fn init_gather_echo2(&mut self, inst: usize){
-        let vaba_context = self.acs_state.vaba_states.get_mut(&inst).unwrap();
+    pub async fn init_gather_echo2(&mut self, session: usize, inst: usize){
+        let vaba_context = self.acs_states.get_mut(&session).unwrap().vaba_states.get_mut(&inst).unwrap();
         
         // Union witnesses
         if !vaba_context.gather_state.gather2_started{
@@ -90,31 +94,35 @@ impl Context{
             }
             
             log::debug!("Starting Gather Echo2 with witnesses {:?}", vec_witnesses);
-            let prot_msg = ProtMsg::GatherEcho2(inst , vec_witnesses);
+            let prot_msg = ProtMsg::GatherEcho2(vaba_instance_id(session, inst) , vec_witnesses);
             vaba_context.gather_state.gather2_started = true;
             self.broadcast(prot_msg).await;
         }
     }
 
-    pub async fn process_gather_echo2(&mut self, gather_indices: Vec<Replica>, broadcaster: usize, inst: usize){
+    pub async fn process_gather_echo2(&mut self, gather_indices: Vec<Replica>, broadcaster: usize, session: usize, inst: usize){
         log::debug!("Processing Gather Echo2 from {} with indices {:?}", broadcaster, gather_indices);
-        if !self.acs_state.vaba_states.contains_key(&inst){
-            let vaba_context = VABAState::new_without_pre_justify();
-            self.acs_state.vaba_states.insert(inst, vaba_context);
+        if !self.init_session(session){
+            return;
+        }
+        let acs_state = self.acs_states.get_mut(&session).unwrap();
+        if !acs_state.vaba_states.contains_key(&inst){
+            let vaba_context = VABAState::new_without_pre_justify(inst);
+            acs_state.vaba_states.insert(inst, vaba_context);
         }
 
-        let vaba_context = self.acs_state.vaba_states.get_mut(&inst).unwrap();
+        let vaba_context = acs_state.vaba_states.get_mut(&inst).unwrap();
         vaba_context.gather_state.received_gather_echo2s.insert(broadcaster , gather_indices.clone());
         let mut set_indices = HashSet::default();
         set_indices.extend(gather_indices);
         vaba_context.gather_state.unvalidated_gather_echo2s.insert(broadcaster, set_indices);
 
         // Check gather termination
-        self.check_gather_echo2_new_party(inst, broadcaster).await;
+        self.check_gather_echo2_new_party(session, inst, broadcaster).await;
     }
 
-    pub async fn check_gather_echo2_termination(&mut self, inst: usize, terminated_rbcs: Vec<Replica>){
-        let vaba_context = self.acs_state.vaba_states.get_mut(&inst).unwrap();
+    pub async fn check_gather_echo2_termination(&mut self, session: usize, inst: usize, terminated_rbcs: Vec<Replica>){
+        let vaba_context = self.acs_states.get_mut(&session).unwrap().vaba_states.get_mut(&inst).unwrap();
         let mut new_witnesses = Vec::new();
         for (rep, map) in vaba_context.gather_state.unvalidated_gather_echo2s.iter_mut(){
             // First, the broadcasts of parties in map must terminate and be validated
@@ -136,12 +144,12 @@ impl Context{
         
         // Upon collecting n-f ECHOs, broadcast this list again as ECHO2s. 
         if vaba_context.gather_state.validated_gather_echo2s.len() >= self.num_nodes - self.num_faults{
-            self.init_asks_reconstruction(inst).await;
+            self.init_asks_reconstruction(session, inst).await;
         }
     }
 
-    pub async fn check_gather_echo2_new_party(&mut self, inst: usize, sender: Replica){
-        let vaba_context = self.acs_state.vaba_states.get_mut(&inst).unwrap();
+    pub async fn check_gather_echo2_new_party(&mut self, session: usize, inst: usize, sender: Replica){
+        let vaba_context = self.acs_states.get_mut(&session).unwrap().vaba_states.get_mut(&inst).unwrap();
         let gather_indices = vaba_context.gather_state.unvalidated_gather_echo2s.get_mut(&sender).unwrap();
         for index in gather_indices.clone().into_iter(){
             if vaba_context.validated_pre_justify_votes.contains(&index) && vaba_context.reliable_agreement.contains(&index){
@@ -157,7 +165,7 @@ impl Context{
         // Upon collecting n-f ECHOs, broadcast this list again as ECHO2s. 
         if vaba_context.gather_state.validated_gather_echo2s.len() >= self.num_nodes - self.num_faults{
             // Start next phase of the protocol. Reconstruct ASKS instances. 
-            self.init_asks_reconstruction(inst).await;
+            self.init_asks_reconstruction(session, inst).await;
         }
     }    
 }
\ No newline at end of file
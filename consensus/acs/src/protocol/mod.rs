@@ -1,6 +1,12 @@
 pub mod state;
 pub use state::*;
 
+mod view;
+pub use view::*;
+
+mod session;
+pub use session::*;
+
 mod ctrbc;
 
 mod vaba;
@@ -0,0 +1,73 @@
+use consensus::{sub_instance_id, split_sub_instance_id};
+
+use crate::Context;
+
+use super::ACSState;
+
+/// Maximum number of views of an ACS session
+pub const MAX_VIEWS: usize = 1 << 16;
+
+/// Number of sessions beyond the highest locally started session for which peers can create state
+pub const MAX_SESSIONS_AHEAD: usize = 16;
+
+/// Views of an ACS session run as ASKS, Reliable Agreement and Gather sub-instances of the session, see `consensus::sub_instance_id`
+pub fn vaba_instance_id(session: usize, view: usize) -> usize{
+    sub_instance_id(session, view)
+}
+
+/// Returns the session and the view of a VABA instance
+pub fn split_vaba_instance_id(instance: usize) -> (usize, usize){
+    split_sub_instance_id(instance)
+}
+
+/// Whether a session named by a peer is close enough to the sessions started locally to create state for it
+pub fn session_in_window(session: usize, highest_local_session: usize) -> bool{
+    session <= highest_local_session.saturating_add(MAX_SESSIONS_AHEAD)
+}
+
+impl Context{
+    /// Creates the state of an ACS session on its first event.
+    /// Returns false if the session terminated and its state was pruned, or if it is too far ahead of the sessions started locally.
+    pub fn init_session(&mut self, session: usize) -> bool{
+        if self.gc.is_collected(&session){
+            log::debug!("ACS session {} already terminated and pruned, skipping message", session);
+            return false;
+        }
+        if !self.acs_states.contains_key(&session) && !session_in_window(session, self.highest_local_session){
+            log::warn!("ACS session {} is too far ahead of local session {}, skipping message", session, self.highest_local_session);
+            return false;
+        }
+        if !self.acs_states.contains_key(&session){
+            log::debug!("Starting ACS session {}", session);
+            self.acs_states.insert(session, ACSState::new());
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vaba_instance_ids_are_disjoint_across_sessions() {
+        let mut seen = std::collections::HashSet::new();
+        for session in 0..8 {
+            for view in 1..64 {
+                let instance = vaba_instance_id(session, view);
+                assert!(seen.insert(instance));
+                assert_eq!(split_vaba_instance_id(instance), (session, view));
+            }
+        }
+    }
+
+    #[test]
+    fn test_session_window() {
+        assert!(session_in_window(0, 0));
+        assert!(session_in_window(MAX_SESSIONS_AHEAD, 0));
+        assert!(!session_in_window(MAX_SESSIONS_AHEAD + 1, 0));
+        assert!(session_in_window(MAX_SESSIONS_AHEAD + 5, 5));
+        assert!(!session_in_window(usize::MAX, 5));
+        assert!(session_in_window(usize::MAX, usize::MAX));
+    }
+}
@@ -1,9 +1,14 @@
 use std::collections::{HashSet, HashMap};
 
-use crypto::{LargeField};
+use consensus::LargeField;
 use types::Replica;
 
+use super::{Vote, VoteTally};
+
+/// State of one ACS session
 pub struct ACSState{
+    /// Parties whose sharing completed locally
+    pub acs_input_set: HashSet<Replica>,
     pub broadcast_messages: HashMap<Replica, Vec<Replica>>,
     
     pub re_broadcast_messages: HashMap<Replica, Vec<Replica>>,
@@ -12,6 +17,10 @@ pub struct ACSState{
 
     pub vaba_started: bool,
     pub vaba_states: HashMap<usize, VABAState>,
+    /// Highest VABA view this party entered
+    pub current_view: usize,
+    pub termination_gadget_started: bool,
+    /// Value output by the termination gadget
     pub ra_value: Option<Replica>,
 
     pub acs_output: HashSet<Replica>,
@@ -20,6 +29,7 @@ pub struct ACSState{
 impl ACSState{
     pub fn new()-> ACSState{
         ACSState { 
+            acs_input_set: HashSet::default(),
             broadcast_messages: HashMap::default(),
 
             re_broadcast_messages: HashMap::default(),
@@ -28,6 +38,8 @@ impl ACSState{
 
             vaba_started: false,
             vaba_states: HashMap::default(), 
+            current_view: 0,
+            termination_gadget_started: false,
             ra_value: None,
 
             acs_output: HashSet::default(),
@@ -36,23 +48,27 @@ impl ACSState{
 }
 
 pub struct VABAState{
+    /// View of this VABA instance, views are numbered from 1 and double as the instance id
+    pub view: usize,
 
     pub pre: Option<Replica>,
-    pub justify: Option<Vec<(Replica, Replica)>>,
+    pub justify: Option<Vec<(Replica, Vote)>>,
     
     pub term_asks_instances: HashSet<Replica>,
     pub pre_broadcast: bool,
     
     // Pre, ASKS instances terminated, Justify values
-    pub pre_justify_votes: HashMap<Replica, (Replica, Vec<Replica>, Vec<(Replica,Replica)>)>,
-    // If Pre has been validated, remaining ASKS instances left to be terminated, Justify values remaining
-    pub unvalidated_pre_justify_votes: HashMap<Replica, (Option<Replica>, HashSet<Replica>, HashMap<Replica, Replica>)>,
+    pub pre_justify_votes: HashMap<Replica, (Replica, Vec<Replica>, Vec<(Replica, Vote)>)>,
+    // Pre broadcasts waiting on the pre's witness, their ASKS instances, or the votes of the previous view
+    pub pending_pre_votes: HashSet<Replica>,
     pub validated_pre_justify_votes: HashSet<Replica>,
 
     pub reliable_agreement: HashSet<Replica>,
 
     pub gather_started: bool, 
     pub gather_state: GatherState,
+    // Union of the validated Gather ECHO2s, the candidates this party votes for
+    pub gather_output: HashSet<Replica>,
     
     pub asks_reconstruction_started: bool,
     pub asks_reconstruction_list: HashMap<Replica, HashSet<Replica>>,
@@ -63,13 +79,15 @@ pub struct VABAState{
     pub elected_leader: Option<Replica>,
 
     pub vote_broadcasted: bool,
-    pub votes: HashMap<Replica, HashSet<Replica>>,
+    pub vote_tally: VoteTally,
     pub termination_gadget: bool,
+    pub next_view_started: bool,
 }
 
 impl VABAState{
-    pub fn new(pre: Replica, justify: Vec<(Replica, Replica)>)-> VABAState{
+    pub fn new(view: usize, pre: Replica, justify: Vec<(Replica, Vote)>)-> VABAState{
         VABAState {
+            view: view,
             pre: Some(pre),
             justify: Some(justify),
 
@@ -77,30 +95,33 @@ impl VABAState{
             pre_broadcast: false,
 
             pre_justify_votes: HashMap::default(),
-            unvalidated_pre_justify_votes: HashMap::default(), 
+            pending_pre_votes: HashSet::default(), 
             validated_pre_justify_votes: HashSet::default(), 
 
             reliable_agreement: HashSet::default(),
 
             gather_started: false,
             gather_state: GatherState::new(), 
+            gather_output: HashSet::default(),
             
             asks_reconstruction_started: false,
             asks_reconstruction_list: HashMap::default(),
             asks_reconstructed_values: HashMap::default(),
             ranks_parties: HashMap::default(),
             
-            votes: HashMap::default(),
+            vote_tally: VoteTally::default(),
             vote_broadcasted: false,
             termination_gadget: false,
+            next_view_started: false,
 
             reconstructed_values: HashMap::default(),
             elected_leader: None
         }
     }
 
-    pub fn new_without_pre_justify()-> VABAState{
+    pub fn new_without_pre_justify(view: usize)-> VABAState{
         VABAState {
+            view: view,
             pre: None,
             justify: None,
 
@@ -108,22 +129,24 @@ impl VABAState{
             pre_broadcast: false,
 
             pre_justify_votes: HashMap::default(),
-            unvalidated_pre_justify_votes: HashMap::default(),
+            pending_pre_votes: HashSet::default(),
             validated_pre_justify_votes: HashSet::default(),
 
             reliable_agreement: HashSet::default(),
 
             gather_started: false,
             gather_state: GatherState::new(), 
+            gather_output: HashSet::default(),
 
             asks_reconstruction_started: false,
             asks_reconstruction_list: HashMap::default(),
             asks_reconstructed_values: HashMap::default(),
             ranks_parties: HashMap::default(),
 
-            votes: HashMap::default(),
+            vote_tally: VoteTally::default(),
             vote_broadcasted: false,
             termination_gadget: false,
+            next_view_started: false,
 
             reconstructed_values: HashMap::default(),
             elected_leader: None
@@ -1,235 +1,279 @@
-use std::collections::{HashSet, HashMap};
-
+use async_recursion::async_recursion;
+use consensus::ReliableBroadcast;
 use types::Replica;
 
-use crate::{Context, msg::{ProtMsg, CTRBCInterface}};
+use crate::{Context, msg::{ProtMsg, RBCId}};
 
-use super::VABAState;
+use super::{VABAState, Vote, VoteOutcome, ValidationStatus, TERMINATION_GADGET_INSTANCE, MAX_VIEWS, check_justify, pre_broadcast_id, vote_broadcast_id, vaba_instance_id};
 
 impl Context{
-    pub async fn start_vaba(&mut self, pre: Replica, justify: Vec<(Replica, Replica)>, instance: usize){
+    pub async fn start_vaba(&mut self, session: usize, pre: Replica, justify: Vec<(Replica, Vote)>, instance: usize){
+        if !self.init_session(session){
+            return;
+        }
+        let acs_state = self.acs_states.get_mut(&session).unwrap();
         // Create VABA state
-        if !self.acs_state.vaba_states.contains_key(&instance){
-            let vaba_context = VABAState::new(pre, justify);
-            self.acs_state.vaba_states.insert(instance , vaba_context);
+        if !acs_state.vaba_states.contains_key(&instance){
+            let vaba_context = VABAState::new(instance, pre, justify);
+            acs_state.vaba_states.insert(instance , vaba_context);
         }
         else{
-            let vaba_context = self.acs_state.vaba_states.get_mut(&instance).unwrap();
+            let vaba_context = acs_state.vaba_states.get_mut(&instance).unwrap();
             vaba_context.pre = Some(pre);
             vaba_context.justify = Some(justify);
         }
+        if instance > acs_state.current_view{
+            acs_state.current_view = instance;
+        }
+        log::debug!("Entering VABA view {} of session {} with pre {}", instance, session, pre);
 
         // Start ASKS
-        // (Instance ID, Number of secrets to be proposed, All_to_all reconstruction, Reconstruction Request?, Reconstruction_related_data) 
-        let status = self.asks_req.send((instance,1, true, false, None, None)).await;
+        // One random secret per view, reconstructed by all parties
+        let status = self.asks.share(vaba_instance_id(session, instance), 1, None, true).await;
         log::debug!("Sent ASKS request for instance {} with status: {:?}", instance, status);
-        self.broadcast_pre(instance).await;
+        self.broadcast_pre(session, instance).await;
         if status.is_err(){
             log::error!("Error sending transaction to the ASKS queue, abandoning ACS instance");
             return;
         }
+        // Votes of this view might have been delivered before this party entered it
+        self.evaluate_votes(session, instance).await;
     }
 
-    pub async fn process_pre_broadcast(&mut self, inst: usize, broadcaster: usize, rbc_value: Vec<u8>){
-        log::debug!("Received pre-broadcast for instance {} from Replica {}", inst, broadcaster);
-        let msg: (Replica, Vec<Replica>, Vec<(Replica,Replica)>) = bincode::deserialize(rbc_value.as_slice()).unwrap();
-        
-        if !self.acs_state.vaba_states.contains_key(&inst){
-            let vaba_context = VABAState::new_without_pre_justify();
-            self.acs_state.vaba_states.insert(inst, vaba_context);
+    pub async fn process_pre_broadcast(&mut self, session: usize, inst: usize, broadcaster: usize, rbc_value: Vec<u8>){
+        log::debug!("Received pre-broadcast for instance {} of session {} from Replica {}", inst, session, broadcaster);
+        let msg: (Replica, Vec<Replica>, Vec<(Replica, Vote)>) = match bincode::deserialize(rbc_value.as_slice()){
+            Ok(msg) => msg,
+            Err(e) => {
+                log::error!("Unable to deserialize pre-broadcast of party {} in instance {}: {:?}", broadcaster, inst, e);
+                return;
+            }
+        };
+
+        if !self.init_session(session){
+            return;
+        }
+        let acs_state = self.acs_states.get_mut(&session).unwrap();
+        if !acs_state.vaba_states.contains_key(&inst){
+            let vaba_context = VABAState::new_without_pre_justify(inst);
+            acs_state.vaba_states.insert(inst, vaba_context);
         }
-        let vaba_context = self.acs_state.vaba_states.get_mut(&inst).unwrap();
+        let vaba_context = acs_state.vaba_states.get_mut(&inst).unwrap();
         vaba_context.pre_justify_votes.insert(broadcaster, msg.clone());
+        let is_leader = vaba_context.elected_leader == Some(broadcaster);
+        if is_leader{
+            // Votes for the leader's pre can only be counted once the pre is known
+            vaba_context.vote_tally.leader_pre = Some(msg.0);
+        }
 
         //vaba_context.gather_state.terminated_rbcs.insert(broadcaster, p_i);
         // Process witness
-        self.check_witness_single_party(inst, broadcaster).await;
+        self.check_witness_single_party(session, inst, broadcaster).await;
+        if is_leader{
+            self.evaluate_votes(session, inst).await;
+            self.check_witness_pre_broadcast(session, inst+1).await;
+        }
     }
 
-    pub async fn process_ra_termination(&mut self, inst: usize, representative_rep: usize, value: usize){
-        if !self.acs_state.vaba_states.contains_key(&inst){
-            let vaba_context = VABAState::new_without_pre_justify();
-            self.acs_state.vaba_states.insert(inst , vaba_context);
+    pub async fn process_ra_termination(&mut self, session: usize, inst: usize, representative_rep: usize, value: usize){
+        if !self.init_session(session){
+            return;
+        }
+        let acs_state = self.acs_states.get_mut(&session).unwrap();
+        if !acs_state.vaba_states.contains_key(&inst){
+            let vaba_context = VABAState::new_without_pre_justify(inst);
+            acs_state.vaba_states.insert(inst , vaba_context);
         }
-        
+
         // Termination Gadget value
         if representative_rep == self.num_nodes{
+            if acs_state.ra_value.is_some(){
+                return;
+            }
             // Output this value finally
-            log::debug!("ACS output of value {}", value);
-            log::debug!("ACS output {:?}", self.acs_state.re_broadcast_messages.get(&value).unwrap());
+            log::debug!("ACS output of value {} in view {} of session {}", value, acs_state.current_view, session);
+            log::debug!("ACS output {:?}", acs_state.re_broadcast_messages.get(&value).unwrap());
+            acs_state.ra_value = Some(value);
             // Shift all this part of the code to a new repository
             // Compute random linear combination of shares
-            let output_set = self.acs_state.re_broadcast_messages.get(&value).unwrap();
-            let _status = self.acs_out_channel.send((1,output_set.clone())).await;
-            
+            let output_set = acs_state.re_broadcast_messages.get(&value).unwrap().clone();
+            let _status = self.acs_out_channel.send((session, output_set)).await;
+            // Every view of this session terminates with the output
+            self.gc.mark_terminated(session);
+            self.gc.prune(&mut self.acs_states);
+            return;
+
             //self.acs_state.acs_output.extend(output_set);
             //self.gen_rand_shares().await;
         }
         else{
-            let vaba_context = self.acs_state.vaba_states.get_mut(&inst).unwrap();
+            let vaba_context = acs_state.vaba_states.get_mut(&inst).unwrap();
             vaba_context.reliable_agreement.insert(representative_rep);
         }
-              
-        // Check if received enough Reliable Agreement instances to start Gather protocol. 
-        self.check_gather_start(inst).await;
-        // Check if received enough Reliable Agreement instances to start next phase of Gather protocol. 
-        self.check_gather_echo_termination(inst, vec![representative_rep]).await;
+
+        // Check if received enough Reliable Agreement instances to start Gather protocol.
+        self.check_gather_start(session, inst).await;
+        // Check if received enough Reliable Agreement instances to start next phase of Gather protocol.
+        self.check_gather_echo_termination(session, inst, vec![representative_rep]).await;
+        // Prune ACS sessions that terminated before the retention window
+        self.gc.prune(&mut self.acs_states);
     }
 
-    pub async fn broadcast_pre(&mut self, inst: usize){
-        let vaba_context = self.acs_state.vaba_states.get_mut(&inst).unwrap();
+    pub async fn broadcast_pre(&mut self, session: usize, inst: usize){
+        let vaba_context = self.acs_states.get_mut(&session).unwrap().vaba_states.get_mut(&inst).unwrap();
         if vaba_context.term_asks_instances.len() >= self.num_faults+1 &&
-            vaba_context.pre.is_some() && 
+            vaba_context.pre.is_some() &&
             vaba_context.justify.is_some() &&
             !vaba_context.pre_broadcast {
-            log::debug!("Starting Pre broadcast for instance_id {}", inst);
+            log::debug!("Starting Pre broadcast for instance_id {} of session {}", inst, session);
             // Start new RBC instance
             let mut p_i: Vec<Replica> = vaba_context.term_asks_instances.clone().into_iter().collect();
             p_i.truncate(self.num_faults+1);
-            
+
             let ctrbc_msg = (
-                vaba_context.pre.clone().unwrap(), 
-                p_i, 
+                vaba_context.pre.clone().unwrap(),
+                p_i,
                 vaba_context.justify.clone().unwrap()
             );
 
             let ser_msg = bincode::serialize(&(ctrbc_msg)).unwrap();
 
-            let ctrbc_msg = CTRBCInterface{
-                id: 3,
-                msg: ser_msg
+            let rbc_id = RBCId{
+                session: session,
+                id: pre_broadcast_id(inst)
             };
 
-            let ser_msg_inst_id = bincode::serialize(&ctrbc_msg).unwrap();
-            let status = self.ctrbc_req.send(ser_msg_inst_id).await;
+            let status = self.rbc.broadcast(rbc_id, ser_msg).await;
 
             if status.is_err(){
-                log::error!("Error sending transaction to the ASKS queue, abandoning ACS instance");
+                log::error!("Error sending transaction to the RBC queue, abandoning ACS instance: {:?}", status.err().unwrap());
                 return;
             }
             vaba_context.pre_broadcast = true;
         }
     }
 
+    /// Validates the pre broadcast of a party in view `inst`. The pre must be a witness of the ACS broadcasts and its ASKS instances must have terminated.
+    /// After the first view, the justification must be valid against the votes of the previous view.
+    pub fn pre_vote_status(&self, session: usize, inst: usize, broadcaster: Replica) -> ValidationStatus{
+        let acs_state = self.acs_states.get(&session).unwrap();
+        let vaba_context = acs_state.vaba_states.get(&inst).unwrap();
+        let (pre, asks_insts, justify) = vaba_context.pre_justify_votes.get(&broadcaster).unwrap();
+        if inst > 1{
+            let status = match acs_state.vaba_states.get(&(inst-1)){
+                Some(prev_context) => check_justify(*pre, justify, &prev_context.vote_tally, self.num_nodes, self.num_faults),
+                None => ValidationStatus::Pending
+            };
+            if status != ValidationStatus::Valid{
+                return status;
+            }
+        }
+        if !acs_state.accepted_witnesses.contains(pre){
+            return ValidationStatus::Pending;
+        }
+        if asks_insts.iter().any(|asks_inst| !vaba_context.term_asks_instances.contains(asks_inst)){
+            return ValidationStatus::Pending;
+        }
+        ValidationStatus::Valid
+    }
+
     // Checks if the termination of an RBC added any new witnesses for PRE Broadcast
-    pub async fn check_witness_pre_broadcast(&mut self, inst: usize){
-        log::debug!("Checking for witnesses in inst {}", inst);
+    // Validating pre votes can complete Gather and the vote of this view, which checks the pre votes of the next view
+    #[async_recursion]
+    pub async fn check_witness_pre_broadcast(&mut self, session: usize, inst: usize){
+        log::debug!("Checking for witnesses in inst {} of session {}", inst, session);
         let mut list_of_witnesses = Vec::new();
-        if !self.acs_state.vaba_states.contains_key(&inst){
+        let mut invalid_votes = Vec::new();
+        if !self.acs_states.get(&session).map_or(false, |acs_state| acs_state.vaba_states.contains_key(&inst)){
             return;
         }
-        if inst == 1{
-            let vaba_context = self.acs_state.vaba_states.get_mut(&inst).unwrap();
-            // For the first RBC instance, check the list of witnesses
-            for (key, entry) in vaba_context.unvalidated_pre_justify_votes.iter_mut(){
-                // If this party indeed indicated the broadcaster as a pre-vote, then check if other conditions are true as well
-                if (entry.0.is_some() && (self.acs_state.accepted_witnesses.contains(&entry.0.clone().unwrap()))) || 
-                    entry.0.is_none(){
-                    if entry.1.is_empty(){
-                        log::debug!("Found new witness {} at check_witness_pre_broadcast for inst {}", *key, inst);
-                        list_of_witnesses.push(*key);
-                    }
-                    else{
-                        // More ASKS instances need to be accepted
-                        entry.0 = None;
-                    }
-                }
+        let vaba_context = self.acs_states.get(&session).unwrap().vaba_states.get(&inst).unwrap();
+        for broadcaster in vaba_context.pending_pre_votes.iter(){
+            match self.pre_vote_status(session, inst, *broadcaster){
+                ValidationStatus::Valid => {
+                    log::debug!("Found new witness {} at check_witness_pre_broadcast for inst {}", *broadcaster, inst);
+                    list_of_witnesses.push(*broadcaster);
+                },
+                ValidationStatus::Invalid => invalid_votes.push(*broadcaster),
+                ValidationStatus::Pending => {}
             }
-            
-        }
-        else{
-            // 
         }
         // Start reliable agreement for new witnesses
-        let vaba_context = self.acs_state.vaba_states.get_mut(&inst).unwrap();
+        let vaba_context = self.acs_states.get_mut(&session).unwrap().vaba_states.get_mut(&inst).unwrap();
+        for broadcaster in invalid_votes.iter(){
+            log::error!("Party {}'s Pre vote in instance {} has an invalid justification, discarding it", *broadcaster, inst);
+            vaba_context.pending_pre_votes.remove(broadcaster);
+        }
         for witness in list_of_witnesses.iter(){
-            vaba_context.unvalidated_pre_justify_votes.remove(witness);
+            vaba_context.pending_pre_votes.remove(witness);
             log::debug!("Validated party {}'s Pre vote, adding party to validated list", *witness);
             vaba_context.validated_pre_justify_votes.insert(*witness);
-            
+
             if vaba_context.reliable_agreement.len() <= self.num_nodes - self.num_faults{
                 log::debug!("Starting Reliable Agreement for witness {}", *witness);
-                let status = self.ra_req_send.send((*witness,1, inst)).await;
+                let status = self.ra_req_send.send((*witness,1, vaba_instance_id(session, inst))).await;
                 if status.is_err(){
                     log::error!("Error sending transaction to the RA queue, abandoning ACS instance");
                     return;
                 }
             }
         }
-        self.check_gather_start(inst).await;
+        self.check_gather_start(session, inst).await;
+        if !list_of_witnesses.is_empty(){
+            self.check_gather_echo_termination(session, inst, list_of_witnesses).await;
+        }
     }
 
-    pub async fn check_witness_single_party(&mut self, inst: usize, broadcaster: Replica){
-        if !self.acs_state.vaba_states.contains_key(&inst){
-            return;
+    // Pre votes of every view wait on the ACS witnesses
+    pub async fn check_witness_pre_broadcast_all_views(&mut self, session: usize){
+        let mut views: Vec<usize> = self.acs_states.get(&session).unwrap().vaba_states.keys().cloned().collect();
+        views.sort();
+        for view in views{
+            self.check_witness_pre_broadcast(session, view).await;
         }
-        let vaba_context = self.acs_state.vaba_states.get_mut(&inst).unwrap();
-        let (pre,asks_insts, justify) = vaba_context.pre_justify_votes.get(&broadcaster).unwrap();
-
-        if inst == 1{
-            // Check if party pre's specified ASKS instances have terminated
-            let mut remaining_asks_instances = HashSet::default();
-            remaining_asks_instances.extend(asks_insts.clone());
-            for asks_inst in asks_insts{
-                if vaba_context.term_asks_instances.contains(asks_inst){
-                    remaining_asks_instances.remove(asks_inst);
-                }
-            }
+    }
 
-            // Check if party pre's RBC terminated in the first phase
-            if remaining_asks_instances.is_empty() && self.acs_state.accepted_witnesses.contains(pre){
+    pub async fn check_witness_single_party(&mut self, session: usize, inst: usize, broadcaster: Replica){
+        if !self.acs_states.get(&session).map_or(false, |acs_state| acs_state.vaba_states.contains_key(&inst)){
+            return;
+        }
+        let status = self.pre_vote_status(session, inst, broadcaster);
+        let vaba_context = self.acs_states.get_mut(&session).unwrap().vaba_states.get_mut(&inst).unwrap();
+        match status{
+            ValidationStatus::Valid => {
                 // Add party to set of witnesses
                 log::debug!("Validated party {}'s Pre vote, adding party to validated list", broadcaster);
+                vaba_context.pending_pre_votes.remove(&broadcaster);
                 vaba_context.validated_pre_justify_votes.insert(broadcaster.clone());
-            }
-
-            else{
+            },
+            ValidationStatus::Pending => {
                 log::debug!("Party {}'s Pre vote is not validated, adding to unvalidated votes", broadcaster);
-                // Create an entry in unvalidated votes
-                let pre_option;
-                if self.acs_state.accepted_witnesses.contains(pre){
-                    pre_option = None;
-                }
-                else {
-                    pre_option = Some(*pre);
-                }
-
-                // Create remaining justifies
-                let mut vote_map = HashMap::default();
-                for (vote_broadcaster, vote) in justify.into_iter(){
-                    vote_map.insert(*vote_broadcaster, *vote);
-                }
-                
-                vaba_context.unvalidated_pre_justify_votes.insert(broadcaster, (pre_option, remaining_asks_instances, vote_map));
+                vaba_context.pending_pre_votes.insert(broadcaster);
+                return;
+            },
+            ValidationStatus::Invalid => {
+                log::error!("Party {}'s Pre vote in instance {} has an invalid justification, discarding it", broadcaster, inst);
+                return;
             }
         }
-        else{
-            // Check if justified votes have been broadcasted and validated. 
-            // Fetch the previous VABA context
-            // TODO: Case unhandled
-            return;
-        }
 
-        //let vaba_context = self.acs_state.vaba_states.get_mut(&inst).unwrap();
         // Start reliable agreement if needed
-        
-        if vaba_context.validated_pre_justify_votes.contains(&broadcaster) && 
-            vaba_context.reliable_agreement.len() <= self.num_nodes - self.num_faults{
+        if vaba_context.reliable_agreement.len() <= self.num_nodes - self.num_faults{
             log::debug!("Starting Reliable Agreement for witness {} under method check_witness_single_party", broadcaster);
-            let status = self.ra_req_send.send((broadcaster,1, inst)).await;
+            let status = self.ra_req_send.send((broadcaster,1, vaba_instance_id(session, inst))).await;
             if status.is_err(){
                 log::error!("Error sending transaction to the RA queue, abandoning ACS instance");
                 return;
             }
         }
-        self.check_gather_start(inst).await;
-        self.check_gather_echo_termination(inst, vec![broadcaster]).await;
+        self.check_gather_start(session, inst).await;
+        self.check_gather_echo_termination(session, inst, vec![broadcaster]).await;
     }
 
-    pub async fn check_gather_start(&mut self, inst: usize){
-        log::debug!("Checking if Gather can be started for instance {}", inst);
-        let vaba_context = self.acs_state.vaba_states.get_mut(&inst).unwrap();
-        if vaba_context.validated_pre_justify_votes.len() >= self.num_nodes - self.num_faults && 
+    pub async fn check_gather_start(&mut self, session: usize, inst: usize){
+        log::debug!("Checking if Gather can be started for instance {} of session {}", inst, session);
+        let vaba_context = self.acs_states.get_mut(&session).unwrap().vaba_states.get_mut(&inst).unwrap();
+        if vaba_context.validated_pre_justify_votes.len() >= self.num_nodes - self.num_faults &&
             vaba_context.reliable_agreement.len() >= self.num_nodes-self.num_faults &&
             !vaba_context.gather_started{
             // Check if the intersection of pre_justify votes and reliable agreement votes is greater than n-f
@@ -240,12 +284,12 @@ impl Context{
                     gather_start_set.push(*rep);
                 }
             }
-            
+
             if gather_start_set.len() >= self.num_nodes - self.num_faults{
                 // Start Gather by sending Gather Echo
                 log::debug!("Starting Gather Phase 1 with indices {:?}", gather_start_set);
-                let prot_msg = ProtMsg::GatherEcho(inst , gather_start_set);
-                
+                let prot_msg = ProtMsg::GatherEcho(vaba_instance_id(session, inst) , gather_start_set);
+
                 // Gather started here
                 vaba_context.gather_started = true;
                 self.broadcast(prot_msg).await;
@@ -253,65 +297,113 @@ impl Context{
         }
     }
 
-    pub async fn start_vote_phase(&mut self, instance: usize, leader: Replica){
-        log::debug!("Starting Vote Phase for instance {} with leader {}", instance, leader);
-        let vaba_context = self.acs_state.vaba_states.get_mut(&instance).unwrap();
-        let pre_value_of_leader = vaba_context.pre_justify_votes.get(&leader);
-        if pre_value_of_leader.is_none(){
-            return;
+    pub async fn start_vote_phase(&mut self, session: usize, instance: usize, leader: Replica){
+        log::debug!("Starting Vote Phase for instance {} of session {} with leader {}", instance, session, leader);
+        let vaba_context = self.acs_states.get_mut(&session).unwrap().vaba_states.get_mut(&instance).unwrap();
+        let pre_value_of_leader = vaba_context.pre_justify_votes.get(&leader).map(|(pre, _, _)| *pre);
+        if pre_value_of_leader.is_some(){
+            vaba_context.vote_tally.leader_pre = pre_value_of_leader;
         }
-        let pre_value_of_leader = pre_value_of_leader.unwrap().0;
 
         // Broadcast this value
         if !vaba_context.vote_broadcasted{
-            
-            let ctrbc_msg = CTRBCInterface{
-                id: 4,
-                msg: pre_value_of_leader.to_be_bytes().to_vec()
+            // Vote for the leader's pre only if the leader is in this party's Gather output, skip otherwise
+            let vote: Vote = if vaba_context.gather_output.contains(&leader){
+                pre_value_of_leader
+            }
+            else{
+                None
+            };
+            log::debug!("Voting {:?} in instance {}", vote, instance);
+            let rbc_id = RBCId{
+                session: session,
+                id: vote_broadcast_id(instance)
             };
-            
-            let ser_msg_inst_id = bincode::serialize(&ctrbc_msg).unwrap();
 
-            let status = self.ctrbc_req.send(ser_msg_inst_id).await;
+            let status = self.rbc.broadcast(rbc_id, bincode::serialize(&vote).unwrap()).await;
             if status.is_err(){
-                log::error!("Error sending transaction to the ASKS queue, abandoning ACS instance");
+                log::error!("Error sending transaction to the RBC queue, abandoning ACS instance: {:?}", status.err().unwrap());
                 return;
             }
             vaba_context.vote_broadcasted = true;
         }
+        self.evaluate_votes(session, instance).await;
+        self.check_witness_pre_broadcast(session, instance+1).await;
     }
 
-    pub async fn process_vote(&mut self, inst: usize, value: Vec<u8>, broadcaster: Replica){
-        if !self.acs_state.vaba_states.contains_key(&inst){
-            let vaba_context = VABAState::new_without_pre_justify();
-            self.acs_state.vaba_states.insert(inst , vaba_context);
-        }
-        
-        let vaba_context = self.acs_state.vaba_states.get_mut(&inst).unwrap();
-        let mut bytes: [u8;8] = [0;8];
-        for (index, value) in (0..8).into_iter().zip(value.into_iter()){
-            bytes[index] = value;
-        }
-
-        let vote_rep = usize::from_be_bytes(bytes);
-        log::debug!("Received vote for instance {} from party {}", vote_rep, broadcaster);
-        if vaba_context.votes.contains_key(&vote_rep){
-            let rep_list = vaba_context.votes.get_mut(&vote_rep).unwrap();
-            rep_list.insert(broadcaster);
-            if rep_list.len() == self.num_nodes - self.num_faults{
-                // Start Reliable Agreement as a termination gadget
-                log::debug!("Vote for {} has been validated, starting Reliable Agreement", vote_rep);
-                let status = self.ra_req_send.send((self.num_nodes, vote_rep, inst)).await;
-                if status.is_err(){
-                    log::error!("Error sending transaction to the RA queue, abandoning ACS instance");
-                    return;
-                }
+    pub async fn process_vote(&mut self, session: usize, inst: usize, value: Vec<u8>, broadcaster: Replica){
+        if !self.init_session(session){
+            return;
+        }
+        let acs_state = self.acs_states.get_mut(&session).unwrap();
+        if !acs_state.vaba_states.contains_key(&inst){
+            let vaba_context = VABAState::new_without_pre_justify(inst);
+            acs_state.vaba_states.insert(inst , vaba_context);
+        }
+
+        let vote: Vote = match bincode::deserialize(value.as_slice()){
+            Ok(vote) => vote,
+            Err(e) => {
+                log::error!("Unable to deserialize vote of party {} in instance {}: {:?}", broadcaster, inst, e);
+                return;
+            }
+        };
+        log::debug!("Received vote {:?} for instance {} of session {} from party {}", vote, inst, session, broadcaster);
+        let vaba_context = acs_state.vaba_states.get_mut(&inst).unwrap();
+        vaba_context.vote_tally.votes.insert(broadcaster, vote);
+
+        self.evaluate_votes(session, inst).await;
+        // Pre votes of the next view might be waiting on this vote
+        self.check_witness_pre_broadcast(session, inst+1).await;
+    }
+
+    /// Checks whether the votes of view `inst` decide a value or move this party to the next view.
+    /// A party that decides starts the termination gadget and keeps moving to the next views with the decided value, so that parties that did not decide yet can catch up.
+    #[async_recursion]
+    pub async fn evaluate_votes(&mut self, session: usize, inst: usize){
+        let acs_state = match self.acs_states.get_mut(&session){
+            Some(acs_state) => acs_state,
+            None => {
+                return;
             }
+        };
+        if acs_state.ra_value.is_some() || !acs_state.vaba_states.contains_key(&inst){
+            return;
         }
-        else{
-            let mut rep_list: HashSet<Replica> = HashSet::default();
-            rep_list.insert(broadcaster);
-            vaba_context.votes.insert(vote_rep, rep_list);
+        let vaba_context = acs_state.vaba_states.get_mut(&inst).unwrap();
+        if vaba_context.elected_leader.is_none() || vaba_context.pre.is_none() || vaba_context.next_view_started{
+            return;
+        }
+        let (pre, justify) = match vaba_context.vote_tally.outcome(self.num_nodes, self.num_faults, vaba_context.pre.unwrap()){
+            VoteOutcome::Pending => {
+                return;
+            },
+            VoteOutcome::Decide(value) => {
+                log::debug!("Vote for {} has been validated in instance {}, starting Reliable Agreement", value, inst);
+                vaba_context.termination_gadget = true;
+                let justify = vaba_context.vote_tally.valid_votes();
+                if !acs_state.termination_gadget_started{
+                    // Start Reliable Agreement as a termination gadget
+                    let status = self.ra_req_send.send((self.num_nodes, value, vaba_instance_id(session, TERMINATION_GADGET_INSTANCE))).await;
+                    if status.is_err(){
+                        log::error!("Error sending transaction to the RA queue, abandoning ACS instance");
+                        return;
+                    }
+                    acs_state.termination_gadget_started = true;
+                }
+                (value, justify)
+            },
+            VoteOutcome::NextView(pre, justify) => {
+                log::debug!("No value decided in instance {}, moving to the next view with pre {}", inst, pre);
+                (pre, justify)
+            }
+        };
+        if inst + 1 >= MAX_VIEWS{
+            log::error!("ACS session {} ran out of views, abandoning it", session);
+            return;
         }
+        let vaba_context = acs_state.vaba_states.get_mut(&inst).unwrap();
+        vaba_context.next_view_started = true;
+        self.start_vaba(session, pre, justify, inst+1).await;
     }
-}
\ No newline at end of file
+}
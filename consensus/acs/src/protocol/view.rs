@@ -0,0 +1,212 @@
+use std::collections::{HashMap, HashSet};
+
+use types::Replica;
+
+/// Vote of a party in a view of VABA: the pre value of the elected leader if the leader is in the party's Gather output, a skip otherwise.
+pub type Vote = Option<Replica>;
+
+/// CTRBC identifiers of the pre broadcast and the vote of a view, identifiers 1 and 2 belong to the ACS broadcasts
+pub fn pre_broadcast_id(view: usize) -> usize{
+    2*view + 1
+}
+
+pub fn vote_broadcast_id(view: usize) -> usize{
+    2*view + 2
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViewBroadcast{
+    Pre(usize),
+    Vote(usize),
+}
+
+/// Inverse of `pre_broadcast_id` and `vote_broadcast_id`, None for identifiers that belong to no view
+pub fn view_broadcast(id: usize) -> Option<ViewBroadcast>{
+    if id < pre_broadcast_id(1){
+        return None;
+    }
+    if id % 2 == 1{
+        Some(ViewBroadcast::Pre((id - 1) / 2))
+    }
+    else{
+        Some(ViewBroadcast::Vote((id - 2) / 2))
+    }
+}
+
+/// Reliable Agreement instance of the termination gadget. Parties decide in different views, so the gadget is shared by all views.
+pub const TERMINATION_GADGET_INSTANCE: usize = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VoteOutcome{
+    /// Not enough valid votes yet
+    Pending,
+    /// n-f parties voted for the leader's pre, start the termination gadget with this value
+    Decide(Replica),
+    /// n-f valid votes without agreement, move to the next view with this pre and justification
+    NextView(Replica, Vec<(Replica, Vote)>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationStatus{
+    Valid,
+    /// Waiting on broadcasts or ASKS instances that have not terminated yet
+    Pending,
+    Invalid,
+}
+
+/// Votes delivered in one view
+#[derive(Debug, Clone, Default)]
+pub struct VoteTally{
+    pub votes: HashMap<Replica, Vote>,
+    /// Pre value broadcast by the elected leader, once delivered
+    pub leader_pre: Option<Replica>,
+}
+
+impl VoteTally{
+    /// Skips and votes for the leader's pre, ordered by voter. Votes for any other value are invalid and never count.
+    /// Votes for a value are only counted once the leader's pre is known.
+    pub fn valid_votes(&self) -> Vec<(Replica, Vote)>{
+        let mut valid: Vec<(Replica, Vote)> = self.votes.iter().filter(|(_, vote)| {
+            vote.is_none() || (self.leader_pre.is_some() && **vote == self.leader_pre)
+        }).map(|(voter, vote)| (*voter, *vote)).collect();
+        valid.sort();
+        valid
+    }
+
+    pub fn outcome(&self, num_nodes: usize, num_faults: usize, own_pre: Replica) -> VoteOutcome{
+        let valid = self.valid_votes();
+        let supporting = valid.iter().filter(|(_, vote)| vote.is_some()).count();
+        if supporting >= num_nodes - num_faults{
+            return VoteOutcome::Decide(self.leader_pre.unwrap());
+        }
+        if valid.len() < num_nodes - num_faults{
+            return VoteOutcome::Pending;
+        }
+        // A value that might have been decided appears in every set of n-f valid votes and must be carried over
+        let pre = match valid.iter().find(|(_, vote)| vote.is_some()){
+            Some((_, vote)) => vote.unwrap(),
+            None => own_pre
+        };
+        VoteOutcome::NextView(pre, valid)
+    }
+}
+
+/// Checks the justification of a pre broadcast in a view after the first against the votes delivered in the previous view.
+/// The justification must contain n-f distinct valid votes that match the delivered votes, and if it contains a vote for a value, the pre must be that value.
+pub fn check_justify(
+    pre: Replica,
+    justify: &Vec<(Replica, Vote)>,
+    prev_tally: &VoteTally,
+    num_nodes: usize,
+    num_faults: usize
+) -> ValidationStatus{
+    let voters: HashSet<Replica> = justify.iter().map(|(voter, _)| *voter).collect();
+    if voters.len() != justify.len() || voters.len() < num_nodes - num_faults || voters.iter().any(|voter| *voter >= num_nodes){
+        return ValidationStatus::Invalid;
+    }
+    if justify.iter().any(|(_, vote)| vote.is_some() && *vote != Some(pre)){
+        return ValidationStatus::Invalid;
+    }
+    let mut status = ValidationStatus::Valid;
+    if justify.iter().any(|(_, vote)| vote.is_some()){
+        match prev_tally.leader_pre{
+            Some(leader_pre) if leader_pre != pre => return ValidationStatus::Invalid,
+            Some(_) => {},
+            None => status = ValidationStatus::Pending,
+        }
+    }
+    for (voter, vote) in justify.iter(){
+        match prev_tally.votes.get(voter){
+            Some(delivered) if delivered != vote => return ValidationStatus::Invalid,
+            Some(_) => {},
+            None => status = ValidationStatus::Pending,
+        }
+    }
+    status
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NUM_NODES: usize = 4;
+    const NUM_FAULTS: usize = 1;
+    const BYZANTINE: Replica = 3;
+
+    fn tally(votes: &[(Replica, Vote)], leader_pre: Option<Replica>) -> VoteTally {
+        VoteTally {
+            votes: votes.iter().cloned().collect(),
+            leader_pre,
+        }
+    }
+
+    #[test]
+    fn test_byzantine_first_leader_falls_back_to_next_view() {
+        // View 1: the Byzantine party is elected and only party 0 has it in its Gather output.
+        // It votes for a different value than its pre to split the honest parties.
+        let leader_pre = Some(2);
+        let view_1_votes = vec![(0, leader_pre), (1, None), (2, None), (BYZANTINE, Some(1))];
+        let own_pres = [0usize, 1, 2];
+        let mut next_views = Vec::new();
+        for own_pre in own_pres.iter() {
+            // Until party 0's vote arrives, the Byzantine vote does not count towards n-f
+            let partial = tally(&view_1_votes[1..], leader_pre);
+            assert_eq!(partial.outcome(NUM_NODES, NUM_FAULTS, *own_pre), VoteOutcome::Pending);
+
+            match tally(&view_1_votes, leader_pre).outcome(NUM_NODES, NUM_FAULTS, *own_pre) {
+                VoteOutcome::NextView(pre, justify) => next_views.push((pre, justify)),
+                outcome => panic!("Expected a view change, got {:?}", outcome),
+            }
+        }
+        // Every honest party carries the only value that could have been decided into view 2
+        let prev_tally = tally(&view_1_votes, leader_pre);
+        for (pre, justify) in next_views.iter() {
+            assert_eq!(*pre, 2);
+            assert_eq!(check_justify(*pre, justify, &prev_tally, NUM_NODES, NUM_FAULTS), ValidationStatus::Valid);
+        }
+
+        // The Byzantine party cannot justify another pre in view 2
+        let skips = vec![(0, None), (1, None), (2, None)];
+        assert_eq!(check_justify(1, &skips, &prev_tally, NUM_NODES, NUM_FAULTS), ValidationStatus::Invalid);
+        let own_vote = vec![(1, None), (2, None), (BYZANTINE, Some(1))];
+        assert_eq!(check_justify(1, &own_vote, &prev_tally, NUM_NODES, NUM_FAULTS), ValidationStatus::Invalid);
+        let too_few = vec![(1, None), (2, None)];
+        assert_eq!(check_justify(1, &too_few, &prev_tally, NUM_NODES, NUM_FAULTS), ValidationStatus::Invalid);
+        // Votes not delivered yet keep the pre pending
+        let partial_tally = tally(&view_1_votes[1..], leader_pre);
+        assert_eq!(check_justify(2, &next_views[0].1, &partial_tally, NUM_NODES, NUM_FAULTS), ValidationStatus::Pending);
+
+        // View 2: an honest leader is elected, every honest party has it in its Gather output and votes for its pre
+        let view_2_votes = vec![(0, Some(2)), (1, Some(2)), (2, Some(2)), (BYZANTINE, None)];
+        for own_pre in own_pres.iter() {
+            assert_eq!(tally(&view_2_votes, Some(2)).outcome(NUM_NODES, NUM_FAULTS, *own_pre), VoteOutcome::Decide(2));
+        }
+    }
+
+    #[test]
+    fn test_silent_leader_skips_to_next_view_with_own_pre() {
+        // The leader never broadcast its pre, every honest party skips
+        let votes = vec![(0, None), (1, None), (2, None)];
+        for own_pre in 0..3 {
+            match tally(&votes, None).outcome(NUM_NODES, NUM_FAULTS, own_pre) {
+                VoteOutcome::NextView(pre, justify) => {
+                    assert_eq!(pre, own_pre);
+                    assert_eq!(justify, votes);
+                },
+                outcome => panic!("Expected a view change, got {:?}", outcome),
+            }
+        }
+    }
+
+    #[test]
+    fn test_broadcast_ids_map_back_to_views() {
+        for view in 1..10 {
+            assert_eq!(view_broadcast(pre_broadcast_id(view)), Some(ViewBroadcast::Pre(view)));
+            assert_eq!(view_broadcast(vote_broadcast_id(view)), Some(ViewBroadcast::Vote(view)));
+        }
+        // The ACS broadcasts and instance 0 belong to no view
+        for id in 0..3 {
+            assert_eq!(view_broadcast(id), None);
+        }
+    }
+}
@@ -14,22 +14,22 @@ use network::{
 };
 use num_bigint_dig::{BigInt};
 use tokio::sync::{
-    mpsc::{unbounded_channel, UnboundedReceiver, Receiver, Sender},
+    mpsc::{Receiver, Sender},
     oneshot,
 };
 // use tokio_util::time::DelayQueue;
 use types::{Replica,WrapperMsg};
 
-use consensus::{LargeFieldSSS, FoldingDZKContext};
+use consensus::{LargeFieldSSS, FoldingDZKContext, ingress_channel, ingress_per_sender_capacity, IngressReceiver, SessionMsg, SessionContext, session_id, LargeField, LargeFieldSer};
 
-use ha_crypto::{aes_hash::HashState, LargeField, LargeFieldSer, hash::Hash};
+use ha_crypto::{aes_hash::HashState, hash::Hash};
 
 use crate::{msg::ProtMsg, handlers::Handler, protocol::BatchACSSState};
 
 pub struct Context {
     /// Networking context
-    pub net_send: TcpReliableSender<Replica, WrapperMsg<ProtMsg>, Acknowledgement>,
-    pub net_recv: UnboundedReceiver<WrapperMsg<ProtMsg>>,
+    pub net_send: TcpReliableSender<Replica, WrapperMsg<SessionMsg<ProtMsg>>, Acknowledgement>,
+    pub net_recv: IngressReceiver<WrapperMsg<SessionMsg<ProtMsg>>>,
     
     /// Data context
     pub num_nodes: usize,
@@ -43,6 +43,8 @@ pub struct Context {
 
     /// Secret Key map
     pub sec_key_map: HashMap<Replica, Vec<u8>>,
+    /// Session binding and replay protection
+    pub session: SessionContext,
 
     /// Hardware acceleration context
     pub hash_context: HashState,
@@ -100,13 +102,13 @@ impl Context {
         let my_address = to_socket_address("0.0.0.0", my_port.port());
         
         // Setup networking
-        let (tx_net_to_consensus, rx_net_to_consensus) = unbounded_channel();
-        TcpReceiver::<Acknowledgement, WrapperMsg<ProtMsg>, _>::spawn(
+        let (tx_net_to_consensus, rx_net_to_consensus) = ingress_channel(ingress_per_sender_capacity(), config.sk_map.clone());
+        TcpReceiver::<Acknowledgement, WrapperMsg<SessionMsg<ProtMsg>>, _>::spawn(
             my_address,
             Handler::new(tx_net_to_consensus),
         );
 
-        let consensus_net = TcpReliableSender::<Replica, WrapperMsg<ProtMsg>, Acknowledgement>::with_peers(
+        let consensus_net = TcpReliableSender::<Replica, WrapperMsg<SessionMsg<ProtMsg>>, Acknowledgement>::with_peers(
             consensus_addrs.clone(),
         );
         let (exit_tx, exit_rx) = oneshot::channel();
@@ -164,16 +166,20 @@ impl Context {
             hash_context: hashstate2,
             poly_split_evaluation_map: ss_contexts,
             evaluation_points: (1..config.num_nodes+1).into_iter().collect(),
+            evaluation_domain: None,
             recon_threshold: config.num_faults+1,
             end_degree_threshold: end_degree,
         };
 
+        let session = SessionContext::new(session_id(), "acss_bv", config.id, &consensus_addrs);
+
         tokio::spawn(async move {
             let mut c = Context {
                 net_send: consensus_net,
                 net_recv: rx_net_to_consensus,
                 num_nodes: config.num_nodes,
                 sec_key_map: HashMap::default(),
+                session: session,
                 hash_context: hashstate,
                 myid: config.id,
                 byz: byz,
@@ -224,7 +230,7 @@ impl Context {
                 continue;
             }
             if replica != self.myid {
-                let wrapper_msg = WrapperMsg::new(protmsg.clone(), self.myid, &sec_key.as_slice());
+                let wrapper_msg = WrapperMsg::new(self.session.seal(replica, protmsg.clone()), self.myid, &sec_key.as_slice());
                 let cancel_handler: CancelHandler<Acknowledgement> =
                     self.net_send.send(replica, wrapper_msg).await;
                 self.add_cancel_handler(cancel_handler);
@@ -233,10 +239,10 @@ impl Context {
     }
 
     pub fn add_cancel_handler(&mut self, canc: CancelHandler<Acknowledgement>) {
-        self.cancel_handlers.entry(0).or_default().push(canc);
+        consensus::add_cancel_handler_gc(&mut self.cancel_handlers, canc);
     }
 
-    pub async fn send(&mut self, replica: Replica, wrapper_msg: WrapperMsg<ProtMsg>) {
+    pub async fn send(&mut self, replica: Replica, wrapper_msg: WrapperMsg<SessionMsg<ProtMsg>>) {
         let cancel_handler: CancelHandler<Acknowledgement> =
             self.net_send.send(replica, wrapper_msg).await;
         self.add_cancel_handler(cancel_handler);
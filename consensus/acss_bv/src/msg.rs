@@ -1,5 +1,5 @@
-use consensus::{LargeFieldSSS};
-use ha_crypto::{LargeField, aes_hash::{Proof, HashState, MerkleTree}, LargeFieldSer, hash::Hash};
+use consensus::{LargeFieldSSS, LargeField, LargeFieldSer, Transcript};
+use ha_crypto::{aes_hash::{Proof, HashState, MerkleTree}, hash::Hash};
 use ctrbc::msg::CTRBCMsg;
 use lambdaworks_math::traits::ByteConversion;
 use serde::{Serialize, Deserialize};
@@ -137,10 +137,18 @@ impl RowPolynomialsBatch{
         let mr = MerkleTree::new(roots,hc);
         let hash_two = hc.hash_two(mr.root() , self.blinding_poly_proof.root());
 
-        LargeField::from_bytes_be(hash_two.as_slice()).unwrap()
+        batch_challenge(&hash_two)
     }
 }
 
+/// Challenge the polynomials of a batch are aggregated with in the DZK proof.
+/// `batch_root` commits to the batch and its blinding polynomial. It is longer than an element of small fields, so it is never decoded as one.
+pub fn batch_challenge(batch_root: &Hash) -> LargeField{
+    let mut transcript = Transcript::new(b"acss-bv batch");
+    transcript.append_hash(b"batch root", batch_root);
+    transcript.challenge_field_element(b"batch aggregation")
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct RowPolynomialsBatchSer{
     pub coefficients: Vec<Vec<LargeFieldSer>>,
@@ -292,4 +300,20 @@ pub enum ProtMsg{
         Vec<u8>, // Encrypted shares on row and column
         usize // ACSS Instance ID
     )
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_challenge_accepts_every_root() {
+        // Roots above the modulus of every supported field
+        for root in [[0xffu8; 32], [0x80u8; 32], [0u8; 32]].iter() {
+            assert_eq!(batch_challenge(root), batch_challenge(root));
+        }
+        assert_ne!(batch_challenge(&[1u8; 32]), batch_challenge(&[2u8; 32]));
+        // Separated from the challenges of other protocols over the same root
+        let dzk_challenge: LargeField = consensus::dzk_aggregation_challenge(&[1u8; 32]);
+        assert_ne!(batch_challenge(&[1u8; 32]), dzk_challenge);
+    }
+}
@@ -1,39 +1,28 @@
 use std::sync::Arc;
 
 use crate::{context::Context, msg::ProtMsg};
-use consensus::delay_message_processing;
-use ha_crypto::hash::verf_mac;
+use consensus::{delay_message_processing, SessionMsg};
 //use network::{plaintcp::CancelHandler, Acknowledgement};
 use types::{WrapperMsg};
 
 impl Context {
-    // This function verifies the Message Authentication Code (MAC) of a sent message
-    // A node cannot impersonate as another node because of MACs
-    pub fn check_proposal(&self, wrapper_msg: Arc<WrapperMsg<ProtMsg>>) -> bool {
-        // validate MAC
-        let byte_val =
-            bincode::serialize(&wrapper_msg.protmsg).expect("Failed to serialize object");
-        let sec_key = match self.sec_key_map.get(&wrapper_msg.clone().sender) {
-            Some(val) => val,
-            None => {
-                panic!("Secret key not available, this shouldn't happen")
-            }
-        };
-        if !verf_mac(&byte_val, &sec_key.as_slice(), &wrapper_msg.mac) {
-            log::warn!("MAC Verification failed.");
+    // The MAC was verified against the key of the sender when the message entered the ingress queue
+    pub fn check_proposal(&mut self, wrapper_msg: Arc<WrapperMsg<SessionMsg<ProtMsg>>>) -> bool {
+        // Reject messages of other sessions and replayed messages
+        if !self.session.accept(wrapper_msg.sender, &wrapper_msg.protmsg){
             return false;
         }
         true
     }
 
-    pub(crate) async fn process_msg(&mut self, wrapper_msg: WrapperMsg<ProtMsg>) {
+    pub(crate) async fn process_msg(&mut self, wrapper_msg: WrapperMsg<SessionMsg<ProtMsg>>) {
         log::trace!("Received protocol msg: {:?}", wrapper_msg);
         delay_message_processing().await;
         let msg = Arc::new(wrapper_msg.clone());
 
-        // Verify the message's authenticity before proceeding
+        // Drop messages of other sessions and replays before proceeding
         if self.check_proposal(msg) {
-            match wrapper_msg.clone().protmsg {
+            match wrapper_msg.clone().protmsg.msg {
                 // ProtMsg::Init(enc_shares, num_secrets, comm, polynomial, dealer, instance_id) => {
                 //     // RBC initialized
                 //     log::debug!("Received Init for instance id {} from node : {}", instance_id, dealer);
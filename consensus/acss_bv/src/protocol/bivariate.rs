@@ -1,7 +1,7 @@
 use std::collections::{HashSet, HashMap};
 
-use consensus::LargeFieldSSS;
-use ha_crypto::{LargeField, pseudorandom_lf, hash::Hash, rand_field_element};
+use consensus::{LargeFieldSSS, cached_inverse_vandermonde, LargeField, pseudorandom_lf, rand_field_element};
+use ha_crypto::hash::Hash;
 use types::Replica;
 
 use crate::{Context, msg::{Commitment, PointsBV}};
@@ -170,8 +170,7 @@ impl Context{
         indices_vec.sort();
         let mut indices_lf: Vec<LargeField> = indices_verified_points.iter().map(|el| LargeField::from(*el as u64)).collect();
         indices_lf.sort();
-        let vandermonde_matrix = LargeFieldSSS::vandermonde_matrix(indices_lf);
-        let inverse_vandermonde = LargeFieldSSS::inverse_vandermonde(vandermonde_matrix);
+        let inverse_vandermonde = cached_inverse_vandermonde(&indices_lf);
 
         // Shares and nonces of each replica
         let mut share_map: Vec<(Vec<Vec<LargeField>>, Vec<LargeField>)> = Vec::new();
@@ -1,5 +1,5 @@
-use consensus::reconstruct_data;
-use ha_crypto::{hash::{Hash}, aes_hash::{MerkleTree, Proof}, decrypt, encrypt, LargeField};
+use consensus::{reconstruct_data, LargeField};
+use ha_crypto::{hash::{Hash}, aes_hash::{MerkleTree, Proof}, decrypt, encrypt};
 use ctrbc::CTRBCMsg;
 use network::{plaintcp::CancelHandler, Acknowledgement};
 
@@ -186,7 +186,7 @@ impl Context{
                     let enc_share = encrypt(secret_key, bincode::serialize(&ser_share).unwrap());
 
                     let ready_msg = ProtMsg::Ready(rbc_msg.clone(), enc_share, instance_id);
-                    let wrapper_msg = WrapperMsg::new(ready_msg, self.myid, &secret_key);
+                    let wrapper_msg = WrapperMsg::new(self.session.seal(recipient, ready_msg), self.myid, &secret_key);
                     let cancel_handler: CancelHandler<Acknowledgement> = self.net_send.send(recipient, wrapper_msg).await;
                     self.add_cancel_handler(cancel_handler);
                 }
@@ -1,13 +1,13 @@
-use std::{time::{SystemTime, UNIX_EPOCH}, collections::HashMap};
+use std::{time::{SystemTime, UNIX_EPOCH}, collections::HashMap, sync::Arc};
 
-use consensus::{get_shards, LargeFieldSSS};
-use ha_crypto::{LargeField, aes_hash::{HashState, MerkleTree}, decrypt, encrypt, hash::Hash, pseudorandom_lf, rand_field_element};
+use consensus::{get_shards, LargeFieldSSS, cached_inverse_vandermonde, LargeField, pseudorandom_lf, rand_field_element};
+use ha_crypto::{aes_hash::{HashState, MerkleTree}, decrypt, encrypt, hash::Hash};
 use ctrbc::CTRBCMsg;
 use lambdaworks_math::traits::ByteConversion;
 use network::{plaintcp::CancelHandler, Acknowledgement};
 use rayon::prelude::IntoParallelRefIterator;
 use types::{WrapperMsg, Replica};
-use crate::{Context, msg::{RowPolynomialsBatch, Commitment, RowPolynomialsBatchSer, ProtMsg, PointsBV}, protocol::BatchACSSState};
+use crate::{Context, msg::{RowPolynomialsBatch, Commitment, RowPolynomialsBatchSer, ProtMsg, PointsBV, batch_challenge}, protocol::BatchACSSState};
 use rayon::prelude::{ParallelIterator};
 
 impl Context{
@@ -36,8 +36,7 @@ impl Context{
         //let vandermonde_matrix_shares_ht = self.large_field_uv_sss.vandermonde_matrix(&eval_point_indices_lf);
         //let inverse_vandermonde_shares = self.large_field_uv_sss.inverse_vandermonde(vandermonde_matrix_shares_ht);
 
-        let vandermonde_matrix_ht =  LargeFieldSSS::vandermonde_matrix(ht_indices);
-        let inverse_vandermonde = LargeFieldSSS::inverse_vandermonde(vandermonde_matrix_ht);
+        let inverse_vandermonde = cached_inverse_vandermonde(&ht_indices);
 
         // Parallelize this part using tokio
         let num_cores = 4;
@@ -183,7 +182,7 @@ impl Context{
             let blinding_root = blinding_mt.root();
 
             let master_root_batch = self.hash_context.hash_two(master_root, blinding_root);
-            let master_root_lf = LargeField::from_bytes_be(master_root_batch.as_slice()).unwrap();
+            let master_root_lf = batch_challenge(&master_root_batch);
             
             
             // Generate Distributed ZK polynomial
@@ -307,7 +306,7 @@ impl Context{
             let enc_share = encrypt(&secret_key, ser_share_msg);
 
             let init_msg = ProtMsg::Init(enc_share, commitment.clone(), self.myid, instance_id);
-            let wrapper_msg = WrapperMsg::new(init_msg,self.myid, secret_key.as_slice());
+            let wrapper_msg = WrapperMsg::new(self.session.seal(rep, init_msg),self.myid, secret_key.as_slice());
 
             let cancel_handler: CancelHandler<Acknowledgement> = self.net_send.send(rep, wrapper_msg).await;
             self.add_cancel_handler(cancel_handler);
@@ -338,7 +337,7 @@ impl Context{
         batched_secrets: Vec<Vec<LargeField>>, 
         instance_id: usize, 
         batch_index: usize, 
-        inverse_vandermonde: Vec<Vec<LargeField>>,
+        inverse_vandermonde: Arc<Vec<Vec<LargeField>>>,
         num_faults: usize,
         num_nodes: usize,
         large_field_bv_sss: LargeFieldSSS,
@@ -601,7 +600,7 @@ impl Context{
             };
 
             let echo = ProtMsg::Echo(rbc_msg, enc_msg, instance_id);
-            let wrapper_msg = WrapperMsg::new(echo,self.myid, secret_key_party.as_slice());
+            let wrapper_msg = WrapperMsg::new(self.session.seal(rep, echo),self.myid, secret_key_party.as_slice());
 
             let cancel_handler: CancelHandler<Acknowledgement> = self.net_send.send(rep, wrapper_msg).await;
             self.add_cancel_handler(cancel_handler);
@@ -1,11 +1,11 @@
-use consensus::reconstruct_data;
-use ha_crypto::{decrypt, hash::{Hash}, aes_hash::{MerkleTree, Proof}, encrypt, LargeField};
+use consensus::{reconstruct_data, LargeField};
+use ha_crypto::{decrypt, hash::{Hash}, aes_hash::{MerkleTree, Proof}, encrypt};
 use ctrbc::CTRBCMsg;
 use lambdaworks_math::traits::ByteConversion;
 use network::{plaintcp::CancelHandler, Acknowledgement};
 use types::{Replica, WrapperMsg};
 
-use crate::{Context, msg::{PointsBV, PointsBVSer, ProtMsg, Commitment}, protocol::BatchACSSState};
+use crate::{Context, msg::{PointsBV, PointsBVSer, ProtMsg, Commitment, batch_challenge}, protocol::BatchACSSState};
 
 impl Context{
     pub async fn process_ready(self: &mut Context, ctrbc_msg: CTRBCMsg, enc_share: Vec<u8>, ready_sender: Replica, instance_id: usize){
@@ -123,7 +123,7 @@ impl Context{
                             let enc_share = encrypt(secret_key, bincode::serialize(&ser_share).unwrap());
 
                             let ready_msg = ProtMsg::Ready(rbc_msg.clone(), enc_share, instance_id);
-                            let wrapper_msg = WrapperMsg::new(ready_msg, self.myid, &secret_key);
+                            let wrapper_msg = WrapperMsg::new(self.session.seal(recipient, ready_msg), self.myid, &secret_key);
                             let cancel_handler: CancelHandler<Acknowledgement> = self.net_send.send(recipient, wrapper_msg).await;
                             self.add_cancel_handler(cancel_handler);
                         }
@@ -178,7 +178,7 @@ impl Context{
                     let blinding_commitment = batch_blinding_commitments[self.myid].clone();
                     let blinding_root = MerkleTree::new(batch_blinding_commitments, &self.hash_context).root();
                     let master_root_batch = self.hash_context.hash_two(dzk_root, blinding_root);
-                    let master_root_lf = LargeField::from_bytes_be(master_root_batch.as_slice()).unwrap();
+                    let master_root_lf = batch_challenge(&master_root_batch);
                     
                     // Generate DZK polynomial
                     let mut agg_value = LargeField::from(0);
@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 
-use ha_crypto::{LargeField, hash::Hash, aes_hash::MerkleTree};
+use ha_crypto::{hash::Hash, aes_hash::MerkleTree};
+use consensus::LargeField;
 use ctrbc::RBCState;
 use types::Replica;
 
@@ -0,0 +1,48 @@
+//! Micro-benchmarks of the polynomial grouping and commitments of the ACSS dealer.
+//! Run with `cargo bench -p acss_ske`. The committee sizes and batch sizes default to
+//! `BENCH_NODES=16,64,256` and `BENCH_BATCH=100,1000` and can be overridden through these variables.
+use acss_ske::Context;
+use consensus::{FieldType, LargeField, ProtocolField};
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use ha_crypto::aes_hash::HashState;
+use lambdaworks_math::polynomial::Polynomial;
+
+fn params(var: &str, default: &[usize]) -> Vec<usize> {
+    match std::env::var(var) {
+        Ok(list) => list.split(',').map(|el| el.trim().parse::<usize>().expect("Unable to parse benchmark parameter")).collect(),
+        Err(_) => default.to_vec(),
+    }
+}
+
+fn random_polynomials(count: usize, degree: usize) -> Vec<Polynomial<LargeField>> {
+    (0..count).map(|_| Polynomial::new(&(0..degree + 1).map(|_| FieldType::random_element()).collect::<Vec<LargeField>>())).collect()
+}
+
+fn bench_public_reconstruction(c: &mut Criterion) {
+    let mut group = c.benchmark_group("acss_dealer");
+    group.sample_size(10);
+    let hash_context = HashState::new([5u8; 16], [29u8; 16], [23u8; 16]);
+    for num_nodes in params("BENCH_NODES", &[16, 64, 256]) {
+        let num_faults = (num_nodes - 1) / 3;
+        let evaluation_points: Vec<LargeField> = (1..num_nodes as u64 + 1).map(LargeField::from).collect();
+        for batch in params("BENCH_BATCH", &[100, 1000]) {
+            let polynomials = random_polynomials(batch, num_faults);
+            group.throughput(Throughput::Elements(batch as u64));
+            group.bench_with_input(BenchmarkId::new(format!("group_polynomials_for_public_reconstruction/n={}", num_nodes), batch), &polynomials, |b, polynomials| {
+                b.iter(|| Context::group_polynomials_for_public_reconstruction(polynomials.clone(), evaluation_points.clone(), num_faults + 1))
+            });
+
+            // Merkle commitments over the grouped polynomials, one tree per evaluation point of the grouping
+            let grouped = Context::group_polynomials_for_public_reconstruction(polynomials, evaluation_points.clone(), num_faults + 1);
+            let nonce_evaluations: Vec<Vec<LargeField>> = grouped.iter()
+                .map(|_| (0..num_nodes).map(|_| FieldType::random_element()).collect()).collect();
+            group.bench_with_input(BenchmarkId::new(format!("compute_commitments/n={}", num_nodes), batch), &grouped, |b, grouped| {
+                b.iter(|| Context::compute_commitments(grouped.clone(), evaluation_points.clone(), nonce_evaluations.clone(), &hash_context))
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_public_reconstruction);
+criterion_main!(benches);
@@ -1,5 +1,5 @@
 use std::{
-    collections::{HashMap},
+    collections::{HashMap, HashSet, VecDeque},
     net::{SocketAddr, SocketAddrV4},
     time::{SystemTime, UNIX_EPOCH},
 };
@@ -10,27 +10,26 @@ use config::Node;
 
 use fnv::FnvHashMap;
 use ha_crypto::aes_hash::HashState;
-use lambdaworks_math::{ fft::cpu::roots_of_unity::get_powers_of_primitive_root, field::traits::RootsConfig};
 use network::{plaintcp::{CancelHandler, TcpReceiver, TcpReliableSender}, Acknowledgement, Message};
-use consensus::{LargeField, LargeFieldSSS, FoldingDZKContext};
+use consensus::{LargeField, FieldType, LargeFieldSSS, FoldingDZKContext, DZKContext, select_dzk_context, dzk_batch_size, roots_of_unity, InstanceGC, gc_retention, dealing_chunk_size, ingress_channel, ingress_per_sender_capacity, service_channel, IngressReceiver, SessionMsg, SessionContext, session_id, RBCService, RBCBackend, ReliableBroadcast, rbc_backend, AvidThrottle};
 
 use tokio::{sync::{
-    mpsc::{Receiver, Sender, channel, unbounded_channel, UnboundedReceiver},
+    mpsc::{Receiver, Sender},
     oneshot,
-}};
+}, time::{sleep_until, Instant}};
 // use tokio_util::time::DelayQueue;
 use types::{Replica, WrapperMsg};
 
-use ha_crypto::hash::Hash;
+use asks::{ASKSOutput, ASKSService};
 
-use crate::{protocol::{ACSSABState, SymmetricKeyState}, msg::ProtMsg};
+use crate::{protocol::{ACSSABState, BlameState, ChunkedOutput, SymmetricKeyState}, msg::{ACSSBroadcastId, ProtMsg}, ACSSChunkOutput, PubRecChunkOutput};
 
 use crate::Handler;
 
 pub struct Context {
     /// Networking context
-    pub net_send: TcpReliableSender<Replica, WrapperMsg<ProtMsg>, Acknowledgement>,
-    pub net_recv: UnboundedReceiver<WrapperMsg<ProtMsg>>,
+    pub net_send: TcpReliableSender<Replica, WrapperMsg<SessionMsg<ProtMsg>>, Acknowledgement>,
+    pub net_recv: IngressReceiver<WrapperMsg<SessionMsg<ProtMsg>>>,
     
     /// Data context
     pub num_nodes: usize,
@@ -39,6 +38,8 @@ pub struct Context {
 
     /// Secret Key map
     pub sec_key_map: HashMap<Replica, Vec<u8>>,
+    /// Session binding and replay protection
+    pub session: SessionContext,
 
     /// Hardware acceleration context
     pub hash_context: HashState,
@@ -50,8 +51,25 @@ pub struct Context {
     pub symmetric_keys_avid: SymmetricKeyState,
 
     pub acss_ab_state: HashMap<usize,ACSSABState>,
+    /// Lifecycle of ACSS instances in which all dealers settled, pruned after the retention window
+    pub gc: InstanceGC<usize>,
+    /// Lifecycle of each settled dealing keyed by (dealer, instance), its state is dropped after the retention window even if other dealers never settle
+    pub dealer_gc: InstanceGC<(Replica, usize)>,
+    /// Number of secrets dealt per chunk, zero deals every batch in one chunk
+    pub chunk_size: usize,
+    /// Settlement of the chunks of each (instance, dealer), whose outputs are streamed to the parent as they settle
+    pub chunked_outputs: HashMap<(usize, Replica), ChunkedOutput>,
+    /// Chunks of this dealer's dealings waiting to be generated, as (chunk instance, number of chunks, secrets)
+    pub pending_chunks: VecDeque<(usize, usize, Vec<LargeField>)>,
+    /// Chunk instances of this dealer whose own dispersal has not terminated yet
+    pub chunks_in_flight: HashSet<usize>,
     pub avss_state: ACSSABState,
-    pub folding_dzk_context: FoldingDZKContext,
+    pub dzk_context: Box<dyn DZKContext>,
+    /// Dealings whose shares are waiting to be verified in the next DZK batch, keyed by (instance, dealer)
+    pub dzk_batch: HashSet<(usize, Replica)>,
+    pub dzk_batch_size: usize,
+    /// Time at which the pending dealings are verified even if the batch did not fill up
+    pub dzk_batch_deadline: Option<Instant>,
 
     // Maximum number of RBCs that can be initiated by a node. Keep this as an identifier for RBC service. 
     pub threshold: usize,
@@ -63,18 +81,19 @@ pub struct Context {
     
     // Input queue for receiving acss requests with bool field indicating ACSS or AVSS.
     pub inp_acss: Receiver<(usize, Vec<LargeField>)>,
-    pub out_acss: Sender<(usize, Replica, Hash, Option<Vec<LargeField>>)>,
+    pub out_acss: Sender<ACSSChunkOutput>,
+    // Dealers blamed for dealing invalid shares, with the instance in which they were blamed
+    pub out_blame: Sender<(usize, Replica)>,
+    pub blame_state: BlameState,
 
     pub inp_pub_rec_in: Receiver<(usize, Replica)>,
-    pub out_pub_rec_out: Sender<(usize, Replica, Vec<LargeField>)>,
+    pub out_pub_rec_out: Sender<PubRecChunkOutput>,
 
     /// ASKS input and output channels
-    pub asks_inp_channel: Sender<(usize, usize, bool, bool, Option<Vec<LargeField>>, Option<usize>)>,
-    pub asks_recv_out: Receiver<(usize, Replica, Option<Vec<LargeField>>)>,
+    pub asks: ASKSService,
 
     /// CTRBC input and output channels
-    pub inp_ctrbc: Sender<Vec<u8>>,
-    pub recv_out_ctrbc: Receiver<(usize,usize, Vec<u8>)>,
+    pub rbc: RBCService<ACSSBroadcastId>,
 
     /// AVID input and output channels
     pub inp_avid_channel: Sender<Vec<(Replica,Option<Vec<u8>>)>>,
@@ -84,6 +103,7 @@ pub struct Context {
     pub inp_ra_channel: Sender<(usize,usize,usize)>,
     pub recv_out_ra: Receiver<(usize,Replica,usize)>,
 
+    /// Share at the roots of unity and evaluate polynomials with FFTs instead of sharing at 1..n
     pub use_fft: bool,
     // Public reconstruction flag. If false, parties broadcast shares to everyone. 
     // If true, parties use public reconstruction with linear cost
@@ -91,15 +111,18 @@ pub struct Context {
     pub roots_of_unity: Vec<LargeField>,
 
     pub avss_inst_id: usize,
+    /// AVID instances of this dealer, keyed by ACSS instance
+    pub avid_throttle: AvidThrottle<usize, Vec<(Replica,Option<Vec<u8>>)>>,
 }
 
 impl Context {
     pub fn spawn(
         config: Node,
         input_acss: Receiver<(usize,Vec<LargeField>)>, 
-        output_acss: Sender<(usize,Replica,Hash,Option<Vec<LargeField>>)>,
+        output_acss: Sender<ACSSChunkOutput>,
+        output_blame: Sender<(usize, Replica)>,
         input_pubrec: Receiver<(usize, Replica)>,
-        output_pubrec: Sender<(usize, Replica, Vec<LargeField>)>, 
+        output_pubrec: Sender<PubRecChunkOutput>, 
         use_fft: bool,
         lin_or_quad: bool,
         _byz: bool
@@ -138,8 +161,8 @@ impl Context {
         // syncer_map.insert(0, config.client_addr);
 
         // Setup networking
-        let (tx_net_to_consensus, rx_net_to_consensus) = unbounded_channel();
-        TcpReceiver::<Acknowledgement, WrapperMsg<ProtMsg>, _>::spawn(
+        let (tx_net_to_consensus, rx_net_to_consensus) = ingress_channel(ingress_per_sender_capacity(), config.sk_map.clone());
+        TcpReceiver::<Acknowledgement, WrapperMsg<SessionMsg<ProtMsg>>, _>::spawn(
             my_address,
             Handler::new(tx_net_to_consensus),
         );
@@ -154,7 +177,7 @@ impl Context {
         //     SyncHandler::new(tx_net_to_client),
         // );
 
-        let consensus_net = TcpReliableSender::<Replica, WrapperMsg<ProtMsg>, Acknowledgement>::with_peers(
+        let consensus_net = TcpReliableSender::<Replica, WrapperMsg<SessionMsg<ProtMsg>>, Acknowledgement>::with_peers(
             consensus_addrs.clone(),
         );
         // let sync_net =
@@ -200,21 +223,25 @@ impl Context {
             hash_context: hashstate2,
             poly_split_evaluation_map: ss_contexts,
             evaluation_points: (1..config.num_nodes+1).into_iter().collect(),
+            evaluation_domain: if use_fft {Some(Self::gen_roots_of_unity(config.num_nodes))} else {None},
             recon_threshold: config.num_faults+1,
             end_degree_threshold: end_degree,
         };
         
-        let (asks_req_send_channel, asks_req_recv_channel) = channel(10000);
-        let (asks_out_send_channel, asks_out_recv_channel) = channel(10000);
+        let (asks, asks_status) = ASKSService::spawn(asks_config, false);
 
-        let (ctrbc_req_send_channel, ctrbc_req_recv_channel) = channel(10000);
-        let (ctrbc_out_send_channel, ctrbc_out_recv_channel) = channel(10000);
+        let (rbc, ctrbc_status) = RBCService::spawn(
+            rbc_backend(RBCBackend::CTRBC),
+            ctrbc_config,
+            false
+        );
 
-        let (avid_req_send_channel, avid_req_recv_channel) = channel(10000);
-        let (avid_out_send_channel, avid_out_recv_channel) = channel(10000);
+        let (avid_req_send_channel, avid_req_recv_channel) = service_channel();
+        let (avid_out_send_channel, avid_out_recv_channel) = service_channel();
         
-        let (ra_req_send_channel, ra_req_recv_channel) = channel(10000);
-        let (ra_out_send_channel, ra_out_recv_channel) = channel(10000);
+        let (ra_req_send_channel, ra_req_recv_channel) = service_channel();
+        let (ra_out_send_channel, ra_out_recv_channel) = service_channel();
+        let session = SessionContext::new(session_id(), "acss_ske", config.id, &consensus_addrs);
         tokio::spawn(async move {
             let mut c = Context {
                 net_send: consensus_net,
@@ -222,6 +249,7 @@ impl Context {
 
                 num_nodes: config.num_nodes,
                 sec_key_map: HashMap::default(),
+                session: session,
                 hash_context: hashstate,
                 myid: config.id,
                 
@@ -232,8 +260,17 @@ impl Context {
                 symmetric_keys_avid: SymmetricKeyState::new(),
 
                 acss_ab_state: HashMap::default(),
+                gc: InstanceGC::new(gc_retention()),
+                dealer_gc: InstanceGC::new(gc_retention()),
+                chunk_size: dealing_chunk_size(),
+                chunked_outputs: HashMap::default(),
+                pending_chunks: VecDeque::new(),
+                chunks_in_flight: HashSet::default(),
                 avss_state: ACSSABState::new(),
-                folding_dzk_context: folding_context,
+                dzk_context: select_dzk_context(folding_context),
+                dzk_batch: HashSet::default(),
+                dzk_batch_size: dzk_batch_size(),
+                dzk_batch_deadline: None,
 
                 threshold: 10000,
 
@@ -243,17 +280,17 @@ impl Context {
                 num_threads: 4,
                 inp_acss: input_acss,
                 out_acss: output_acss,
+                out_blame: output_blame,
+                blame_state: BlameState::new(),
 
                 inp_pub_rec_in: input_pubrec,
                 out_pub_rec_out: output_pubrec,
 
                 roots_of_unity: Self::gen_roots_of_unity(config.num_nodes),
 
-                asks_inp_channel: asks_req_send_channel,
-                asks_recv_out: asks_out_recv_channel,
+                asks: asks,
 
-                inp_ctrbc: ctrbc_req_send_channel,
-                recv_out_ctrbc: ctrbc_out_recv_channel,
+                rbc: rbc,
 
                 inp_avid_channel: avid_req_send_channel,
                 recv_out_avid: avid_out_recv_channel,
@@ -265,6 +302,7 @@ impl Context {
                 lin_or_quad: lin_or_quad,
 
                 avss_inst_id: 200,
+                avid_throttle: AvidThrottle::new(config.num_nodes),
 
                 // Syncer related stuff
                 // sync_send: sync_net,
@@ -283,22 +321,9 @@ impl Context {
         });
         let mut vector_statuses = Vec::new();
         
-        let _status =  asks::Context::spawn(
-            asks_config, 
-            asks_req_recv_channel, 
-            asks_out_send_channel, 
-            false
-        );
-        vector_statuses.push(_status);
+        vector_statuses.push(asks_status);
         
-        let _status =  ctrbc::Context::spawn(
-            ctrbc_config, 
-            ctrbc_req_recv_channel, 
-            ctrbc_out_send_channel, 
-            false
-        );
-
-        vector_statuses.push(_status);
+        vector_statuses.push(ctrbc_status);
         let _status =  avid::Context::spawn(
             avid_config, 
             avid_req_recv_channel, 
@@ -320,7 +345,7 @@ impl Context {
     pub async fn broadcast(&mut self, protmsg: ProtMsg) {
         let sec_key_map = self.sec_key_map.clone();
         for (replica, sec_key) in sec_key_map.into_iter() {
-            let wrapper_msg = WrapperMsg::new(protmsg.clone(), self.myid, &sec_key.as_slice());
+            let wrapper_msg = WrapperMsg::new(self.session.seal(replica, protmsg.clone()), self.myid, &sec_key.as_slice());
             let cancel_handler: CancelHandler<Acknowledgement> = self.net_send.send(replica, wrapper_msg).await;
             self.add_cancel_handler(cancel_handler);
         }
@@ -331,7 +356,7 @@ impl Context {
         let mut total_bytes = 0;
         let sec_key_map = self.sec_key_map.clone();
         for (replica, sec_key) in sec_key_map.into_iter() {
-            let wrapper_msg = WrapperMsg::new(protmsg.clone(), self.myid, &sec_key.as_slice());
+            let wrapper_msg = WrapperMsg::new(self.session.seal(replica, protmsg.clone()), self.myid, &sec_key.as_slice());
             total_bytes += Bytes::from(wrapper_msg.to_bytes()).len();
             let cancel_handler: CancelHandler<Acknowledgement> = self.net_send.send(replica, wrapper_msg).await;
             self.add_cancel_handler(cancel_handler);
@@ -340,7 +365,7 @@ impl Context {
     }
 
     pub fn add_cancel_handler(&mut self, canc: CancelHandler<Acknowledgement>) {
-        self.cancel_handlers.entry(0).or_default().push(canc);
+        consensus::add_cancel_handler_gc(&mut self.cancel_handlers, canc);
     }
 
     pub async fn run(&mut self) -> Result<()> {
@@ -363,6 +388,9 @@ impl Context {
                     log::debug!("Termination signal received by the server. Exiting.");
                     break
                 },
+                _ = sleep_until(self.dzk_batch_deadline.unwrap_or_else(Instant::now)), if self.dzk_batch_deadline.is_some() => {
+                    self.verify_share_batch().await;
+                },
                 acss_msg = self.inp_acss.recv() =>{
                     let (id,secrets) = acss_msg.ok_or_else(||
                         anyhow!("Networking layer has closed")
@@ -379,12 +407,7 @@ impl Context {
                     let (instance_id, cheating_party) = avss_msg.ok_or_else(||
                         anyhow!("Networking layer has closed")
                     )?;
-                    if self.lin_or_quad{
-                        self.init_pubrec(instance_id, cheating_party).await;
-                    }
-                    else{
-                        self.init_pubrec_quad(instance_id, cheating_party).await;
-                    }
+                    self.init_pubrec_chunks(instance_id, cheating_party).await;
                     // if sharing {
                     //     let secrets = secrets.unwrap();
                     //     log::debug!("Received request to start AVSS for {} secrets at time: {:?}",secrets.len() , SystemTime::now()
@@ -399,29 +422,31 @@ impl Context {
                     //     self.share_validity_oracle(recon_request.0, recon_request.1, recon_request.2).await;
                     // }
                 },
-                asks_msg = self.asks_recv_out.recv() => {
+                asks_msg = self.asks.next_output() => {
                     let asks_msg = asks_msg.ok_or_else(||
                         anyhow!("Networking layer has closed")
                     )?;
-                    if asks_msg.2.is_none() {
-                        log::debug!("Got ASKS termination event from party {:?}", asks_msg.clone());
-                        self.init_symmetric_key_reconstruction(asks_msg.1).await;
-                    }
-                    else{
-                        log::debug!("Got ASKS termination reconstruction event from party {:?}", asks_msg.clone());
-                        self.process_symmetric_key_reconstruction(asks_msg.1, asks_msg.2.unwrap()).await;
+                    match asks_msg {
+                        ASKSOutput::Shared { dealer, .. } => {
+                            log::debug!("Got ASKS termination event from party {}", dealer);
+                            self.init_symmetric_key_reconstruction(dealer).await;
+                        },
+                        ASKSOutput::Reconstructed { dealer, results, .. } => {
+                            log::debug!("Got ASKS termination reconstruction event from party {} {:?}", dealer, results);
+                            let secrets = results.iter().map(|result| result.secret_or_default()).collect();
+                            self.process_symmetric_key_reconstruction(dealer, secrets).await;
+                        }
                     }
                 },
-                ctrbc_msg = self.recv_out_ctrbc.recv() =>{
-                    let ctrbc_msg = ctrbc_msg.ok_or_else(||
+                rbc_msg = self.rbc.deliver() =>{
+                    let rbc_msg = rbc_msg.ok_or_else(||
                         anyhow!("Networking layer has closed")
                     )?;
-                    log::debug!("Received termination event from CTRBC channel from party {} at time: {:?}", ctrbc_msg.1, SystemTime::now()
+                    log::debug!("Received termination event from RBC channel from party {} at time: {:?}", rbc_msg.broadcaster, SystemTime::now()
                                 .duration_since(UNIX_EPOCH)
                                 .unwrap()
                                 .as_millis());
-                    // TODO: Change the -1 after fixing bug in CTRBC in ACSS.rs
-                    self.handle_ctrbc_termination(ctrbc_msg.0-1,ctrbc_msg.1,ctrbc_msg.2).await;
+                    self.handle_ctrbc_termination(rbc_msg.id, rbc_msg.broadcaster, rbc_msg.payload).await;
                 },
                 avid_msg = self.recv_out_avid.recv() =>{
                     let avid_msg = avid_msg.ok_or_else(||
@@ -492,11 +517,9 @@ impl Context {
         Ok(())
     }
 
-    // temporary fix
+    /// Evaluation points of the n parties on the FFT path
     pub fn gen_roots_of_unity(n: usize) -> Vec<LargeField> {
-        let len = n.next_power_of_two();
-        let order = len.trailing_zeros();
-        get_powers_of_primitive_root(order.into(), len, RootsConfig::Natural).unwrap()
+        roots_of_unity::<FieldType>(n)
     }
 }
 
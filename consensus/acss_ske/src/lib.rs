@@ -6,4 +6,7 @@ mod protocol;
 mod msg;
 pub use msg::*;
 
+mod output;
+pub use output::*;
+
 mod process;
\ No newline at end of file
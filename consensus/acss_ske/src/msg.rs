@@ -1,5 +1,5 @@
-use consensus::{LargeFieldSer, DZKProof};
-use ha_crypto::aes_hash::Proof;
+use consensus::{LargeFieldSer, DZKProof, VACommitment, SignedEvidence};
+use ha_crypto::{aes_hash::Proof, hash::Hash};
 use serde::{Serialize, Deserialize};
 use types::Replica;
 
@@ -12,6 +12,58 @@ pub struct AcssSKEShares{
     pub rep: Replica
 }
 
+/// Shares dealt to `recipient` in an instance.
+/// When signatures are enabled, the dealer signs this structure so that the recipient can prove that its shares are invalid.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DealtShares{
+    pub instance_id: usize,
+    pub recipient: Replica,
+    // Symmetric key of the recipient, needed to expand the shares of the first t parties
+    pub key: Vec<u8>,
+    pub shares: AcssSKEShares,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum BlameReason{
+    ShareRootMismatch,
+    ShareCommitment,
+    BlindingRootMismatch,
+    BlindingCommitment,
+    DZKProof,
+    MalformedShares,
+}
+
+/// Complaint against a dealer, broadcast through CTRBC by the party that received invalid shares.
+/// With signatures, the record carries the signed shares of the complainant, which anyone can check against the dealer's commitment.
+/// Without signatures, the dealer is blamed once t+1 parties complain.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BlameRecord{
+    pub dealer: Replica,
+    pub instance_id: usize,
+    pub reason: BlameReason,
+    // Root commitment of the dealer's broadcast commitment
+    pub commitment: Hash,
+    pub evidence: Option<SignedEvidence<DealtShares>>,
+}
+
+/// Id of an ACSS reliable broadcast.
+/// Dealing commitments and complaints live in separate id spaces, so a party's complaints never share an id with its own commitment or with each other.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ACSSBroadcastId{
+    // Commitment of the broadcaster to its dealing of a chunk instance
+    Commitment(usize),
+    // Complaint of the broadcaster against a dealer in a chunk instance
+    Blame(usize, Replica),
+}
+
+/// Messages reliably broadcast under an `ACSSBroadcastId` of the same kind
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum ACSSBroadcast{
+    // Commitment to one chunk of a dealing, with the number of chunks of the dealing
+    Commitment(VACommitment, usize),
+    Blame(BlameRecord),
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub enum ProtMsg{
     PubRec(usize, AcssSKEShares),
@@ -22,27 +74,29 @@ pub enum ProtMsg{
 use async_trait::async_trait;
 use futures_util::SinkExt;
 use network::Acknowledgement;
-use tokio::sync::mpsc::UnboundedSender;
+use consensus::{IngressSender, SessionMsg};
 
 use types::WrapperMsg;
 
 #[derive(Debug, Clone)]
 pub struct Handler {
-    consensus_tx: UnboundedSender<WrapperMsg<ProtMsg>>,
+    consensus_tx: IngressSender<WrapperMsg<SessionMsg<ProtMsg>>>,
 }
 
 impl Handler {
-    pub fn new(consensus_tx: UnboundedSender<WrapperMsg<ProtMsg>>) -> Self {
+    pub fn new(consensus_tx: IngressSender<WrapperMsg<SessionMsg<ProtMsg>>>) -> Self {
         Self { consensus_tx }
     }
 }
 
 #[async_trait]
-impl network::Handler<Acknowledgement, WrapperMsg<ProtMsg>> for Handler {
-    async fn dispatch(&self, msg: WrapperMsg<ProtMsg>, writer: &mut network::Writer<Acknowledgement>) {
+impl network::Handler<Acknowledgement, WrapperMsg<SessionMsg<ProtMsg>>> for Handler {
+    async fn dispatch(&self, msg: WrapperMsg<SessionMsg<ProtMsg>>, writer: &mut network::Writer<Acknowledgement>) {
         // Forward the message
+        // Waits if the queue of this sender is full, delaying the acknowledgement
         let status = self.consensus_tx
-            .send(msg);
+            .send(msg)
+            .await;
         if status.is_err(){
             log::error!("Failed to send consensus message to the channel because of {:?}", status.err().unwrap());
         }
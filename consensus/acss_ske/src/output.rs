@@ -0,0 +1,88 @@
+use std::collections::BTreeMap;
+
+use consensus::LargeField;
+use ha_crypto::{aes_hash::HashState, hash::Hash};
+use types::Replica;
+
+/// Output of one chunk of a dealing, streamed to the parent as soon as the chunk settles.
+/// A failed dealing is reported once with `output` set to None, and none of its chunks are streamed after that.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ACSSChunkOutput{
+    pub instance_id: usize,
+    pub dealer: Replica,
+    pub chunk: usize,
+    pub num_chunks: usize,
+    // Root commitment and shares of the chunk
+    pub output: Option<(Hash, Vec<LargeField>)>,
+}
+
+/// Publicly reconstructed secrets of one chunk of a dealing, streamed to the parent as soon as the chunk is reconstructed
+#[derive(Debug, Clone, PartialEq)]
+pub struct PubRecChunkOutput{
+    pub instance_id: usize,
+    pub dealer: Replica,
+    pub chunk: usize,
+    pub num_chunks: usize,
+    pub secrets: Vec<LargeField>,
+}
+
+/// Running result of the chunks of one dealing, for parents that consume whole dealings.
+/// A chunk is appended once every chunk before it is in, so only chunks that settled out of order are held apart.
+#[derive(Debug, Clone, Default)]
+pub struct ChunkFold{
+    pub roots: Vec<Hash>,
+    pub shares: Vec<LargeField>,
+    out_of_order: BTreeMap<usize, (Hash, Vec<LargeField>)>,
+}
+
+impl ChunkFold{
+    /// Folds in the root and shares of `chunk`. Returns true once all `num_chunks` chunks are in.
+    pub fn add(&mut self, chunk: usize, num_chunks: usize, root: Hash, shares: Vec<LargeField>) -> bool{
+        if chunk >= self.roots.len(){
+            self.out_of_order.insert(chunk, (root, shares));
+        }
+        while let Some((root, shares)) = self.out_of_order.remove(&self.roots.len()){
+            self.roots.push(root);
+            self.shares.extend(shares);
+        }
+        self.roots.len() == num_chunks
+    }
+
+    /// Commitment to the whole dealing. Unchunked dealings keep the root of their only chunk.
+    pub fn root(&self, hash_context: &HashState) -> Hash{
+        if self.roots.len() == 1{
+            self.roots[0]
+        }
+        else{
+            hash_context.do_hash_aes(self.roots.concat().as_slice())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secrets(from: u64, to: u64) -> Vec<LargeField> {
+        (from..to).map(LargeField::from).collect()
+    }
+
+    #[test]
+    fn test_chunks_fold_in_order() {
+        let mut fold = ChunkFold::default();
+        assert!(!fold.add(2, 3, [3; 32], secrets(4, 6)));
+        // Chunk 2 waits for chunk 1
+        assert!(fold.shares.is_empty());
+        assert!(!fold.add(0, 3, [1; 32], secrets(0, 2)));
+        assert_eq!(fold.shares, secrets(0, 2));
+        assert!(fold.add(1, 3, [2; 32], secrets(2, 4)));
+        assert_eq!(fold.shares, secrets(0, 6));
+        assert_eq!(fold.roots, vec![[1; 32], [2; 32], [3; 32]]);
+
+        let hash_context = HashState::new([5u8; 16], [29u8; 16], [23u8; 16]);
+        let mut single = ChunkFold::default();
+        assert!(single.add(0, 1, [7; 32], secrets(0, 2)));
+        assert_eq!(single.root(&hash_context), [7; 32]);
+        assert_eq!(fold.root(&hash_context), hash_context.do_hash_aes([[1u8; 32], [2; 32], [3; 32]].concat().as_slice()));
+    }
+}
@@ -1,36 +1,26 @@
 use std::sync::Arc;
 
 use crate::{context::Context, msg::ProtMsg};
-use ha_crypto::hash::verf_mac;
 use types::{WrapperMsg};
+use consensus::SessionMsg;
 
 impl Context {
-    // This function verifies the Message Authentication Code (MAC) of a sent message
-    // A node cannot impersonate as another node because of MACs
-    pub fn check_proposal(&self, wrapper_msg: Arc<WrapperMsg<ProtMsg>>) -> bool {
-        // validate MAC
-        let byte_val =
-            bincode::serialize(&wrapper_msg.protmsg).expect("Failed to serialize object");
-        let sec_key = match self.sec_key_map.get(&wrapper_msg.clone().sender) {
-            Some(val) => val,
-            None => {
-                panic!("Secret key not available, this shouldn't happen")
-            }
-        };
-        if !verf_mac(&byte_val, &sec_key.as_slice(), &wrapper_msg.mac) {
-            log::warn!("MAC Verification failed.");
+    // The MAC was verified against the key of the sender when the message entered the ingress queue
+    pub fn check_proposal(&mut self, wrapper_msg: Arc<WrapperMsg<SessionMsg<ProtMsg>>>) -> bool {
+        // Reject messages of other sessions and replayed messages
+        if !self.session.accept(wrapper_msg.sender, &wrapper_msg.protmsg){
             return false;
         }
         true
     }
 
-    pub(crate) async fn process_msg(&mut self, wrapper_msg: WrapperMsg<ProtMsg>) {
+    pub(crate) async fn process_msg(&mut self, wrapper_msg: WrapperMsg<SessionMsg<ProtMsg>>) {
         log::trace!("Received protocol msg: {:?}", wrapper_msg);
         let msg = Arc::new(wrapper_msg.clone());
 
-        // Verify the message's authenticity before proceeding
+        // Drop messages of other sessions and replays before proceeding
         if self.check_proposal(msg) {
-            match wrapper_msg.clone().protmsg {
+            match wrapper_msg.clone().protmsg.msg {
                 ProtMsg::PubRec(instance_id, acss_ske_msg) =>{
                     // RBC initialized
                     log::debug!("Received PubRecQuad message for instance id {} from node : {}", instance_id, wrapper_msg.sender);
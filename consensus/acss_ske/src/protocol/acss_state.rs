@@ -5,7 +5,7 @@ use lambdaworks_math::polynomial::Polynomial;
 use consensus::{LargeField, VACommitment};
 use types::Replica;
 
-use crate::msg::AcssSKEShares;
+use crate::msg::{AcssSKEShares, BlameRecord};
 
 #[derive(Clone, Debug)]
 pub struct ACSSABState{
@@ -30,6 +30,11 @@ pub struct ACSSABState{
 
     pub dzk_poly: HashMap<Replica,Polynomial<LargeField>>,
     pub commitment_root_fe: HashMap<Replica, Hash>,
+
+    // Dealer signatures on the shares dealt to this party
+    pub dealt_signatures: HashMap<Replica, Vec<u8>>,
+    // Blame records received before the commitment of the blamed dealer
+    pub pending_blames: Vec<(Replica, BlameRecord)>,
 }
 
 impl ACSSABState{
@@ -51,8 +56,40 @@ impl ACSSABState{
 
             dzk_poly: HashMap::default(),
             commitment_root_fe: HashMap::default(),
+
+            dealt_signatures: HashMap::default(),
+            pending_blames: Vec::new(),
         }
     }
+
+    /// Drops everything kept for the dealing of `dealer`. Its settlement status stays so that it is not delivered twice.
+    pub fn prune_dealer(&mut self, dealer: Replica){
+        self.enc_shares.remove(&dealer);
+        self.shares.remove(&dealer);
+        self.commitments.remove(&dealer);
+        self.verification_status.remove(&dealer);
+        self.public_reconstruction_quad_shares.remove(&dealer);
+        self.public_reconstruction_l1_shares.remove(&dealer);
+        self.public_reconstruction_l2_shares.remove(&dealer);
+        self.dzk_poly.remove(&dealer);
+        self.commitment_root_fe.remove(&dealer);
+        self.dealt_signatures.remove(&dealer);
+        self.pending_blames.retain(|(_, record)| record.dealer != dealer);
+    }
+}
+
+/// Outputs of the chunks of one dealer's dealing in an instance
+#[derive(Clone, Debug, Default)]
+pub struct ChunkedOutput{
+    // Number of chunks announced by the dealer
+    pub num_chunks: Option<usize>,
+    pub inconsistent: bool,
+    // Chunks streamed to the parent
+    pub settled: HashSet<usize>,
+    // Set once a failed chunk failed the dealing
+    pub failed: bool,
+    // Chunks whose publicly reconstructed secrets were streamed to the parent
+    pub reconstructed: HashSet<usize>,
 }
 
 pub struct SymmetricKeyState{
@@ -71,4 +108,43 @@ impl SymmetricKeyState {
             term_asks_recon: HashSet::default(),
         }
     }
-}
\ No newline at end of file
+}
+pub struct BlameState{
+    // Dealings, keyed by (dealer, instance), blamed with a valid record or by t+1 complaints
+    pub blamed: HashSet<(Replica, usize)>,
+    // Complainants without a provable record, per dealing
+    pub complaints: HashMap<(Replica, usize), HashSet<Replica>>,
+}
+
+impl BlameState {
+    pub fn new() -> Self {
+        Self {
+            blamed: HashSet::default(),
+            complaints: HashMap::default(),
+        }
+    }
+
+    pub fn is_blamed(&self, dealer: Replica, instance_id: usize) -> bool {
+        self.blamed.contains(&(dealer, instance_id))
+    }
+
+    /// Records a complaint without a provable record. Returns true once `threshold` distinct parties complained about the dealing.
+    pub fn add_complaint(&mut self, dealer: Replica, instance_id: usize, complainant: Replica, threshold: usize) -> bool {
+        let complaints = self.complaints.entry((dealer, instance_id)).or_default();
+        complaints.insert(complainant);
+        complaints.len() >= threshold
+    }
+
+    /// Marks the dealing as blamed. Returns false if it already was.
+    pub fn blame(&mut self, dealer: Replica, instance_id: usize) -> bool {
+        self.complaints.remove(&(dealer, instance_id));
+        self.blamed.insert((dealer, instance_id))
+    }
+
+    /// Drops the complaints and blames of pruned instances.
+    pub fn prune(&mut self, instances: &[usize]) {
+        let instances: HashSet<usize> = instances.iter().cloned().collect();
+        self.blamed.retain(|(_, instance_id)| !instances.contains(instance_id));
+        self.complaints.retain(|(_, instance_id), _| !instances.contains(instance_id));
+    }
+}
@@ -8,6 +8,22 @@ impl Context{
 
             let (instance_id,enc_shares) : (usize,Vec<u8>) = bincode::deserialize(content.unwrap().as_slice()).unwrap();
             
+            if sender == self.myid && self.avid_throttle.complete(&instance_id){
+                // Own dispersal terminated, schedule the next AVID instances
+                self.throttle_avid_instances().await;
+            }
+            if sender == self.myid && self.chunks_in_flight.remove(&instance_id){
+                // Make room for the next chunk of this dealer
+                self.deal_pending_chunks().await;
+            }
+            if self.gc.is_collected(&instance_id){
+                log::debug!("ACSS instance {} already terminated and pruned, skipping message", instance_id);
+                return;
+            }
+            if self.dealer_gc.is_collected(&(sender, instance_id)){
+                log::debug!("Dealing of party {} in ACSS instance {} already settled and pruned, skipping message", sender, instance_id);
+                return;
+            }
             if !self.acss_ab_state.contains_key(&instance_id) {
                 let acss_state = ACSSABState::new();
                 self.acss_ab_state.insert(instance_id, acss_state);
@@ -0,0 +1,274 @@
+use consensus::{signed_bytes, SignatureKeys, SignedEvidence, VACommitment, ReliableBroadcast};
+use types::Replica;
+
+use crate::{Context, msg::{ACSSBroadcast, ACSSBroadcastId, BlameReason, BlameRecord, DealtShares}, protocol::{ACSSABState, chunk_key, split_chunk_instance_id}};
+
+impl Context{
+    pub fn sign_dealt_shares(&self, dealt: &DealtShares) -> Option<Vec<u8>>{
+        let keys = self.session.signature_keys.as_ref()?;
+        let bytes = signed_bytes(self.session.session_id, self.session.service.as_str(), self.myid, dealt);
+        Some(keys.sign(bytes.as_slice()))
+    }
+
+    /// Rejects the shares of a dealer and broadcasts a complaint through CTRBC.
+    pub async fn complain(&mut self, dealer: Replica, instance_id: usize, reason: BlameReason){
+        let acss_ab_state = match self.acss_ab_state.get_mut(&instance_id){
+            Some(state) => state,
+            None => {
+                return;
+            }
+        };
+        if acss_ab_state.verification_status.contains_key(&dealer){
+            return;
+        }
+        acss_ab_state.verification_status.insert(dealer, false);
+
+        let commitment = acss_ab_state.commitment_root_fe.get(&dealer).cloned().unwrap_or([0; 32]);
+        let evidence = match (
+            acss_ab_state.shares.get(&dealer),
+            acss_ab_state.dealt_signatures.get(&dealer),
+            self.symmetric_keys_avid.keys_to_me.get(&dealer)
+        ){
+            (Some(shares), Some(signature), Some(key)) => {
+                let mut shares = shares.clone();
+                if self.myid < self.num_faults{
+                    // The dealer signed only the Merkle proofs, the evaluations were expanded from the key
+                    shares.evaluations.0.clear();
+                    shares.evaluations.1.clear();
+                    shares.blinding_evaluations.0.clear();
+                    shares.blinding_evaluations.1.clear();
+                }
+                // Revealing our own shares and key is safe, the dealer is faulty
+                Some(SignedEvidence{
+                    signer: dealer,
+                    session_id: self.session.session_id,
+                    service: self.session.service.clone(),
+                    msg: DealtShares{
+                        instance_id: instance_id,
+                        recipient: self.myid,
+                        key: chunk_key(key, split_chunk_instance_id(instance_id).1),
+                        shares: shares,
+                    },
+                    signature: signature.clone(),
+                })
+            },
+            _ => None
+        };
+        log::error!("Complaining against dealer {} in instance {} because of {:?}, provable: {}", dealer, instance_id, reason, evidence.is_some());
+        let blame_record = BlameRecord{
+            dealer: dealer,
+            instance_id: instance_id,
+            reason: reason,
+            commitment: commitment,
+            evidence: evidence,
+        };
+        let ser_blame = bincode::serialize(&ACSSBroadcast::Blame(blame_record)).unwrap();
+        let rbc_status = self.rbc.broadcast(ACSSBroadcastId::Blame(instance_id, dealer), ser_blame).await;
+        if rbc_status.is_err(){
+            log::error!("Failed to broadcast complaint in instance {}: {:?}", instance_id, rbc_status.err().unwrap());
+        }
+        self.check_termination(dealer, instance_id).await;
+    }
+
+    pub async fn process_blame_record(&mut self, complainant: Replica, blame_record: BlameRecord){
+        let dealer = blame_record.dealer;
+        let instance_id = blame_record.instance_id;
+        log::debug!("Received complaint from party {} against dealer {} in instance {}", complainant, dealer, instance_id);
+        if self.blame_state.is_blamed(dealer, instance_id){
+            return;
+        }
+        if blame_record.evidence.is_none(){
+            // At least one of t+1 complainants is honest
+            if self.blame_state.add_complaint(dealer, instance_id, complainant, self.num_faults+1){
+                self.blame_dealer(dealer, instance_id).await;
+            }
+            return;
+        }
+        if self.gc.is_collected(&instance_id){
+            log::debug!("ACSS instance {} already terminated and pruned, skipping complaint", instance_id);
+            return;
+        }
+        if !self.acss_ab_state.contains_key(&instance_id) {
+            let acss_state = ACSSABState::new();
+            self.acss_ab_state.insert(instance_id, acss_state);
+        }
+        let acss_ab_state = self.acss_ab_state.get_mut(&instance_id).unwrap();
+        if !acss_ab_state.commitments.contains_key(&dealer){
+            // Wait for the dealer's commitment
+            acss_ab_state.pending_blames.push((complainant, blame_record));
+            return;
+        }
+        let va_commitment = acss_ab_state.commitments.get(&dealer).unwrap().clone();
+        let root_commitment = acss_ab_state.commitment_root_fe.get(&dealer).unwrap().clone();
+        if root_commitment != blame_record.commitment{
+            log::warn!("Complaint from party {} against dealer {} refers to a different commitment", complainant, dealer);
+            return;
+        }
+        if self.verify_blame_evidence(complainant, &blame_record, &va_commitment){
+            self.blame_dealer(dealer, instance_id).await;
+        }
+        else{
+            log::warn!("Invalid complaint from party {} against dealer {} in instance {}", complainant, dealer, instance_id);
+        }
+    }
+
+    /// A record is valid if the dealer signed the complainant's shares and these shares fail verification against the dealer's commitment.
+    pub fn verify_blame_evidence(&self, complainant: Replica, blame_record: &BlameRecord, va_commitment: &VACommitment) -> bool{
+        let keys = match self.session.signature_keys.as_ref(){
+            Some(keys) => keys,
+            None => {
+                log::warn!("Received a signed complaint but signatures are not enabled");
+                return false;
+            }
+        };
+        let dealt = match check_blame_evidence(keys, self.session.session_id, self.session.service.as_str(), complainant, blame_record){
+            Some(dealt) => dealt,
+            None => {
+                return false;
+            }
+        };
+        let mut shares = dealt.shares.clone();
+        if complainant < self.num_faults{
+            Self::expand_shares_from_key(dealt.key.clone(), va_commitment.tot_shares, self.num_nodes, &mut shares);
+        }
+        self.check_dealt_shares(shares, va_commitment, complainant).is_err()
+    }
+
+    pub async fn blame_dealer(&mut self, dealer: Replica, chunk_instance_id: usize){
+        if !self.blame_state.blame(dealer, chunk_instance_id){
+            return;
+        }
+        let (instance_id, chunk) = split_chunk_instance_id(chunk_instance_id);
+        log::error!("Dealer {} blamed in chunk {} of instance {}", dealer, chunk, instance_id);
+        let _status = self.out_blame.send((instance_id, dealer)).await;
+    }
+}
+
+/// Checks that the evidence of a blame record is the blamed dealer's signature on the shares it dealt to the complainant in this instance and session.
+/// Returns the signed shares, which still have to fail verification against the dealer's commitment for the record to be valid.
+pub fn check_blame_evidence<'a>(keys: &SignatureKeys, session_id: u64, service: &str, complainant: Replica, blame_record: &'a BlameRecord) -> Option<&'a DealtShares>{
+    let evidence = blame_record.evidence.as_ref()?;
+    if evidence.signer != blame_record.dealer
+        || evidence.msg.recipient != complainant
+        || evidence.msg.instance_id != blame_record.instance_id
+        || evidence.session_id != session_id
+        || evidence.service != service{
+        return None;
+    }
+    if !evidence.verify(keys){
+        return None;
+    }
+    Some(&evidence.msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::{msg::AcssSKEShares, protocol::BlameState};
+
+    const SESSION_ID: u64 = 7;
+    const SERVICE: &str = "acss";
+
+    fn keys(myid: Replica) -> SignatureKeys {
+        let secret_keys: HashMap<Replica, [u8; 32]> = (0..4).map(|rep| (rep, [rep as u8 + 1; 32])).collect();
+        SignatureKeys::from_secret_keys(myid, &secret_keys)
+    }
+
+    fn signed_record(dealer: Replica, recipient: Replica, instance_id: usize) -> BlameRecord{
+        let dealt = DealtShares{
+            instance_id: instance_id,
+            recipient: recipient,
+            key: vec![1u8; 16],
+            shares: AcssSKEShares{
+                evaluations: (Vec::new(), Vec::new(), Vec::new()),
+                blinding_evaluations: (Vec::new(), Vec::new(), Vec::new()),
+                dzk_iters: Vec::new(),
+                rep: recipient,
+            },
+        };
+        let bytes = signed_bytes(SESSION_ID, SERVICE, dealer, &dealt);
+        BlameRecord{
+            dealer: dealer,
+            instance_id: instance_id,
+            reason: BlameReason::ShareCommitment,
+            commitment: [0; 32],
+            evidence: Some(SignedEvidence{
+                signer: dealer,
+                session_id: SESSION_ID,
+                service: SERVICE.to_string(),
+                msg: dealt,
+                signature: keys(dealer).sign(bytes.as_slice()),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_blame_evidence_accepted_for_shares_signed_by_the_dealer() {
+        let record = signed_record(0, 2, 5);
+        let dealt = check_blame_evidence(&keys(3), SESSION_ID, SERVICE, 2, &record).unwrap();
+        assert_eq!(dealt.recipient, 2);
+        assert_eq!(dealt.instance_id, 5);
+    }
+
+    #[test]
+    fn test_blame_evidence_rejected_unless_bound_to_dealer_complainant_and_instance() {
+        let keys = keys(3);
+        let record = signed_record(0, 2, 5);
+        // Shares dealt to someone else
+        assert!(check_blame_evidence(&keys, SESSION_ID, SERVICE, 1, &record).is_none());
+        // Other session or service
+        assert!(check_blame_evidence(&keys, SESSION_ID + 1, SERVICE, 2, &record).is_none());
+        assert!(check_blame_evidence(&keys, SESSION_ID, "dpss", 2, &record).is_none());
+
+        // Shares of another instance
+        let mut other_instance = record.clone();
+        other_instance.instance_id = 6;
+        assert!(check_blame_evidence(&keys, SESSION_ID, SERVICE, 2, &other_instance).is_none());
+
+        // Signed by a party other than the blamed dealer
+        let mut other_dealer = signed_record(1, 2, 5);
+        other_dealer.dealer = 0;
+        assert!(check_blame_evidence(&keys, SESSION_ID, SERVICE, 2, &other_dealer).is_none());
+
+        // Tampered shares
+        let mut tampered = record.clone();
+        tampered.evidence.as_mut().unwrap().msg.key = vec![2u8; 16];
+        assert!(check_blame_evidence(&keys, SESSION_ID, SERVICE, 2, &tampered).is_none());
+
+        let mut unsigned = record;
+        unsigned.evidence = None;
+        assert!(check_blame_evidence(&keys, SESSION_ID, SERVICE, 2, &unsigned).is_none());
+    }
+
+    #[test]
+    fn test_unprovable_complaints_blame_a_dealing_after_t_plus_one_parties() {
+        let mut blame_state = BlameState::new();
+        // n = 4, t = 1
+        assert!(!blame_state.add_complaint(0, 5, 1, 2));
+        // The same complainant twice does not count
+        assert!(!blame_state.add_complaint(0, 5, 1, 2));
+        // Complaints about other instances of the dealer do not count either
+        assert!(!blame_state.add_complaint(0, 6, 2, 2));
+        assert!(blame_state.add_complaint(0, 5, 3, 2));
+
+        assert!(blame_state.blame(0, 5));
+        assert!(!blame_state.blame(0, 5));
+        assert!(blame_state.is_blamed(0, 5));
+        assert!(!blame_state.is_blamed(0, 6));
+        assert!(!blame_state.is_blamed(1, 5));
+    }
+
+    #[test]
+    fn test_blame_state_pruned_with_its_instances() {
+        let mut blame_state = BlameState::new();
+        blame_state.blame(0, 5);
+        blame_state.add_complaint(1, 5, 2, 2);
+        blame_state.add_complaint(1, 6, 2, 2);
+        blame_state.prune(&[5]);
+        assert!(!blame_state.is_blamed(0, 5));
+        assert!(!blame_state.complaints.contains_key(&(1, 5)));
+        assert!(blame_state.complaints.contains_key(&(1, 6)));
+    }
+}
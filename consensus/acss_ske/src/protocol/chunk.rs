@@ -0,0 +1,281 @@
+use std::collections::{HashMap, HashSet};
+
+use consensus::{LargeField, sub_instance_id, split_sub_instance_id};
+use ha_crypto::hash::Hash;
+use types::Replica;
+
+use crate::{ACSSChunkOutput, Context, PubRecChunkOutput, protocol::ChunkedOutput};
+
+/// Maximum number of chunks of a dealing
+pub const MAX_CHUNKS: usize = 1 << 16;
+
+/// Maximum number of chunks a dealer has generated whose own dispersal has not terminated yet.
+/// Later chunks are only generated once earlier ones leave, so the dealer holds the evaluations of at most this many chunks.
+pub const MAX_CHUNKS_IN_FLIGHT: usize = 4;
+
+/// Chunks of a dealing run as sub-instances of the dealing, see `consensus::sub_instance_id`.
+/// The first chunk keeps the instance id, so unchunked dealings use the plain instance ids.
+pub fn chunk_instance_id(instance_id: usize, chunk: usize) -> usize{
+    sub_instance_id(instance_id, chunk)
+}
+
+/// Returns the instance and the chunk of a sub-instance
+pub fn split_chunk_instance_id(chunk_instance_id: usize) -> (usize, usize){
+    split_sub_instance_id(chunk_instance_id)
+}
+
+/// Key from which the PRF-derived shares of a chunk are expanded.
+/// The first chunk uses the key itself, so that single-chunk dealings are unchanged.
+pub fn chunk_key(key: &Vec<u8>, chunk: usize) -> Vec<u8>{
+    let mut key = key.clone();
+    if chunk > 0{
+        key.extend((chunk as u64).to_be_bytes());
+    }
+    key
+}
+
+impl Context{
+    pub fn chunk_keys(&self, chunk: usize) -> HashMap<Replica, Vec<u8>>{
+        self.symmetric_keys_avid.keys_from_me.iter().map(|(rep, key)| (*rep, chunk_key(key, chunk))).collect()
+    }
+
+    pub fn split_into_chunks(&self, secrets: Vec<LargeField>) -> Vec<Vec<LargeField>>{
+        split_into_chunks(secrets, self.chunk_size, self.num_faults)
+    }
+
+    /// Records the number of chunks announced in the commitment of a chunk. Returns false if the dealer announced inconsistent chunk counts.
+    pub fn record_num_chunks(&mut self, chunk_instance_id: usize, dealer: Replica, num_chunks: usize) -> bool{
+        let (instance_id, chunk) = split_chunk_instance_id(chunk_instance_id);
+        let recorded = self.chunked_outputs.entry((instance_id, dealer)).or_default().record_num_chunks(chunk, num_chunks);
+        if !recorded{
+            log::error!("Dealer {} announced inconsistent chunk counts in instance {}", dealer, instance_id);
+        }
+        recorded
+    }
+
+    /// Queues the chunks of a dealing and generates as many of them as the in-flight cap allows.
+    pub async fn deal_chunks(&mut self, instance_id: usize, chunks: Vec<Vec<LargeField>>){
+        let num_chunks = chunks.len();
+        for (chunk, chunk_secrets) in chunks.into_iter().enumerate(){
+            self.pending_chunks.push_back((chunk_instance_id(instance_id, chunk), num_chunks, chunk_secrets));
+        }
+        self.deal_pending_chunks().await;
+    }
+
+    /// Generates queued chunks while fewer than `MAX_CHUNKS_IN_FLIGHT` of this dealer's chunks are being dispersed.
+    /// A chunk is committed to and handed to CTRBC and AVID before the next one is generated, so dispersal of a chunk overlaps the computation of the next.
+    pub async fn deal_pending_chunks(&mut self){
+        while self.chunks_in_flight.len() < MAX_CHUNKS_IN_FLIGHT{
+            let (chunk_instance_id, num_chunks, chunk_secrets) = match self.pending_chunks.pop_front(){
+                Some(pending) => pending,
+                None => break,
+            };
+            let (instance_id, chunk) = split_chunk_instance_id(chunk_instance_id);
+            log::debug!("Dealing chunk {} of {} with {} secrets in instance {}", chunk, num_chunks, chunk_secrets.len(), instance_id);
+            self.chunks_in_flight.insert(chunk_instance_id);
+            self.deal_chunk(chunk_secrets, chunk_instance_id, num_chunks).await;
+        }
+    }
+
+    /// Streams the output of a chunk to the parent as soon as it settles.
+    /// The dealing fails as a whole once one of its chunks fails, which is reported once.
+    pub async fn deliver_chunk(&mut self, chunk_instance_id: usize, dealer: Replica, output: Option<(Hash, Vec<LargeField>)>){
+        let (instance_id, chunk) = split_chunk_instance_id(chunk_instance_id);
+        let chunked_output = self.chunked_outputs.entry((instance_id, dealer)).or_default();
+        let settlement = chunked_output.settle_chunk(chunk, output);
+        let num_chunks = chunked_output.num_chunks.unwrap_or(0);
+        let output = match settlement{
+            ChunkSettlement::Ignored => return,
+            ChunkSettlement::Failed => None,
+            ChunkSettlement::Settled(root, shares) => {
+                log::debug!("Chunk {} of {} of dealer {} in instance {} settled", chunk, num_chunks, dealer, instance_id);
+                Some((root, shares))
+            }
+        };
+        let _status = self.out_acss.send(ACSSChunkOutput {
+            instance_id: instance_id,
+            dealer: dealer,
+            chunk: chunk,
+            num_chunks: num_chunks,
+            output: output,
+        }).await;
+    }
+
+    /// Drops the chunk bookkeeping of instances whose every chunk was collected by the instance GC
+    pub fn prune_chunked_outputs(&mut self, expired: &[usize]){
+        let live: HashSet<usize> = self.acss_ab_state.keys().map(|id| split_chunk_instance_id(*id).0).collect();
+        let expired: HashSet<usize> = expired.iter().map(|id| split_chunk_instance_id(*id).0).filter(|instance_id| !live.contains(instance_id)).collect();
+        self.chunked_outputs.retain(|(instance_id, _dealer), _| !expired.contains(instance_id));
+    }
+
+    /// Publicly reconstructs every chunk of the secrets dealt by `party`.
+    pub async fn init_pubrec_chunks(&mut self, instance_id: usize, party: Replica){
+        let num_chunks = match self.chunked_outputs.get(&(instance_id, party)).and_then(|chunked_output| chunked_output.num_chunks){
+            Some(num_chunks) => num_chunks,
+            None => {
+                log::error!("No commitment from party {} in instance {}, cannot reconstruct", party, instance_id);
+                return;
+            }
+        };
+        for chunk in 0..num_chunks{
+            if self.lin_or_quad{
+                self.init_pubrec(chunk_instance_id(instance_id, chunk), party).await;
+            }
+            else{
+                self.init_pubrec_quad(chunk_instance_id(instance_id, chunk), party).await;
+            }
+        }
+    }
+
+    /// Streams the publicly reconstructed secrets of a chunk to the parent, once per chunk.
+    pub async fn deliver_pub_rec_chunk(&mut self, chunk_instance_id: usize, party: Replica, secrets: Vec<LargeField>){
+        let (instance_id, chunk) = split_chunk_instance_id(chunk_instance_id);
+        let chunked_output: &mut ChunkedOutput = self.chunked_outputs.entry((instance_id, party)).or_default();
+        let num_chunks = match chunked_output.num_chunks{
+            Some(num_chunks) => num_chunks,
+            None => {
+                log::error!("Reconstructed chunk {} of party {} in instance {} without its commitment", chunk, party, instance_id);
+                return;
+            }
+        };
+        if !chunked_output.reconstructed.insert(chunk){
+            return;
+        }
+        let _status = self.out_pub_rec_out.send(PubRecChunkOutput {
+            instance_id: instance_id,
+            dealer: party,
+            chunk: chunk,
+            num_chunks: num_chunks,
+            secrets: secrets,
+        }).await;
+    }
+}
+
+/// Splits a batch of secrets into chunks of at most `chunk_size` secrets, rounded down to a multiple of t+1.
+/// A chunk size of zero deals the batch in one chunk.
+pub fn split_into_chunks(secrets: Vec<LargeField>, chunk_size: usize, num_faults: usize) -> Vec<Vec<LargeField>>{
+    let group = num_faults + 1;
+    if chunk_size == 0 || secrets.len() <= chunk_size{
+        return vec![secrets];
+    }
+    let chunk_size = std::cmp::max(group, (chunk_size / group) * group);
+    let chunks: Vec<Vec<LargeField>> = secrets.chunks(chunk_size).map(|chunk| chunk.to_vec()).collect();
+    if chunks.len() > MAX_CHUNKS{
+        log::error!("Batch of {} secrets needs {} chunks, more than the supported {}, dealing it in one chunk", secrets.len(), chunks.len(), MAX_CHUNKS);
+        return vec![secrets];
+    }
+    chunks
+}
+
+/// Outcome of settling a chunk of a dealing
+#[derive(Debug, PartialEq)]
+pub enum ChunkSettlement{
+    // Already settled, or the dealing already failed
+    Ignored,
+    Failed,
+    // Root commitment and shares of the chunk
+    Settled(Hash, Vec<LargeField>),
+}
+
+impl ChunkedOutput{
+    pub fn record_num_chunks(&mut self, chunk: usize, num_chunks: usize) -> bool{
+        if chunk >= num_chunks || num_chunks > MAX_CHUNKS || self.num_chunks.map_or(false, |announced| announced != num_chunks){
+            self.inconsistent = true;
+            return false;
+        }
+        self.num_chunks = Some(num_chunks);
+        true
+    }
+
+    /// Records the settlement of a chunk, without keeping its output. The dealing fails as soon as one of its chunks fails, and each chunk settles at most once.
+    pub fn settle_chunk(&mut self, chunk: usize, output: Option<(Hash, Vec<LargeField>)>) -> ChunkSettlement{
+        if self.failed || !self.settled.insert(chunk){
+            return ChunkSettlement::Ignored;
+        }
+        match output{
+            Some((root, shares)) if !self.inconsistent => ChunkSettlement::Settled(root, shares),
+            _ => {
+                self.failed = true;
+                ChunkSettlement::Failed
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secrets(num: u64) -> Vec<LargeField> {
+        (0..num).map(LargeField::from).collect()
+    }
+
+    #[test]
+    fn test_split_into_chunks() {
+        // Chunking disabled or batch fits in a chunk
+        assert_eq!(split_into_chunks(secrets(10), 0, 2).len(), 1);
+        assert_eq!(split_into_chunks(secrets(10), 10, 2).len(), 1);
+        // Chunk size 10 rounds down to 9, a multiple of t+1 = 3
+        let chunks = split_into_chunks(secrets(20), 10, 2);
+        assert_eq!(chunks.iter().map(|chunk| chunk.len()).collect::<Vec<usize>>(), vec![9, 9, 2]);
+        assert_eq!(chunks.concat(), secrets(20));
+        // Chunks never get smaller than t+1
+        assert!(split_into_chunks(secrets(9), 1, 2).iter().all(|chunk| chunk.len() == 3));
+    }
+
+    #[test]
+    fn test_record_num_chunks() {
+        let mut output = ChunkedOutput::default();
+        assert!(output.record_num_chunks(0, 3));
+        assert!(output.record_num_chunks(2, 3));
+        assert!(!output.inconsistent);
+        // A different count in a later chunk flags the dealing
+        assert!(!output.record_num_chunks(1, 4));
+        assert!(output.inconsistent);
+
+        let mut output = ChunkedOutput::default();
+        assert!(!output.record_num_chunks(3, 3));
+        let mut output = ChunkedOutput::default();
+        assert!(!output.record_num_chunks(0, MAX_CHUNKS + 1));
+    }
+
+    #[test]
+    fn test_chunks_are_streamed_as_they_settle() {
+        let mut output = ChunkedOutput::default();
+        assert!(output.record_num_chunks(1, 2));
+        // Chunks are handed over in the order they settle, without being kept
+        assert_eq!(output.settle_chunk(1, Some(([2; 32], secrets(2)[1..].to_vec()))), ChunkSettlement::Settled([2; 32], secrets(2)[1..].to_vec()));
+        assert_eq!(output.settle_chunk(0, Some(([1; 32], secrets(1)))), ChunkSettlement::Settled([1; 32], secrets(1)));
+        // A late duplicate is ignored
+        assert_eq!(output.settle_chunk(0, None), ChunkSettlement::Ignored);
+    }
+
+    #[test]
+    fn test_failed_chunk_fails_dealing() {
+        let mut output = ChunkedOutput::default();
+        assert!(output.record_num_chunks(0, 3));
+        assert_eq!(output.settle_chunk(0, Some(([1; 32], secrets(1)))), ChunkSettlement::Settled([1; 32], secrets(1)));
+        assert_eq!(output.settle_chunk(1, None), ChunkSettlement::Failed);
+        assert_eq!(output.settle_chunk(2, Some(([3; 32], secrets(1)))), ChunkSettlement::Ignored);
+
+        let mut output = ChunkedOutput::default();
+        output.record_num_chunks(0, 2);
+        output.record_num_chunks(1, 3);
+        assert_eq!(output.settle_chunk(0, Some(([1; 32], secrets(1)))), ChunkSettlement::Failed);
+    }
+
+    #[test]
+    fn test_chunk_key() {
+        let key = vec![7u8; 16];
+        assert_eq!(chunk_key(&key, 0), key);
+        let keys: HashSet<Vec<u8>> = (0..8).map(|chunk| chunk_key(&key, chunk)).collect();
+        assert_eq!(keys.len(), 8);
+        assert!(keys.iter().all(|chunk_key| chunk_key.starts_with(&key)));
+    }
+
+    #[test]
+    fn test_unchunked_dealings_keep_instance_ids() {
+        assert_eq!(chunk_instance_id(5, 0), 5);
+        assert_eq!(split_chunk_instance_id(chunk_instance_id(5, 3)), (5, 3));
+    }
+}
@@ -1,16 +1,57 @@
 use consensus::VACommitment;
 use ha_crypto::{aes_hash::HashState, hash::Hash};
 
-use crate::{Context, protocol::ACSSABState};
+use crate::{Context, msg::{ACSSBroadcast, ACSSBroadcastId, BlameRecord}, protocol::ACSSABState};
 
 impl Context{
-    pub async fn handle_ctrbc_termination(&mut self, _inst_id: usize, sender_rep: usize, content: Vec<u8>){
+    pub async fn handle_ctrbc_termination(&mut self, rbc_id: ACSSBroadcastId, sender_rep: usize, content: Vec<u8>){
         log::debug!("Received CTRBC termination message from sender {}",sender_rep);
-        // Deserialize message
-        let va_comm: VACommitment = bincode::deserialize(content.as_slice()).unwrap();
+        // Deserialize message, it must match the kind and the instance of its id
+        let (va_comm, num_chunks): (VACommitment, usize) = match (rbc_id, bincode::deserialize::<ACSSBroadcast>(content.as_slice())){
+            (ACSSBroadcastId::Commitment(inst_id), Ok(ACSSBroadcast::Commitment(va_comm, num_chunks))) => {
+                if va_comm.instance_id != inst_id{
+                    log::error!("Party {} broadcast a commitment for instance {} under instance {}", sender_rep, va_comm.instance_id, inst_id);
+                    return;
+                }
+                (va_comm, num_chunks)
+            },
+            (ACSSBroadcastId::Blame(inst_id, dealer), Ok(ACSSBroadcast::Blame(blame_record))) => {
+                if blame_record.instance_id != inst_id || blame_record.dealer != dealer{
+                    log::error!("Party {} broadcast a complaint against dealer {} in instance {} under {:?}", sender_rep, blame_record.dealer, blame_record.instance_id, rbc_id);
+                    return;
+                }
+                self.process_blame_record(sender_rep, blame_record).await;
+                return;
+            },
+            (_, Ok(_)) => {
+                log::error!("Party {} broadcast a message of another kind under {:?}", sender_rep, rbc_id);
+                return;
+            },
+            (_, Err(e)) => {
+                log::error!("Failed to deserialize CTRBC message from party {}: {}", sender_rep, e);
+                return;
+            }
+        };
         let instance_id = va_comm.instance_id;
         log::debug!("Successfully deserialized CTRBC message from party {} with instance_id {}", sender_rep, instance_id);
 
+        if self.gc.is_collected(&instance_id){
+            log::debug!("ACSS instance {} already terminated and pruned, skipping message", instance_id);
+            return;
+        }
+        if self.dealer_gc.is_collected(&(sender_rep, instance_id)){
+            log::debug!("Dealing of party {} in ACSS instance {} already settled and pruned, skipping message", sender_rep, instance_id);
+            return;
+        }
+        if self.acss_ab_state.get(&instance_id).map(|acss_state| acss_state.commitments.contains_key(&sender_rep)).unwrap_or(false){
+            log::error!("Party {} broadcast a second commitment for instance {}, keeping the first", sender_rep, instance_id);
+            return;
+        }
+        if !self.record_num_chunks(instance_id, sender_rep, num_chunks){
+            // The dealing of this dealer fails as a whole
+            self.deliver_chunk(instance_id, sender_rep, None).await;
+            return;
+        }
         if !self.acss_ab_state.contains_key(&instance_id) {
             let acss_state = ACSSABState::new();
             self.acss_ab_state.insert(instance_id, acss_state);
@@ -26,8 +67,13 @@ impl Context{
 
         acss_state.commitment_root_fe.insert(sender_rep, root_commitment);
         log::debug!("Deserialization successful for sender {} for instance ID {}",sender_rep,instance_id);
+        let pending_blames: Vec<(usize, BlameRecord)> = acss_state.pending_blames.drain(..).collect();
         self.interpolate_shares(sender_rep, instance_id).await;
         self.verify_shares(sender_rep,instance_id).await;
+        // Blame records waiting for this commitment
+        for (complainant, blame_record) in pending_blames{
+            self.process_blame_record(complainant, blame_record).await;
+        }
     }
 
     pub fn compute_root_commitment(
@@ -1,13 +1,14 @@
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use crate::{Context, msg::AcssSKEShares};
+use crate::{Context, msg::{AcssSKEShares, ACSSBroadcast, ACSSBroadcastId, BlameReason, DealtShares}};
 use ha_crypto::{hash::{Hash}, aes_hash::{MerkleTree, Proof}, encrypt};
-use lambdaworks_math::{traits::ByteConversion};
-use consensus::{LargeField, LargeFieldSer, generate_evaluation_points_fft, expand_sharing_to_n_evaluation_points, expand_sharing_to_n_evaluation_points_opt, sample_polynomials_from_prf, rand_field_element, VACommitment};
+use lambdaworks_math::{polynomial::Polynomial, traits::ByteConversion};
+use consensus::{LargeField, LargeFieldSer, expand_sharing_to_n_evaluation_points_fft, expand_sharing_to_n_evaluation_points_opt, sample_polynomials_from_prf, rand_field_element, VACommitment, ReliableBroadcast, DZKBatchItem, DZK_BATCH_DELAY, dzk_aggregation_challenge, dzk_column_commitment};
 use rayon::prelude::{ParallelIterator, IndexedParallelIterator, IntoParallelIterator};
+use tokio::time::Instant;
 use types::Replica;
 
-use super::ACSSABState;
+use super::{ACSSABState, split_chunk_instance_id};
 
 impl Context{
     pub async fn init_symmetric_key_setup(&mut self){
@@ -22,14 +23,7 @@ impl Context{
             }
             log::debug!("Symmetric keys generated: {:?}", symm_keys);
             // Now share these keys through ASKS
-            let _status = self.asks_inp_channel.send((
-                1,
-                self.num_nodes, 
-                false, 
-                false, 
-                Some(symm_keys), 
-                None
-            )).await;
+            let _status = self.asks.share(1, self.num_nodes, Some(symm_keys), false).await;
             if _status.is_err(){
                 log::error!("Failed to send ASKS init request");
                 return;
@@ -44,14 +38,7 @@ impl Context{
             self.symmetric_keys_avid.term_asks_sharing.insert(party);
             // Initiate reconstruction
 
-            let _status = self.asks_inp_channel.send((
-                1,
-                self.num_nodes, 
-                false, 
-                true, 
-                None, 
-                Some(party)
-            )).await;
+            let _status = self.asks.reconstruct(1, party, None, None).await;
             if _status.is_err(){
                 log::error!("Failed to send ASKS termination request for symmetric keys");
                 return;
@@ -82,13 +69,29 @@ impl Context{
         evaluation_points
     }
 
+    /// Interpolates the polynomials through the PRF-derived evaluations and evaluates them at the points of all parties.
+    /// The FFT path evaluates at the roots of unity, the Vandermonde path at 1..n.
+    pub fn expand_prf_evaluations(&self, evaluations_prf: Vec<Vec<LargeField>>) -> (Vec<Vec<LargeField>>, Vec<Polynomial<LargeField>>){
+        if self.use_fft{
+            expand_sharing_to_n_evaluation_points_fft(
+                evaluations_prf,
+                self.num_faults,
+                self.num_nodes,
+                &self.roots_of_unity
+            )
+        }
+        else{
+            expand_sharing_to_n_evaluation_points_opt(
+                evaluations_prf,
+                self.num_faults,
+                self.num_nodes,
+            )
+        }
+    }
+
     pub async fn init_acss_ab(&mut self, secrets: Vec<LargeField>, instance_id: usize){
         // Init ASKS first
         self.init_symmetric_key_setup().await;
-        if !self.acss_ab_state.contains_key(&instance_id){
-            let acss_ab_state = ACSSABState::new();
-            self.acss_ab_state.insert(instance_id, acss_ab_state);
-        }
         // Number of secrets must be a multiple of self.num_faults+1
         let mut secrets = secrets;
         if secrets.len() % (self.num_faults + 1) != 0 {
@@ -97,6 +100,18 @@ impl Context{
                 secrets.push(rand_field_element());
             }
         }
+        let chunks = self.split_into_chunks(secrets);
+        self.deal_chunks(instance_id, chunks).await;
+    }
+
+    /// Commits to and disperses the shares of one chunk of secrets, whose length is a multiple of t+1.
+    pub async fn deal_chunk(&mut self, secrets: Vec<LargeField>, instance_id: usize, num_chunks: usize){
+        if !self.acss_ab_state.contains_key(&instance_id){
+            let acss_ab_state = ACSSABState::new();
+            self.acss_ab_state.insert(instance_id, acss_ab_state);
+        }
+        let chunk = split_chunk_instance_id(instance_id).1;
+        let chunk_keys = self.chunk_keys(chunk);
 
         let consensus_start_time = SystemTime::now()
                                 .duration_since(UNIX_EPOCH)
@@ -105,159 +120,49 @@ impl Context{
         
         log::debug!("Starting sharing preparation");
         let tot_sharings = secrets.len();
-        let mut handles = Vec::new();
-        let mut _indices;
-        let mut evaluations;
-        let nonce_evaluations;
-        let mut coefficients;
-        
-        let blinding_poly_evaluations;
-        let blinding_poly_coefficients;
-        let nonce_blinding_poly_evaluations;
+        // Evaluations at 0 and at the points of the first t parties are derived from the PRF keys, the remaining shares are interpolated
+        let evaluations_prf = sample_polynomials_from_prf(
+            secrets, 
+            chunk_keys.clone(), 
+            self.num_faults, 
+            false, 
+            1u8
+        );
+        let (evaluations, coefficients) = self.expand_prf_evaluations(evaluations_prf);
         
-        if !self.use_fft{
-            // Generate evaluations right here
-            let evaluations_prf = sample_polynomials_from_prf(
-                secrets, 
-                self.symmetric_keys_avid.keys_from_me.clone(), 
-                self.num_faults, 
-                false, 
-                1u8
-            );
-            (evaluations, coefficients) = expand_sharing_to_n_evaluation_points_opt(
-                evaluations_prf,
-                self.num_faults,
-                self.num_nodes,
-            );
-
-            //let evaluation_prf_chunks: Vec<Vec<Vec<LargeField>>> = evaluations_prf.chunks(evaluations_prf.len()/self.num_threads).map(|el| el.to_vec()).collect();
-            // for eval_prfs in evaluation_prf_chunks{
-            //     let handle = tokio::spawn(
-                    
-            //     );
-            //     handles.push(handle);
-            // }
-
-            // evaluations = Vec::new();
-            // coefficients = Vec::new();
-            // _indices = Vec::new();
-            // for party in 0..self.num_nodes{
-            //     _indices.push(LargeField::new(UnsignedInteger::from((party+1) as u64)));
-            // }
-                    
-            // for handle in handles{
-            //     let (
-            //         evaluations_batch, 
-            //         coefficients_batch) = handle.await.unwrap();
-            //     evaluations.extend(evaluations_batch);
-            //     coefficients.extend(coefficients_batch);
-            // }
-            
-            // Generate nonce evaluations
-            let nonce_secrets:Vec<LargeField> = (0..self.num_nodes).into_iter().map(|_| rand_field_element()).collect();
-            let evaluations_nonce_prf = sample_polynomials_from_prf(
-                nonce_secrets,
-                self.symmetric_keys_avid.keys_from_me.clone(), 
-                self.num_faults, 
-                true, 
-                1u8
-            );
-            let (nonce_evaluations_ret,_nonce_coefficients) = expand_sharing_to_n_evaluation_points(
-                evaluations_nonce_prf,
-                self.num_faults,
-                self.num_nodes
-            );
-            nonce_evaluations = nonce_evaluations_ret;
-
-            // Generate the DZK proofs and commitments and utilize RBC to broadcast these proofs
-            
-            // Sample blinding polynomials
-            let blinding_secrets: Vec<LargeField> = (0..self.num_nodes).into_iter().map(|_| rand_field_element()).collect();
-            let blinding_prf = sample_polynomials_from_prf(
-                blinding_secrets, 
-                self.symmetric_keys_avid.keys_from_me.clone(), 
-                self.num_faults, 
-                true, 
-                2u8
-            );
-            let (blinding_poly_evaluations_vec, blinding_poly_coefficients_vec) = expand_sharing_to_n_evaluation_points(
-                blinding_prf,
-                self.num_faults,
-                self.num_nodes
-            );
-
-            blinding_poly_evaluations = blinding_poly_evaluations_vec;
-            blinding_poly_coefficients = blinding_poly_coefficients_vec;
-
-            let blinding_nonce_secrets: Vec<LargeField> = (0..self.num_nodes).into_iter().map(|_| rand_field_element()).collect();
-            let blinding_nonce_prf = sample_polynomials_from_prf(
-                blinding_nonce_secrets, 
-                self.symmetric_keys_avid.keys_from_me.clone(), 
-                self.num_faults, 
-                true, 
-                3u8
-            );
-
-            let (nonce_blinding_poly_evaluations_vec, _nonce_blinding_poly_coefficients_vec) = expand_sharing_to_n_evaluation_points(
-                blinding_nonce_prf,
-                self.num_faults,
-                self.num_nodes,
-            );
-            nonce_blinding_poly_evaluations = nonce_blinding_poly_evaluations_vec;
-        }
-        else{
-            // Parallelize the generation of evaluation points
-            let mut secret_shards: Vec<Vec<LargeField>> = secrets.chunks(self.num_threads).map(|el_vec| el_vec.to_vec()).collect();
-            for shard in secret_shards.iter_mut(){
-                let secrets = shard.clone();
-                let handle = tokio::spawn(
-                    generate_evaluation_points_fft(
-                        secrets,
-                        self.num_faults-1,
-                        self.num_nodes
-                    )
-                );
-                handles.push(handle);
-            }
-            evaluations = Vec::new();
-            coefficients = Vec::new();
-            _indices = self.roots_of_unity.clone();
-            for handle in handles{
-                let (
-                    evaluations_batch, 
-                    coefficients_batch) = handle.await.unwrap();
-                evaluations.extend(evaluations_batch);
-                coefficients.extend(coefficients_batch);
-            }
+        // Generate nonce evaluations
+        let nonce_secrets:Vec<LargeField> = (0..self.num_nodes).into_iter().map(|_| rand_field_element()).collect();
+        let evaluations_nonce_prf = sample_polynomials_from_prf(
+            nonce_secrets,
+            chunk_keys.clone(), 
+            self.num_faults, 
+            true, 
+            1u8
+        );
+        let (nonce_evaluations,_nonce_coefficients) = self.expand_prf_evaluations(evaluations_nonce_prf);
 
-            // Generate nonce evaluations
-            let nonce_secrets:Vec<LargeField> = (0..self.num_nodes).into_iter().map(|_| rand_field_element()).collect();
-            let (nonce_evaluations_ret,_nonce_coefficients) = generate_evaluation_points_fft(
-                nonce_secrets,
-                self.num_faults-1,
-                self.num_nodes,
-            ).await;
-            nonce_evaluations = nonce_evaluations_ret;
-
-            // Generate blinding polynomials
-            let blinding_secrets:Vec<LargeField> = (0..self.num_nodes).into_iter().map(|_| rand_field_element()).collect();
-            let (blinding_poly_evaluations_vec, blinding_poly_coefficients_vec) = generate_evaluation_points_fft(
-                blinding_secrets, 
-                self.num_faults-1, 
-                self.num_nodes
-            ).await;
-            blinding_poly_evaluations = blinding_poly_evaluations_vec;
-            blinding_poly_coefficients = blinding_poly_coefficients_vec;
-
-            // Generate blinding nonce polynomials
-            let blinding_nonce_secrets: Vec<LargeField> = (0..self.num_nodes).into_iter().map(|_| rand_field_element()).collect();
-            let (nonce_blinding_evaluations_vec, _nonce_coefficients_vec) = generate_evaluation_points_fft(
-                blinding_nonce_secrets, 
-                self.num_faults-1, 
-                self.num_nodes
-            ).await;
-            nonce_blinding_poly_evaluations = nonce_blinding_evaluations_vec;
-        }
+        // Generate the DZK proofs and commitments and utilize RBC to broadcast these proofs
+        
+        // Sample blinding polynomials
+        let blinding_secrets: Vec<LargeField> = (0..self.num_nodes).into_iter().map(|_| rand_field_element()).collect();
+        let blinding_prf = sample_polynomials_from_prf(
+            blinding_secrets, 
+            chunk_keys.clone(), 
+            self.num_faults, 
+            true, 
+            2u8
+        );
+        let (blinding_poly_evaluations, blinding_poly_coefficients) = self.expand_prf_evaluations(blinding_prf);
+
+        let blinding_nonce_secrets: Vec<LargeField> = (0..self.num_nodes).into_iter().map(|_| rand_field_element()).collect();
+        let blinding_nonce_prf = sample_polynomials_from_prf(
+            blinding_nonce_secrets, 
+            chunk_keys.clone(), 
+            self.num_faults, 
+            true, 
+            3u8
+        );
+        let (nonce_blinding_poly_evaluations, _nonce_blinding_poly_coefficients) = self.expand_prf_evaluations(blinding_nonce_prf);
         
         log::debug!("Finished generating evaluations at time: {}",
             SystemTime::now()
@@ -305,11 +210,10 @@ impl Context{
 
         let blinding_roots = blinding_merkle_trees.iter().map(|mt| mt.root()).collect::<Vec<Hash>>();
 
-        let root_comm_fe: Vec<LargeField> = roots.iter().zip(blinding_roots.iter()).map(|(root, b_root)|{
-            //let root = mt.root();
-            let root_combined = self.hash_context.hash_two(root.clone(), b_root.clone());
-            return LargeField::from_bytes_be(root_combined.as_slice()).unwrap();
+        let column_commitments: Vec<Hash> = roots.iter().zip(blinding_roots.iter()).map(|(root, b_root)|{
+            Self::column_commitment(instance_id, root, b_root)
         }).collect();
+        let root_comm_fe: Vec<LargeField> = column_commitments.iter().map(|commitment| dzk_aggregation_challenge(commitment)).collect();
 
         log::debug!("Finished commitment generation at time: {}",
             SystemTime::now()
@@ -327,7 +231,7 @@ impl Context{
         // Initialize DZK procedure
         let (dzk_proofs, dzk_broadcast_polys, commitment_hashes) = self.compute_dzk_proofs(
             agg_polys, 
-            root_comm_fe
+            column_commitments
         );
 
         log::debug!("Finished generating DZK proofs at time: {}",
@@ -406,7 +310,7 @@ impl Context{
             let merkle_proofs = merkle_proofs_party_wise[rep].clone();
             let blinding_merkle_proofs = blinding_merkle_proofs_party_wise[rep].clone();
             let shares_struct;
-            if (self.use_fft) || (!self.use_fft && rep >= self.num_faults){
+            if rep >= self.num_faults{
                 shares_struct = AcssSKEShares{
                     evaluations: (shares_party, nonce_shares, merkle_proofs),
                     blinding_evaluations: (blinding_shares, nonce_blinding_poly_shares, blinding_merkle_proofs),
@@ -423,26 +327,53 @@ impl Context{
                 }; 
             }
 
-            let shares_ser = bincode::serialize(&shares_struct).unwrap();
-            
             let sec_key = self.symmetric_keys_avid.keys_from_me.get(&rep).unwrap().clone();
+            // Sign the shares so that the recipient can prove their invalidity
+            let dealt = DealtShares{
+                instance_id: instance_id,
+                recipient: rep,
+                key: chunk_keys.get(&rep).unwrap().clone(),
+                shares: shares_struct,
+            };
+            let signature = self.sign_dealt_shares(&dealt);
+            let shares_ser = bincode::serialize(&(dealt.shares, signature)).unwrap();
+            
             let enc_shares = encrypt(sec_key.as_slice(), shares_ser);
             
             let ser_enc_msg = bincode::serialize(&(instance_id,enc_shares)).unwrap();
             shares.push((rep, Some(ser_enc_msg)));
         }
 
-        let ser_broadcast_vec: Vec<u8> = bincode::serialize(&va_comm).unwrap();
+        let rbc_id = va_comm.instance_id;
+        let ser_broadcast_vec: Vec<u8> = bincode::serialize(&ACSSBroadcast::Commitment(va_comm, num_chunks)).unwrap();
         // Reliably broadcast this vector
-        let _rbc_status = self.inp_ctrbc.send(ser_broadcast_vec).await;
+        let rbc_status = self.rbc.broadcast(ACSSBroadcastId::Commitment(rbc_id), ser_broadcast_vec).await;
+        if rbc_status.is_err(){
+            log::error!("Failed to broadcast commitment of instance {}: {:?}", rbc_id, rbc_status.err().unwrap());
+        }
         
         // Invoke AVID on vectors of shares
         // Use AVID to send the shares to parties
         // Utilize a single batched AVID instance for all shares. 
-        let _avid_status = self.inp_avid_channel.send(shares).await;
+        let avid_bytes = shares.iter().map(|(_, share)| share.as_ref().map_or(0, |share| share.len())).sum();
+        self.avid_throttle.push(instance_id, shares, avid_bytes);
+        self.throttle_avid_instances().await;
+    }
+
+    /// Starts as many queued AVID instances as the congestion window and byte budget allow.
+    pub async fn throttle_avid_instances(&mut self){
+        let released = self.avid_throttle.release();
+        log::debug!("Throttling AVID instances, starting {} instances with window {}, {} bytes in flight, {} queued", released.len(), self.avid_throttle.window(), self.avid_throttle.outstanding_bytes(), self.avid_throttle.pending());
+        for shares in released.into_iter(){
+            let _avid_status = self.inp_avid_channel.send(shares).await;
+        }
     }
 
     pub async fn verify_shares(&mut self, sender: Replica, instance_id: usize){
+        if self.gc.is_collected(&instance_id){
+            log::debug!("ACSS instance {} already terminated and pruned, skipping message", instance_id);
+            return;
+        }
         if !self.acss_ab_state.contains_key(&instance_id){
             let acss_state = ACSSABState::new();
             self.acss_ab_state.insert(instance_id, acss_state);
@@ -459,31 +390,90 @@ impl Context{
             return;
         }
 
+        // The DZK proofs of dealings that terminate together are verified in one batch
+        self.dzk_batch.insert((instance_id, sender));
+        if self.dzk_batch.len() >= self.dzk_batch_size{
+            self.verify_share_batch().await;
+        }
+        else if self.dzk_batch_deadline.is_none(){
+            self.dzk_batch_deadline = Some(Instant::now() + DZK_BATCH_DELAY);
+        }
+    }
+
+    /// Verifies the shares of all pending dealings, with a single batch verification of their DZK proofs.
+    pub async fn verify_share_batch(&mut self){
+        self.dzk_batch_deadline = None;
+        let batch: Vec<(usize, Replica)> = self.dzk_batch.drain().collect();
+
+        let mut dzk_items = Vec::new();
+        let mut dealings = Vec::new();
+        for (instance_id, sender) in batch.into_iter(){
+            if self.gc.is_collected(&instance_id){
+                continue;
+            }
+            let (shares_full, va_commitment) = match self.acss_ab_state.get(&instance_id){
+                Some(acss_ab_state) if !acss_ab_state.verification_status.contains_key(&sender) => {
+                    (acss_ab_state.shares.get(&sender).unwrap().clone(), acss_ab_state.commitments.get(&sender).unwrap().clone())
+                },
+                _ => continue
+            };
+            match self.check_dealt_commitments(shares_full, &va_commitment, self.myid){
+                Ok(items) => {
+                    dealings.push((instance_id, sender, dzk_items.len()..dzk_items.len()+items.len()));
+                    dzk_items.extend(items);
+                },
+                Err(reason) => {
+                    log::error!("Share verification failed for instance {} from sender {}: {:?}", instance_id, sender, reason);
+                    self.complain(sender, instance_id, reason).await;
+                }
+            }
+        }
+        if dealings.is_empty(){
+            return;
+        }
+
+        log::debug!("Verifying DZK proofs of {} dealings in a batch", dealings.len());
+        let statuses = self.dzk_context.verify_dzk_batch(dzk_items);
+        for (instance_id, sender, columns) in dealings.into_iter(){
+            if !statuses[columns].iter().all(|status| *status){
+                log::error!("Share verification failed for instance {} from sender {}: {:?}", instance_id, sender, BlameReason::DZKProof);
+                self.complain(sender, instance_id, BlameReason::DZKProof).await;
+                continue;
+            }
+            let acss_ab_state = match self.acss_ab_state.get_mut(&instance_id){
+                Some(acss_ab_state) => acss_ab_state,
+                None => continue
+            };
+            log::debug!("Share from {} verified", sender);
+            acss_ab_state.verification_status.insert(sender,true);
+            // Start reliable agreement
+            let _status = self.inp_ra_channel.send((sender,1,instance_id)).await;
+            self.check_termination(sender, instance_id).await;
+        }
+    }
+
+    /// Verifies the shares dealt to party `rep` against the dealer's broadcast commitment.
+    pub fn check_dealt_shares(&self, shares_full: AcssSKEShares, va_commitment: &VACommitment, rep: Replica) -> Result<(), BlameReason>{
+        let dzk_items = self.check_dealt_commitments(shares_full, va_commitment, rep)?;
+        if !self.dzk_context.verify_dzk_batch(dzk_items).into_iter().all(|status| status){
+            return Err(BlameReason::DZKProof);
+        }
+        Ok(())
+    }
+
+    /// Verifies the shares dealt to party `rep` against the Merkle commitments of the dealer, and returns the DZK checks of every column.
+    pub fn check_dealt_commitments(&self, shares_full: AcssSKEShares, va_commitment: &VACommitment, rep: Replica) -> Result<Vec<DZKBatchItem>, BlameReason>{
         // Share verification first
-        let shares_full = acss_ab_state.shares.get(&sender).unwrap().clone();
-        let va_commitment = acss_ab_state.commitments.get(&sender).unwrap().clone();
-        
-        let shares: Vec<LargeField> = shares_full.evaluations.0.into_iter().map(|el| 
-            LargeField::from_bytes_be(el.as_slice()).unwrap()
-        ).collect();
-        let nonce_shares = shares_full.evaluations.1.into_iter().map(|el| 
-            LargeField::from_bytes_be(el.as_slice()).unwrap()
-        ).collect();
+        let shares: Vec<LargeField> = Self::deser_field_elements(shares_full.evaluations.0)?;
+        let nonce_shares: Vec<LargeField> = Self::deser_field_elements(shares_full.evaluations.1)?;
         let merkle_proofs = shares_full.evaluations.2;
         
-        let evaluation_points;
-        if !self.use_fft{
-            evaluation_points = (1..self.num_nodes+1).into_iter().map(|x| LargeField::from(x as u64)).collect();
-        }
-        else{
-            evaluation_points = self.roots_of_unity.clone();
-        }
+        let evaluation_points = self.gen_evaluation_points();
 
         let roots_from_proofs: Vec<Hash> = merkle_proofs.iter().map(|proof| proof.root()).collect();
         
         if roots_from_proofs != va_commitment.column_roots {
-            log::error!("Share commitment roots mismatch for instance {} from sender {}", instance_id, sender);
-            return;
+            return Err(BlameReason::ShareRootMismatch);
         }
         if !Self::verify_commitments(
             self.num_faults+1, 
@@ -493,24 +483,17 @@ impl Context{
             merkle_proofs, 
             &self.hash_context
         ){
-            log::error!("Share commitment verification failed for instance {} from sender {}", instance_id, sender);
-            return;
+            return Err(BlameReason::ShareCommitment);
         }
 
-        log::debug!("Successfully verified commitments of shares sent by sender {} in instance_id {}", sender, instance_id);
         // Blinding share verification next
-        let blinding_shares: Vec<LargeField> = shares_full.blinding_evaluations.0.into_iter().map(|el| 
-            LargeField::from_bytes_be(el.as_slice()).unwrap()
-        ).collect();
-        let blinding_nonce_shares = shares_full.blinding_evaluations.1.into_iter().map(|el| 
-            LargeField::from_bytes_be(el.as_slice()).unwrap()
-        ).collect();
+        let blinding_shares: Vec<LargeField> = Self::deser_field_elements(shares_full.blinding_evaluations.0)?;
+        let blinding_nonce_shares: Vec<LargeField> = Self::deser_field_elements(shares_full.blinding_evaluations.1)?;
         let blinding_merkle_proofs = shares_full.blinding_evaluations.2;
 
         let blinding_merkle_roots: Vec<Hash> = blinding_merkle_proofs.iter().map(|proof| proof.root()).collect();
         if blinding_merkle_roots != va_commitment.blinding_column_roots {
-            log::error!("Blinding share commitment roots mismatch for instance {} from sender {}", instance_id, sender);
-            return;
+            return Err(BlameReason::BlindingRootMismatch);
         }
 
         if !Self::verify_blinding_commitments(
@@ -519,11 +502,9 @@ impl Context{
             blinding_merkle_proofs, 
             &self.hash_context
         ){
-            log::error!("Blinding share commitment verification failed for instance {} from sender {}", instance_id, sender);
-            return;
+            return Err(BlameReason::BlindingCommitment);
         }
 
-        log::debug!("Successfully verified blinding commitments of shares sent by sender {} in instance_id {}", sender, instance_id);
         // Finally, verify DZK proofs
         let grouped_points = Self::group_points_for_public_reconstruction(
             shares, 
@@ -531,36 +512,47 @@ impl Context{
             self.num_faults+1
         );
 
-        let root_comm_fe: Vec<LargeField> = roots_from_proofs.iter().zip(blinding_merkle_roots.iter()).map(|(root, b_root)|{
-            //let root = mt.root();
-            let root_combined = self.hash_context.hash_two(root.clone(), b_root.clone());
-            return LargeField::from_bytes_be(root_combined.as_slice()).unwrap();
+        let column_commitments: Vec<Hash> = roots_from_proofs.iter().zip(blinding_merkle_roots.iter()).map(|(root, b_root)|{
+            Self::column_commitment(va_commitment.instance_id, root, b_root)
         }).collect();
 
         let dzk_aggregated_points: Vec<LargeField> = grouped_points.into_iter().zip(
-            root_comm_fe.clone().into_iter()).map(|(shares, root)|{
-                return self.folding_dzk_context.gen_agg_poly_dzk(shares, root.to_bytes_be());
+            column_commitments.iter()).map(|(shares, commitment)|{
+                return self.dzk_context.gen_agg_poly_dzk(shares, *commitment);
             }).collect();
 
-        let status = self.folding_dzk_context.verify_dzk_proof_row(
-            shares_full.dzk_iters.clone(), 
-            va_commitment.dzk_roots.clone(), 
-            va_commitment.polys.clone(), 
-            root_comm_fe.into_iter().map(|el| el.to_bytes_be()).collect(), 
-            dzk_aggregated_points, 
-            blinding_shares.clone(), 
-            self.myid+1
-        );
-
-        if !status{
-            log::error!("DZK proof verification failed for instance {} from sender {}", instance_id, sender);
-            return;
+        let columns = column_commitments.len();
+        if shares_full.dzk_iters.len() != columns || va_commitment.dzk_roots.len() != columns 
+                || va_commitment.polys.len() != columns || dzk_aggregated_points.len() != columns || blinding_shares.len() != columns{
+            return Err(BlameReason::DZKProof);
         }
-        
-        log::debug!("Share from {} verified", sender);
-        acss_ab_state.verification_status.insert(sender,true);
-        // Start reliable agreement
-        let _status = self.inp_ra_channel.send((sender,1,instance_id)).await;
-        self.check_termination(sender, instance_id).await;
+        let evaluation_point = evaluation_points[rep].clone();
+        let dzk_items = shares_full.dzk_iters.into_iter().zip(
+                va_commitment.dzk_roots.iter().zip(va_commitment.polys.iter())
+            ).zip(
+                column_commitments.into_iter().zip(dzk_aggregated_points.into_iter().zip(blinding_shares.into_iter()))
+            ).map(|((dzk_proof, (dzk_roots, dzk_poly)), (column_root, (row_share, blinding_row_share)))|{
+                DZKBatchItem{
+                    dzk_proof: dzk_proof,
+                    dzk_roots: dzk_roots.clone(),
+                    dzk_poly: dzk_poly.clone(),
+                    column_root: column_root,
+                    row_share: row_share,
+                    blinding_row_share: blinding_row_share,
+                    evaluation_point: evaluation_point.clone(),
+                }
+            }).collect();
+        Ok(dzk_items)
+    }
+
+    /// Commitment to a column and its blinding column, binding the instance. The DZK challenges of the column are derived from it.
+    pub fn column_commitment(instance_id: usize, root: &Hash, blinding_root: &Hash) -> Hash{
+        dzk_column_commitment(b"acss-ske column", instance_id, root, blinding_root)
+    }
+
+    fn deser_field_elements(elements: Vec<LargeFieldSer>) -> Result<Vec<LargeField>, BlameReason>{
+        elements.into_iter().map(|el| 
+            LargeField::from_bytes_be(el.as_slice()).map_err(|_| BlameReason::MalformedShares)
+        ).collect()
     }
-}
\ No newline at end of file
+}
@@ -14,6 +14,11 @@ mod ske;
 mod pub_rec;
 
 mod pub_rec_quad;
+
+mod blame;
+
+mod chunk;
+pub use chunk::*;
 // mod echo;
 // pub use echo::*;
 
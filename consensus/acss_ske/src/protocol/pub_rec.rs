@@ -1,6 +1,6 @@
 use std::{ops::{Add, Mul, Div}, collections::HashMap};
 use bytes::Bytes;
-use consensus::{LargeField, LargeFieldSer, DZKProof, vandermonde_matrix, inverse_vandermonde, matrix_vector_multiply};
+use consensus::{LargeField, LargeFieldSer, DZKProof, interpolate_at_point, interpolate_polynomials, dzk_aggregation_challenge};
 use lambdaworks_math::{polynomial::Polynomial, traits::ByteConversion};
 use rayon::prelude::{IntoParallelIterator, ParallelIterator, IntoParallelRefIterator, IndexedParallelIterator};
 use types::{Replica, WrapperMsg};
@@ -207,7 +207,7 @@ impl Context{
     pub fn compute_dzk_proofs(
         &self,
         dzk_share_polynomials: Vec<Polynomial<LargeField>>,
-        column_wise_roots: Vec<LargeField>
+        column_commitments: Vec<Hash>
     ) -> (Vec<Vec<DZKProof>>, Vec<Vec<LargeFieldSer>>, Vec<Vec<LargeFieldSer>>){
         // (Replica, (g_0 values), (g_1 values), (Vector of Merkle Proofs for each g_0,g_1 value))
         let mut shares_proofs_dzk: Vec<Vec<DZKProof>> = Vec::new();
@@ -216,7 +216,7 @@ impl Context{
         for _ in 0..self.num_nodes{
             shares_proofs_dzk.push(Vec::new());
         }
-        for (dzk_poly,column_root) in dzk_share_polynomials.into_iter().zip(column_wise_roots.into_iter()){
+        for (dzk_poly,column_root) in dzk_share_polynomials.into_iter().zip(column_commitments.into_iter()){
             
             let mut merkle_roots = Vec::new();
             let mut eval_points = Vec::new();
@@ -231,12 +231,12 @@ impl Context{
             //merkle_roots.push(root.clone());
 
             // Reliably broadcast these coefficients
-            let coeffs_const_size: Vec<LargeFieldSer> = self.folding_dzk_context.gen_dzk_proof(
+            let coeffs_const_size: Vec<LargeFieldSer> = self.dzk_context.gen_dzk_proof(
                 &mut eval_points, 
                 &mut trees, 
                 coefficients.coefficients.clone(), 
                 iteration, 
-                root.to_bytes_be()
+                root
             ).into_iter().map(|x| x.to_bytes_be()).collect();
             
             for tree in trees.iter(){
@@ -272,6 +272,10 @@ impl Context{
 
     pub async fn init_pubrec_quad(&mut self, instance_id: usize, party: Replica){
         log::debug!("Received request to publicly reconstruct secrets in instance {} with quadratic cost", instance_id);
+        if self.gc.is_collected(&instance_id){
+            log::error!("ACSS AB state for instance {} was pruned after termination, cannot reconstruct", instance_id);
+            return;
+        }
         if !self.acss_ab_state.contains_key(&instance_id){
             log::error!("No ACSS AB state found for instance {}", instance_id);
             return;
@@ -306,6 +310,10 @@ impl Context{
 
     pub async fn init_pubrec(&mut self, instance_id: usize, party: Replica){
         log::debug!("Received request to publicly reconstruct secrets in instance {}", instance_id);
+        if self.gc.is_collected(&instance_id){
+            log::error!("ACSS AB state for instance {} was pruned after termination, cannot reconstruct", instance_id);
+            return;
+        }
         if !self.acss_ab_state.contains_key(&instance_id){
             log::error!("No ACSS AB state found for instance {}", instance_id);
             return;
@@ -353,7 +361,7 @@ impl Context{
 
             let prot_msg = ProtMsg::PubRecL1(instance_id,acss_ske_msg);
             let secret_key = self.sec_key_map.get(&rep).unwrap().clone();
-            let wrapper_msg = WrapperMsg::new(prot_msg, self.myid, secret_key.as_slice());
+            let wrapper_msg = WrapperMsg::new(self.session.seal(rep, prot_msg), self.myid, secret_key.as_slice());
             #[cfg(feature = "bandwidth")]
             log::info!("Network sending bytes: {:?}", Bytes::from(wrapper_msg.to_bytes()).len());
             let cancel_handler = self.net_send.send(rep, wrapper_msg).await;
@@ -368,10 +376,16 @@ impl Context{
         share_sender: Replica,
     ){
         log::debug!("Received PubRecL1 message for instance {} of party {}, shares received from party {}", instance_id, acss_msg.rep, share_sender);
+        if self.gc.is_collected(&instance_id){
+            log::debug!("ACSS instance {} already terminated and pruned, skipping message", instance_id);
+            return;
+        }
         if !self.acss_ab_state.contains_key(&instance_id){
             let acss_ab_state = ACSSABState::new();
             self.acss_ab_state.insert(instance_id, acss_ab_state);
         }
+        // Evaluation point of each party
+        let party_points = self.gen_evaluation_points();
 
         let acss_ab_state = self.acss_ab_state.get_mut(&instance_id).unwrap();
         
@@ -440,7 +454,8 @@ impl Context{
         // Verify shares first
         let shares: Vec<LargeField> = acss_msg.evaluations.0.clone().into_iter().map(|el| LargeField::from_bytes_be(el.as_slice()).unwrap()).collect();
         let blinding_share = LargeField::from_bytes_be(acss_msg.blinding_evaluations.0[0].as_slice()).unwrap();
-        let root_comm_fe = LargeField::from_bytes_be(self.hash_context.hash_two(share_mp.root(), blinding_mp.root()).as_slice()).unwrap();
+        let column_commitment = Self::column_commitment(va_commitment.instance_id, &share_mp.root(), &blinding_mp.root());
+        let root_comm_fe: LargeField = dzk_aggregation_challenge(&column_commitment);
 
         let share_agg_point = Self::aggregate_points_for_dzk(
             vec![shares.clone()], 
@@ -448,14 +463,14 @@ impl Context{
             vec![root_comm_fe]
         )[0].clone();
         let dzk_proof = acss_msg.dzk_iters[0].clone();
-        let status = self.folding_dzk_context.verify_dzk_proof(
+        let status = self.dzk_context.verify_dzk_proof(
             dzk_proof, 
             va_commitment.dzk_roots[self.myid].clone(), 
             va_commitment.polys[self.myid].clone(), 
-            root_comm_fe.to_bytes_be(), 
+            column_commitment, 
             (share_agg_point-blinding_share).div(root_comm_fe), 
             blinding_share, 
-            share_sender+1
+            party_points[share_sender].clone()
         );
         if !status{
             log::error!("Dzk proof verification failed for instance {} of party {}, shares received from party {}", instance_id, acss_msg.rep, share_sender);
@@ -485,29 +500,18 @@ impl Context{
             }
             for party in 0..self.num_nodes{
                 if share_map.contains_key(&party){
-                    eval_points.push(LargeField::from((party+1) as u64));
+                    eval_points.push(party_points[party].clone());
                     let shares_party = share_map.get(&party).unwrap();
                     for (index,share) in shares_party.into_iter().enumerate(){
                         shares_indexed[index].push(share.clone());
                     }
                 }
             }
-            // Interpolate polynomials
-            let secret_evaluation_point;
-            if !self.use_fft{
-                secret_evaluation_point = LargeField::from(0 as u64);
-            }
-            else{
-                secret_evaluation_point = self.roots_of_unity.get(0).unwrap().clone();
-            }
-
-            // Generate vandermonde matrix
-            let vandermonde = vandermonde_matrix(eval_points.clone());
-            let inverse_vandermonde = inverse_vandermonde(vandermonde);
+            // Interpolate polynomials, the secrets are the evaluations at 0 on both evaluation domains
+            let secret_evaluation_point = LargeField::from(0 as u64);
 
-            let l2_shares : Vec<LargeFieldSer> = shares_indexed.into_par_iter().map(|evals|{
-                let coefficients = matrix_vector_multiply(&inverse_vandermonde, &evals);
-                return Polynomial::new(&coefficients).evaluate(&secret_evaluation_point).to_bytes_be();
+            let l2_shares : Vec<LargeFieldSer> = interpolate_at_point(&eval_points, shares_indexed, &secret_evaluation_point).into_iter().map(|share|{
+                return share.to_bytes_be();
             }).collect();
 
             acss_ab_state.public_reconstruction_l1_status.insert(acss_msg.rep);
@@ -520,10 +524,16 @@ impl Context{
 
     pub async fn process_pub_rec_l2_msg(&mut self, instance_id: usize, source_party: Replica, shares: Vec<LargeFieldSer>, share_sender: Replica){
         log::debug!("Received PubRecL2 message for instance {} of party {}, shares received from party {} with shares.len() {}", instance_id, source_party, share_sender, shares.len());
+        if self.gc.is_collected(&instance_id){
+            log::debug!("ACSS instance {} already terminated and pruned, skipping message", instance_id);
+            return;
+        }
         if !self.acss_ab_state.contains_key(&instance_id){
             let acss_ab_state = ACSSABState::new();
             self.acss_ab_state.insert(instance_id, acss_ab_state);
         }
+        // Evaluation point of each party
+        let party_points = self.gen_evaluation_points();
         let acss_ab_state = self.acss_ab_state.get_mut(&instance_id).unwrap();
 
         if acss_ab_state.public_reconstruction_l2_status.contains(&source_party){
@@ -550,7 +560,7 @@ impl Context{
             }
             for party in 0..self.num_nodes{
                 if share_map.contains_key(&party){
-                    evaluation_points.push(LargeField::from((party+1) as u64));
+                    evaluation_points.push(party_points[party].clone());
                     let shares_party = share_map.get(&party).unwrap();
                     for (index, share) in shares_party.into_iter().enumerate(){
                         shares_indexed[index].push(share.clone());
@@ -558,20 +568,13 @@ impl Context{
                 }
             }
 
-            // Interpolate polynomials
-            // Generate vandermonde matrix
-            let vandermonde = vandermonde_matrix(evaluation_points.clone());
-            let inverse_vandermonde = inverse_vandermonde(vandermonde);
-
-            let secrets : Vec<LargeField> = shares_indexed.into_par_iter().map(|evals|{
-                let coefficients = matrix_vector_multiply(&inverse_vandermonde, &evals);
-                return Polynomial::new(&coefficients).coefficients;
-            }).flatten().collect();
+            // Interpolate polynomials, every polynomial packs t+1 secrets in its coefficients
+            let secrets : Vec<LargeField> = interpolate_polynomials(&evaluation_points, shares_indexed).into_iter().flatten().collect();
 
             log::debug!("Successfully interpolated secrets after l2 public reconstruction for instance id {} and source party {} with secrets_len: {}", instance_id, source_party, secrets.len());
 
             acss_ab_state.public_reconstruction_l2_status.insert(source_party);
-            let _status = self.out_pub_rec_out.send((instance_id, source_party, secrets)).await;
+            self.deliver_pub_rec_chunk(instance_id, source_party, secrets).await;
         }
     }
 }
\ No newline at end of file
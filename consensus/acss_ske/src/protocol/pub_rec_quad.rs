@@ -1,18 +1,19 @@
 use std::{collections::HashMap};
 
-use consensus::{LargeField, vandermonde_matrix, inverse_vandermonde, matrix_vector_multiply};
+use consensus::{LargeField, interpolate_at_point};
 use ha_crypto::hash::Hash;
-use lambdaworks_math::{traits::ByteConversion, polynomial::Polynomial};
+use lambdaworks_math::traits::ByteConversion;
 use types::Replica;
 
-use rayon::prelude::{IntoParallelIterator, ParallelIterator};
-
-
 use crate::{Context, AcssSKEShares, protocol::ACSSABState};
 
 impl Context{
     pub async fn process_pub_rec_quad_msg(&mut self, instance_id: usize, acss_msg: AcssSKEShares, share_sender: Replica){
         log::debug!("Received PubRecL1 message for instance {} of party {}, shares received from party {}", instance_id, acss_msg.rep, share_sender);
+        if self.gc.is_collected(&instance_id){
+            log::debug!("ACSS instance {} already terminated and pruned, skipping message", instance_id);
+            return;
+        }
         if !self.acss_ab_state.contains_key(&instance_id){
             let acss_ab_state = ACSSABState::new();
             self.acss_ab_state.insert(instance_id, acss_ab_state);
@@ -105,25 +106,23 @@ impl Context{
             self.num_faults+1
         );
 
-        let root_comm_fe: Vec<LargeField> = roots_from_proofs.iter().zip(blinding_merkle_roots.iter()).map(|(root, b_root)|{
-            //let root = mt.root();
-            let root_combined = self.hash_context.hash_two(root.clone(), b_root.clone());
-            return LargeField::from_bytes_be(root_combined.as_slice()).unwrap();
+        let column_commitments: Vec<Hash> = roots_from_proofs.iter().zip(blinding_merkle_roots.iter()).map(|(root, b_root)|{
+            Self::column_commitment(va_commitment.instance_id, root, b_root)
         }).collect();
 
         let dzk_aggregated_points: Vec<LargeField> = grouped_points.into_iter().zip(
-            root_comm_fe.clone().into_iter()).map(|(shares, root)|{
-                return self.folding_dzk_context.gen_agg_poly_dzk(shares, root.to_bytes_be());
+            column_commitments.iter()).map(|(shares, commitment)|{
+                return self.dzk_context.gen_agg_poly_dzk(shares, *commitment);
             }).collect();
 
-        let status = self.folding_dzk_context.verify_dzk_proof_row(
+        let status = self.dzk_context.verify_dzk_proof_row(
             shares_full.dzk_iters.clone(), 
             va_commitment.dzk_roots.clone(), 
             va_commitment.polys.clone(), 
-            root_comm_fe.into_iter().map(|el| el.to_bytes_be()).collect(), 
+            column_commitments, 
             dzk_aggregated_points, 
             blinding_shares.clone(), 
-            share_sender+1
+            evaluation_points[share_sender].clone()
         );
 
         if !status{
@@ -153,34 +152,21 @@ impl Context{
             }
             for party in 0..self.num_nodes{
                 if quad_pub_rec_map.contains_key(&party){
-                    eval_points.push(LargeField::from((party+1) as u64));
+                    eval_points.push(evaluation_points[party].clone());
                     let shares_party = quad_pub_rec_map.get(&party).unwrap();
                     for (index,share) in shares_party.into_iter().enumerate(){
                         shares_indexed[index].push(share.clone());
                     }
                 }
             }
-            // Interpolate polynomials
-            let secret_evaluation_point;
-            if !self.use_fft{
-                secret_evaluation_point = LargeField::from(0 as u64);
-            }
-            else{
-                secret_evaluation_point = self.roots_of_unity.get(0).unwrap().clone();
-            }
+            // Interpolate polynomials, the secrets are the evaluations at 0 on both evaluation domains
+            let secret_evaluation_point = LargeField::from(0 as u64);
 
-            // Generate vandermonde matrix
-            let vandermonde = vandermonde_matrix(eval_points.clone());
-            let inverse_vandermonde = inverse_vandermonde(vandermonde);
-
-            let secrets : Vec<LargeField> = shares_indexed.into_par_iter().map(|evals|{
-                let coefficients = matrix_vector_multiply(&inverse_vandermonde, &evals);
-                return Polynomial::new(&coefficients).evaluate(&secret_evaluation_point);
-            }).collect();
+            let secrets : Vec<LargeField> = interpolate_at_point(&eval_points, shares_indexed, &secret_evaluation_point);
 
             acss_ab_state.public_reconstruction_l1_status.insert(sender);
             log::debug!("Successfully interpolated shares for l2 public reconstruction for instance id {} and source party {}", instance_id, sender);
-            let _status = self.out_pub_rec_out.send((instance_id, sender, secrets)).await;
+            self.deliver_pub_rec_chunk(instance_id, sender, secrets).await;
         }
     }
 }
\ No newline at end of file
@@ -6,6 +6,10 @@ use crate::{Context, protocol::ACSSABState};
 impl Context{
     pub async fn handle_ra_termination(&mut self, instance_id: usize, sender: usize, value: usize){
         log::debug!("Received RA termination message from sender {} with value {}",sender, value);
+        if self.gc.is_collected(&instance_id){
+            log::debug!("ACSS instance {} already terminated and pruned, skipping message", instance_id);
+            return;
+        }
         if !self.acss_ab_state.contains_key(&instance_id) {
             let acss_state = ACSSABState::new();
             self.acss_ab_state.insert(instance_id, acss_state);
@@ -27,6 +31,7 @@ impl Context{
         if acss_state.shares.contains_key(&sender) 
         && acss_state.ra_outputs.contains(&sender) 
         && acss_state.verification_status.contains_key(&sender){
+            let output;
             if acss_state.verification_status.get(&sender).unwrap().clone(){
                 // Send shares back to parent process
                 log::debug!("Sending shares back to syncer for sender {} for instance id {}",sender, instance_id);
@@ -53,16 +58,44 @@ impl Context{
                 let root_comm = acss_state.commitment_root_fe.get(&sender).unwrap().clone();
 
                 let shares: Vec<LargeField> = acss_state.shares.get(&sender).unwrap().clone().evaluations.0.into_iter().map(|el| LargeField::from_bytes_be(el.as_slice()).unwrap()).collect();
-                let _status = self.out_acss.send((instance_id,sender, root_comm,Some(shares))).await;
-                acss_state.acss_status.insert(sender);
+                output = Some((root_comm, shares));
                 //}
                 //self.terminate("Hello".to_string()).await;
             }
             else{
-                let _status = self.out_acss.send((instance_id, sender, [0;32],None)).await;
-                acss_state.acss_status.insert(sender);
+                output = None;
+            }
+            acss_state.acss_status.insert(sender);
+            let all_settled = acss_state.acss_status.len() == self.num_nodes;
+            self.deliver_chunk(instance_id, sender, output).await;
+            // The dealing only needs to be kept for public reconstruction within the retention window
+            self.dealer_gc.mark_terminated((sender, instance_id));
+            if all_settled{
+                self.gc.mark_terminated(instance_id);
+            }
+            self.prune_settled_dealings();
+        }
+    }
+
+    /// Drops the state of dealings and instances whose retention window elapsed.
+    /// Dealings are pruned one by one, so that a dealer that never settles does not pin the state of the others.
+    /// Dealings of a dealer that have not settled yet keep its messages from being dropped as collected.
+    pub fn prune_settled_dealings(&mut self){
+        let acss_ab_state = &self.acss_ab_state;
+        let expired_dealings = self.dealer_gc.collect_expired(|dealer| {
+            acss_ab_state.iter()
+                .filter(|(_, acss_state)| !acss_state.acss_status.contains(dealer))
+                .map(|(instance_id, _)| *instance_id)
+                .min()
+        });
+        for (dealer, instance_id) in expired_dealings{
+            if let Some(acss_state) = self.acss_ab_state.get_mut(&instance_id){
+                acss_state.prune_dealer(dealer);
             }
         }
+        let expired = self.gc.prune(&mut self.acss_ab_state);
+        self.blame_state.prune(&expired);
+        self.prune_chunked_outputs(&expired);
     }
     // Invoke this function once you terminate the protocol
     // pub async fn terminate(&mut self, data: String) {
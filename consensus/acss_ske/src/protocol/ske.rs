@@ -1,8 +1,8 @@
-use consensus::{interpolate_shares};
+use consensus::{interpolate_shares, signed_bytes};
 use ha_crypto::{decrypt};
 use types::Replica;
 
-use crate::{Context, msg::AcssSKEShares};
+use crate::{Context, msg::{AcssSKEShares, BlameReason, DealtShares}, protocol::{chunk_key, split_chunk_instance_id}};
 
 impl Context{
     pub async fn interpolate_shares(&mut self, sender_rep: Replica, instance_id: usize){
@@ -27,21 +27,26 @@ impl Context{
             
         let comm_dzk_vals = acss_ab_state.commitments.get(&sender_rep).unwrap().clone();
         // Interpolate shares here for first t parties
-        if !self.use_fft && self.myid < self.num_faults{
+        if self.myid < self.num_faults{
             // Interpolate your shares in this case
-            let secret_key = self.symmetric_keys_avid.keys_to_me.get(&sender_rep).clone().unwrap().clone();
-            let shares = interpolate_shares(secret_key.clone(), comm_dzk_vals.tot_shares, false, 1).into_iter().map(|el| el.to_bytes_be()).collect();
-            let nonce_shares = interpolate_shares(secret_key.clone(),self.num_nodes, true, 1u8).into_iter().map(|el| el.to_bytes_be()).collect();
-            let blinding_shares = interpolate_shares(secret_key.clone(), self.num_nodes, true, 2u8).into_iter().map(|el| el.to_bytes_be()).collect();
-            let blinding_nonce_shares = interpolate_shares(secret_key, self.num_nodes, true, 3u8).into_iter().map(|el| el.to_bytes_be()).collect();
-            
+            let secret_key = chunk_key(self.symmetric_keys_avid.keys_to_me.get(&sender_rep).unwrap(), split_chunk_instance_id(instance_id).1);
             let acss_shares = acss_ab_state.shares.get_mut(&sender_rep).unwrap();
-            acss_shares.evaluations = (shares, nonce_shares, acss_shares.evaluations.2.clone());
-            acss_shares.blinding_evaluations = (blinding_shares,blinding_nonce_shares, acss_shares.blinding_evaluations.2.clone());
+            Self::expand_shares_from_key(secret_key, comm_dzk_vals.tot_shares, self.num_nodes, acss_shares);
         }
         self.verify_shares(sender_rep,instance_id).await;
     }
 
+    /// The first t parties receive only Merkle proofs, their shares are expanded from the PRF key of the dealer.
+    pub fn expand_shares_from_key(secret_key: Vec<u8>, tot_shares: usize, num_nodes: usize, acss_shares: &mut AcssSKEShares){
+        let shares = interpolate_shares(secret_key.clone(), tot_shares, false, 1).into_iter().map(|el| el.to_bytes_be()).collect();
+        let nonce_shares = interpolate_shares(secret_key.clone(),num_nodes, true, 1u8).into_iter().map(|el| el.to_bytes_be()).collect();
+        let blinding_shares = interpolate_shares(secret_key.clone(), num_nodes, true, 2u8).into_iter().map(|el| el.to_bytes_be()).collect();
+        let blinding_nonce_shares = interpolate_shares(secret_key, num_nodes, true, 3u8).into_iter().map(|el| el.to_bytes_be()).collect();
+        
+        acss_shares.evaluations = (shares, nonce_shares, acss_shares.evaluations.2.clone());
+        acss_shares.blinding_evaluations = (blinding_shares,blinding_nonce_shares, acss_shares.blinding_evaluations.2.clone());
+    }
+
     pub async fn decrypt_shares(&mut self, sender_rep: Replica, instance_id: usize) {
         if !self.acss_ab_state.contains_key(&instance_id) {
             return;
@@ -59,11 +64,42 @@ impl Context{
         let shares = acss_ab_state.enc_shares.get(&sender_rep).unwrap().clone();
         
         let dec_shares = decrypt(sec_key.as_slice(), shares);
-        let shares : AcssSKEShares = bincode::deserialize(dec_shares.as_slice()).unwrap();
-        // Decrypt shares here
-        // Assuming decrypt function is defined elsewhere
+        let (shares, signature) : (AcssSKEShares, Option<Vec<u8>>) = match bincode::deserialize(dec_shares.as_slice()){
+            Ok(dealt) => dealt,
+            Err(e) => {
+                log::error!("Failed to deserialize shares from sender {} in instance_id {}: {}", sender_rep, instance_id, e);
+                self.complain(sender_rep, instance_id, BlameReason::MalformedShares).await;
+                return;
+            }
+        };
         log::debug!("Decrypted shares for sender {} in instance_id {}", sender_rep, instance_id);
-        acss_ab_state.shares.insert(sender_rep, shares);
+        // Keep the dealer's signature only if it is valid, otherwise a complaint against this dealer cannot be proven
+        if let Some(signature) = signature{
+            let dealt = DealtShares{
+                instance_id: instance_id,
+                recipient: self.myid,
+                key: chunk_key(&sec_key, split_chunk_instance_id(instance_id).1),
+                shares: shares,
+            };
+            let valid = match self.session.signature_keys.as_ref(){
+                Some(keys) => keys.verify(
+                    sender_rep, 
+                    signed_bytes(self.session.session_id, self.session.service.as_str(), sender_rep, &dealt).as_slice(),
+                    signature.as_slice()
+                ),
+                None => false,
+            };
+            if valid{
+                acss_ab_state.dealt_signatures.insert(sender_rep, signature);
+            }
+            else{
+                log::warn!("Invalid signature on shares from sender {} in instance_id {}", sender_rep, instance_id);
+            }
+            acss_ab_state.shares.insert(sender_rep, dealt.shares);
+        }
+        else{
+            acss_ab_state.shares.insert(sender_rep, shares);
+        }
         if self.myid < self.num_faults{
             self.interpolate_shares(sender_rep, instance_id).await;
         }
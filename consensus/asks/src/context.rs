@@ -8,22 +8,22 @@ use config::Node;
 use fnv::FnvHashMap;
 use network::{plaintcp::{CancelHandler, TcpReceiver, TcpReliableSender}, Acknowledgement, Message};
 use tokio::sync::{
-    mpsc::{unbounded_channel, UnboundedReceiver, Receiver, Sender},
+    mpsc::{Receiver, Sender},
     oneshot,
 };
 // use tokio_util::time::DelayQueue;
 use types::{Replica, WrapperMsg};
 
-use consensus::{LargeFieldSSS};
+use consensus::{LargeFieldSSS, ClientOutputChannel, InstanceGC, gc_retention, ingress_channel, ingress_per_sender_capacity, IngressReceiver, SessionMsg, SessionContext, session_id};
 
-use ha_crypto::{aes_hash::HashState, LargeField};
+use ha_crypto::aes_hash::HashState;
 
-use crate::{protocol::ASKSState, msg::ProtMsg, handlers::Handler};
+use crate::{protocol::ASKSState, msg::ProtMsg, handlers::Handler, ASKSRequest, ASKSOutput};
 
 pub struct Context {
     /// Networking context
-    pub net_send: TcpReliableSender<Replica, WrapperMsg<ProtMsg>, Acknowledgement>,
-    pub net_recv: UnboundedReceiver<WrapperMsg<ProtMsg>>,
+    pub net_send: TcpReliableSender<Replica, WrapperMsg<SessionMsg<ProtMsg>>, Acknowledgement>,
+    pub net_recv: IngressReceiver<WrapperMsg<SessionMsg<ProtMsg>>>,
     
     /// Data context
     pub num_nodes: usize,
@@ -33,6 +33,8 @@ pub struct Context {
 
     /// Secret Key map
     pub sec_key_map: HashMap<Replica, Vec<u8>>,
+    /// Session binding and replay protection
+    pub session: SessionContext,
 
     /// Hardware acceleration context
     pub hash_context: HashState,
@@ -41,10 +43,6 @@ pub struct Context {
     pub cancel_handlers: HashMap<u64, Vec<CancelHandler<Acknowledgement>>>,
     exit_rx: oneshot::Receiver<()>,
     
-    // Maximum number of RBCs that can be initiated by a node. Keep this as an identifier for RBC service. 
-    pub threshold: usize, 
-
-    pub max_id: usize,
 
     pub large_field_uv_sss: LargeFieldSSS,
 
@@ -53,17 +51,20 @@ pub struct Context {
 
     /// State for ACSS
     pub asks_state: HashMap<usize, ASKSState>,
-
-    /// Input and output request channels
-    /// First: Instance id, Second: Number of secrets, Third: Reconstruction to all or none, Fourth: Request for reconstruction/sharing, Fifth: Reconstruction ID
-    pub inp_asks_requests: Receiver<(usize, usize, bool, bool, Option<Vec<LargeField>>, Option<usize>)>,
-    pub out_asks_values: Sender<(usize, Replica, Option<Vec<LargeField>>)>
+    /// Channel to the client, set if private output delivery is enabled
+    pub client_output: Option<ClientOutputChannel>,
+    /// Lifecycle of ASKS instances whose secrets were reconstructed, pruned after the retention window
+    pub gc: InstanceGC<usize>,
+
+    /// Input and output request channels, driven through `ASKSService`
+    pub inp_asks_requests: Receiver<ASKSRequest>,
+    pub out_asks_values: Sender<ASKSOutput>
 }
 
 impl Context {
     pub fn spawn(config: Node,
-        input_reqs: Receiver<(usize, usize, bool, bool, Option<Vec<LargeField>>, Option<usize>)>, 
-        output_shares: Sender<(usize,Replica,Option<Vec<LargeField>>)>,
+        input_reqs: Receiver<ASKSRequest>, 
+        output_shares: Sender<ASKSOutput>,
         byz: bool) -> anyhow::Result<oneshot::Sender<()>> {
         // Add a separate configuration for RBC service. 
 
@@ -76,13 +77,13 @@ impl Context {
         let my_address = to_socket_address("0.0.0.0", my_port.port());
 
         // Setup networking
-        let (tx_net_to_consensus, rx_net_to_consensus) = unbounded_channel();
-        TcpReceiver::<Acknowledgement, WrapperMsg<ProtMsg>, _>::spawn(
+        let (tx_net_to_consensus, rx_net_to_consensus) = ingress_channel(ingress_per_sender_capacity(), config.sk_map.clone());
+        TcpReceiver::<Acknowledgement, WrapperMsg<SessionMsg<ProtMsg>>, _>::spawn(
             my_address,
             Handler::new(tx_net_to_consensus),
         );
 
-        let consensus_net = TcpReliableSender::<Replica, WrapperMsg<ProtMsg>, Acknowledgement>::with_peers(
+        let consensus_net = TcpReliableSender::<Replica, WrapperMsg<SessionMsg<ProtMsg>>, Acknowledgement>::with_peers(
             consensus_addrs.clone(),
         );
         
@@ -94,14 +95,14 @@ impl Context {
         let key2 = [23u8; 16];
         let hashstate = HashState::new(key0, key1, key2);
 
-        let threshold:usize = 10000;
-        let rbc_start_id = threshold*config.id;
-
         let lf_uv_sss = LargeFieldSSS::new_with_vandermonde(
             config.num_faults +1,
             config.num_nodes,
         );
 
+        let session = SessionContext::new(session_id(), "asks", config.id, &consensus_addrs);
+        let client_output = ClientOutputChannel::from_config(&config);
+
         tokio::spawn(async move {
             let mut c = Context {
                 net_send: consensus_net,
@@ -109,6 +110,7 @@ impl Context {
                 
                 num_nodes: config.num_nodes,
                 sec_key_map: HashMap::default(),
+                session: session,
                 hash_context: hashstate,
                 myid: config.id,
                 _byz: byz,
@@ -117,13 +119,12 @@ impl Context {
                 exit_rx: exit_rx,
                 
                 //avid_context:HashMap::default(),
-                threshold: 10000,
-
-                max_id: rbc_start_id, 
 
                 large_field_uv_sss: lf_uv_sss,
 
                 asks_state: HashMap::default(),
+                client_output: client_output,
+                gc: InstanceGC::new(gc_retention()),
                 nonce_seed: 1,
 
                 inp_asks_requests: input_reqs,
@@ -146,7 +147,7 @@ impl Context {
     pub async fn broadcast(&mut self, protmsg: ProtMsg) {
         let sec_key_map = self.sec_key_map.clone();
         for (replica, sec_key) in sec_key_map.into_iter() {
-            let wrapper_msg = WrapperMsg::new(protmsg.clone(), self.myid, &sec_key.as_slice());
+            let wrapper_msg = WrapperMsg::new(self.session.seal(replica, protmsg.clone()), self.myid, &sec_key.as_slice());
             let cancel_handler: CancelHandler<Acknowledgement> = self.net_send.send(replica, wrapper_msg).await;
             self.add_cancel_handler(cancel_handler);
         }
@@ -156,7 +157,7 @@ impl Context {
         let mut total_bytes = 0;
         let sec_key_map = self.sec_key_map.clone();
         for (replica, sec_key) in sec_key_map.into_iter() {
-            let wrapper_msg = WrapperMsg::new(protmsg.clone(), self.myid, &sec_key.as_slice());
+            let wrapper_msg = WrapperMsg::new(self.session.seal(replica, protmsg.clone()), self.myid, &sec_key.as_slice());
             total_bytes += Bytes::from(wrapper_msg.to_bytes()).len();
             let cancel_handler: CancelHandler<Acknowledgement> = self.net_send.send(replica, wrapper_msg).await;
             self.add_cancel_handler(cancel_handler);
@@ -165,10 +166,10 @@ impl Context {
     }
 
     pub fn add_cancel_handler(&mut self, canc: CancelHandler<Acknowledgement>) {
-        self.cancel_handlers.entry(0).or_default().push(canc);
+        consensus::add_cancel_handler_gc(&mut self.cancel_handlers, canc);
     }
 
-    pub async fn send(&mut self, replica: Replica, wrapper_msg: WrapperMsg<ProtMsg>) {
+    pub async fn send(&mut self, replica: Replica, wrapper_msg: WrapperMsg<SessionMsg<ProtMsg>>) {
         #[cfg(feature = "bandwidth")]
         log::info!("Network sending bytes: {:?}", Bytes::from(wrapper_msg.to_bytes()).len());
         let cancel_handler: CancelHandler<Acknowledgement> =
@@ -176,6 +177,24 @@ impl Context {
         self.add_cancel_handler(cancel_handler);
     }
 
+    /// Internal id of the dealing `id` of `dealer`. Dealers choose their ids independently, so the dealer is packed into the id.
+    fn asks_instance_id(&self, dealer: Replica, id: usize) -> Option<usize>{
+        if dealer >= self.num_nodes{
+            log::error!("Invalid dealer {} of ASKS instance {}", dealer, id);
+            return None;
+        }
+        let instance_id = id.checked_mul(self.num_nodes).and_then(|packed| packed.checked_add(dealer));
+        if instance_id.is_none(){
+            log::error!("ASKS instance id {} of dealer {} is too large", id, dealer);
+        }
+        instance_id
+    }
+
+    /// Inverse of `asks_instance_id`, returns the caller's id and the dealer
+    pub fn split_asks_instance_id(&self, instance_id: usize) -> (usize, Replica){
+        (instance_id / self.num_nodes, instance_id % self.num_nodes)
+    }
+
     pub async fn run(&mut self){
         // The process starts listening to messages in this process.
         // First, the node sends an alive message
@@ -200,17 +219,22 @@ impl Context {
                         log::error!("Request channel closed");
                         return;
                     }
-                    let req_msg = req_msg.unwrap();
-                    if !req_msg.3{
-                        let acss_inst_id = self.max_id + 1;
-                        self.max_id = acss_inst_id;
-                        
-                        self.init_asks(acss_inst_id, req_msg.1, req_msg.2, req_msg.4).await;
-                    }
-                    else {
-                        // Reconstruct this message
-                        let instance_id = self.threshold*req_msg.5.unwrap() + req_msg.0;
-                        self.reconstruct_asks(instance_id, req_msg.2).await;
+                    match req_msg.unwrap(){
+                        ASKSRequest::Share { id, num_secrets, secrets, reconstruct_to_all } => {
+                            if let Some(instance_id) = self.asks_instance_id(self.myid, id){
+                                self.init_asks(instance_id, num_secrets, reconstruct_to_all, secrets).await;
+                            }
+                        },
+                        ASKSRequest::Reconstruct { id, dealer, indices, recipients } => {
+                            if let Some(instance_id) = self.asks_instance_id(dealer, id){
+                                self.reconstruct_asks(instance_id, indices, recipients).await;
+                            }
+                        },
+                        ASKSRequest::ReconstructToClient { id, dealer, indices } => {
+                            if let Some(instance_id) = self.asks_instance_id(dealer, id){
+                                self.reconstruct_asks_to_client(instance_id, indices).await;
+                            }
+                        }
                     }
                 },
             };
@@ -1,6 +1,9 @@
 mod context;
 pub use context::*;
 
+mod service;
+pub use service::*;
+
 mod msg;
 
 mod protocol;
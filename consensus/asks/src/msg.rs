@@ -1,5 +1,5 @@
-use consensus::CTRBCMsg;
-use ha_crypto::{LargeField, LargeFieldSer, aes_hash::{HashState, Proof}, hash::Hash};
+use consensus::{share_commitment, CTRBCMsg, LargeField, LargeFieldSer};
+use ha_crypto::{aes_hash::{HashState, Proof}, hash::Hash};
 
 use lambdaworks_math::traits::ByteConversion;
 use serde::{Serialize, Deserialize};
@@ -43,13 +43,11 @@ impl WSSMsgSer {
         }
     }
 
-    pub fn compute_commitments(&self, hc: &HashState) -> Vec<Hash>{
+    /// Commitments to the shares of the party at `position`
+    pub fn compute_commitments(&self, hc: &HashState, position: Replica) -> Vec<Hash>{
         let mut comm_vector = Vec::new();
         for (share,nonce) in self.shares.iter().zip(self.nonce_shares.iter()){
-            let mut appended_vec = Vec::new();
-            appended_vec.extend(share.clone());
-            appended_vec.extend(nonce.clone());
-            comm_vector.push(hc.do_hash_aes(appended_vec.as_slice()));
+            comm_vector.push(share_commitment(hc, position, share, nonce));
         }
         comm_vector
     }
@@ -63,7 +61,7 @@ pub enum ProtMsg{
     Echo(CTRBCMsg, bool,usize),
     // READY contains only indices and roots.
     Ready(CTRBCMsg, bool,usize),
-    // Reconstruct message
-    Reconstruct(WSSMsgSer, usize)
+    // Reconstruct message, carrying the shares of the secrets at the given indices
+    Reconstruct(WSSMsgSer, Vec<usize>, usize)
 }
 
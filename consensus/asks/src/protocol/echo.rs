@@ -9,6 +9,10 @@ use super::ASKSState;
 
 impl Context{
     pub async fn process_asks_echo(&mut self, ctrbc_msg: CTRBCMsg, echo_sender: Replica, reconstruct_to_all: bool, instance_id: usize){
+        if self.gc.is_collected(&instance_id){
+            log::debug!("ASKS instance {} already terminated and pruned, skipping message", instance_id);
+            return;
+        }
         log::debug!("Processing ASKS ECHO from {} for instance {}", echo_sender, instance_id);
         if !self.asks_state.contains_key(&instance_id){
             let new_state = ASKSState::new(ctrbc_msg.origin, reconstruct_to_all);
@@ -114,7 +118,7 @@ impl Context{
                 asks_state.terminated = true;
                 let _message = asks_state.rbc_state.message.clone().unwrap();
                 //self.reconstruct_asks(instance_id).await;
-                self.terminate(instance_id, None).await;
+                self.terminate(instance_id).await;
             }
         }
     }
@@ -1,6 +1,6 @@
 use bytes::Bytes;
-use consensus::{CTRBCMsg, get_shards};
-use ha_crypto::{LargeField, hash::Hash, encrypt, decrypt, aes_hash::{MerkleTree, Proof}, rand_field_element};
+use consensus::{CTRBCMsg, get_shards, share_commitment, LargeField, rand_field_element};
+use ha_crypto::{hash::Hash, encrypt, decrypt, aes_hash::{MerkleTree, Proof}};
 
 use network::{plaintcp::CancelHandler, Acknowledgement, Message};
 use types::{WrapperMsg, Replica};
@@ -12,6 +12,14 @@ use super::state::ASKSState;
 impl Context{
     pub async fn init_asks(&mut self, instance_id: usize, num_secrets: usize, reconstruct_to_all: bool, secret_vec: Option<Vec<LargeField>>){        
         log::debug!("Got request to initialize ASKS instance {} with {} secrets, reconstruct_all_to_all: {}", instance_id,num_secrets, reconstruct_to_all);
+        if secret_vec.as_ref().map(|secrets| secrets.len() != num_secrets).unwrap_or(false){
+            log::error!("Request to share {} secrets in ASKS instance {} provides {} secrets", num_secrets, instance_id, secret_vec.unwrap().len());
+            return;
+        }
+        if !reconstruct_to_all && num_secrets > self.num_nodes{
+            log::error!("Private ASKS instance {} shares {} secrets, but each of the {} parties owns at most one", instance_id, num_secrets, self.num_nodes);
+            return;
+        }
         // Sample secret polynomial first
         let mut shares_vec = Vec::new();
         let mut nonce_shares_vec = Vec::new();
@@ -49,11 +57,8 @@ impl Context{
                 self.large_field_uv_sss.mod_evaluate_at(&nonce_coefficients, point)
             ).collect();
     
-            let commitments: Vec<Hash> = shares.clone().into_iter().zip(nonce_shares.clone().into_iter()).map(|(share,nonce)|{
-                let mut appended_vec = Vec::new();
-                appended_vec.extend(share.to_bytes_be());
-                appended_vec.extend(nonce.to_bytes_be());
-                return self.hash_context.do_hash_aes(appended_vec.as_slice());
+            let commitments: Vec<Hash> = shares.iter().zip(nonce_shares.iter()).enumerate().map(|(position, (share,nonce))|{
+                share_commitment(&self.hash_context, position, &share.to_bytes_be(), &nonce.to_bytes_be())
             }).collect();
 
             let merkle_tree = MerkleTree::new(commitments,&self.hash_context);
@@ -86,7 +91,7 @@ impl Context{
             let encrypted_share = encrypt(&secret_key, bincode::serialize(&wss_sermsg).unwrap());
 
             let prot_msg_init = ProtMsg::Init( encrypted_share, instance_id);
-            let wrapper_msg = WrapperMsg::new(prot_msg_init, self.myid, &secret_key);
+            let wrapper_msg = WrapperMsg::new(self.session.seal(rep, prot_msg_init), self.myid, &secret_key);
             #[cfg(feature = "bandwidth")]
             log::info!("Network sending bytes: {:?}", Bytes::from(wrapper_msg.to_bytes()).len());
             let cancel_handler = self.net_send.send(rep, wrapper_msg).await;
@@ -95,6 +100,10 @@ impl Context{
     }
 
     pub async fn process_init_asks(&mut self, enc_shares: Vec<u8>, sender: Replica, instance_id: usize){
+        if self.gc.is_collected(&instance_id){
+            log::debug!("ASKS instance {} already terminated and pruned, skipping message", instance_id);
+            return;
+        }
         // Decrypt message
         log::debug!("Processing Init ASKS message from party {} in instance id {}", sender, instance_id);
         let secret_key_sender = self.sec_key_map.get(&sender).unwrap();
@@ -102,7 +111,8 @@ impl Context{
         let deser_msg: WSSMsgSer = bincode::deserialize(dec_msg.as_slice()).unwrap();
         
         // Verify commitment
-        let share_comm = deser_msg.compute_commitments(&self.hash_context);
+        // The leaves bind the shares to the position of this party
+        let share_comm = deser_msg.compute_commitments(&self.hash_context, self.myid);
         let share_msg = deser_msg.to_unser();
         let mut bool_flag = true;
         for (proof,item) in share_msg.merkle_proofs.iter().zip(share_comm.iter()){
@@ -147,7 +157,7 @@ impl Context{
             };
 
             let echo = ProtMsg::Echo(rbc_msg, deser_msg.reconstruct_to_all, instance_id);
-            let wrapper_msg = WrapperMsg::new(echo,self.myid, secret_key_party.as_slice());
+            let wrapper_msg = WrapperMsg::new(self.session.seal(rep, echo),self.myid, secret_key_party.as_slice());
             #[cfg(feature = "bandwidth")]
             log::info!("Network sending bytes: {:?}", Bytes::from(wrapper_msg.to_bytes()).len());
             let cancel_handler: CancelHandler<Acknowledgement> = self.net_send.send(rep, wrapper_msg).await;
@@ -1,12 +1,16 @@
 use consensus::{reconstruct_data, CTRBCMsg};
-use ha_crypto::{hash::Hash, aes_hash::MerkleTree, LargeField};
+use ha_crypto::{hash::Hash, aes_hash::MerkleTree};
 use types::Replica;
 
-use crate::{context::Context, protocol::ASKSState, msg::ProtMsg};
+use crate::{context::Context, protocol::ASKSState, msg::ProtMsg, ASKSOutput};
 
 impl Context{
 
     pub async fn process_asks_ready(&mut self, ctrbc_msg: CTRBCMsg, ready_sender: Replica, reconstruct_to_all: bool,instance_id: usize){
+        if self.gc.is_collected(&instance_id){
+            log::debug!("ASKS instance {} already terminated and pruned, skipping message", instance_id);
+            return;
+        }
         log::debug!("Processing ASKS READY from {} for instance {}", ready_sender, instance_id);
         if !self.asks_state.contains_key(&instance_id){
             let asks_state = ASKSState::new(ctrbc_msg.origin, reconstruct_to_all);
@@ -118,25 +122,15 @@ impl Context{
             // Terminate protocol
             asks_context.rbc_state.terminated = true;
             asks_context.terminated = true;
-            self.terminate(instance_id, None).await;
+            self.terminate(instance_id).await;
         }
     }
 
-    pub async fn terminate(&mut self, instance_id: usize, secrets: Option<Vec<LargeField>>){
-        let instance: usize = instance_id % self.threshold;
-        let rep = instance_id/self.threshold;
-
-        if secrets.is_none(){
-            // Completed sharing
-            let msg = (instance, rep, None);
-            let status = self.out_asks_values.send(msg).await;
-            log::debug!("Sent result back to original channel {:?}", status);
-        }
-        else{
-            // Completed reconstruction of the secret
-            let msg = (instance,rep, Some(secrets.unwrap()));
-            let status = self.out_asks_values.send(msg).await;
-            log::debug!("Sent result back to original channel {:?}", status);
-        }
+    pub async fn terminate(&mut self, instance_id: usize){
+        // Completed sharing
+        let (id, dealer) = self.split_asks_instance_id(instance_id);
+        let msg = ASKSOutput::Shared { id: id, dealer: dealer };
+        let status = self.out_asks_values.send(msg).await;
+        log::debug!("Sent result back to original channel {:?}", status);
     }
 }
\ No newline at end of file
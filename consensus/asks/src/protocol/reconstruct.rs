@@ -1,71 +1,102 @@
-use std::collections::HashMap;
-use bytes::Bytes;
-use ha_crypto::{LargeField, hash::Hash, aes_hash::MerkleTree};
-use lambdaworks_math::polynomial::Polynomial;
-use network::Message;
-use types::WrapperMsg;
+use std::collections::HashSet;
+use consensus::{ClientShare, ClientShares};
+use types::{Replica, WrapperMsg};
 
-use crate::{context::Context, msg::{WSSMsg, WSSMsgSer, ProtMsg}};
+use crate::{context::Context, msg::{WSSMsg, WSSMsgSer, ProtMsg}, ASKSOutput, ASKSReconstruction};
 
 use super::ASKSState;
 
 impl Context{
-    pub async fn reconstruct_asks(&mut self, instance_id: usize, reconstruct_to_all: bool){
+    pub async fn reconstruct_asks(&mut self, instance_id: usize, indices: Option<Vec<usize>>, recipients: Option<Vec<Replica>>){
         if !self.asks_state.contains_key(&instance_id){
             log::error!("Do not possess ASKS state with the given instance ID {}", instance_id);
             return;
         }
 
         let asks_context = self.asks_state.get(&instance_id).unwrap();
-        // Check if the reconstruction needs to be all-to-all or just to a single dealer
-        if asks_context.shares.is_some(){
-            if reconstruct_to_all{
-                let share_msg = WSSMsg{
-                    shares: asks_context.shares.clone().unwrap(),
-                    nonce_shares: asks_context.nonce_shares.clone().unwrap(),
-                    merkle_proofs: asks_context.merkle_proofs.clone().unwrap(),
-                    reconstruct_to_all: reconstruct_to_all,
-                    origin: asks_context.origin
-                };
-                let share_msg_ser = WSSMsgSer::from_unser(&share_msg);
-                // Broadcast message
-                self.broadcast(ProtMsg::Reconstruct(share_msg_ser, instance_id)).await;
-            }
-            else{
-                let shares = asks_context.shares.clone().unwrap();
-                let nonce_shares = asks_context.nonce_shares.clone().unwrap();
-                let merkle_proofs = asks_context.merkle_proofs.clone().unwrap();
-                let origin = asks_context.origin.clone();
-                for (((index, share), nonce_share), merkle_proof) in (shares.iter().enumerate().zip(nonce_shares.iter())).zip(merkle_proofs.iter()){
-                    
-                    let share_msg = WSSMsg{
-                        shares: vec![share.clone()],
-                        nonce_shares: vec![nonce_share.clone()],
-                        merkle_proofs: vec![merkle_proof.clone()],
-                        reconstruct_to_all: reconstruct_to_all,
-                        origin: origin
-                    };
-                    let share_msg_ser = WSSMsgSer::from_unser(&share_msg);
-                    let sec_key = self.sec_key_map.get(&index).clone().unwrap();
-                    let wrapper_msg = WrapperMsg::new(
-                        ProtMsg::Reconstruct(share_msg_ser, instance_id),
-                        self.myid, 
-                        sec_key
-                    );
-                    #[cfg(feature = "bandwidth")]
-                    log::info!("Network sending bytes: {:?}", Bytes::from(wrapper_msg.to_bytes()).len());
-                    let cancel_handler = self.net_send.send(index, wrapper_msg).await;
-                    self.add_cancel_handler(cancel_handler);
-                }
-            }
-        }
-        else{
+        if asks_context.shares.is_none(){
             log::debug!("Did not receive share from dealer of instance id {}", instance_id);
             return;
         }
+        let shares = asks_context.shares.clone().unwrap();
+        let nonce_shares = asks_context.nonce_shares.clone().unwrap();
+        let merkle_proofs = asks_context.merkle_proofs.clone().unwrap();
+        let reconstruct_to_all = asks_context.reconstruct_to_all;
+        let origin = asks_context.origin;
+
+        let indices = indices.unwrap_or((0..shares.len()).collect());
+        if indices.iter().any(|index| *index >= shares.len()){
+            log::error!("Reconstruction request for ASKS instance {} selects indices {:?} out of {} secrets", instance_id, indices, shares.len());
+            return;
+        }
+        let recipients = recipients.unwrap_or((0..self.num_nodes).collect());
+        for (rep, rep_indices) in asks_context.share_recipients(&indices, recipients, self.num_nodes).into_iter(){
+            let share_msg = WSSMsg{
+                shares: rep_indices.iter().map(|index| shares[*index].clone()).collect(),
+                nonce_shares: rep_indices.iter().map(|index| nonce_shares[*index].clone()).collect(),
+                merkle_proofs: rep_indices.iter().map(|index| merkle_proofs[*index].clone()).collect(),
+                reconstruct_to_all: reconstruct_to_all,
+                origin: origin
+            };
+            let share_msg_ser = WSSMsgSer::from_unser(&share_msg);
+            let sec_key = self.sec_key_map.get(&rep).clone().unwrap();
+            let wrapper_msg = WrapperMsg::new(
+                self.session.seal(rep, ProtMsg::Reconstruct(share_msg_ser, rep_indices, instance_id)),
+                self.myid,
+                sec_key
+            );
+            self.send(rep, wrapper_msg).await;
+        }
     }
 
-    pub async fn process_asks_reconstruct(&mut self, share: WSSMsgSer, share_sender: usize, instance_id: usize){
+    /// Sends the shares of this party to the client instead of the committee, with the openings of their commitments.
+    pub async fn reconstruct_asks_to_client(&mut self, instance_id: usize, indices: Option<Vec<usize>>){
+        if self.client_output.is_none(){
+            log::error!("Client output is not enabled, cannot reconstruct ASKS instance {} to the client", instance_id);
+            return;
+        }
+        if !self.asks_state.contains_key(&instance_id){
+            log::error!("Do not possess ASKS state with the given instance ID {}", instance_id);
+            return;
+        }
+
+        let asks_context = self.asks_state.get(&instance_id).unwrap();
+        if asks_context.shares.is_none() || asks_context.roots.is_none(){
+            log::debug!("Did not receive share from dealer or commitments of instance id {}", instance_id);
+            return;
+        }
+        if !asks_context.reconstruct_to_all{
+            // The secrets of a private dealing belong to the parties
+            log::error!("ASKS instance {} is private, refusing to reconstruct it to the client", instance_id);
+            return;
+        }
+        let shares = asks_context.shares.clone().unwrap();
+        let nonce_shares = asks_context.nonce_shares.clone().unwrap();
+        let merkle_proofs = asks_context.merkle_proofs.clone().unwrap();
+        let roots = asks_context.roots.clone().unwrap();
+
+        let indices = indices.unwrap_or((0..shares.len()).collect());
+        if indices.iter().any(|index| *index >= shares.len()){
+            log::error!("Reconstruction request for ASKS instance {} selects indices {:?} out of {} secrets", instance_id, indices, shares.len());
+            return;
+        }
+        let client_shares = ClientShares {
+            shares: indices.into_iter().map(|index| ClientShare {
+                index: index,
+                share: shares[index].to_bytes_be(),
+                opening: Some((nonce_shares[index].to_bytes_be(), merkle_proofs[index].clone())),
+            }).collect(),
+            roots: Some(roots),
+        };
+        let cancel_handler = self.client_output.as_mut().unwrap().send("asks", instance_id, client_shares).await;
+        self.add_cancel_handler(cancel_handler);
+    }
+
+    pub async fn process_asks_reconstruct(&mut self, share: WSSMsgSer, indices: Vec<usize>, share_sender: usize, instance_id: usize){
+        if self.gc.is_collected(&instance_id){
+            log::debug!("ASKS instance {} already terminated and pruned, skipping message", instance_id);
+            return;
+        }
         if !self.asks_state.contains_key(&instance_id){
             let new_state = ASKSState::new(share.origin, share.reconstruct_to_all);
             self.asks_state.insert(instance_id, new_state);
@@ -77,91 +108,47 @@ impl Context{
             return;
         }
 
-        let mut roots = asks_state.roots.clone().unwrap();
-
-        // compute commitment of share and match it with terminated commitments
-        let share_comm: Vec<Hash> = share.merkle_proofs.iter().map(|proof| proof.root()).collect();
-        if !asks_state.reconstruct_to_all{
-            roots = vec![roots[self.myid].clone()];
-        }
-
-        if share_comm != roots{
-            log::error!("Commitment does not match broadcasted commitment for ASKS instance {}", instance_id);
+        let roots = asks_state.roots.clone().unwrap();
+        if indices.len() != share.shares.len() || indices.len() != share.nonce_shares.len() || indices.len() != share.merkle_proofs.len(){
+            log::error!("Malformed reconstruction message from party {} for ASKS instance {}", share_sender, instance_id);
             return;
         }
+        let mut distinct_indices = HashSet::new();
+        for index in indices.iter(){
+            if *index >= roots.len() || !distinct_indices.insert(*index) || (!asks_state.reconstruct_to_all && *index != self.myid){
+                log::error!("Party {} sent shares of indices {:?} for ASKS instance {}, which this party cannot reconstruct", share_sender, indices, instance_id);
+                return;
+            }
+        }
 
         let deser_share = share.to_unser();
-        if asks_state.secret_shares.len() < deser_share.shares.len(){
-            // append remaining vectors to the list
-            for _ in 0..deser_share.shares.len() - asks_state.secret_shares.len(){
-                asks_state.secret_shares.push(HashMap::new());
+        let mut results = Vec::new();
+        for ((index, secret_share), (nonce_share, proof)) in indices.into_iter().zip(deser_share.shares.into_iter()).zip(deser_share.nonce_shares.into_iter().zip(deser_share.merkle_proofs.iter())){
+            if let Some(result) = asks_state.add_secret_share(index, share_sender, secret_share, nonce_share, proof, self.num_nodes, self.num_faults, &self.hash_context){
+                results.push(result);
             }
         }
-        let mut recon_commitments = Vec::new();
-        let mut secrets = Vec::new();
-        for (share_map, (share,nonce)) in asks_state.secret_shares.iter_mut().zip(deser_share.shares.iter().zip(deser_share.nonce_shares.iter())){
-            share_map.insert(share_sender , (share.clone(), nonce.clone()));
-            if share_map.len() == self.num_faults +1 {
-                // Interpolate polynomial shares and coefficients
-                let mut share_poly_shares = Vec::new();
-                let mut nonce_poly_shares = Vec::new();
-                let mut evaluation_indices = Vec::new();
-    
-                for rep in 0..self.num_nodes{
-                    if share_map.contains_key(&rep){
-                        let shares_party = share_map.get(&rep).unwrap();
-                        let evaluation_index = LargeField::from((rep+1) as u64);
-                        evaluation_indices.push(evaluation_index);
-                        share_poly_shares.push(shares_party.0.clone());
-                        nonce_poly_shares.push(shares_party.1.clone());
-                    }
-                }
-                
-                // Interpolate polynomial
-                let share_poly_coeffs = Polynomial::interpolate(&evaluation_indices, &share_poly_shares).unwrap();
-                let nonce_poly_coeffs = Polynomial::interpolate(&evaluation_indices, &nonce_poly_shares).unwrap();
-                
-                let all_shares: Vec<LargeField> = (1..self.num_nodes+1).into_iter().map(|val| share_poly_coeffs.evaluate(&LargeField::from(val as u64))).collect();
-                let nonce_all_shares: Vec<LargeField> = (1..self.num_nodes+1).into_iter().map(|val| nonce_poly_coeffs.evaluate(&LargeField::from(val as u64))).collect();
-                
-                // Compute and match commitments
-                let all_commitments: Vec<Hash> = all_shares.into_iter().zip(nonce_all_shares.into_iter()).map(|(share,nonce)|{
-                    let mut appended_vec = Vec::new();
-                    appended_vec.extend(share.to_bytes_be());
-                    appended_vec.extend(nonce.to_bytes_be());
-                    return self.hash_context.do_hash_aes(appended_vec.as_slice());
-                }).collect();
-                
-                let root_comm = MerkleTree::new(all_commitments, &self.hash_context).root();
-                log::debug!("Reconstructed roots in ASKS instance {} initiated by party {}", instance_id, instance_id/self.threshold);
-                
-                recon_commitments.push(root_comm);
-                let secret = share_poly_coeffs.evaluate(&LargeField::zero()).clone();
-                secrets.push(secret);
-            }
+        if !results.is_empty(){
+            log::debug!("Sending back reconstructed values {:?} for ASKS instance {}", results, instance_id);
+            self.terminate_reconstruction(instance_id, results).await;
         }
-        if !recon_commitments.is_empty(){
-            if asks_state.reconstruct_to_all{
-                // Match state roots of all secrets
-                for ((index,recon_comm), broadcast_comm) in recon_commitments.into_iter().enumerate().zip(roots.into_iter()){
-                    if recon_comm != broadcast_comm {
-                        log::error!("Reconstructed commitment does not match the state roots for ASKS instance {}", instance_id);
-                        secrets[index] = LargeField::from(0 as u64);
-                    }
-                }
-            }
-            else{
-                // Else get the ith root from the set of commitments
-                let my_commitment = roots[0].clone();
-                let recon_comm = recon_commitments[0].clone();
-                if recon_comm != my_commitment{
-                    log::error!("Reconstructed commitment does not match the state roots for ASKS instance {} for secret key reconstruction", instance_id);
-                    secrets[0] = LargeField::from(0 as u64);
-                }
-            }
-            
-            log::debug!("Sending back value to ACS: {:?} for ASKS instance {}", secrets,instance_id);
-            self.terminate(instance_id, Some(secrets)).await;
+    }
+
+    async fn terminate_reconstruction(&mut self, instance_id: usize, results: Vec<ASKSReconstruction>){
+        let (id, dealer) = self.split_asks_instance_id(instance_id);
+        let msg = ASKSOutput::Reconstructed {
+            id: id,
+            dealer: dealer,
+            results: results,
+        };
+        let status = self.out_asks_values.send(msg).await;
+        log::debug!("Sent result back to original channel {:?}", status);
+
+        // The instance is complete once every secret this party can receive is reconstructed
+        let asks_state = self.asks_state.get(&instance_id).unwrap();
+        if asks_state.reconstructable_indices(self.myid).iter().all(|index| asks_state.secrets.contains_key(index)){
+            self.gc.mark_terminated(instance_id);
+            self.gc.prune(&mut self.asks_state);
         }
     }
-}
\ No newline at end of file
+}
@@ -1,10 +1,13 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use consensus::RBCState;
-use ha_crypto::{LargeField, hash::Hash, aes_hash::Proof};
+use consensus::{share_commitment, RBCState, LargeField};
+use ha_crypto::{hash::Hash, aes_hash::{HashState, MerkleTree, Proof}};
+use lambdaworks_math::polynomial::Polynomial;
 
 use types::Replica;
 
+use crate::ASKSReconstruction;
+
 pub struct ASKSState {
     pub reconstruct_to_all: bool,
     pub origin: Replica,
@@ -15,9 +18,13 @@ pub struct ASKSState {
 
     pub roots: Option<Vec<Hash>>,
 
-    pub secret_shares: Vec<HashMap<Replica, (LargeField, LargeField)>>,
+    /// Shares and nonce shares of each secret that passed the Merkle checks
+    pub secret_shares: HashMap<usize, HashMap<Replica, (LargeField, LargeField)>>,
+    /// Parties whose shares of a secret failed the Merkle checks
+    pub faulty_shares: HashMap<usize, HashSet<Replica>>,
 
-    pub secret: Option<Vec<LargeField>>,
+    /// Reconstructed secrets, None if the shares did not match the broadcast commitment
+    pub secrets: HashMap<usize, Option<LargeField>>,
     
     pub verified_hash: Option<Hash>,
 
@@ -39,9 +46,9 @@ impl ASKSState{
             merkle_proofs: None,
             roots: None,
 
-            secret: None, 
-            
-            secret_shares: Vec::new(), 
+            secret_shares: HashMap::default(), 
+            faulty_shares: HashMap::default(),
+            secrets: HashMap::default(), 
 
             verified_hash: None, 
             echo_sent: false, 
@@ -51,4 +58,236 @@ impl ASKSState{
             rbc_state: RBCState::new(origin)
         }
     }
-}
\ No newline at end of file
+
+    /// Indices of the secrets this party can reconstruct, known once the commitments are delivered
+    pub fn reconstructable_indices(&self, myid: Replica) -> Vec<usize>{
+        let num_secrets = self.roots.as_ref().map(|roots| roots.len()).unwrap_or(0);
+        if self.reconstruct_to_all{
+            (0..num_secrets).collect()
+        }
+        else if myid < num_secrets{
+            vec![myid]
+        }
+        else{
+            Vec::new()
+        }
+    }
+
+    /// Parties to send shares to, with the indices of the secrets each of them may receive.
+    /// The secrets of a private dealing are only ever reconstructed to their owners.
+    pub fn share_recipients(&self, indices: &[usize], recipients: Vec<Replica>, num_nodes: usize) -> Vec<(Replica, Vec<usize>)>{
+        let mut share_recipients = Vec::new();
+        for rep in recipients.into_iter(){
+            if rep >= num_nodes{
+                log::error!("Reconstruction request names unknown party {}", rep);
+                continue;
+            }
+            let rep_indices: Vec<usize> = indices.iter().cloned().filter(|index| self.reconstruct_to_all || *index == rep).collect();
+            if !rep_indices.is_empty(){
+                share_recipients.push((rep, rep_indices));
+            }
+        }
+        share_recipients
+    }
+
+    /// Adds the share of secret `index` sent by `sender`, checked against the commitment at the position of the sender.
+    /// Returns the outcome once the shares determine the secret.
+    pub fn add_secret_share(&mut self,
+        index: usize,
+        sender: Replica,
+        share: LargeField,
+        nonce: LargeField,
+        proof: &Proof,
+        num_nodes: usize,
+        num_faults: usize,
+        hash_context: &HashState
+    ) -> Option<ASKSReconstruction>{
+        if self.secrets.contains_key(&index){
+            // Already reconstructed
+            return None;
+        }
+        let root = self.roots.as_ref()?.get(index)?.clone();
+        let share_map = self.secret_shares.entry(index).or_default();
+        if share_map.contains_key(&sender){
+            return None;
+        }
+        let commitment = share_commitment(hash_context, sender, &share.to_bytes_be(), &nonce.to_bytes_be());
+        if !proof.validate(hash_context) || proof.item() != commitment || proof.root() != root{
+            log::error!("Share of secret {} sent by party {} failed the Merkle check", index, sender);
+            self.faulty_shares.entry(index).or_default().insert(sender);
+            return None;
+        }
+        share_map.insert(sender, (share, nonce));
+        if share_map.len() < num_faults+1{
+            return None;
+        }
+        let secret = reconstruct_committed_secret(share_map, root, num_nodes, num_faults, hash_context)?;
+        self.secrets.insert(index, secret.clone());
+
+        let mut faulty_parties: Vec<Replica> = self.faulty_shares.get(&index).map(|parties| parties.iter().cloned().collect()).unwrap_or_default();
+        faulty_parties.sort();
+        Some(ASKSReconstruction {
+            index: index,
+            secret: secret,
+            faulty_parties: faulty_parties,
+        })
+    }
+}
+
+/// Interpolates the verified shares of a secret and matches the commitments of the interpolated polynomial against `root`.
+/// Every verified share is the value the dealer committed to at the position of its sender.
+/// Shares on a polynomial of degree t whose commitments do not match the root mean that the dealer committed to other values at positions not received yet.
+/// None asks for more shares until n-f shares are verified. Their t+1 honest shares fix the polynomial, so the dealing is inconsistent and every honest party decides Some(None),
+/// even if the off-polynomial values sit at positions of corrupt parties that never send them.
+/// Some(None) is also returned as soon as the verified shares themselves do not lie on a polynomial of degree t.
+fn reconstruct_committed_secret(
+    shares: &HashMap<Replica, (LargeField, LargeField)>,
+    root: Hash,
+    num_nodes: usize,
+    num_faults: usize,
+    hash_context: &HashState
+) -> Option<Option<LargeField>>{
+    let mut senders: Vec<Replica> = shares.keys().cloned().collect();
+    senders.sort();
+    let evaluation_indices: Vec<LargeField> = senders.iter().map(|rep| LargeField::from((rep+1) as u64)).collect();
+    let share_poly_shares: Vec<LargeField> = senders.iter().map(|rep| shares.get(rep).unwrap().0.clone()).collect();
+    let nonce_poly_shares: Vec<LargeField> = senders.iter().map(|rep| shares.get(rep).unwrap().1.clone()).collect();
+
+    let share_poly = Polynomial::interpolate(&evaluation_indices, &share_poly_shares).unwrap();
+    let nonce_poly = Polynomial::interpolate(&evaluation_indices, &nonce_poly_shares).unwrap();
+    if share_poly.degree() > num_faults || nonce_poly.degree() > num_faults{
+        log::error!("Shares committed to by the dealer do not lie on a polynomial of degree {}", num_faults);
+        return Some(None);
+    }
+
+    // Compute and match commitments
+    let all_commitments: Vec<Hash> = (0..num_nodes).map(|rep| {
+        let point = LargeField::from((rep+1) as u64);
+        share_commitment(hash_context, rep, &share_poly.evaluate(&point).to_bytes_be(), &nonce_poly.evaluate(&point).to_bytes_be())
+    }).collect();
+    if MerkleTree::new(all_commitments, hash_context).root() == root{
+        Some(Some(share_poly.evaluate(&LargeField::zero())))
+    }
+    else if shares.len() >= num_nodes - num_faults{
+        log::error!("Reconstructed commitment does not match the root with {} shares, the dealing is inconsistent", shares.len());
+        Some(None)
+    }
+    else{
+        log::warn!("Reconstructed commitment does not match the root with {} shares, waiting for more shares", shares.len());
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NUM_NODES: usize = 4;
+    const NUM_FAULTS: usize = 1;
+
+    fn hash_context() -> HashState {
+        HashState::new([5u8; 16], [29u8; 16], [23u8; 16])
+    }
+
+    /// Shares, nonce shares and Merkle proofs of each party for a dealing of `secrets`, committed like the dealer does.
+    /// The share of party `corrupt` in the first secret, if any, is moved off the polynomial before committing.
+    fn dealing(secrets: &[u64], corrupt: Option<Replica>) -> (Vec<Hash>, Vec<Vec<(LargeField, LargeField, Proof)>>) {
+        let hash_context = hash_context();
+        let mut roots = Vec::new();
+        let mut party_shares = vec![Vec::new(); NUM_NODES];
+        for (index, secret) in secrets.iter().enumerate() {
+            let share_poly = Polynomial::new(&[LargeField::from(*secret), LargeField::from(11 + index as u64)]);
+            let nonce_poly = Polynomial::new(&[LargeField::from(3), LargeField::from(5 + index as u64)]);
+            let mut shares: Vec<(LargeField, LargeField)> = (1..NUM_NODES as u64 + 1)
+                .map(|x| (share_poly.evaluate(&LargeField::from(x)), nonce_poly.evaluate(&LargeField::from(x)))).collect();
+            if let (0, Some(corrupt)) = (index, corrupt) {
+                shares[corrupt].0 = &shares[corrupt].0 + LargeField::one();
+            }
+            let commitments: Vec<Hash> = shares.iter().enumerate()
+                .map(|(rep, (share, nonce))| share_commitment(&hash_context, rep, &share.to_bytes_be(), &nonce.to_bytes_be())).collect();
+            let tree = MerkleTree::new(commitments, &hash_context);
+            roots.push(tree.root());
+            for (rep, (share, nonce)) in shares.into_iter().enumerate() {
+                party_shares[rep].push((share, nonce, tree.gen_proof(rep)));
+            }
+        }
+        (roots, party_shares)
+    }
+
+    fn state(roots: Vec<Hash>, reconstruct_to_all: bool) -> ASKSState {
+        let mut state = ASKSState::new(0, reconstruct_to_all);
+        state.roots = Some(roots);
+        state
+    }
+
+    fn add(state: &mut ASKSState, index: usize, sender: Replica, share: &(LargeField, LargeField, Proof)) -> Option<ASKSReconstruction> {
+        state.add_secret_share(index, sender, share.0.clone(), share.1.clone(), &share.2, NUM_NODES, NUM_FAULTS, &hash_context())
+    }
+
+    #[test]
+    fn test_secrets_are_reconstructed_per_index() {
+        let (roots, shares) = dealing(&[42, 43, 44], None);
+        let mut state = state(roots, true);
+        assert!(add(&mut state, 1, 0, &shares[0][1]).is_none());
+        let result = add(&mut state, 1, 2, &shares[2][1]).unwrap();
+        assert_eq!(result, ASKSReconstruction { index: 1, secret: Some(LargeField::from(43)), faulty_parties: vec![] });
+        // Only the requested secret was reconstructed, later shares of it are ignored
+        assert_eq!(state.secrets.keys().cloned().collect::<Vec<usize>>(), vec![1]);
+        assert!(add(&mut state, 1, 3, &shares[3][1]).is_none());
+
+        assert!(add(&mut state, 2, 3, &shares[3][2]).is_none());
+        assert_eq!(add(&mut state, 2, 1, &shares[1][2]).unwrap().secret, Some(LargeField::from(44)));
+        assert!(!state.secrets.contains_key(&0));
+        // Indices outside the dealing are dropped
+        assert!(add(&mut state, 3, 0, &shares[0][0]).is_none());
+    }
+
+    #[test]
+    fn test_private_secrets_are_only_sent_to_their_owners() {
+        let (roots, _) = dealing(&[1, 2, 3], None);
+        let private = state(roots.clone(), false);
+        assert_eq!(private.share_recipients(&[0, 1, 2], vec![0, 1, 2, 3, 7], NUM_NODES), vec![(0, vec![0]), (1, vec![1]), (2, vec![2])]);
+        assert_eq!(private.share_recipients(&[1], vec![0, 2], NUM_NODES), vec![]);
+        assert_eq!(private.reconstructable_indices(3), Vec::<usize>::new());
+
+        let public = state(roots, true);
+        assert_eq!(public.share_recipients(&[0, 2], vec![1, 3], NUM_NODES), vec![(1, vec![0, 2]), (3, vec![0, 2])]);
+        assert_eq!(public.reconstructable_indices(3), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_faulty_parties_are_reported() {
+        let (roots, shares) = dealing(&[42], None);
+        let mut state = state(roots, true);
+        // Party 0 replays the share of party 1, party 3 sends a wrong share
+        assert!(add(&mut state, 0, 0, &shares[1][0]).is_none());
+        let mut wrong = shares[3][0].clone();
+        wrong.0 = LargeField::from(7);
+        assert!(add(&mut state, 0, 3, &wrong).is_none());
+        // The share of party 1 is accepted even though party 0 sent the same share first
+        assert!(add(&mut state, 0, 1, &shares[1][0]).is_none());
+        let result = add(&mut state, 0, 2, &shares[2][0]).unwrap();
+        assert_eq!(result, ASKSReconstruction { index: 0, secret: Some(LargeField::from(42)), faulty_parties: vec![0, 3] });
+    }
+
+    #[test]
+    fn test_inconsistent_dealing_fails_with_n_minus_f_shares() {
+        // The dealer commits to a share of party 3 off the polynomial, and party 3 never sends it
+        let (roots, shares) = dealing(&[42], Some(3));
+        let mut honest = state(roots, true);
+        assert!(add(&mut honest, 0, 0, &shares[0][0]).is_none());
+        // The commitments do not match, but t+1 shares may still miss honest shares
+        assert!(add(&mut honest, 0, 1, &shares[1][0]).is_none());
+        assert!(!honest.secrets.contains_key(&0));
+        // n-f shares on one polynomial of degree t fix the polynomial, the dealing is inconsistent
+        let result = add(&mut honest, 0, 2, &shares[2][0]).unwrap();
+        assert_eq!(result, ASKSReconstruction { index: 0, secret: None, faulty_parties: vec![] });
+        assert_eq!(honest.secrets.get(&0), Some(&None));
+
+        // Parties that receive the off-polynomial share decide the same
+        let mut other = state(dealing(&[42], Some(3)).0, true);
+        assert!(add(&mut other, 0, 3, &shares[3][0]).is_none());
+        assert!(add(&mut other, 0, 1, &shares[1][0]).is_none());
+        assert_eq!(add(&mut other, 0, 0, &shares[0][0]).unwrap().secret, None);
+    }
+}
@@ -0,0 +1,177 @@
+use config::Node;
+use tokio::sync::{
+    mpsc::{Receiver, Sender},
+    oneshot,
+};
+use types::Replica;
+
+use consensus::{service_channel, LargeField};
+
+use crate::Context;
+
+/// Request to the ASKS service.
+#[derive(Debug, Clone)]
+pub enum ASKSRequest {
+    /// Deal `num_secrets` secrets, sampled at random if `secrets` is None.
+    /// With `reconstruct_to_all`, every secret can be reconstructed by every party.
+    /// Otherwise the i-th secret is private to party i and only ever reconstructed to it.
+    Share {
+        id: usize,
+        num_secrets: usize,
+        secrets: Option<Vec<LargeField>>,
+        reconstruct_to_all: bool,
+    },
+    /// Send the shares of this party in the dealing `id` of `dealer` to `recipients`.
+    /// `None` selects all secrets and all parties, restricted to the owner of each secret if the dealing is private.
+    Reconstruct {
+        id: usize,
+        dealer: Replica,
+        indices: Option<Vec<usize>>,
+        recipients: Option<Vec<Replica>>,
+    },
+    /// Send the shares of this party in the public dealing `id` of `dealer` to the client registered in the config.
+    /// The client reconstructs them as instance `id*n + dealer` of protocol "asks".
+    ReconstructToClient {
+        id: usize,
+        dealer: Replica,
+        indices: Option<Vec<usize>>,
+    },
+}
+
+/// Outcome of the reconstruction of one secret.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ASKSReconstruction {
+    /// Position of the secret in the dealing
+    pub index: usize,
+    /// None if the reconstructed shares do not match the commitment broadcast by the dealer
+    pub secret: Option<LargeField>,
+    /// Parties whose shares of this secret failed the Merkle checks before the secret was reconstructed
+    pub faulty_parties: Vec<Replica>,
+}
+
+impl ASKSReconstruction {
+    /// Failed reconstructions output zero, the default value of the protocols built on ASKS.
+    pub fn secret_or_default(&self) -> LargeField {
+        self.secret.clone().unwrap_or(LargeField::zero())
+    }
+}
+
+/// Event output by the ASKS service.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ASKSOutput {
+    /// The dealing `id` of `dealer` terminated, its secrets can now be reconstructed
+    Shared { id: usize, dealer: Replica },
+    /// Secrets of the dealing `id` of `dealer` reconstructed by this party, in the order their shares completed
+    Reconstructed {
+        id: usize,
+        dealer: Replica,
+        results: Vec<ASKSReconstruction>,
+    },
+}
+
+/// Handle to an ASKS service running on its own port.
+/// Dealings are identified by the dealer and an id chosen by the dealer, unique among its dealings.
+pub struct ASKSService {
+    req: Sender<ASKSRequest>,
+    out: Receiver<ASKSOutput>,
+}
+
+impl ASKSService {
+    /// Spawns the service on the address of `config`.
+    pub fn spawn(config: Node, byz: bool) -> (ASKSService, anyhow::Result<oneshot::Sender<()>>) {
+        let (req_send_channel, req_recv_channel) = service_channel();
+        let (out_send_channel, out_recv_channel) = service_channel();
+
+        let status = Context::spawn(config, req_recv_channel, out_send_channel, byz);
+        (ASKSService::new(req_send_channel, out_recv_channel), status)
+    }
+
+    /// Wraps the channels of an already running service.
+    pub fn new(req: Sender<ASKSRequest>, out: Receiver<ASKSOutput>) -> ASKSService {
+        ASKSService { req: req, out: out }
+    }
+
+    pub async fn share(&self, id: usize, num_secrets: usize, secrets: Option<Vec<LargeField>>, reconstruct_to_all: bool) -> anyhow::Result<()> {
+        self.request(ASKSRequest::Share {
+            id: id,
+            num_secrets: num_secrets,
+            secrets: secrets,
+            reconstruct_to_all: reconstruct_to_all,
+        }).await
+    }
+
+    pub async fn reconstruct(&self, id: usize, dealer: Replica, indices: Option<Vec<usize>>, recipients: Option<Vec<Replica>>) -> anyhow::Result<()> {
+        self.request(ASKSRequest::Reconstruct {
+            id: id,
+            dealer: dealer,
+            indices: indices,
+            recipients: recipients,
+        }).await
+    }
+
+    pub async fn reconstruct_to_client(&self, id: usize, dealer: Replica, indices: Option<Vec<usize>>) -> anyhow::Result<()> {
+        self.request(ASKSRequest::ReconstructToClient {
+            id: id,
+            dealer: dealer,
+            indices: indices,
+        }).await
+    }
+
+    async fn request(&self, request: ASKSRequest) -> anyhow::Result<()> {
+        self.req
+            .send(request)
+            .await
+            .map_err(|_| anyhow::anyhow!("ASKS service has shut down"))
+    }
+
+    /// Waits for the next event. Returns None once the service has shut down.
+    /// Cancel safe, so it can be used as a `tokio::select!` branch.
+    pub async fn next_output(&mut self) -> Option<ASKSOutput> {
+        self.out.recv().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_service_forwards_requests_and_outputs() {
+        let (req_send, mut req_recv) = service_channel();
+        let (out_send, out_recv) = service_channel();
+        let mut asks = ASKSService::new(req_send, out_recv);
+
+        asks.share(3, 2, None, true).await.unwrap();
+        match req_recv.recv().await.unwrap() {
+            ASKSRequest::Share { id, num_secrets, secrets, reconstruct_to_all } => {
+                assert_eq!((id, num_secrets, secrets, reconstruct_to_all), (3, 2, None, true));
+            }
+            request => panic!("Unexpected request {:?}", request),
+        }
+        asks.reconstruct(3, 1, Some(vec![1]), Some(vec![0, 2])).await.unwrap();
+        match req_recv.recv().await.unwrap() {
+            ASKSRequest::Reconstruct { id, dealer, indices, recipients } => {
+                assert_eq!((id, dealer, indices, recipients), (3, 1, Some(vec![1]), Some(vec![0, 2])));
+            }
+            request => panic!("Unexpected request {:?}", request),
+        }
+        asks.reconstruct_to_client(3, 1, None).await.unwrap();
+        match req_recv.recv().await.unwrap() {
+            ASKSRequest::ReconstructToClient { id, dealer, indices } => {
+                assert_eq!((id, dealer, indices), (3, 1, None));
+            }
+            request => panic!("Unexpected request {:?}", request),
+        }
+
+        let failed = ASKSReconstruction { index: 1, secret: None, faulty_parties: vec![2] };
+        assert_eq!(failed.secret_or_default(), LargeField::zero());
+        let output = ASKSOutput::Reconstructed { id: 3, dealer: 1, results: vec![failed] };
+        out_send.send(output.clone()).await.unwrap();
+        assert_eq!(asks.next_output().await, Some(output));
+
+        drop(out_send);
+        assert!(asks.next_output().await.is_none());
+        drop(req_recv);
+        assert!(asks.share(4, 1, None, true).await.is_err());
+    }
+}
@@ -11,11 +11,12 @@ use config::Node;
 use fnv::FnvHashMap;
 use network::{plaintcp::{CancelHandler, TcpReceiver, TcpReliableSender}, Acknowledgement, Message};
 use tokio::sync::{
-    mpsc::{unbounded_channel, UnboundedReceiver, Receiver, Sender},
+    mpsc::{Receiver, Sender},
     oneshot,
 };
 // use tokio_util::time::DelayQueue;
 use types::{Replica, WrapperMsg};
+use consensus::{ingress_channel, ingress_per_sender_capacity, IngressReceiver, SessionMsg, SessionContext, session_id};
 
 use crate::AVIDState;
 
@@ -25,8 +26,8 @@ use crypto::{aes_hash::HashState, hash::Hash};
 
 pub struct Context {
     /// Networking context
-    pub net_send: TcpReliableSender<Replica, WrapperMsg<ProtMsg>, Acknowledgement>,
-    pub net_recv: UnboundedReceiver<WrapperMsg<ProtMsg>>,
+    pub net_send: TcpReliableSender<Replica, WrapperMsg<SessionMsg<ProtMsg>>, Acknowledgement>,
+    pub net_recv: IngressReceiver<WrapperMsg<SessionMsg<ProtMsg>>>,
 
     /// Data context
     pub num_nodes: usize,
@@ -36,6 +37,8 @@ pub struct Context {
 
     /// Secret Key map
     pub sec_key_map: HashMap<Replica, Vec<u8>>,
+    /// Session binding and replay protection
+    pub session: SessionContext,
 
     /// Hardware acceleration context
     pub hash_context: HashState,
@@ -78,13 +81,13 @@ impl Context {
         let my_address = to_socket_address("0.0.0.0", my_port.port());
         
         // Setup networking
-        let (tx_net_to_consensus, rx_net_to_consensus) = unbounded_channel();
-        TcpReceiver::<Acknowledgement, WrapperMsg<ProtMsg>, _>::spawn(
+        let (tx_net_to_consensus, rx_net_to_consensus) = ingress_channel(ingress_per_sender_capacity(), config.sk_map.clone());
+        TcpReceiver::<Acknowledgement, WrapperMsg<SessionMsg<ProtMsg>>, _>::spawn(
             my_address,
             Handler::new(tx_net_to_consensus),
         );
 
-        let consensus_net = TcpReliableSender::<Replica, WrapperMsg<ProtMsg>, Acknowledgement>::with_peers(
+        let consensus_net = TcpReliableSender::<Replica, WrapperMsg<SessionMsg<ProtMsg>>, Acknowledgement>::with_peers(
             consensus_addrs.clone(),
         );
 
@@ -98,6 +101,7 @@ impl Context {
 
         let threshold:usize = 10000;
         let rbc_start_id = threshold*config.id;
+        let session = SessionContext::new(session_id(), "avid", config.id, &consensus_addrs);
         tokio::spawn(async move {
             let mut c = Context {
                 net_send: consensus_net,
@@ -105,6 +109,7 @@ impl Context {
                 
                 num_nodes: config.num_nodes,
                 sec_key_map: HashMap::default(),
+                session: session,
                 hash_context: hashstate,
                 myid: config.id,
                 _byz: _byz,
@@ -141,7 +146,7 @@ impl Context {
     pub async fn broadcast(&mut self, protmsg: ProtMsg) {
         let sec_key_map = self.sec_key_map.clone();
         for (replica, sec_key) in sec_key_map.into_iter() {
-            let wrapper_msg = WrapperMsg::new(protmsg.clone(), self.myid, &sec_key.as_slice());
+            let wrapper_msg = WrapperMsg::new(self.session.seal(replica, protmsg.clone()), self.myid, &sec_key.as_slice());
             let cancel_handler: CancelHandler<Acknowledgement> = self.net_send.send(replica, wrapper_msg).await;
             self.add_cancel_handler(cancel_handler);
         }
@@ -152,7 +157,7 @@ impl Context {
         let mut total_bytes = 0;
         let sec_key_map = self.sec_key_map.clone();
         for (replica, sec_key) in sec_key_map.into_iter() {
-            let wrapper_msg = WrapperMsg::new(protmsg.clone(), self.myid, &sec_key.as_slice());
+            let wrapper_msg = WrapperMsg::new(self.session.seal(replica, protmsg.clone()), self.myid, &sec_key.as_slice());
             total_bytes += Bytes::from(wrapper_msg.to_bytes()).len();
             let cancel_handler: CancelHandler<Acknowledgement> = self.net_send.send(replica, wrapper_msg).await;
             self.add_cancel_handler(cancel_handler);
@@ -161,10 +166,10 @@ impl Context {
     }
 
     pub fn add_cancel_handler(&mut self, canc: CancelHandler<Acknowledgement>) {
-        self.cancel_handlers.entry(0).or_default().push(canc);
+        consensus::add_cancel_handler_gc(&mut self.cancel_handlers, canc);
     }
 
-    pub async fn send(&mut self, replica: Replica, wrapper_msg: WrapperMsg<ProtMsg>) {
+    pub async fn send(&mut self, replica: Replica, wrapper_msg: WrapperMsg<SessionMsg<ProtMsg>>) {
         #[cfg(feature = "bandwidth")]
         log::info!("Network sending bytes: {:?}", Bytes::from(wrapper_msg.to_bytes()).len());
         let cancel_handler: CancelHandler<Acknowledgement> =
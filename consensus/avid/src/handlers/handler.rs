@@ -1,7 +1,7 @@
 use async_trait::async_trait;
 use futures_util::SinkExt;
 use network::Acknowledgement;
-use tokio::sync::mpsc::UnboundedSender;
+use consensus::{IngressSender, SessionMsg};
 
 use types::WrapperMsg;
 
@@ -9,21 +9,23 @@ use crate::ProtMsg;
 
 #[derive(Debug, Clone)]
 pub struct Handler {
-    consensus_tx: UnboundedSender<WrapperMsg<ProtMsg>>,
+    consensus_tx: IngressSender<WrapperMsg<SessionMsg<ProtMsg>>>,
 }
 
 impl Handler {
-    pub fn new(consensus_tx: UnboundedSender<WrapperMsg<ProtMsg>>) -> Self {
+    pub fn new(consensus_tx: IngressSender<WrapperMsg<SessionMsg<ProtMsg>>>) -> Self {
         Self { consensus_tx }
     }
 }
 
 #[async_trait]
-impl network::Handler<Acknowledgement, WrapperMsg<ProtMsg>> for Handler {
-    async fn dispatch(&self, msg: WrapperMsg<ProtMsg>, writer: &mut network::Writer<Acknowledgement>) {
+impl network::Handler<Acknowledgement, WrapperMsg<SessionMsg<ProtMsg>>> for Handler {
+    async fn dispatch(&self, msg: WrapperMsg<SessionMsg<ProtMsg>>, writer: &mut network::Writer<Acknowledgement>) {
         // Forward the message
+        // Waits if the queue of this sender is full, delaying the acknowledgement
         let status = self.consensus_tx
-            .send(msg);
+            .send(msg)
+            .await;
         if status.is_err(){
             log::error!("Failed to send consensus message to the channel because of {:?}", status.err().unwrap());
         }
@@ -17,9 +17,17 @@ pub struct AVIDShard{
     pub master_proof: Proof,
 }
 
+/// Merkle leaf of the shard at `position`. Hashing the position into the leaf binds every proof to the party the shard belongs to.
+pub fn shard_leaf(position: Replica, shard: &[u8]) -> Hash{
+    let mut leaf = (position as u64).to_be_bytes().to_vec();
+    leaf.extend_from_slice(shard);
+    do_hash(leaf.as_slice())
+}
+
 impl AVIDShard{
-    pub fn verify(&self, hash_state: &HashState)->bool{
-        let hash_of_shard: [u8; 32] = do_hash(self.shard.as_slice());
+    /// Verifies that this is the shard at `position` of the codeword committed in the master tree
+    pub fn verify(&self, position: Replica, hash_state: &HashState)->bool{
+        let hash_of_shard: [u8; 32] = shard_leaf(position, self.shard.as_slice());
         // log::debug!("Hash of shard {:?},
         // Hash of shard from proof {:?}, root of proof {:?},
         // Hash of shard from master proof {:?}", 
@@ -50,13 +58,13 @@ pub struct AVIDMsg {
 
 impl AVIDMsg {
     
-    pub fn verify_mr_proofs(&self, hf: &HashState) -> bool {
+    pub fn verify_mr_proofs(&self, position: Replica, hf: &HashState) -> bool {
         let mut state = true;
         // 2. Validate Merkle Proofs
         let mut hashes_vec: HashSet<Hash> = HashSet::default();
 
         for avid_state in self.shards.iter(){
-            state = state&& avid_state.verify(hf);
+            state = state&& avid_state.verify(position, hf);
             hashes_vec.insert(avid_state.master_proof.root());
         }
 
@@ -57,7 +57,7 @@ impl Context {
                     echo_parties.insert(recipient);
 
                     let sec_key = self.sec_key_map.get(&recipient).unwrap();
-                    let wrapper_msg = WrapperMsg::new(ready_msg, self.myid, sec_key);
+                    let wrapper_msg = WrapperMsg::new(self.session.seal(recipient, ready_msg), self.myid, sec_key);
                     #[cfg(feature = "bandwidth")]
                     log::info!("Network sending bytes: {:?}", Bytes::from(wrapper_msg.to_bytes()).len());
                     let _cancel_handler = self.net_send.send(recipient, wrapper_msg).await;
@@ -67,7 +67,7 @@ impl Context {
                     if !echo_parties.contains(&party){
                         let ready_msg = ProtMsg::Ready(avid_index.proof.root(), avid_index.origin, None, instance_id);
                         let sec_key = self.sec_key_map.get(&party).unwrap();
-                        let wrapper_msg = WrapperMsg::new(ready_msg, self.myid, sec_key);
+                        let wrapper_msg = WrapperMsg::new(self.session.seal(party, ready_msg), self.myid, sec_key);
                         #[cfg(feature = "bandwidth")]
                         log::info!("Network sending bytes: {:?}", Bytes::from(wrapper_msg.to_bytes()).len());
                         let _cancel_handler = self.net_send.send(party, wrapper_msg).await;
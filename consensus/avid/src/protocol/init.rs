@@ -3,11 +3,11 @@ use bytes::Bytes;
 use consensus::get_shards;
 use crypto::{
     aes_hash::{MerkleTree, HashState},
-    hash::{do_hash, Hash},
+    hash::Hash,
 };
 use types::{WrapperMsg, Replica};
 
-use crate::{Context, msg::{AVIDMsg, AVIDShard}, AVIDState};
+use crate::{Context, msg::{AVIDMsg, AVIDShard, shard_leaf}, AVIDState};
 use crate::{ProtMsg};
 use network::{plaintcp::CancelHandler, Acknowledgement, Message};
 
@@ -71,7 +71,7 @@ impl Context {
             };
             
             let protocol_msg = ProtMsg::Init(avid_msg, instance_id);
-            let wrapper_msg = WrapperMsg::new(protocol_msg.clone(), self.myid, &sec_key.as_slice());
+            let wrapper_msg = WrapperMsg::new(self.session.seal(replica, protocol_msg.clone()), self.myid, &sec_key.as_slice());
             #[cfg(feature = "bandwidth")]
             log::info!("Network sending bytes: {:?}", Bytes::from(wrapper_msg.to_bytes()).len());
             let cancel_handler: CancelHandler<Acknowledgement> = self.net_send.send(replica, wrapper_msg).await;
@@ -81,7 +81,7 @@ impl Context {
 
     pub async fn handle_init(self: &mut Context, msg: AVIDMsg, instance_id:usize) {
         
-        if !msg.verify_mr_proofs(&self.hash_context) {
+        if !msg.verify_mr_proofs(self.myid, &self.hash_context) {
             log::error!(
                 "Invalid Merkle Proof sent by node {}, abandoning AVID instance",
                 msg.origin
@@ -108,7 +108,7 @@ impl Context {
             let recipient = index_msg.recipient;
             let protocol_msg = ProtMsg::Echo(index_msg, instance_id);
             let sec_key = self.sec_key_map.get(&recipient).unwrap().clone();
-            let wrapper_msg = WrapperMsg::new(protocol_msg.clone(), self.myid, &sec_key.as_slice());
+            let wrapper_msg = WrapperMsg::new(self.session.seal(recipient, protocol_msg.clone()), self.myid, &sec_key.as_slice());
             #[cfg(feature = "bandwidth")]
             log::info!("Network sending bytes: {:?}", Bytes::from(wrapper_msg.to_bytes()).len());
             let cancel_handler: CancelHandler<Acknowledgement> = self.net_send.send(recipient, wrapper_msg).await;
@@ -120,7 +120,8 @@ impl Context {
 pub fn construct_merkle_tree(shards:Vec<Vec<u8>>, hc: &HashState)->MerkleTree{
     let hashes_rbc: Vec<Hash> = shards
         .into_iter()
-        .map(|x| do_hash(x.as_slice()))
+        .enumerate()
+        .map(|(position, x)| shard_leaf(position, x.as_slice()))
         .collect();
 
     MerkleTree::new(hashes_rbc, hc)
@@ -4,5 +4,8 @@ mod echo;
 
 mod ready;
 
+mod retrieve;
+pub use retrieve::*;
+
 mod avid_state;
 pub use avid_state::*;
\ No newline at end of file
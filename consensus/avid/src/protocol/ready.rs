@@ -1,11 +1,10 @@
 use std::collections::{HashMap};
 
-use consensus::reconstruct_data;
 use crypto::hash::Hash;
 use types::Replica;
 
 use crate::msg::{AVIDShard};
-use crate::protocol::init::construct_merkle_tree;
+use crate::protocol::decode_committed_shards;
 use crate::{AVIDState};
 
 use crate::Context;
@@ -43,59 +42,52 @@ impl Context {
             avid_context.deliveries.insert(root_hash.clone(), hashmap);
         }
         let shards_map = avid_context.deliveries.get_mut(&root_hash).unwrap();
+        let mut new_shard = None;
         if avid_shard.is_some(){
             let avid_shard = avid_shard.unwrap();
-            if avid_shard.verify(&self.hash_context) && (avid_shard.master_proof.root() == root_hash){
+            if avid_shard.verify(ready_sender, &self.hash_context) && (avid_shard.master_proof.root() == root_hash) && avid_shard.recipient == self.myid{
+                new_shard = Some(avid_shard.master_proof.item());
                 shards_map.insert(ready_sender, avid_shard);
             }
             else{
                 log::error!("Received invalid shard from sender {} in instance_id {} because {} and {}",
                     ready_sender,
                     instance_id,
-                    avid_shard.verify(&self.hash_context),
+                    avid_shard.verify(ready_sender, &self.hash_context),
                     avid_shard.master_proof.root() == root_hash
                 );
                 return;
             }
         }
 
-        if shards_map.len() == self.num_faults + 1 && avid_context.message.is_none(){
-            // Sent ECHOs and getting a ready message for the same ECHO
-            log::debug!("Received enough messages for interpolating AVID message in instance {} sent by origin {}", instance_id, origin);
-            let mut shards:Vec<Option<Vec<u8>>> = Vec::new();
-            let mut proof_master_root = None;
-            for rep in 0..self.num_nodes{   
-                if shards_map.contains_key(&rep){
-                    let shard = shards_map.get(&rep).unwrap();
-                    if proof_master_root.is_none(){
-                        proof_master_root = Some(shard.master_proof.clone());
+        if new_shard.is_some() && shards_map.len() >= self.num_faults + 1 && avid_context.message.is_none(){
+            log::debug!("Received {} shards for interpolating AVID message in instance {} sent by origin {}", shards_map.len(), instance_id, origin);
+            let shard_root = new_shard.unwrap();
+            let shards: HashMap<Replica, Vec<u8>> = shards_map.iter()
+                .filter(|(_rep, shard)| shard.master_proof.item() == shard_root)
+                .map(|(rep, shard)| (*rep, shard.shard.clone()))
+                .collect();
+            if shards.len() >= self.num_faults + 1{
+                match decode_committed_shards(&shards, shard_root, self.num_nodes, self.num_faults, &self.hash_context){
+                    Some(shards) => {
+                        log::debug!("Reconstructed Merkle root and message successfully with validation for instance id {} from sender {}", instance_id, origin);
+                        let mut message = Vec::new();
+                        for i in 0..self.num_faults+1{
+                            message.extend(shards.get(i).clone().unwrap());
+                        }
+                        avid_context.message = Some(message.clone());
+                    },
+                    None => {
+                        // The dealer committed to shards that are not a codeword, every honest party sees the same
+                        log::error!("Shards committed by origin {} in instance id {} are not a codeword, delivering nothing", origin, instance_id);
+                        avid_context.terminated = true;
+                        let status = self.out_avid.send((instance_id, avid_context.sender, None)).await;
+                        if status.is_err(){
+                            log::error!("Error sending message to parent channel {:?}", status.unwrap_err());
+                        }
+                        return;
                     }
-                    shards.push(Some(shard.shard.clone()));
                 }
-                else{
-                    shards.push(None);
-                }
-            }
-            let status = reconstruct_data(&mut shards, self.num_faults+1 , 2*self.num_faults);
-            if status.is_err(){
-                log::error!("FATAL: Error in Lagrange interpolation {}",status.err().unwrap());
-                // Do something else here
-                return;
-            }
-            let shards:Vec<Vec<u8>> = shards.into_iter().map(| opt | opt.unwrap()).collect();
-            // Reconstruct Merkle Root
-            let merkle_tree = construct_merkle_tree(shards.clone(), &self.hash_context);
-            if merkle_tree.root() == proof_master_root.unwrap().item(){
-                log::debug!("Reconstructed Merkle root and message successfully with validation for instance id {} from sender {}", instance_id, origin);
-                let mut message = Vec::new();
-                for i in 0..self.num_faults+1{
-                    message.extend(shards.get(i).clone().unwrap());
-                }
-                avid_context.message = Some(message.clone());
-            }
-            else{
-                log::error!("FATAL: Reconstructed Merkle root and message failed with validation for instance id {} from sender {}", instance_id, origin);
-                return;
             }
         }
         if ready_senders.len() >= self.num_nodes - self.num_faults && !avid_context.terminated{
@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+
+use consensus::reconstruct_data;
+use crypto::{aes_hash::HashState, hash::Hash};
+use types::Replica;
+
+use crate::protocol::init::construct_merkle_tree;
+
+/// Decodes the codeword committed by `root` from t+1 of the shards received so far.
+/// Every shard must have been verified against its sender's position (see `AVIDShard::verify`), so all received shards are the committed ones and any t+1 of them decode to the same codeword.
+/// Returns `None` if the dealer committed to shards that are not a codeword. Every honest party reaches the same verdict, whichever shards it decodes from.
+pub fn decode_committed_shards(
+    shards: &HashMap<Replica, Vec<u8>>,
+    root: Hash,
+    num_nodes: usize,
+    num_faults: usize,
+    hash_context: &HashState
+) -> Option<Vec<Vec<u8>>>{
+    let data_shards = num_faults + 1;
+    let mut senders: Vec<Replica> = shards.keys().cloned().collect();
+    senders.sort();
+    let mut subset: Vec<Option<Vec<u8>>> = vec![None; num_nodes];
+    for rep in senders.into_iter().take(data_shards){
+        subset[rep] = Some(shards.get(&rep).unwrap().clone());
+    }
+    if reconstruct_data(&mut subset, data_shards, 2*num_faults).is_err(){
+        return None;
+    }
+    let decoded: Vec<Vec<u8>> = subset.into_iter().map(|opt| opt.unwrap()).collect();
+    if construct_merkle_tree(decoded.clone(), hash_context).root() != root{
+        return None;
+    }
+    Some(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use consensus::get_shards;
+    use crypto::aes_hash::MerkleTree;
+
+    use crate::msg::AVIDShard;
+
+    const NUM_NODES: usize = 7;
+    const NUM_FAULTS: usize = 2;
+
+    fn hash_context() -> HashState {
+        HashState::new([5u8; 16], [29u8; 16], [23u8; 16])
+    }
+
+    fn encode() -> Vec<Vec<u8>> {
+        let message: Vec<u8> = (0..100u8).collect();
+        let ser = bincode::serialize(&(message.clone(), message.len())).unwrap();
+        get_shards(ser, NUM_FAULTS+1, 2*NUM_FAULTS)
+    }
+
+    fn received(shards: &Vec<Vec<u8>>, senders: &[Replica]) -> HashMap<Replica, Vec<u8>> {
+        senders.iter().map(|rep| (*rep, shards[*rep].clone())).collect()
+    }
+
+    #[test]
+    fn test_any_t_plus_one_shards_decode() {
+        let hash_context = hash_context();
+        let shards = encode();
+        let root = construct_merkle_tree(shards.clone(), &hash_context).root();
+        for senders in vec![vec![0, 1, 2], vec![4, 5, 6], vec![1, 3, 6, 0]] {
+            let decoded = decode_committed_shards(&received(&shards, &senders), root, NUM_NODES, NUM_FAULTS, &hash_context);
+            assert_eq!(decoded, Some(shards.clone()));
+        }
+    }
+
+    #[test]
+    fn test_non_codeword_commitment_is_rejected_by_every_subset() {
+        let hash_context = hash_context();
+        let mut shards = encode();
+        // The dealer commits to a vector that is not a codeword
+        shards[4][0] ^= 0xff;
+        let root = construct_merkle_tree(shards.clone(), &hash_context).root();
+        for senders in vec![vec![0, 1, 2], vec![4, 5, 6], vec![2, 3, 4]] {
+            assert!(decode_committed_shards(&received(&shards, &senders), root, NUM_NODES, NUM_FAULTS, &hash_context).is_none());
+        }
+    }
+
+    #[test]
+    fn test_shard_verifies_only_at_its_position() {
+        let hash_context = hash_context();
+        let shards = encode();
+        let tree = construct_merkle_tree(shards.clone(), &hash_context);
+        let master_tree = MerkleTree::new(vec![tree.root()], &hash_context);
+        let shard = AVIDShard {
+            id: 1,
+            origin: 0,
+            recipient: 0,
+            shard: shards[2].clone(),
+            proof: tree.gen_proof(2),
+            master_proof: master_tree.gen_proof(0),
+        };
+        assert!(shard.verify(2, &hash_context));
+        // A READY sender replaying the shard of another party is caught
+        assert!(!shard.verify(5, &hash_context));
+        let misplaced = AVIDShard { shard: shards[5].clone(), ..shard };
+        assert!(!misplaced.verify(2, &hash_context));
+    }
+}
@@ -0,0 +1,267 @@
+//! Micro-benchmarks of the primitives the sharing protocols spend their time in.
+//! Run with `cargo bench -p consensus`. The committee sizes and batch sizes default to
+//! `BENCH_NODES=16,64,256` and `BENCH_BATCH=100,1000` and can be overridden through these variables.
+use std::collections::HashMap;
+
+use consensus::{
+    DZKProof, FieldType, FoldingDZKContext, Interpolator, LargeField, LargeFieldSer, LargeFieldSSS, PackedMerkleTree, ProductTree, ProtocolField,
+    SmallField, SmallFieldSSS, SmallFieldType, expand_sharing_to_n_evaluation_points_fft, expand_sharing_to_n_evaluation_points_opt,
+    get_shards, inverse_vandermonde, matrix_vector_multiply, packing_factor, reconstruct_data, roots_of_unity, vandermonde_matrix
+};
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use ha_crypto::{aes_hash::{HashState, MerkleTree}, hash::Hash};
+use lambdaworks_math::{polynomial::Polynomial, traits::ByteConversion};
+
+fn params(var: &str, default: &[usize]) -> Vec<usize> {
+    match std::env::var(var) {
+        Ok(list) => list.split(',').map(|el| el.trim().parse::<usize>().expect("Unable to parse benchmark parameter")).collect(),
+        Err(_) => default.to_vec(),
+    }
+}
+
+fn committees() -> Vec<(usize, usize)> {
+    params("BENCH_NODES", &[16, 64, 256]).into_iter().map(|num_nodes| (num_nodes, (num_nodes - 1) / 3)).collect()
+}
+
+fn batches() -> Vec<usize> {
+    params("BENCH_BATCH", &[100, 1000])
+}
+
+fn hash_context() -> HashState {
+    HashState::new([5u8; 16], [29u8; 16], [23u8; 16])
+}
+
+fn random_elements(count: usize) -> Vec<LargeField> {
+    (0..count).map(|_| FieldType::random_element()).collect()
+}
+
+/// Same folding context as the ACSS services
+fn folding_context(num_nodes: usize, num_faults: usize) -> FoldingDZKContext {
+    let mut start_degree = num_faults as isize;
+    let mut poly_split_evaluation_map = HashMap::default();
+    while start_degree > 0 {
+        let split_point = if start_degree % 2 == 0 { start_degree / 2 } else { (start_degree + 1) / 2 };
+        start_degree = start_degree - split_point;
+        poly_split_evaluation_map.insert(start_degree, split_point);
+    }
+    FoldingDZKContext {
+        large_field_uv_sss: LargeFieldSSS::new_with_vandermonde(num_faults + 1, num_nodes),
+        hash_context: hash_context(),
+        poly_split_evaluation_map,
+        evaluation_points: (1..num_nodes + 1).collect(),
+        evaluation_domain: None,
+        recon_threshold: num_faults + 1,
+        end_degree_threshold: 3,
+    }
+}
+
+fn bench_sss(c: &mut Criterion) {
+    let mut group = c.benchmark_group("large_field_sss");
+    group.sample_size(10);
+    for (num_nodes, num_faults) in committees() {
+        for batch in batches() {
+            let sss = LargeFieldSSS::<FieldType>::new(num_faults + 1, num_nodes);
+            let secrets = random_elements(batch);
+            group.throughput(Throughput::Elements(batch as u64));
+            group.bench_with_input(BenchmarkId::new(format!("split/n={}", num_nodes), batch), &secrets, |b, secrets| {
+                b.iter(|| secrets.iter().map(|secret| sss.split(secret.clone())).collect::<Vec<Vec<LargeField>>>())
+            });
+            let shares: Vec<Vec<(usize, LargeField)>> = secrets.iter().map(|secret| {
+                sss.split(secret.clone()).into_iter().enumerate().take(num_faults + 1).map(|(index, share)| (index + 1, share)).collect()
+            }).collect();
+            group.bench_with_input(BenchmarkId::new(format!("recover/n={}", num_nodes), batch), &shares, |b, shares| {
+                b.iter(|| shares.iter().map(|shares| sss.recover(shares)).collect::<Vec<LargeField>>())
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bench_folding_dzk(c: &mut Criterion) {
+    let mut group = c.benchmark_group("folding_dzk");
+    group.sample_size(10);
+    for (num_nodes, num_faults) in committees() {
+        let context = folding_context(num_nodes, num_faults);
+        let coefficients = random_elements(num_faults + 1);
+        let column_root: Hash = context.hash_context.do_hash_aes(b"column commitment");
+        group.bench_function(BenchmarkId::new("gen_dzk_proof", num_nodes), |b| {
+            b.iter(|| context.gen_dzk_proof(&mut Vec::new(), &mut Vec::new(), coefficients.clone(), 1, column_root))
+        });
+
+        // Proof of party 0, laid out as in the ACSS dealing
+        let mut eval_points = Vec::new();
+        let mut trees: Vec<MerkleTree> = Vec::new();
+        let dzk_poly: Vec<LargeFieldSer> = context.gen_dzk_proof(&mut eval_points, &mut trees, coefficients.clone(), 1, column_root)
+            .into_iter().map(|x| x.to_bytes_be()).collect();
+        let dzk_roots: Vec<Hash> = trees.iter().map(|tree| tree.root()).collect();
+        let dzk_proof = DZKProof {
+            g_0_x: eval_points.iter().map(|points| points[0].0.to_bytes_be()).collect(),
+            g_1_x: eval_points.iter().map(|points| points[0].1.to_bytes_be()).collect(),
+            proof: trees.iter().map(|tree| tree.gen_proof(0)).collect(),
+        };
+        let evaluation_point = LargeField::from(1u64);
+        let share = Polynomial::new(&coefficients).evaluate(&evaluation_point);
+        assert!(context.verify_dzk_proof(dzk_proof.clone(), dzk_roots.clone(), dzk_poly.clone(), column_root, LargeField::zero(), share.clone(), evaluation_point.clone()));
+        group.bench_function(BenchmarkId::new("verify_dzk_proof", num_nodes), |b| {
+            b.iter(|| context.verify_dzk_proof(dzk_proof.clone(), dzk_roots.clone(), dzk_poly.clone(), column_root, LargeField::zero(), share.clone(), evaluation_point.clone()))
+        });
+    }
+    group.finish();
+}
+
+fn bench_expand_sharing(c: &mut Criterion) {
+    let mut group = c.benchmark_group("expand_sharing_to_n_evaluation_points_opt");
+    group.sample_size(10);
+    for (num_nodes, num_faults) in committees() {
+        for batch in batches() {
+            let evaluations_prf: Vec<Vec<LargeField>> = (0..batch).map(|_| random_elements(num_faults + 1)).collect();
+            group.throughput(Throughput::Elements(batch as u64));
+            group.bench_with_input(BenchmarkId::new(format!("n={}", num_nodes), batch), &evaluations_prf, |b, evaluations_prf| {
+                b.iter(|| expand_sharing_to_n_evaluation_points_opt(evaluations_prf.clone(), num_faults, num_nodes))
+            });
+        }
+    }
+    group.finish();
+}
+
+/// Expansion of PRF-derived sharings to all n parties, at the points 1..n through Vandermonde matrices and at the roots of unity through FFTs
+fn bench_fft_sharing(c: &mut Criterion) {
+    let mut group = c.benchmark_group("fft_against_vandermonde_sharing");
+    group.sample_size(10);
+    for (num_nodes, num_faults) in committees() {
+        let roots = roots_of_unity::<FieldType>(num_nodes);
+        for batch in batches() {
+            let evaluations_prf: Vec<Vec<LargeField>> = (0..batch).map(|_| random_elements(num_faults + 1)).collect();
+            group.throughput(Throughput::Elements(batch as u64));
+            group.bench_with_input(BenchmarkId::new(format!("vandermonde/n={}", num_nodes), batch), &evaluations_prf, |b, evaluations_prf| {
+                b.iter(|| expand_sharing_to_n_evaluation_points_opt(evaluations_prf.clone(), num_faults, num_nodes))
+            });
+            group.bench_with_input(BenchmarkId::new(format!("fft/n={}", num_nodes), batch), &evaluations_prf, |b, evaluations_prf| {
+                b.iter(|| expand_sharing_to_n_evaluation_points_fft(evaluations_prf.clone(), num_faults, num_nodes, &roots))
+            });
+        }
+    }
+    group.finish();
+}
+
+/// Evaluation and interpolation at 1..n, point by point and through Vandermonde matrices against product trees.
+/// Includes building the tree and inverting the matrix, as the first use of a point set does.
+fn bench_product_tree(c: &mut Criterion) {
+    let mut group = c.benchmark_group("product_tree_against_dense");
+    group.sample_size(10);
+    for (num_nodes, num_faults) in committees() {
+        let points: Vec<LargeField> = (1..num_nodes as u64 + 1).map(LargeField::from).collect();
+        for batch in batches() {
+            let polys: Vec<Polynomial<LargeField>> = (0..batch).map(|_| Polynomial::new(&random_elements(num_faults + 1))).collect();
+            let evaluations: Vec<Vec<LargeField>> = polys.iter().map(|poly| points.iter().map(|point| poly.evaluate(point)).collect()).collect();
+            group.throughput(Throughput::Elements(batch as u64));
+            group.bench_with_input(BenchmarkId::new(format!("evaluate_dense/n={}", num_nodes), batch), &polys, |b, polys| {
+                b.iter(|| polys.iter().map(|poly| points.iter().map(|point| poly.evaluate(point)).collect()).collect::<Vec<Vec<LargeField>>>())
+            });
+            group.bench_with_input(BenchmarkId::new(format!("evaluate_product_tree/n={}", num_nodes), batch), &polys, |b, polys| {
+                b.iter(|| {
+                    let tree = ProductTree::new(&points);
+                    polys.iter().map(|poly| tree.evaluate(poly.coefficients())).collect::<Vec<Vec<LargeField>>>()
+                })
+            });
+            group.bench_with_input(BenchmarkId::new(format!("interpolate_vandermonde/n={}", num_nodes), batch), &evaluations, |b, evaluations| {
+                b.iter(|| {
+                    let inverse = inverse_vandermonde(vandermonde_matrix(points.clone()));
+                    evaluations.iter().map(|evals| matrix_vector_multiply(&inverse, evals)).collect::<Vec<Vec<LargeField>>>()
+                })
+            });
+            group.bench_with_input(BenchmarkId::new(format!("interpolate_product_tree/n={}", num_nodes), batch), &evaluations, |b, evaluations| {
+                b.iter(|| {
+                    let interpolator = Interpolator::new(&points);
+                    evaluations.iter().map(|evals| interpolator.interpolate(evals)).collect::<Vec<Vec<LargeField>>>()
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bench_reed_solomon(c: &mut Criterion) {
+    let mut group = c.benchmark_group("reed_solomon");
+    group.sample_size(10);
+    for (num_nodes, num_faults) in committees() {
+        for batch in batches() {
+            // A batch of serialized field elements, as dispersed by AVID
+            let data: Vec<u8> = random_elements(batch).into_iter().flat_map(|el| el.to_bytes_be()).collect();
+            group.throughput(Throughput::Bytes(data.len() as u64));
+            group.bench_with_input(BenchmarkId::new(format!("get_shards/n={}", num_nodes), batch), &data, |b, data| {
+                b.iter(|| get_shards(data.clone(), num_faults + 1, num_nodes - num_faults - 1))
+            });
+            let shards = get_shards(data.clone(), num_faults + 1, num_nodes - num_faults - 1);
+            // Only the last t+1 shards arrive, every data shard has to be reconstructed
+            let received: Vec<Option<Vec<u8>>> = shards.into_iter().enumerate()
+                .map(|(index, shard)| if index >= num_nodes - num_faults - 1 { Some(shard) } else { None }).collect();
+            group.bench_with_input(BenchmarkId::new(format!("reconstruct_data/n={}", num_nodes), batch), &received, |b, received| {
+                b.iter(|| {
+                    let mut received = received.clone();
+                    reconstruct_data(&mut received, num_faults + 1, num_nodes - num_faults - 1).unwrap();
+                    received
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bench_merkle_commitment(c: &mut Criterion) {
+    let mut group = c.benchmark_group("merkle_commitment");
+    group.sample_size(10);
+    let hash_context = hash_context();
+    for (num_nodes, _num_faults) in committees() {
+        for batch in batches() {
+            // One leaf per party over its serialized shares of the batch
+            let shares: Vec<Vec<u8>> = (0..num_nodes).map(|_| random_elements(batch).into_iter().flat_map(|el| el.to_bytes_be()).collect()).collect();
+            group.throughput(Throughput::Elements(batch as u64));
+            group.bench_with_input(BenchmarkId::new(format!("n={}", num_nodes), batch), &shares, |b, shares| {
+                b.iter(|| {
+                    let hashes: Vec<Hash> = shares.iter().map(|share| hash_context.do_hash_aes(share.as_slice())).collect();
+                    MerkleTree::new(hashes, &hash_context).root()
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+/// 64-bit sharing path against the default field: sharing, expansion and packed commitments of a batch
+fn bench_small_field(c: &mut Criterion) {
+    let mut group = c.benchmark_group("small_field");
+    group.sample_size(10);
+    let hash_context = hash_context();
+    for (num_nodes, num_faults) in committees() {
+        for batch in batches() {
+            group.throughput(Throughput::Elements(batch as u64));
+            let sss = SmallFieldSSS::new(num_faults + 1, num_nodes);
+            let secrets: Vec<SmallField> = (0..batch).map(|_| SmallFieldType::random_element()).collect();
+            group.bench_with_input(BenchmarkId::new(format!("split/n={}", num_nodes), batch), &secrets, |b, secrets| {
+                b.iter(|| secrets.iter().map(|secret| sss.split(secret.clone())).collect::<Vec<Vec<SmallField>>>())
+            });
+
+            let evaluations_prf: Vec<Vec<SmallField>> = (0..batch).map(|_| {
+                (0..num_faults + 1).map(|_| SmallFieldType::random_element()).collect()
+            }).collect();
+            group.bench_with_input(BenchmarkId::new(format!("expand/n={}", num_nodes), batch), &evaluations_prf, |b, evaluations_prf| {
+                b.iter(|| expand_sharing_to_n_evaluation_points_opt(evaluations_prf.clone(), num_faults, num_nodes))
+            });
+
+            // Commitment to the shares of one party, packed for the small field and one element per leaf for the default field
+            let small_shares: Vec<SmallField> = (0..batch).map(|_| SmallFieldType::random_element()).collect();
+            group.bench_with_input(BenchmarkId::new(format!("packed_commitment/n={}", num_nodes), batch), &small_shares, |b, shares| {
+                b.iter(|| PackedMerkleTree::new(shares, packing_factor::<SmallFieldType>(), &hash_context).root())
+            });
+            let large_shares = random_elements(batch);
+            group.bench_with_input(BenchmarkId::new(format!("large_field_commitment/n={}", num_nodes), batch), &large_shares, |b, shares| {
+                b.iter(|| PackedMerkleTree::new(shares, packing_factor::<FieldType>(), &hash_context).root())
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_sss, bench_folding_dzk, bench_expand_sharing, bench_fft_sharing, bench_product_tree, bench_reed_solomon, bench_merkle_commitment, bench_small_field);
+criterion_main!(benches);
@@ -1,5 +1,5 @@
 use std::{
-    collections::{HashMap, HashSet, VecDeque},
+    collections::{HashMap, HashSet},
     net::{SocketAddr, SocketAddrV4},
 };
 use bytes::Bytes;
@@ -8,22 +8,22 @@ use config::Node;
 use fnv::FnvHashMap;
 use network::{plaintcp::{CancelHandler, TcpReceiver, TcpReliableSender}, Acknowledgement, Message};
 use tokio::sync::{
-    mpsc::{unbounded_channel, UnboundedReceiver, Receiver, Sender},
+    mpsc::{Receiver, Sender},
     oneshot,
 };
 // use tokio_util::time::DelayQueue;
 use types::{Replica, WrapperMsg};
 
-use consensus::{LargeFieldSer};
+use consensus::{LargeFieldSer, InstanceGC, gc_retention, ingress_channel, ingress_per_sender_capacity, service_channel, IngressReceiver, SessionMsg, SessionContext, session_id};
 
 use crypto::{aes_hash::HashState};
 
-use crate::{msg::{ProtMsg, Handler}, protocol::{RoundStateBin, Val}};
+use crate::{msg::{ProtMsg, Handler}, protocol::{RoundStateBin, Val, CoinPool}};
 
 pub struct Context {
     /// Networking context
-    pub net_send: TcpReliableSender<Replica, WrapperMsg<ProtMsg>, Acknowledgement>,
-    pub net_recv: UnboundedReceiver<WrapperMsg<ProtMsg>>,
+    pub net_send: TcpReliableSender<Replica, WrapperMsg<SessionMsg<ProtMsg>>, Acknowledgement>,
+    pub net_recv: IngressReceiver<WrapperMsg<SessionMsg<ProtMsg>>>,
     
     /// Data context
     pub num_nodes: usize,
@@ -33,6 +33,8 @@ pub struct Context {
 
     /// Secret Key map
     pub sec_key_map: HashMap<Replica, Vec<u8>>,
+    /// Session binding and replay protection
+    pub session: SessionContext,
 
     /// Hardware acceleration context
     pub hash_context: HashState,
@@ -43,13 +45,22 @@ pub struct Context {
     
     /// State for Binary AA
     pub round_state: HashMap<usize,(HashMap<usize,RoundStateBin>, HashSet<usize>)>,
-    pub coin_shares: HashMap<usize, VecDeque<LargeFieldSer>>,
+    pub coin_pools: HashMap<usize, CoinPool>,
     pub terminated_rounds: HashSet<usize>,
+    /// Lifecycle of terminated instances, whose round state is pruned after the retention window
+    pub gc: InstanceGC<usize>,
 
     /// Input and output request channels
     /// First: Instance id, Second: Number of secrets, Third: Reconstruction to all or none, Fourth: Request for reconstruction/sharing, Fifth: Reconstruction ID
     pub inp_bin_ba_requests: Receiver<(usize,Val, Vec<LargeFieldSer>)>,
-    pub out_bin_ba_values: Sender<(usize, Val)>
+    pub out_bin_ba_values: Sender<(usize, Val)>,
+
+    /// Coin replenishment from the parent service
+    /// Request: Instance id, first round, number of coins. Reply: Instance id, first round, coin shares
+    pub coin_req_send: Sender<(usize, usize, usize)>,
+    pub coin_recv: Receiver<(usize, usize, Vec<LargeFieldSer>)>,
+    /// False if the parent does not replenish coins, rounds beyond the initial coins then never terminate
+    pub coin_source: bool,
 }
 
 impl Context {
@@ -57,6 +68,29 @@ impl Context {
         input_reqs: Receiver<(usize, Val, Vec<LargeFieldSer>)>, 
         output_shares: Sender<(usize,Val)>,
         byz: bool) -> anyhow::Result<oneshot::Sender<()>> {
+        let (coin_req_send, _coin_req_recv) = service_channel();
+        let (_coin_send, coin_recv) = service_channel();
+        Self::spawn_with_coins(config, input_reqs, output_shares, coin_req_send, coin_recv, false, byz)
+    }
+
+    /// Spawns a BBA that requests more coins on `coin_requests` when an instance runs past its coins.
+    /// The parent must answer every party with shares of the same coins for a given instance and round.
+    pub fn spawn_with_coin_source(config: Node,
+        input_reqs: Receiver<(usize, Val, Vec<LargeFieldSer>)>, 
+        output_shares: Sender<(usize,Val)>,
+        coin_requests: Sender<(usize, usize, usize)>,
+        coin_replies: Receiver<(usize, usize, Vec<LargeFieldSer>)>,
+        byz: bool) -> anyhow::Result<oneshot::Sender<()>> {
+        Self::spawn_with_coins(config, input_reqs, output_shares, coin_requests, coin_replies, true, byz)
+    }
+
+    fn spawn_with_coins(config: Node,
+        input_reqs: Receiver<(usize, Val, Vec<LargeFieldSer>)>, 
+        output_shares: Sender<(usize,Val)>,
+        coin_req_send: Sender<(usize, usize, usize)>,
+        coin_recv: Receiver<(usize, usize, Vec<LargeFieldSer>)>,
+        coin_source: bool,
+        byz: bool) -> anyhow::Result<oneshot::Sender<()>> {
         // Add a separate configuration for RBC service. 
 
         let mut consensus_addrs: FnvHashMap<Replica, SocketAddr> = FnvHashMap::default();
@@ -68,13 +102,13 @@ impl Context {
         let my_address = to_socket_address("0.0.0.0", my_port.port());
 
         // Setup networking
-        let (tx_net_to_consensus, rx_net_to_consensus) = unbounded_channel();
-        TcpReceiver::<Acknowledgement, WrapperMsg<ProtMsg>, _>::spawn(
+        let (tx_net_to_consensus, rx_net_to_consensus) = ingress_channel(ingress_per_sender_capacity(), config.sk_map.clone());
+        TcpReceiver::<Acknowledgement, WrapperMsg<SessionMsg<ProtMsg>>, _>::spawn(
             my_address,
             Handler::new(tx_net_to_consensus),
         );
 
-        let consensus_net = TcpReliableSender::<Replica, WrapperMsg<ProtMsg>, Acknowledgement>::with_peers(
+        let consensus_net = TcpReliableSender::<Replica, WrapperMsg<SessionMsg<ProtMsg>>, Acknowledgement>::with_peers(
             consensus_addrs.clone(),
         );
         
@@ -86,6 +120,8 @@ impl Context {
         let key2 = [23u8; 16];
         let hashstate = HashState::new(key0, key1, key2);
 
+        let session = SessionContext::new(session_id(), "binary_ba", config.id, &consensus_addrs);
+
         tokio::spawn(async move {
             let mut c = Context {
                 net_send: consensus_net,
@@ -93,6 +129,7 @@ impl Context {
                 
                 num_nodes: config.num_nodes,
                 sec_key_map: HashMap::default(),
+                session: session,
                 hash_context: hashstate,
                 myid: config.id,
                 _byz: byz,
@@ -102,10 +139,15 @@ impl Context {
                 
                 round_state: HashMap::default(),
                 terminated_rounds: HashSet::default(),
-                coin_shares: HashMap::default(),
+                gc: InstanceGC::new(gc_retention()),
+                coin_pools: HashMap::default(),
 
                 inp_bin_ba_requests: input_reqs,
-                out_bin_ba_values: output_shares
+                out_bin_ba_values: output_shares,
+
+                coin_req_send: coin_req_send,
+                coin_recv: coin_recv,
+                coin_source: coin_source,
             };
 
             // Populate secret keys from config
@@ -124,7 +166,7 @@ impl Context {
     pub async fn broadcast(&mut self, protmsg: ProtMsg) {
         let sec_key_map = self.sec_key_map.clone();
         for (replica, sec_key) in sec_key_map.into_iter() {
-            let wrapper_msg = WrapperMsg::new(protmsg.clone(), self.myid, &sec_key.as_slice());
+            let wrapper_msg = WrapperMsg::new(self.session.seal(replica, protmsg.clone()), self.myid, &sec_key.as_slice());
             let cancel_handler: CancelHandler<Acknowledgement> = self.net_send.send(replica, wrapper_msg).await;
             self.add_cancel_handler(cancel_handler);
         }
@@ -135,7 +177,7 @@ impl Context {
         let mut total_bytes = 0;
         let sec_key_map = self.sec_key_map.clone();
         for (replica, sec_key) in sec_key_map.into_iter() {
-            let wrapper_msg = WrapperMsg::new(protmsg.clone(), self.myid, &sec_key.as_slice());
+            let wrapper_msg = WrapperMsg::new(self.session.seal(replica, protmsg.clone()), self.myid, &sec_key.as_slice());
             total_bytes += Bytes::from(wrapper_msg.to_bytes()).len();
             let cancel_handler: CancelHandler<Acknowledgement> = self.net_send.send(replica, wrapper_msg).await;
             self.add_cancel_handler(cancel_handler);
@@ -144,10 +186,10 @@ impl Context {
     }
 
     pub fn add_cancel_handler(&mut self, canc: CancelHandler<Acknowledgement>) {
-        self.cancel_handlers.entry(0).or_default().push(canc);
+        consensus::add_cancel_handler_gc(&mut self.cancel_handlers, canc);
     }
 
-    pub async fn send(&mut self, replica: Replica, wrapper_msg: WrapperMsg<ProtMsg>) {
+    pub async fn send(&mut self, replica: Replica, wrapper_msg: WrapperMsg<SessionMsg<ProtMsg>>) {
         #[cfg(feature = "bandwidth")]
         log::info!("Network sending bytes: {:?}", Bytes::from(wrapper_msg.to_bytes()).len());
         let cancel_handler: CancelHandler<Acknowledgement> =
@@ -155,6 +197,14 @@ impl Context {
         self.add_cancel_handler(cancel_handler);
     }
 
+    // Prune round state and coins of instances that terminated before the retention window
+    pub fn collect_garbage(&mut self){
+        let expired = self.gc.prune(&mut self.round_state);
+        for instance_id in expired{
+            self.coin_pools.remove(&instance_id);
+        }
+    }
+
     pub async fn run(&mut self){
         // The process starts listening to messages in this process.
         // First, the node sends an alive message
@@ -181,9 +231,18 @@ impl Context {
                     }
                     let req_msg = req_msg.unwrap();
                     // Save coins first
-                    self.coin_shares.insert(req_msg.0, VecDeque::from(req_msg.2.clone()));
+                    self.coin_pools.insert(req_msg.0, CoinPool::new(req_msg.2.clone()));
                     self.start_baa(req_msg.0, 0, req_msg.1, false).await;
                 },
+                coin_msg = self.coin_recv.recv(), if self.coin_source => {
+                    if coin_msg.is_none(){
+                        log::error!("Coin channel closed, rounds beyond the available coins will not terminate");
+                        self.coin_source = false;
+                        continue;
+                    }
+                    let (instance_id, from_round, coins) = coin_msg.unwrap();
+                    self.process_coin_replenishment(instance_id, from_round, coins).await;
+                },
             };
         }
     }
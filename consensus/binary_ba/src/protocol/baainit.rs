@@ -21,6 +21,7 @@ impl Context{
         log::debug!("Received ECHO1 message from node {} with content {:?} for leader round {}, baa round {}",echo_sender,msg,instance_id,baa_round);
         let val = msg.clone();
         let mut terminate = None;
+        let mut release_coin = false;
         // To avoid mutable borrow
         let mut msgs_to_send = Vec::new();
         if self.round_state.contains_key(&instance_id){
@@ -65,24 +66,9 @@ impl Context{
                 if echo3.is_some(){
                     msgs_to_send.push(ProtMsg::FinBinAAEcho3(echo3.unwrap(), self.myid, instance_id,baa_round));
                     let term = round_state.add_echo3(echo3.unwrap(), self.myid);
-                    if term && !round_state.contains_coin(self.myid) && self.coin_shares.contains_key(&instance_id){
+                    if term && !round_state.contains_coin(self.myid){
                         // Create partial signature and broadcast
-                        if !self.coin_shares.contains_key(&instance_id){
-                            return;
-                        }
-                        let coin_shares = self.coin_shares.get_mut(&instance_id).unwrap();
-                        if coin_shares.len() <= baa_round{
-                            log::error!("Coins unavailable, abandoning BBA round {} for instance {}", baa_round, instance_id);
-                            return;
-                        }
-                        else{
-                            let coin_share = coin_shares[baa_round].clone();
-                            log::debug!("Sending coin share {:?} for lround {}, bround {}",coin_share,instance_id,baa_round);
-                            let prot_msg = ProtMsg::BBACoin(instance_id, baa_round, coin_share, self.myid);
-                            round_state.add_partial_coin(self.myid, LargeField::from_bytes_be(&coin_share).unwrap());
-                            msgs_to_send.push(prot_msg);
-                            terminate = round_state.aggregate_p_coins();
-                        }
+                        release_coin = true;
                     }
                 }
             }
@@ -111,6 +97,10 @@ impl Context{
         for msg in msgs_to_send{
             self.broadcast(msg).await;
         }
+        if release_coin{
+            self.release_coin_share(instance_id, baa_round).await;
+            return;
+        }
         if terminate.is_some(){
             self.round_state.get_mut(&instance_id).unwrap().1.insert(baa_round);  
             self.start_baa(instance_id,baa_round+1, terminate.unwrap().1, terminate.unwrap().0).await;
@@ -122,6 +112,7 @@ impl Context{
             return;
         }
         let mut terminate = None;
+        let mut release_coin = false;
         let mut msgs_to_send = Vec::new();
         log::debug!("Received ECHO2 message from node {} with content {:?} for lround {}, bround {}",echo2_sender,msg,instance_id,baa_round);
         if self.round_state.contains_key(&instance_id){
@@ -138,22 +129,10 @@ impl Context{
                     let term = round_state.add_echo3(echo3.unwrap(), self.myid);
                     msgs_to_send.push(ProtMsg::FinBinAAEcho3(echo3.unwrap(), self.myid, instance_id,baa_round));
                     log::debug!("Sending echo3 message {} for lround {}, bround {}",echo3.unwrap(),instance_id,baa_round);
-                    if term && !round_state.contains_coin(self.myid) && self.coin_shares.contains_key(&instance_id){
+                    if term && !round_state.contains_coin(self.myid){
                         // Create partial signature and broadcast
                         // Create and broadcast coin
-                        let coin_shares = self.coin_shares.get_mut(&instance_id).unwrap();
-                        if coin_shares.len() <= baa_round{
-                            log::error!("Coins unavailable, abandoning BBA round {} for instance {}", baa_round, instance_id);
-                            return;
-                        }
-                        else{
-                            let coin_share = coin_shares[baa_round].clone();
-                            log::debug!("Sending coin share {:?} for lround {}, bround {}",coin_share,instance_id,baa_round);
-                            let prot_msg = ProtMsg::BBACoin(instance_id, baa_round, coin_share, self.myid);
-                            round_state.add_partial_coin(self.myid, LargeField::from_bytes_be(&coin_share).unwrap());
-                            msgs_to_send.push(prot_msg);
-                            terminate = round_state.aggregate_p_coins();
-                        }
+                        release_coin = true;
                     }
                 }
             }
@@ -181,6 +160,10 @@ impl Context{
         for msg in msgs_to_send{
             self.broadcast(msg).await;
         }
+        if release_coin{
+            self.release_coin_share(instance_id, baa_round).await;
+            return;
+        }
         if terminate.is_some(){
             self.round_state.get_mut(&instance_id).unwrap().1.insert(baa_round);
             self.start_baa(instance_id,baa_round+1, terminate.unwrap().1, terminate.unwrap().0).await;
@@ -192,7 +175,8 @@ impl Context{
             return;
         }
         
-        let mut terminate = None;
+        let mut terminate: Option<(bool, Val)> = None;
+        let mut release_coin = false;
         log::debug!("Received ECHO3 message from node {} with content {:?} for lround {}, bround {}",echo3_sender,msg,instance_id,baa_round);
         if self.round_state.contains_key(&instance_id){
             let baa_rnd_state_tup = self.round_state.get_mut(&instance_id).unwrap();
@@ -208,22 +192,8 @@ impl Context{
                     msg,
                     echo3_sender
                 );
-                if term && !round_state.contains_coin(self.myid) && self.coin_shares.contains_key(&instance_id){
-                    // TODO: Broadcasting common coin
-                    let coin_shares = self.coin_shares.get_mut(&instance_id).unwrap();
-                    if coin_shares.len() <= baa_round{
-                        log::error!("Coins unavailable, abandoning BBA round {} for instance {}", baa_round, instance_id);
-                        return;
-                    }
-                    else{
-                        let coin_share = coin_shares[baa_round].clone();
-                        log::debug!("Sending coin share {:?} for lround {}, bround {}",coin_share,instance_id,baa_round);
-                        let prot_msg = ProtMsg::BBACoin(instance_id, baa_round, coin_share, self.myid);
-                        round_state.coin_shares_vec.insert(self.myid,LargeField::from_bytes_be(&coin_share).unwrap());
-                        terminate = round_state.aggregate_p_coins();
-
-                        self.broadcast(prot_msg).await;
-                    }
+                if term && !round_state.contains_coin(self.myid){
+                    release_coin = true;
                 }
             }
             else {
@@ -247,6 +217,10 @@ impl Context{
             baa_rnd_state.insert(baa_round, round_state);
             self.round_state.insert(instance_id, (baa_rnd_state, HashSet::default()));
         }
+        if release_coin{
+            self.release_coin_share(instance_id, baa_round).await;
+            return;
+        }
         if terminate.is_some(){
             self.round_state.get_mut(&instance_id).unwrap().1.insert(baa_round);
             self.start_baa(instance_id,baa_round+1, terminate.unwrap().1, terminate.unwrap().0).await;
@@ -303,6 +277,83 @@ impl Context{
         }
     }
 
+    /// Broadcasts this party's coin share once the round's ECHO3 phase completes.
+    /// If the instance ran past its coins, the round pauses until the parent replenishes them.
+    pub async fn release_coin_share(self: &mut Context, instance_id: usize, baa_round: usize){
+        if self.terminated_rounds.contains(&instance_id){
+            return;
+        }
+        let pool = match self.coin_pools.get_mut(&instance_id){
+            Some(pool) => pool,
+            None => {
+                return;
+            }
+        };
+        let (coin_share, request) = pool.share_for_round(baa_round);
+        if let Some((from_round, num_coins)) = request{
+            self.request_coins(instance_id, from_round, num_coins).await;
+        }
+        let coin_share = match coin_share{
+            Some(coin_share) => coin_share,
+            None => {
+                log::warn!("Coins unavailable for BBA round {} of instance {}, pausing the round until more coins arrive", baa_round, instance_id);
+                return;
+            }
+        };
+        let terminate;
+        {
+            let baa_rnd_state_tup = match self.round_state.get_mut(&instance_id){
+                Some(state) => state,
+                None => {
+                    return;
+                }
+            };
+            if baa_rnd_state_tup.1.contains(&baa_round){
+                return;
+            }
+            let round_state = match baa_rnd_state_tup.0.get_mut(&baa_round){
+                Some(state) => state,
+                None => {
+                    return;
+                }
+            };
+            if round_state.contains_coin(self.myid){
+                return;
+            }
+            log::debug!("Sending coin share {:?} for lround {}, bround {}",coin_share,instance_id,baa_round);
+            round_state.add_partial_coin(self.myid, LargeField::from_bytes_be(&coin_share).unwrap());
+            terminate = round_state.aggregate_p_coins();
+        }
+        self.broadcast(ProtMsg::BBACoin(instance_id, baa_round, coin_share, self.myid)).await;
+        if terminate.is_some(){
+            self.round_state.get_mut(&instance_id).unwrap().1.insert(baa_round);
+            self.start_baa(instance_id,baa_round+1, terminate.unwrap().1, terminate.unwrap().0).await;
+        }
+    }
+
+    pub async fn request_coins(self: &mut Context, instance_id: usize, from_round: usize, num_coins: usize){
+        log::info!("Requesting {} coins from round {} for BBA instance {}", num_coins, from_round, instance_id);
+        if !self.coin_source || self.coin_req_send.send((instance_id, from_round, num_coins)).await.is_err(){
+            log::error!("No coin source available for BBA instance {}, rounds from {} cannot terminate", instance_id, from_round);
+        }
+    }
+
+    pub async fn process_coin_replenishment(self: &mut Context, instance_id: usize, from_round: usize, coins: Vec<LargeFieldSer>){
+        if self.terminated_rounds.contains(&instance_id){
+            return;
+        }
+        log::debug!("Received {} coins from round {} for BBA instance {}", coins.len(), from_round, instance_id);
+        let pool = self.coin_pools.entry(instance_id).or_default();
+        let resumed_rounds = pool.replenish(from_round, coins);
+        let request = pool.pending_request();
+        if let Some((from_round, num_coins)) = request{
+            self.request_coins(instance_id, from_round, num_coins).await;
+        }
+        for baa_round in resumed_rounds{
+            self.release_coin_share(instance_id, baa_round).await;
+        }
+    }
+
     pub async fn start_baa(self: &mut Context,instance_id:usize, baa_round:usize, term_val: Val, terminate: bool){
         if self.terminated_rounds.contains(&instance_id){
             return;
@@ -317,7 +368,8 @@ impl Context{
             self.terminated_rounds.insert(instance_id);
             log::debug!("Terminating BAA round {} for instance {}, broadcasting value {:?}",baa_round,instance_id,term_val);
             let _status = self.out_bin_ba_values.send((instance_id, term_val)).await;
-            // self.round_state.remove(&instance_id);
+            self.gc.mark_terminated(instance_id);
+            self.collect_garbage();
             if _status.is_err(){
                 log::error!("Failed to send BAA value for instance {}",instance_id);
             }
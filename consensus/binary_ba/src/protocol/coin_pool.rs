@@ -0,0 +1,158 @@
+use std::collections::{BTreeSet, VecDeque};
+
+use consensus::LargeFieldSer;
+
+/// Number of coins requested from the parent when a BBA instance runs out of coins
+pub const COIN_BATCH: usize = 5;
+
+/// Coin shares of one BBA instance. Round `r` uses the `r`-th share.
+/// When a round needs a share beyond the pool, the round is paused and more coins are requested from the parent.
+#[derive(Debug, Clone, Default)]
+pub struct CoinPool {
+    pub shares: VecDeque<LargeFieldSer>,
+    /// Rounds waiting for coins
+    pub paused_rounds: BTreeSet<usize>,
+    /// First round of the outstanding coin request, if any
+    pub requested_from: Option<usize>,
+}
+
+impl CoinPool {
+    pub fn new(shares: Vec<LargeFieldSer>) -> CoinPool {
+        CoinPool {
+            shares: VecDeque::from(shares),
+            paused_rounds: BTreeSet::default(),
+            requested_from: None,
+        }
+    }
+
+    /// Returns the share for `round`, or pauses the round.
+    /// When paused, the second value is the (first round, number of coins) to request from the parent, unless a request is outstanding.
+    pub fn share_for_round(&mut self, round: usize) -> (Option<LargeFieldSer>, Option<(usize, usize)>) {
+        if round < self.shares.len() {
+            return (Some(self.shares[round].clone()), None);
+        }
+        self.paused_rounds.insert(round);
+        if self.requested_from.is_some() {
+            return (None, None);
+        }
+        let from = self.shares.len();
+        let count = std::cmp::max(COIN_BATCH, round + 1 - from);
+        self.requested_from = Some(from);
+        (None, Some((from, count)))
+    }
+
+    /// Adds coins for rounds starting at `from_round`. Duplicate and overlapping deliveries are ignored.
+    /// Returns the paused rounds that can resume, in order.
+    pub fn replenish(&mut self, from_round: usize, coins: Vec<LargeFieldSer>) -> Vec<usize> {
+        if from_round > self.shares.len() {
+            log::warn!("Received coins from round {} but the pool ends at round {}, ignoring them", from_round, self.shares.len());
+            return Vec::new();
+        }
+        let skip = self.shares.len() - from_round;
+        self.shares.extend(coins.into_iter().skip(skip));
+        if self.requested_from.map_or(false, |from| from < self.shares.len()) {
+            self.requested_from = None;
+        }
+        let available = self.shares.len();
+        let resumed: Vec<usize> = self.paused_rounds.iter().cloned().filter(|round| *round < available).collect();
+        for round in resumed.iter() {
+            self.paused_rounds.remove(round);
+        }
+        resumed
+    }
+
+    /// The next request to send if rounds are still paused after a replenishment
+    pub fn pending_request(&mut self) -> Option<(usize, usize)> {
+        if self.requested_from.is_some() {
+            return None;
+        }
+        let last_paused = *self.paused_rounds.iter().next_back()?;
+        let from = self.shares.len();
+        let count = std::cmp::max(COIN_BATCH, last_paused + 1 - from);
+        self.requested_from = Some(from);
+        Some((from, count))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    fn coin(round: usize) -> LargeFieldSer {
+        let mut share: LargeFieldSer = Default::default();
+        share[..8].copy_from_slice(&(round as u64).to_be_bytes());
+        share
+    }
+
+    // Parent answering requests with the coins for the requested rounds
+    fn serve(request: (usize, usize)) -> (usize, Vec<LargeFieldSer>) {
+        (request.0, (request.0..request.0 + request.1).map(coin).collect())
+    }
+
+    #[test]
+    fn test_rounds_beyond_initial_pool_pause_and_resume() {
+        let mut pool = CoinPool::new((0..5).map(coin).collect());
+        for round in 0..5 {
+            assert_eq!(pool.share_for_round(round), (Some(coin(round)), None));
+        }
+        let (share, request) = pool.share_for_round(5);
+        assert!(share.is_none());
+        assert_eq!(request, Some((5, COIN_BATCH)));
+        // A second paused round does not trigger a duplicate request
+        assert_eq!(pool.share_for_round(6), (None, None));
+
+        let (from, coins) = serve(request.unwrap());
+        assert_eq!(pool.replenish(from, coins), vec![5, 6]);
+        assert_eq!(pool.share_for_round(6), (Some(coin(6)), None));
+        assert!(pool.pending_request().is_none());
+    }
+
+    #[test]
+    fn test_many_rounds_under_adversarial_scheduling() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut pool = CoinPool::new((0..5).map(coin).collect());
+        // Requests in flight, delivered late, out of order and duplicated
+        let mut in_flight: Vec<(usize, usize)> = Vec::new();
+        let mut used: Vec<Option<LargeFieldSer>> = vec![None; 1000];
+        let mut round = 0;
+        while round < 1000 {
+            let (share, request) = pool.share_for_round(round);
+            if let Some(request) = request {
+                in_flight.push(request);
+            }
+            match share {
+                Some(share) => {
+                    used[round] = Some(share);
+                    round += 1;
+                },
+                None => {
+                    // The scheduler delays the reply for a while, then delivers a random in-flight reply
+                    while pool.paused_rounds.contains(&round) {
+                        if in_flight.is_empty() || rng.gen_range(0, 4) == 0 {
+                            if let Some(request) = pool.pending_request() {
+                                in_flight.push(request);
+                            }
+                            continue;
+                        }
+                        let index = rng.gen_range(0, in_flight.len());
+                        let request = in_flight[index];
+                        if rng.gen_range(0, 2) == 0 {
+                            in_flight.swap_remove(index);
+                        }
+                        let (from, coins) = serve(request);
+                        let resumed = pool.replenish(from, coins);
+                        for resumed_round in resumed {
+                            assert!(resumed_round <= round);
+                        }
+                    }
+                }
+            }
+        }
+        // Every round got the coin of its own index, no round was abandoned
+        for (round, share) in used.into_iter().enumerate() {
+            assert_eq!(share, Some(coin(round)));
+        }
+        assert!(pool.paused_rounds.is_empty());
+    }
+}
@@ -3,3 +3,6 @@ pub use round_state::*;
 
 mod baainit;
 
+mod coin_pool;
+pub use coin_pool::*;
+
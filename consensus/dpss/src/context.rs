@@ -13,21 +13,22 @@ use network::{
 };
 use signal_hook::{iterator::Signals, consts::{SIGINT, SIGTERM}};
 use tokio::{sync::{
-    mpsc::{UnboundedReceiver, Sender, Receiver, channel, unbounded_channel},
+    mpsc::{UnboundedReceiver, Sender, Receiver, unbounded_channel},
     oneshot,
 }};
 // use tokio_util::time::DelayQueue;
 use types::{Replica, SyncMsg, SyncState, WrapperMsg};
 
-use consensus::{SyncHandler, LargeFieldSSS, LargeField, LargeFieldSer};
-use crypto::{aes_hash::HashState, hash::Hash};
+use consensus::{SyncHandler, ClientOutputChannel, LargeFieldSSS, LargeField, LargeFieldSer, FieldType, roots_of_unity, ingress_channel, ingress_per_sender_capacity, service_channel, IngressReceiver, SessionMsg, SessionContext, session_id};
+use crypto::aes_hash::HashState;
+use acss_ske::{ACSSChunkOutput, PubRecChunkOutput};
 
-use crate::{msg::ProtMsg, Handler, protocol::{DPSSState, BAState}};
+use crate::{msg::ProtMsg, Handler, protocol::{DPSSState, BAState, CoinLayout, BBACoinConsumer}};
 
 pub struct Context {
     /// Networking context
-    pub net_send: TcpReliableSender<Replica, WrapperMsg<ProtMsg>, Acknowledgement>,
-    pub net_recv: UnboundedReceiver<WrapperMsg<ProtMsg>>,
+    pub net_send: TcpReliableSender<Replica, WrapperMsg<SessionMsg<ProtMsg>>, Acknowledgement>,
+    pub net_recv: IngressReceiver<WrapperMsg<SessionMsg<ProtMsg>>>,
     pub sync_send: TcpReliableSender<Replica, SyncMsg, Acknowledgement>,
     pub sync_recv: UnboundedReceiver<SyncMsg>,
     /// Data context
@@ -39,12 +40,16 @@ pub struct Context {
     pub opt_or_pess: bool,
     pub lin_or_quad: bool,
     pub ibft: bool,
+    /// Evaluation point of each party's ACSS shares, the roots of unity when sharing with FFTs
+    pub share_evaluation_points: Vec<LargeField>,
 
     pub terminated: bool,
 
     pub large_field_shamir_ss: LargeFieldSSS,
     /// Secret Key map
     pub sec_key_map: HashMap<Replica, Vec<u8>>,
+    /// Session binding and replay protection
+    pub session: SessionContext,
 
     /// Hardware acceleration context
     pub hash_context: HashState,
@@ -57,7 +62,11 @@ pub struct Context {
     pub per_batch: usize,
 
     pub coin_batch: usize,
-    pub coin_shares: VecDeque<LargeField>,
+    /// Generated coins in generation order, addressed through `coin_layout`
+    pub coin_shares: Vec<LargeField>,
+    pub coin_layout: CoinLayout,
+    /// Coin requests of BBA instances that ran past their coins, served once the coins of the run are generated
+    pub pending_coin_requests: VecDeque<(BBACoinConsumer, usize, usize)>,
 
     // Maximum number of RBCs that can be initiated by a node. Keep this as an identifier for RBC service. 
     pub threshold: usize, 
@@ -76,22 +85,31 @@ pub struct Context {
     /// Channels to interact with other services
 
     pub acss_req: Sender<(usize, Vec<LargeField>)>,
-    pub acss_out_recv: Receiver<(usize, usize, Hash, Option<Vec<LargeField>>)>,
+    pub acss_out_recv: Receiver<ACSSChunkOutput>,
+    pub acss_blame_recv: Receiver<(usize, Replica)>,
 
     pub bin_aa_req: Sender<(usize, i64, Vec<LargeFieldSer>)>,
     pub bin_aa_out_recv: Receiver<(usize, i64)>,
+    pub bin_aa_coin_req_recv: Receiver<(usize, usize, usize)>,
+    pub bin_aa_coin_send: Sender<(usize, usize, Vec<LargeFieldSer>)>,
 
     pub fin_mvba_req_send: Sender<(usize, usize, Vec<LargeFieldSer>)>,
     pub fin_mvba_out_recv: Receiver<(usize, Vec<usize>)>,
+    /// Coin requests of the BBAs inside FIN MVBA, only used when FIN MVBA runs
+    pub fin_mvba_coin_req_recv: Receiver<(usize, usize, usize)>,
+    pub fin_mvba_coin_send: Sender<(usize, usize, Vec<LargeFieldSer>)>,
 
     pub acs_term_event: Sender<(usize,usize, Vec<LargeFieldSer>)>,
     pub acs_out_recv: Receiver<(usize,Vec<usize>)>,
 
     pub pub_rec_req_send_channel: Sender<(usize, Replica)>,
-    pub pub_rec_out_recv_channel: Receiver<(usize, Replica, Vec<LargeField>)>,
+    pub pub_rec_out_recv_channel: Receiver<PubRecChunkOutput>,
 
     pub ra_req_send_channel: Sender<(usize, usize, usize)>,
     pub ra_out_recv_channel: Receiver<(usize, usize, usize)>,
+
+    /// Channel to the client, set if the random secrets are delivered privately to it
+    pub client_output: Option<ClientOutputChannel>,
 }
 
 // s = num_batches*per_batch
@@ -108,6 +126,7 @@ impl Context {
         per_batch: usize,
         opt_or_pess: bool,
         lin_or_quad: bool,
+        use_fft: bool,
         ibft: bool,
         byz: bool
     ) -> anyhow::Result<oneshot::Sender<()>> {
@@ -152,8 +171,8 @@ impl Context {
         syncer_map.insert(0, config.client_addr);
 
         // Setup networking
-        let (tx_net_to_consensus, rx_net_to_consensus) = unbounded_channel();
-        TcpReceiver::<Acknowledgement, WrapperMsg<ProtMsg>, _>::spawn(
+        let (tx_net_to_consensus, rx_net_to_consensus) = ingress_channel(ingress_per_sender_capacity(), config.sk_map.clone());
+        TcpReceiver::<Acknowledgement, WrapperMsg<SessionMsg<ProtMsg>>, _>::spawn(
             my_address,
             Handler::new(tx_net_to_consensus),
         );
@@ -168,7 +187,7 @@ impl Context {
             SyncHandler::new(tx_net_to_client),
         );
 
-        let consensus_net = TcpReliableSender::<Replica, WrapperMsg<ProtMsg>, Acknowledgement>::with_peers(
+        let consensus_net = TcpReliableSender::<Replica, WrapperMsg<SessionMsg<ProtMsg>>, Acknowledgement>::with_peers(
             consensus_addrs.clone(),
         );
         let sync_net =
@@ -189,25 +208,32 @@ impl Context {
             config.num_nodes
         );
         // Prepare ACSS context
-        let (bin_aa_req, bin_aa_req_recv) = channel(10000);
-        let (bin_aa_out_send, bin_aa_out_recv) = channel(10000);
-
-        let (fin_mvba_req_send, fin_mvba_req_recv) = channel(10000);
-        let (fin_mvba_out_send, fin_mvba_out_recv) = channel(10000);
+        let (bin_aa_req, bin_aa_req_recv) = service_channel();
+        let (bin_aa_out_send, bin_aa_out_recv) = service_channel();
+        let (bin_aa_coin_req_send, bin_aa_coin_req_recv) = service_channel();
+        let (bin_aa_coin_send, bin_aa_coin_recv) = service_channel();
+
+        let (fin_mvba_req_send, fin_mvba_req_recv) = service_channel();
+        let (fin_mvba_out_send, fin_mvba_out_recv) = service_channel();
+        let (fin_mvba_coin_req_send, fin_mvba_coin_req_recv) = service_channel();
+        let (fin_mvba_coin_send, fin_mvba_coin_recv) = service_channel();
         
-        let (acss_req_send_channel, acss_req_recv_channel) = channel(10000);
-        let (acss_out_send_channel, acss_out_recv_channel) = channel(10000);
+        let (acss_req_send_channel, acss_req_recv_channel) = service_channel();
+        let (acss_out_send_channel, acss_out_recv_channel) = service_channel();
+        let (acss_blame_send_channel, acss_blame_recv_channel) = service_channel();
         
-        let (pub_rec_req_send_channel, pub_rec_req_recv_channel) = channel(10000);
-        let (pub_rec_out_send_channel, pub_rec_out_recv_channel) = channel(10000);
+        let (pub_rec_req_send_channel, pub_rec_req_recv_channel) = service_channel();
+        let (pub_rec_out_send_channel, pub_rec_out_recv_channel) = service_channel();
 
-        let (acs_req_send_channel, acs_req_recv_channel) = channel(10000);
-        let (acs_out_send_channel, acs_out_recv_channel) = channel(10000);
+        let (acs_req_send_channel, acs_req_recv_channel) = service_channel();
+        let (acs_out_send_channel, acs_out_recv_channel) = service_channel();
 
-        let (ra_req_send_channel, ra_req_recv_channel) = channel(10000);
-        let (ra_out_send_channel, ra_out_recv_channel) = channel(10000);        
+        let (ra_req_send_channel, ra_req_recv_channel) = service_channel();
+        let (ra_out_send_channel, ra_out_recv_channel) = service_channel();        
 
         let coin_secrets = (60/(config.num_faults+1))*(config.num_faults+1);
+        let session = SessionContext::new(session_id(), "dpss", config.id, &consensus_addrs);
+        let client_output = ClientOutputChannel::from_config(&config);
         tokio::spawn(async move {
             let mut c = Context {
                 net_send: consensus_net,
@@ -216,6 +242,7 @@ impl Context {
                 sync_recv: rx_net_from_client,
                 num_nodes: config.num_nodes,
                 sec_key_map: HashMap::default(),
+                session: session,
                 hash_context: hashstate,
                 myid: config.id,
                 _byz: byz,
@@ -225,6 +252,11 @@ impl Context {
                 opt_or_pess: opt_or_pess,
                 lin_or_quad: lin_or_quad,
                 ibft: ibft,
+                share_evaluation_points: if use_fft {
+                    roots_of_unity::<FieldType>(config.num_nodes)
+                } else {
+                    (1..config.num_nodes+1).map(|x| LargeField::from(x as u64)).collect()
+                },
 
                 num_faults: config.num_faults,
                 cancel_handlers: HashMap::default(),
@@ -243,7 +275,9 @@ impl Context {
                 per_batch: per_batch, 
                 
                 coin_batch: coin_secrets,
-                coin_shares: VecDeque::new(),
+                coin_shares: Vec::new(),
+                coin_layout: CoinLayout::new(config.num_faults),
+                pending_coin_requests: VecDeque::new(),
                 
                 completed_batches: HashMap::default(),
 
@@ -253,12 +287,17 @@ impl Context {
 
                 acss_req: acss_req_send_channel,
                 acss_out_recv: acss_out_recv_channel,
+                acss_blame_recv: acss_blame_recv_channel,
 
                 bin_aa_req: bin_aa_req,
                 bin_aa_out_recv: bin_aa_out_recv,
+                bin_aa_coin_req_recv: bin_aa_coin_req_recv,
+                bin_aa_coin_send: bin_aa_coin_send,
 
                 fin_mvba_req_send: fin_mvba_req_send,
                 fin_mvba_out_recv: fin_mvba_out_recv,
+                fin_mvba_coin_req_recv: fin_mvba_coin_req_recv,
+                fin_mvba_coin_send: fin_mvba_coin_send,
 
                 acs_term_event: acs_req_send_channel,
                 acs_out_recv: acs_out_recv_channel,
@@ -268,6 +307,8 @@ impl Context {
 
                 ra_req_send_channel: ra_req_send_channel,
                 ra_out_recv_channel: ra_out_recv_channel,
+
+                client_output: client_output,
             };
 
             // Populate secret keys from config
@@ -285,9 +326,10 @@ impl Context {
             acss_config,
             acss_req_recv_channel,
             acss_out_send_channel, 
+            acss_blame_send_channel,
             pub_rec_req_recv_channel,
             pub_rec_out_send_channel,
-            false,
+            use_fft,
             lin_or_quad,
             false
         );
@@ -315,10 +357,12 @@ impl Context {
             log::error!("Error spawning acs because of {:?}", _acs_serv_status.err().unwrap());
         }
 
-        let _ba_serv_status = binary_ba::Context::spawn(
+        let _ba_serv_status = binary_ba::Context::spawn_with_coin_source(
             ba_config,
             bin_aa_req_recv,
             bin_aa_out_send,
+            bin_aa_coin_req_send,
+            bin_aa_coin_recv,
             false
         );
 
@@ -332,6 +376,8 @@ impl Context {
                 mvba_config,
                 fin_mvba_req_recv,
                 fin_mvba_out_send,
+                fin_mvba_coin_req_send,
+                fin_mvba_coin_recv,
                 false
             );
         }
@@ -379,17 +425,17 @@ impl Context {
     pub async fn broadcast(&mut self, protmsg: ProtMsg) {
         let sec_key_map = self.sec_key_map.clone();
         for (replica, sec_key) in sec_key_map.into_iter() {
-            let wrapper_msg = WrapperMsg::new(protmsg.clone(), self.myid, &sec_key.as_slice());
+            let wrapper_msg = WrapperMsg::new(self.session.seal(replica, protmsg.clone()), self.myid, &sec_key.as_slice());
             let cancel_handler: CancelHandler<Acknowledgement> = self.net_send.send(replica, wrapper_msg).await;
             self.add_cancel_handler(cancel_handler);
         }
     }
 
     pub fn add_cancel_handler(&mut self, canc: CancelHandler<Acknowledgement>) {
-        self.cancel_handlers.entry(0).or_default().push(canc);
+        consensus::add_cancel_handler_gc(&mut self.cancel_handlers, canc);
     }
 
-    pub async fn send(&mut self, replica: Replica, wrapper_msg: WrapperMsg<ProtMsg>) {
+    pub async fn send(&mut self, replica: Replica, wrapper_msg: WrapperMsg<SessionMsg<ProtMsg>>) {
         let cancel_handler: CancelHandler<Acknowledgement> =
             self.net_send.send(replica, wrapper_msg).await;
         self.add_cancel_handler(cancel_handler);
@@ -459,15 +505,21 @@ impl Context {
                     let acss_msg = acss_msg.ok_or_else(||
                         anyhow!("Networking layer has closed")
                     )?;
-                    log::debug!("Received message from CTRBC channel {:?}", acss_msg);
-                    self.process_acss_event(acss_msg.0, acss_msg.1, acss_msg.2, acss_msg.3).await;
+                    log::debug!("Received chunk {} of {} of dealer {} in ACSS instance {}", acss_msg.chunk, acss_msg.num_chunks, acss_msg.dealer, acss_msg.instance_id);
+                    self.process_acss_chunk(acss_msg).await;
+                },
+                acss_blame = self.acss_blame_recv.recv() => {
+                    let acss_blame = acss_blame.ok_or_else(||
+                        anyhow!("Networking layer has closed")
+                    )?;
+                    self.process_blamed_dealer(acss_blame.0, acss_blame.1).await;
                 },
                 acs_output = self.acs_out_recv.recv() =>{
                     let acs_output = acs_output.ok_or_else(||
                         anyhow!("Networking layer has closed")
                     )?;
                     log::debug!("Received message from RBC channel {:?}", acs_output);
-                    self.process_consensus_output(acs_output.1).await;
+                    self.process_consensus_output(acs_output.0, acs_output.1).await;
                 },
                 bin_aa_out_msg = self.bin_aa_out_recv.recv() => {
                     let bin_aa_out_msg = bin_aa_out_msg.ok_or_else(||
@@ -476,6 +528,21 @@ impl Context {
                     log::info!("Received message from Binary AA channel {:?}", bin_aa_out_msg);
                     self.process_bin_aa_output(bin_aa_out_msg.0, bin_aa_out_msg.1).await;
                 },
+                bin_aa_coin_req = self.bin_aa_coin_req_recv.recv() => {
+                    let bin_aa_coin_req = bin_aa_coin_req.ok_or_else(||
+                        anyhow!("Networking layer has closed")
+                    )?;
+                    log::info!("Received coin request from Binary AA channel {:?}", bin_aa_coin_req);
+                    self.process_bin_aa_coin_request(BBACoinConsumer::DPSS(bin_aa_coin_req.0), bin_aa_coin_req.1, bin_aa_coin_req.2).await;
+                },
+                fin_mvba_coin_req = self.fin_mvba_coin_req_recv.recv(), if self.ibft => {
+                    let fin_mvba_coin_req = fin_mvba_coin_req.ok_or_else(||
+                        anyhow!("Networking layer has closed")
+                    )?;
+                    log::info!("Received coin request from Fin MVBA channel {:?}", fin_mvba_coin_req);
+                    let (instance_id, mvba_round) = fin_mvba::split_bba_instance_id(fin_mvba_coin_req.0);
+                    self.process_bin_aa_coin_request(BBACoinConsumer::MVBA(instance_id, mvba_round), fin_mvba_coin_req.1, fin_mvba_coin_req.2).await;
+                },
                 fin_mvba_out_msg = self.fin_mvba_out_recv.recv() => {
                     let fin_mvba_out_msg = fin_mvba_out_msg.ok_or_else(||
                         anyhow!("Networking layer has closed")
@@ -495,7 +562,7 @@ impl Context {
                         anyhow!("Networking layer has closed")
                     )?;
                     log::debug!("Received message from Pub Rec channel {:?}", pub_rec_out_msg);
-                    self.process_acss_pubrec_output(pub_rec_out_msg.1, pub_rec_out_msg.2).await;
+                    self.process_acss_pubrec_chunk(pub_rec_out_msg).await;
                 },
                 ra_out_msg = self.ra_out_recv_channel.recv() => {
                     let ra_out_msg = ra_out_msg.ok_or_else(||
@@ -1,7 +1,9 @@
 use std::collections::{HashMap, HashSet};
 
+use acss_ske::ACSSChunkOutput;
 use consensus::LargeFieldSSS;
-use crypto::{LargeField, LargeFieldSer, hash::{Hash}, rand_field_element};
+use consensus::{LargeField, LargeFieldSer, rand_field_element, Transcript};
+use crypto::hash::{Hash};
 use lambdaworks_math::traits::ByteConversion;
 use types::Replica;
 
@@ -22,11 +24,35 @@ impl Context{
         self.max_id = id+2;
     }
 
+    /// Folds a chunk streamed by ACSS into the running result of its dealing, and processes the dealing once all of its chunks are in.
+    pub async fn process_acss_chunk(&mut self, chunk_output: ACSSChunkOutput){
+        let key = (chunk_output.instance_id, chunk_output.dealer);
+        let (root, shares) = match chunk_output.output{
+            Some(output) => output,
+            None => {
+                self.dpss_state.acss_chunks.remove(&key);
+                self.process_acss_event(key.0, key.1, [0;32], None).await;
+                return;
+            }
+        };
+        let fold = self.dpss_state.acss_chunks.entry(key).or_default();
+        if !fold.add(chunk_output.chunk, chunk_output.num_chunks, root, shares){
+            return;
+        }
+        let fold = self.dpss_state.acss_chunks.remove(&key).unwrap();
+        let root_comm = fold.root(&self.hash_context);
+        self.process_acss_event(key.0, key.1, root_comm, Some(fold.shares)).await;
+    }
+
     pub async fn process_acss_event(&mut self, inst: usize, sender: usize, root_comm: Hash, shares_deser: Option<Vec<LargeField>>){
         if shares_deser.is_none(){
             log::error!("Received ACSS terminated event for instance {}, dealer: {}, but shares are None", inst, sender);
             return;
         }
+        if self.dpss_state.blamed_dealers.contains(&sender){
+            log::warn!("Ignoring ACSS output of blamed dealer {} in instance {}", sender, inst);
+            return;
+        }
         let shares_deser = shares_deser.unwrap();
         log::info!("Received ACSS terminated event for instance {}, dealer: {}, with shares: {}", inst, sender, shares_deser.len());
         
@@ -60,8 +86,13 @@ impl Context{
             let root_1 = first_comm_shares.1;
             let root_2 = second_comm_shares.1;
 
-            let core_root = self.hash_context.hash_two(root_1, root_2);
-            let root_comm_lf = LargeField::from_bytes_be(core_root.as_slice()).unwrap();
+            // Both sharings of the dealer are aggregated with the same challenge, derived from both commitments
+            let mut transcript = Transcript::new(b"dpss secret equivalence");
+            transcript.append_u64(b"instance", inst_key as u64);
+            transcript.append_u64(b"dealer", sender as u64);
+            transcript.append_hash(b"first commitment", &root_1);
+            transcript.append_hash(b"second commitment", &root_2);
+            let root_comm_lf: LargeField = transcript.challenge_field_element(b"secret equivalence");
 
             // Construct aggregated shares and broadcast them within committee
             let mut root_comm_mul = root_comm_lf.clone();
@@ -118,7 +149,7 @@ impl Context{
                 let mut evaluations = Vec::new();
                 for rep in 0..self.num_nodes{
                     if c1_val_map.contains_key(&rep){
-                        eval_points.push(LargeField::from((rep+1) as u64));
+                        eval_points.push(self.share_evaluation_points[rep].clone());
                         evaluations.push(c1_val_map.get(&rep).unwrap().clone());
                     }
                 }
@@ -147,7 +178,7 @@ impl Context{
                 let mut evaluations = Vec::new();
                 for rep in 0..self.num_nodes{
                     if c2_val_map.contains_key(&rep){
-                        eval_points.push(LargeField::from((rep+1) as u64));
+                        eval_points.push(self.share_evaluation_points[rep].clone());
                         evaluations.push(c1_val_map.get(&rep).unwrap().clone());
                     }
                 }
@@ -178,7 +209,26 @@ impl Context{
         }
     }
 
+    /// Excludes a dealer blamed by ACSS from the ACS inputs of this and future sharings.
+    /// A blamed dealer that was already proposed to ACS cannot be withdrawn.
+    pub async fn process_blamed_dealer(&mut self, inst: usize, dealer: Replica){
+        if !self.dpss_state.blamed_dealers.insert(dealer){
+            return;
+        }
+        log::error!("Dealer {} blamed in ACSS instance {}, excluding it from future sharings", dealer, inst);
+        if self.acs_input_set.contains(&dealer){
+            log::warn!("Blamed dealer {} was already proposed to ACS", dealer);
+        }
+        // Shares of dealers agreed upon by ACS are still needed
+        if !self.dpss_state.acs_output.contains(&dealer){
+            self.dpss_state.acss_map.remove(&dealer);
+        }
+    }
+
     pub async fn check_acss_and_secret_equivalence_termination(&mut self, origin: Replica){
+        if self.dpss_state.blamed_dealers.contains(&origin){
+            return;
+        }
         if !self.dpss_state.acss_map.contains_key(&origin) {
             return;
         }
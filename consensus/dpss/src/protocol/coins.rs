@@ -0,0 +1,173 @@
+use std::ops::Range;
+
+/// Coins handed to a BBA instance when it starts
+pub const BBA_INITIAL_COINS: usize = 5;
+/// Coins handed to a FIN MVBA instance when it starts
+pub const MVBA_COINS: usize = 30;
+/// Coins reserved per BBA instance for every further block of rounds
+pub const BBA_EXTRA_COIN_BLOCK: usize = 5;
+/// Rounds of a FIN MVBA instance covered by its coins, each round takes a leader coin and the initial coins of its BBA
+pub const MVBA_ROUNDS: usize = MVBA_COINS / (1 + BBA_INITIAL_COINS);
+
+/// BBA instance that asks for the coins of rounds past its initial coins
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BBACoinConsumer{
+    /// BBA instance run by the DPSS
+    DPSS(usize),
+    /// BBA run inside FIN MVBA, identified by the MVBA instance and round
+    MVBA(usize, usize),
+}
+
+/// Fixed placement of the generated coins.
+/// Every coin consumer is identified by its instance id (1..=num_instances) and, for BBA, the round.
+/// The coin of a consumer is a pure function of that identity, so parties hand out shares of the same coin no matter in which order requests arrive or how many coins each party asks for at once.
+///
+/// Layout: [BBA initial coins of instances 1..=n | MVBA coins of instances 1..=n | extra BBA blocks, interleaved across the DPSS BBAs and the BBAs of every MVBA round]
+///
+/// A DPSS run mints its coins once. Rounds whose coin lies past the minted coins are never served, see `bba_round_bound`.
+#[derive(Debug, Clone, Copy)]
+pub struct CoinLayout{
+    pub num_instances: usize,
+}
+
+impl CoinLayout{
+    pub fn new(num_instances: usize) -> CoinLayout{
+        CoinLayout { num_instances }
+    }
+
+    pub fn bba_initial(&self, instance: usize) -> Range<usize>{
+        let start = (instance - 1) * BBA_INITIAL_COINS;
+        start..start + BBA_INITIAL_COINS
+    }
+
+    pub fn mvba(&self, instance: usize) -> Range<usize>{
+        let start = self.num_instances * BBA_INITIAL_COINS + (instance - 1) * MVBA_COINS;
+        start..start + MVBA_COINS
+    }
+
+    /// Index of the coin used by DPSS BBA `instance` in `round`
+    pub fn bba_round(&self, instance: usize, round: usize) -> usize{
+        if round < BBA_INITIAL_COINS{
+            return self.bba_initial(instance).start + round;
+        }
+        self.extra_coin(instance - 1, round - BBA_INITIAL_COINS)
+    }
+
+    /// Index of the coin used in `round` by the BBA of `mvba_round` of MVBA `instance`.
+    /// None for the initial rounds, whose coins the MVBA hands out from its own, and for MVBA rounds without coins.
+    pub fn mvba_bba_round(&self, instance: usize, mvba_round: usize, round: usize) -> Option<usize>{
+        if round < BBA_INITIAL_COINS || mvba_round == 0 || mvba_round > MVBA_ROUNDS{
+            return None;
+        }
+        let slot = self.num_instances + (instance - 1) * MVBA_ROUNDS + mvba_round - 1;
+        Some(self.extra_coin(slot, round - BBA_INITIAL_COINS))
+    }
+
+    /// Indices of the coins for rounds `from_round..from_round+num_coins` of `consumer`
+    pub fn bba_rounds(&self, consumer: BBACoinConsumer, from_round: usize, num_coins: usize) -> Option<Vec<usize>>{
+        (from_round..from_round + num_coins).map(|round| match consumer{
+            BBACoinConsumer::DPSS(instance) => Some(self.bba_round(instance, round)),
+            BBACoinConsumer::MVBA(instance, mvba_round) => self.mvba_bba_round(instance, mvba_round, round),
+        }).collect()
+    }
+
+    /// Number of leading rounds of DPSS BBA `instance` whose coins lie within the first `minted` coins
+    pub fn bba_round_bound(&self, instance: usize, minted: usize) -> usize{
+        let mut round = 0;
+        while self.bba_round(instance, round) < minted{
+            round += 1;
+        }
+        round
+    }
+
+    /// Extra coins are handed out in blocks, interleaved across the DPSS BBAs and the BBAs of every MVBA round
+    fn extra_coin(&self, slot: usize, extra_round: usize) -> usize{
+        let extra_start = self.num_instances * (BBA_INITIAL_COINS + MVBA_COINS);
+        let slots = self.num_instances * (1 + MVBA_ROUNDS);
+        let block = extra_round / BBA_EXTRA_COIN_BLOCK;
+        extra_start
+            + (block * slots + slot) * BBA_EXTRA_COIN_BLOCK
+            + extra_round % BBA_EXTRA_COIN_BLOCK
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use super::*;
+
+    /// Serves requests from a shared coin vector the way `Context` does, returning the coin index used for every (instance, round)
+    fn serve(layout: &CoinLayout, requests: &[(usize, usize, usize)]) -> HashMap<(usize, usize), usize>{
+        let mut served = HashMap::new();
+        for &(instance, from_round, num_coins) in requests{
+            for (offset, index) in layout.bba_rounds(BBACoinConsumer::DPSS(instance), from_round, num_coins).unwrap().into_iter().enumerate(){
+                served.insert((instance, from_round + offset), index);
+            }
+        }
+        served
+    }
+
+    #[test]
+    fn test_parties_agree_on_coins_regardless_of_request_order() {
+        let layout = CoinLayout::new(3);
+        // Party A sees instance 1 run long first, and asks for large batches
+        let party_a = vec![(1, 5, 12), (2, 5, 5), (3, 5, 5), (2, 10, 5)];
+        // Party B sees the same rounds requested in a different order and in differently sized batches
+        let party_b = vec![(3, 5, 5), (2, 5, 10), (1, 5, 5), (1, 10, 7)];
+        let served_a = serve(&layout, &party_a);
+        let served_b = serve(&layout, &party_b);
+        for (key, index) in served_a.iter(){
+            if let Some(other) = served_b.get(key){
+                assert_eq!(index, other, "coin for {:?} differs between parties", key);
+            }
+        }
+        assert_eq!(served_a.get(&(1, 16)), served_b.get(&(1, 16)));
+        assert_eq!(served_a.get(&(2, 14)), served_b.get(&(2, 14)));
+    }
+
+    #[test]
+    fn test_coin_indices_are_disjoint() {
+        let layout = CoinLayout::new(4);
+        let mut used = HashSet::new();
+        for instance in 1..=4{
+            for index in layout.bba_initial(instance).chain(layout.mvba(instance)){
+                assert!(used.insert(index));
+            }
+            for round in 0..BBA_INITIAL_COINS{
+                assert_eq!(layout.bba_round(instance, round), layout.bba_initial(instance).start + round);
+            }
+        }
+        for instance in 1..=4{
+            for round in BBA_INITIAL_COINS..BBA_INITIAL_COINS + 4 * BBA_EXTRA_COIN_BLOCK{
+                assert!(used.insert(layout.bba_round(instance, round)));
+                for mvba_round in 1..=MVBA_ROUNDS{
+                    assert!(used.insert(layout.mvba_bba_round(instance, mvba_round, round).unwrap()));
+                }
+            }
+        }
+        // Layout is dense: no coin is skipped
+        assert_eq!(used.len(), *used.iter().max().unwrap() + 1);
+    }
+
+    #[test]
+    fn test_mvba_bbas_get_only_extra_coins() {
+        let layout = CoinLayout::new(2);
+        assert_eq!(layout.mvba_bba_round(1, 1, BBA_INITIAL_COINS - 1), None);
+        assert_eq!(layout.mvba_bba_round(1, 0, BBA_INITIAL_COINS), None);
+        assert_eq!(layout.mvba_bba_round(1, MVBA_ROUNDS + 1, BBA_INITIAL_COINS), None);
+        assert!(layout.bba_rounds(BBACoinConsumer::MVBA(2, 1), BBA_INITIAL_COINS - 1, 2).is_none());
+        assert_eq!(layout.bba_rounds(BBACoinConsumer::MVBA(2, 1), BBA_INITIAL_COINS, 2).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_round_bound_of_a_single_coin_generation() {
+        // With one instance, 120 coins cover the initial coins and three extra blocks of every BBA
+        let layout = CoinLayout::new(1);
+        let minted = 120;
+        let bound = layout.bba_round_bound(1, minted);
+        assert_eq!(bound, BBA_INITIAL_COINS + 3 * BBA_EXTRA_COIN_BLOCK);
+        assert!(layout.bba_round(1, bound - 1) < minted);
+        assert!(layout.bba_round(1, bound) >= minted);
+    }
+}
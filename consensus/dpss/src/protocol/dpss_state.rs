@@ -1,6 +1,8 @@
 use std::collections::{HashMap, HashSet};
 
-use crypto::{LargeField, hash::Hash};
+use acss_ske::ChunkFold;
+use consensus::LargeField;
+use crypto::hash::Hash;
 use types::Replica;
 
 pub struct DPSSState{
@@ -11,6 +13,11 @@ pub struct DPSSState{
             Option<(Vec<LargeField>, Hash)>)>
         >,
     
+    /// Running results of the dealings streamed chunk by chunk by ACSS, keyed by (instance, dealer)
+    pub acss_chunks: HashMap<(usize, Replica), ChunkFold>,
+    /// Chunks and secrets publicly reconstructed so far, keyed by (instance, dealer)
+    pub pub_rec_chunks: HashMap<(usize, Replica), (usize, usize)>,
+
     pub sec_equivalence: HashMap<Replica, 
         HashMap<usize, (
                         HashMap<usize, LargeField>, 
@@ -23,16 +30,24 @@ pub struct DPSSState{
     pub pub_rec_echo2s: HashMap<Replica, Vec<LargeField>>,
 
     pub acs_output: HashSet<Replica>,
+    /// ACS instance whose output selected the dealers of the random secrets
+    pub acs_instance: Option<usize>,
+    // Dealers proven to have dealt invalid shares, excluded from ACS inputs
+    pub blamed_dealers: HashSet<Replica>,
 }
 
 impl DPSSState{
     pub fn new()-> DPSSState{
         DPSSState {
             acss_map: HashMap::default(),
+            acss_chunks: HashMap::default(),
+            pub_rec_chunks: HashMap::default(),
             sec_equivalence: HashMap::default(),
             pub_rec_echo1s: HashMap::default(),
             pub_rec_echo2s: HashMap::default(),
             acs_output: HashSet::default(),
+            acs_instance: None,
+            blamed_dealers: HashSet::default(),
         }
     }
 }
\ No newline at end of file
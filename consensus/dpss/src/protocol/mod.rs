@@ -5,5 +5,8 @@ mod rand_sh;
 mod dpss_state;
 pub use dpss_state::*;
 
+mod coins;
+pub use coins::*;
+
 mod pub_rec;
 pub use pub_rec::*;
\ No newline at end of file
@@ -1,8 +1,9 @@
 use std::collections::{HashSet};
 
-use consensus::{LargeField};
+use acss_ske::PubRecChunkOutput;
+use consensus::LargeFieldSer;
 use types::Replica;
-use crate::Context;
+use crate::{Context, protocol::BBACoinConsumer};
 
 pub struct BAState{
     pub ba_started: HashSet<Replica>,
@@ -110,27 +111,59 @@ impl Context{
         }
         if !self.ba_state.secrets_reconstructed || 
             !self.ba_state.shares_generated || 
-            self.coin_shares.len() < 5 {
+            self.coin_shares.len() < self.coin_layout.bba_initial(instance).end {
             log::info!("Cannot start binary BA instance {}, prerequisites not met", instance);
             return;
         }
         log::info!("Initializing binary BA for instance {} with inp {}", instance, inp);
-        let mut coin_vals = Vec::new();
-        for _ in 0..5{
-            coin_vals.push(self.coin_shares.pop_front().unwrap().to_bytes_be());
-        }
+        let coin_vals: Vec<LargeFieldSer> = self.coin_layout.bba_initial(instance).map(|index| self.coin_shares[index].to_bytes_be()).collect();
         let _ra_status = self.ra_req_send_channel.send((0, inp, instance)).await;
         let _status = self.bin_aa_req.send((instance, inp as i64, coin_vals)).await;
         self.ba_state.ba_started.insert(instance);
     }
 
+    /// Serves coins to a BBA instance that ran past its initial coins.
+    /// The coin of every round is fixed by `CoinLayout`, so all parties hand out shares of the same coin regardless of request order or batch size.
+    /// A run mints its coins once, so requests past the minted coins are dropped and the rounds they cover do not terminate.
+    pub async fn process_bin_aa_coin_request(&mut self, consumer: BBACoinConsumer, from_round: usize, num_coins: usize){
+        let indices = match self.coin_layout.bba_rounds(consumer, from_round, num_coins){
+            Some(indices) => indices,
+            None => {
+                log::error!("No coins are laid out for rounds {}..{} of {:?}", from_round, from_round + num_coins, consumer);
+                return;
+            }
+        };
+        let needed = indices.iter().max().map(|index| index + 1).unwrap_or(0);
+        if self.coin_shares.len() < needed{
+            if self.ba_state.shares_generated{
+                log::error!("{:?} ran past the {} coins of this run, rounds from {} will not terminate", consumer, self.coin_shares.len(), from_round);
+                return;
+            }
+            log::info!("Not enough coins for {:?}, waiting for {} more coins", consumer, needed - self.coin_shares.len());
+            self.pending_coin_requests.push_back((consumer, from_round, num_coins));
+            return;
+        }
+        let coin_vals: Vec<LargeFieldSer> = indices.into_iter().map(|index| self.coin_shares[index].to_bytes_be()).collect();
+        let _status = match consumer{
+            BBACoinConsumer::DPSS(instance_id) => self.bin_aa_coin_send.send((instance_id, from_round, coin_vals)).await,
+            BBACoinConsumer::MVBA(instance_id, mvba_round) => self.fin_mvba_coin_send.send((fin_mvba::bba_instance_id(instance_id, mvba_round), from_round, coin_vals)).await,
+        };
+    }
+
+    pub async fn serve_pending_coin_requests(&mut self){
+        let pending: Vec<(BBACoinConsumer, usize, usize)> = self.pending_coin_requests.drain(..).collect();
+        for (consumer, from_round, num_coins) in pending{
+            self.process_bin_aa_coin_request(consumer, from_round, num_coins).await;
+        }
+    }
+
     pub async fn init_fin_mvba(&mut self, instance_id: usize){
         if self.ba_state.mvba_started.contains(&instance_id) {
             return;
         }
         if !self.ba_state.secrets_reconstructed || 
             !self.ba_state.shares_generated || 
-            self.coin_shares.len() < 30 ||
+            self.coin_shares.len() < self.coin_layout.mvba(instance_id).end ||
             self.dpss_state.acs_output.len() == 0{
             log::info!("Cannot start FIN MVBA instance {}, prerequisites not met", instance_id);
             return;
@@ -140,10 +173,7 @@ impl Context{
         let corrupted_party = acs_output_set[acs_output_set.len() - instance_id];
 
         log::info!("Initializing FIN MVBA for instance {} with corrupted party {}", instance_id, corrupted_party);
-        let mut coin_vals = Vec::new();
-        for _ in 0..30{
-            coin_vals.push(self.coin_shares.pop_front().unwrap().to_bytes_be());
-        }
+        let coin_vals: Vec<LargeFieldSer> = self.coin_layout.mvba(instance_id).map(|index| self.coin_shares[index].to_bytes_be()).collect();
         
         let _status = self.fin_mvba_req_send.send((instance_id, corrupted_party, coin_vals)).await;
         self.ba_state.mvba_started.insert(instance_id);
@@ -189,8 +219,21 @@ impl Context{
         }
     }
 
-    pub async fn process_acss_pubrec_output(&mut self, corrupted_party: usize, secrets: Vec<LargeField>){
-        log::info!("Received public reconstruction output for corrupted party {} with secrets: {}", corrupted_party, secrets.len());
+    /// Counts the chunks of a public reconstruction streamed by ACSS. The reconstruction terminates once all of its chunks are in.
+    pub async fn process_acss_pubrec_chunk(&mut self, chunk_output: PubRecChunkOutput){
+        let key = (chunk_output.instance_id, chunk_output.dealer);
+        let (num_reconstructed, num_secrets) = self.dpss_state.pub_rec_chunks.entry(key).or_default();
+        *num_reconstructed += 1;
+        *num_secrets += chunk_output.secrets.len();
+        if *num_reconstructed < chunk_output.num_chunks{
+            return;
+        }
+        let (_, num_secrets) = self.dpss_state.pub_rec_chunks.remove(&key).unwrap();
+        self.process_acss_pubrec_output(chunk_output.dealer, num_secrets).await;
+    }
+
+    pub async fn process_acss_pubrec_output(&mut self, corrupted_party: usize, num_secrets: usize){
+        log::info!("Received public reconstruction output for corrupted party {} with secrets: {}", corrupted_party, num_secrets);
         
         self.ba_state.pub_rec_term_parties.insert(corrupted_party);
         if self.ba_state.acs_output_sorted.len() > 0{
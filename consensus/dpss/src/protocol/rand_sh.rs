@@ -1,5 +1,5 @@
-use consensus::{LargeFieldSSS, RBCSyncMsg, inverse_vandermonde, matrix_vector_multiply, vandermonde_matrix};
-use crypto::{LargeField, LargeFieldSer};
+use consensus::{LargeFieldSSS, RBCSyncMsg, ClientShare, ClientShares, matrix_vector_multiply, cached_inverse_vandermonde};
+use consensus::{LargeField, LargeFieldSer};
 use lambdaworks_math::{traits::ByteConversion, polynomial::Polynomial};
 use rayon::prelude::IntoParallelIterator;
 use types::{WrapperMsg, Replica, SyncMsg, SyncState};
@@ -8,7 +8,8 @@ use rayon::prelude::{ParallelIterator};
 use crate::{Context, msg::ProtMsg};
 
 impl Context{
-    pub async fn process_consensus_output(&mut self, mut acs_output: Vec<Replica>){
+    pub async fn process_consensus_output(&mut self, acs_instance: usize, mut acs_output: Vec<Replica>){
+        self.dpss_state.acs_instance = Some(acs_instance);
         self.dpss_state.acs_output.extend(acs_output.clone());
         acs_output.sort();
         self.ba_state.acs_output_sorted.extend(acs_output);
@@ -89,8 +90,38 @@ impl Context{
         }).flatten().collect();
 
         
+        if self.client_output.is_some(){
+            // Deliver the random secrets to the client instead of opening them to the committee
+            let client_shares = ClientShares {
+                shares: combined_shares.into_iter().flatten().enumerate().map(|(index, share)| ClientShare {
+                    index: index,
+                    share: share.to_bytes_be(),
+                    opening: None,
+                }).collect(),
+                roots: None,
+            };
+            // The client tells the outputs of different runs apart by the ACS instance that selected the dealers
+            let acs_instance = self.dpss_state.acs_instance.unwrap_or_default();
+            let cancel_handler = self.client_output.as_mut().unwrap().send("dpss", acs_instance, client_shares).await;
+            self.add_cancel_handler(cancel_handler);
+            log::info!("Sent shares of random secrets to the client");
+            self.ba_state.secrets_reconstructed = true;
+        }
+        else{
+            self.public_reconstruction(combined_shares).await;
+        }
+        log::info!("Prepared {} coin shares", coin_shares.len());
+        self.coin_shares.extend(coin_shares);
+        // These are all the coins of the run, requests they do not cover are dropped
+        self.ba_state.shares_generated = true;
+        self.serve_pending_coin_requests().await;
+        
+        self.verify_start_binary_ba().await;
+    }
+
+    /// Efficient public reconstruction of the combined shares, each vector of t+1 shares is opened to all parties
+    async fn public_reconstruction(&mut self, combined_shares: Vec<Vec<LargeField>>){
         // Encode and reconstruct these combined shares
-        // Efficient Public Reconstruction
         let mut party_wise_shares = Vec::new();
         for _ in 0..self.num_nodes{
             party_wise_shares.push(Vec::new());
@@ -106,15 +137,10 @@ impl Context{
             let secret_key = self.sec_key_map.get(&rep).clone().unwrap();
             let shares_ser = shares.into_iter().map(|x| x.to_bytes_be()).collect();
             let prot_msg = ProtMsg::PubRecEcho1(shares_ser);
-            let wrapper = WrapperMsg::new(prot_msg, self.myid, secret_key.as_slice());
+            let wrapper = WrapperMsg::new(self.session.seal(rep, prot_msg), self.myid, secret_key.as_slice());
             let cancel_handler = self.net_send.send(rep, wrapper).await;
             self.add_cancel_handler(cancel_handler);
         }
-        log::info!("Prepared {} coin shares", coin_shares.len());
-        self.coin_shares.extend(coin_shares);
-        
-        self.ba_state.shares_generated = true;
-        self.verify_start_binary_ba().await;
     }
 
     pub async fn process_pub_rec_echo1_msg(&mut self, shares_ser: Vec<LargeFieldSer>, sender: Replica){
@@ -131,7 +157,7 @@ impl Context{
             }
             for rep in 0..self.num_nodes{
                 if self.dpss_state.pub_rec_echo1s.contains_key(&rep){
-                    evaluation_indices.push(LargeField::from((rep+1) as u64));
+                    evaluation_indices.push(self.share_evaluation_points[rep].clone());
                     let shares_sub_poly = self.dpss_state.pub_rec_echo1s.get(&rep).unwrap().clone();
                     for (index, shares_ind) in (0..shares_len).into_iter().zip(shares_sub_poly.into_iter()){
                         vec_shares_indices[index].push(shares_ind);
@@ -142,9 +168,7 @@ impl Context{
             // Interpolate polynomials
             let secret_evaluation_point= LargeField::from(0 as u64);
 
-            // Generate vandermonde matrix
-            let vandermonde = vandermonde_matrix(evaluation_indices.clone());
-            let inverse_vandermonde = inverse_vandermonde(vandermonde);
+            let inverse_vandermonde = cached_inverse_vandermonde(&evaluation_indices);
 
             let l2_shares : Vec<LargeFieldSer> = vec_shares_indices.into_par_iter().map(|evals|{
                 let coefficients = matrix_vector_multiply(&inverse_vandermonde, &evals);
@@ -180,8 +204,7 @@ impl Context{
             }
 
             // Interpolate entire polynomial
-            let vandermonde_matrix = vandermonde_matrix(ht_indices);
-            let vandermonde_inverse = inverse_vandermonde(vandermonde_matrix);
+            let vandermonde_inverse = cached_inverse_vandermonde(&ht_indices);
             
             let secrets_blinded: Vec<LargeField> = vec_shares_indices.into_par_iter().map(|evals|{
                 let coefficients = matrix_vector_multiply(&vandermonde_inverse, &evals);
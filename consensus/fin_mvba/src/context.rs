@@ -1,6 +1,7 @@
 use std::{
     collections::{HashMap, HashSet, VecDeque},
     net::{SocketAddr, SocketAddrV4},
+    sync::Arc,
 };
 use bytes::Bytes;
 use config::Node;
@@ -8,22 +9,22 @@ use config::Node;
 use fnv::FnvHashMap;
 use network::{plaintcp::{CancelHandler, TcpReceiver, TcpReliableSender}, Acknowledgement, Message};
 use tokio::sync::{
-    mpsc::{unbounded_channel, UnboundedReceiver, Receiver, Sender, channel},
+    mpsc::{Receiver, Sender},
     oneshot,
 };
 // use tokio_util::time::DelayQueue;
 use types::{Replica, WrapperMsg};
 
-use consensus::{LargeFieldSer};
+use consensus::{LargeFieldSer, ingress_channel, ingress_per_sender_capacity, service_channel, IngressReceiver, SessionMsg, SessionContext, session_id, RBCService, RBCBackend, ReliableBroadcast, rbc_backend};
 
 use crypto::{aes_hash::HashState};
 
-use crate::{msg::{ProtMsg, Handler}, protocol::{MVBAExecState}};
+use crate::{msg::{ProtMsg, Handler, RBCId}, protocol::{MVBAExecState}, ValidityPredicate, PartyIndex};
 
 pub struct Context {
     /// Networking context
-    pub net_send: TcpReliableSender<Replica, WrapperMsg<ProtMsg>, Acknowledgement>,
-    pub net_recv: UnboundedReceiver<WrapperMsg<ProtMsg>>,
+    pub net_send: TcpReliableSender<Replica, WrapperMsg<SessionMsg<ProtMsg>>, Acknowledgement>,
+    pub net_recv: IngressReceiver<WrapperMsg<SessionMsg<ProtMsg>>>,
     
     /// Data context
     pub num_nodes: usize,
@@ -33,6 +34,8 @@ pub struct Context {
 
     /// Secret Key map
     pub sec_key_map: HashMap<Replica, Vec<u8>>,
+    /// Session binding and replay protection
+    pub session: SessionContext,
 
     /// Hardware acceleration context
     pub hash_context: HashState,
@@ -53,19 +56,64 @@ pub struct Context {
     pub ra_aa_req: Sender<(usize, usize, usize)>,
     pub ra_aa_out_recv: Receiver<(usize, usize, usize)>,
 
-    pub ctrbc_req: Sender<Vec<u8>>,
-    pub ctrbc_out_recv: Receiver<(usize, usize, Vec<u8>)>,
+    pub rbc: RBCService<RBCId>,
+
+    /// External validity predicate and the channel on which its verdicts arrive
+    /// Instance id, round, broadcaster, proposal, verdict
+    pub validity: Arc<dyn ValidityPredicate>,
+    pub validated_send: Sender<(usize, usize, Replica, Vec<u8>, bool)>,
+    pub validated_recv: Receiver<(usize, usize, Replica, Vec<u8>, bool)>,
 
     /// Input and output request channels
-    /// First: Instance id, Second: Number of secrets, Third: Reconstruction to all or none, Fourth: Request for reconstruction/sharing, Fifth: Reconstruction ID
-    pub inp_mvba_requests: Receiver<(usize, usize, Vec<LargeFieldSer>)>,
-    pub out_mvba_values: Sender<(usize, Vec<usize>)>
+    /// Input: Instance id, proposal, coin shares. Output: Instance id, proposals of the parties in the leader's L2 broadcast
+    pub inp_mvba_requests: Receiver<(usize, Vec<u8>, Vec<LargeFieldSer>)>,
+    pub out_mvba_values: Sender<(usize, Vec<Vec<u8>>)>
 }
 
 impl Context {
+    /// Agreement on a party index, kept for the DPSS and interchangeable with IBFT.
+    /// Wraps the byte-valued MVBA with a predicate accepting valid party indices.
     pub fn spawn(config: Node,
         input_reqs: Receiver<(usize, usize, Vec<LargeFieldSer>)>, 
         output_shares: Sender<(usize, Vec<usize>)>,
+        coin_requests: Sender<(usize, usize, usize)>,
+        coin_replies: Receiver<(usize, usize, Vec<LargeFieldSer>)>,
+        byz: bool) -> anyhow::Result<(oneshot::Sender<()>, Vec<anyhow::Result<oneshot::Sender<()>>>)> {
+        let (bytes_req_send, bytes_req_recv) = service_channel();
+        let (bytes_out_send, mut bytes_out_recv) = service_channel::<(usize, Vec<Vec<u8>>)>();
+        let validity = Arc::new(PartyIndex{ num_nodes: config.num_nodes });
+        let status = Self::spawn_with_validity(config, bytes_req_recv, bytes_out_send, validity, coin_requests, coin_replies, byz)?;
+
+        let mut input_reqs = input_reqs;
+        tokio::spawn(async move {
+            while let Some((instance_id, party, coins)) = input_reqs.recv().await {
+                let proposal = bincode::serialize(&party).unwrap();
+                if bytes_req_send.send((instance_id, proposal, coins)).await.is_err(){
+                    break;
+                }
+            }
+        });
+        tokio::spawn(async move {
+            while let Some((instance_id, proposals)) = bytes_out_recv.recv().await {
+                // Proposals passed the predicate, they decode to party indices
+                let parties: Vec<usize> = proposals.into_iter().filter_map(|proposal| bincode::deserialize(&proposal).ok()).collect();
+                if output_shares.send((instance_id, parties)).await.is_err(){
+                    break;
+                }
+            }
+        });
+        Ok(status)
+    }
+
+    /// Agreement on arbitrary byte proposals satisfying `validity`.
+    /// The BBA of a round that runs past the coins handed to it requests more on `coin_requests`, under the id `bba_instance_id(instance, round)`.
+    /// The parent must answer every party with shares of the same coins for a given BBA id and round.
+    pub fn spawn_with_validity(config: Node,
+        input_reqs: Receiver<(usize, Vec<u8>, Vec<LargeFieldSer>)>, 
+        output_values: Sender<(usize, Vec<Vec<u8>>)>,
+        validity: Arc<dyn ValidityPredicate>,
+        coin_requests: Sender<(usize, usize, usize)>,
+        coin_replies: Receiver<(usize, usize, Vec<LargeFieldSer>)>,
         byz: bool) -> anyhow::Result<(oneshot::Sender<()>, Vec<anyhow::Result<oneshot::Sender<()>>>)> {
         // Add a separate configuration for RBC service. 
 
@@ -97,13 +145,13 @@ impl Context {
         let my_address = to_socket_address("0.0.0.0", my_port.port());
 
         // Setup networking
-        let (tx_net_to_consensus, rx_net_to_consensus) = unbounded_channel();
-        TcpReceiver::<Acknowledgement, WrapperMsg<ProtMsg>, _>::spawn(
+        let (tx_net_to_consensus, rx_net_to_consensus) = ingress_channel(ingress_per_sender_capacity(), config.sk_map.clone());
+        TcpReceiver::<Acknowledgement, WrapperMsg<SessionMsg<ProtMsg>>, _>::spawn(
             my_address,
             Handler::new(tx_net_to_consensus),
         );
 
-        let consensus_net = TcpReliableSender::<Replica, WrapperMsg<ProtMsg>, Acknowledgement>::with_peers(
+        let consensus_net = TcpReliableSender::<Replica, WrapperMsg<SessionMsg<ProtMsg>>, Acknowledgement>::with_peers(
             consensus_addrs.clone(),
         );
         
@@ -115,14 +163,20 @@ impl Context {
         let key2 = [23u8; 16];
         let hashstate = HashState::new(key0, key1, key2);
 
-        let (ctrbc_req_send_channel, ctrbc_req_recv_channel) = channel(10000);
-        let (ctrbc_out_send_channel, ctrbc_out_recv_channel) = channel(10000);
+        let (rbc, _rbc_serv_status) = RBCService::spawn(
+            rbc_backend(RBCBackend::CTRBC),
+            rbc_config,
+            false
+        );
+
+        let (bin_aa_req, bin_aa_req_recv) = service_channel();
+        let (bin_aa_out_send, bin_aa_out_recv) = service_channel();
 
-        let (bin_aa_req, bin_aa_req_recv) = channel(10000);
-        let (bin_aa_out_send, bin_aa_out_recv) = channel(10000);
+        let (ra_aa_req, ra_aa_req_recv) = service_channel();
+        let (ra_aa_out_send, ra_aa_out_recv) = service_channel();
 
-        let (ra_aa_req, ra_aa_req_recv) = channel(10000);
-        let (ra_aa_out_send, ra_aa_out_recv) = channel(10000);
+        let (validated_send, validated_recv) = service_channel();
+        let session = SessionContext::new(session_id(), "fin_mvba", config.id, &consensus_addrs);
         tokio::spawn(async move {
             let mut c = Context {
                 net_send: consensus_net,
@@ -130,6 +184,7 @@ impl Context {
                 
                 num_nodes: config.num_nodes,
                 sec_key_map: HashMap::default(),
+                session: session,
                 hash_context: hashstate,
                 myid: config.id,
                 _byz: byz,
@@ -142,8 +197,7 @@ impl Context {
                 coin_shares: HashMap::default(),
                 instance_id_bin_aa_map: HashMap::default(),
 
-                ctrbc_req: ctrbc_req_send_channel,
-                ctrbc_out_recv: ctrbc_out_recv_channel,
+                rbc: rbc,
 
                 bin_aa_req: bin_aa_req,
                 bin_aa_out_recv: bin_aa_out_recv,
@@ -151,8 +205,12 @@ impl Context {
                 ra_aa_req: ra_aa_req,
                 ra_aa_out_recv: ra_aa_out_recv,
 
+                validity: validity,
+                validated_send: validated_send,
+                validated_recv: validated_recv,
+
                 inp_mvba_requests: input_reqs,
-                out_mvba_values: output_shares
+                out_mvba_values: output_values
             };
 
             // Populate secret keys from config
@@ -166,19 +224,14 @@ impl Context {
 
         let mut statuses = Vec::new();
 
-        let _rbc_serv_status = ctrbc::Context::spawn(
-            rbc_config,
-            ctrbc_req_recv_channel, 
-            ctrbc_out_send_channel, 
-            false
-        );
-
         statuses.push(_rbc_serv_status);
 
-        let _ba_serv_status = binary_ba::Context::spawn(
+        let _ba_serv_status = binary_ba::Context::spawn_with_coin_source(
             ba_config,
             bin_aa_req_recv,
             bin_aa_out_send,
+            coin_requests,
+            coin_replies,
             false
         );
 
@@ -201,7 +254,7 @@ impl Context {
     pub async fn broadcast(&mut self, protmsg: ProtMsg) {
         let sec_key_map = self.sec_key_map.clone();
         for (replica, sec_key) in sec_key_map.into_iter() {
-            let wrapper_msg = WrapperMsg::new(protmsg.clone(), self.myid, &sec_key.as_slice());
+            let wrapper_msg = WrapperMsg::new(self.session.seal(replica, protmsg.clone()), self.myid, &sec_key.as_slice());
             let cancel_handler: CancelHandler<Acknowledgement> = self.net_send.send(replica, wrapper_msg).await;
             self.add_cancel_handler(cancel_handler);
         }
@@ -212,7 +265,7 @@ impl Context {
         let mut total_bytes = 0;
         let sec_key_map = self.sec_key_map.clone();
         for (replica, sec_key) in sec_key_map.into_iter() {
-            let wrapper_msg = WrapperMsg::new(protmsg.clone(), self.myid, &sec_key.as_slice());
+            let wrapper_msg = WrapperMsg::new(self.session.seal(replica, protmsg.clone()), self.myid, &sec_key.as_slice());
             total_bytes += Bytes::from(wrapper_msg.to_bytes()).len();
             let cancel_handler: CancelHandler<Acknowledgement> = self.net_send.send(replica, wrapper_msg).await;
             self.add_cancel_handler(cancel_handler);
@@ -221,10 +274,10 @@ impl Context {
     }
 
     pub fn add_cancel_handler(&mut self, canc: CancelHandler<Acknowledgement>) {
-        self.cancel_handlers.entry(0).or_default().push(canc);
+        consensus::add_cancel_handler_gc(&mut self.cancel_handlers, canc);
     }
 
-    pub async fn send(&mut self, replica: Replica, wrapper_msg: WrapperMsg<ProtMsg>) {
+    pub async fn send(&mut self, replica: Replica, wrapper_msg: WrapperMsg<SessionMsg<ProtMsg>>) {
         #[cfg(feature = "bandwidth")]
         log::info!("Network sending bytes: {:?}", Bytes::from(wrapper_msg.to_bytes()).len());
         let cancel_handler: CancelHandler<Acknowledgement> =
@@ -261,33 +314,49 @@ impl Context {
                     self.coin_shares.insert(req_msg.0, VecDeque::from(req_msg.2.clone()));
                     self.start_fin_mvba(req_msg.0, 1, Some(req_msg.1)).await;
                 },
-                ctrbc_msg = self.ctrbc_out_recv.recv() => {
-                    if ctrbc_msg.is_none(){
+                rbc_msg = self.rbc.deliver() => {
+                    if rbc_msg.is_none(){
                         log::error!("Request channel closed");
                         return;
                     }
-                    let ctrbc_msg = ctrbc_msg.unwrap();
-
-                    let sender_party = ctrbc_msg.1;
-                    let main_msg = ctrbc_msg.2;
-
-                    let deser_msg: (usize, usize, usize, Vec<usize>) = bincode::deserialize(&main_msg).unwrap();
-                    if deser_msg.2 == 1{
-                        self.process_l1_rbc_termination(
-                            deser_msg.0, 
-                            deser_msg.1, 
-                            sender_party, 
-                            deser_msg.3[0]
-                        ).await;
+                    let rbc_msg = rbc_msg.unwrap();
+
+                    let sender_party = rbc_msg.broadcaster;
+                    let main_msg = rbc_msg.payload;
+
+                    match rbc_msg.id{
+                        RBCId::L1(instance_id, round) => {
+                            self.validate_l1_rbc(
+                                instance_id, 
+                                round, 
+                                sender_party, 
+                                main_msg
+                            );
+                        },
+                        RBCId::L2(instance_id, round) => {
+                            let parties: Vec<Replica> = match bincode::deserialize(&main_msg){
+                                Ok(parties) => parties,
+                                Err(e) => {
+                                    log::warn!("Unable to deserialize L2 RBC payload of party {} because of {:?}", sender_party, e);
+                                    continue;
+                                }
+                            };
+                            self.process_l2_rbc_termination(
+                                instance_id,
+                                round,
+                                sender_party,
+                                parties
+                            ).await;
+                        }
                     }
-                    else if deser_msg.2 == 2{
-                        self.process_l2_rbc_termination(
-                            deser_msg.0,
-                            deser_msg.1,
-                            sender_party,
-                            deser_msg.3
-                        ).await;
+                },
+                validated_msg = self.validated_recv.recv() => {
+                    if validated_msg.is_none(){
+                        log::error!("Validation channel closed");
+                        return;
                     }
+                    let (instance_id, round, sender_party, proposal, valid) = validated_msg.unwrap();
+                    self.process_validated_l1_rbc(instance_id, round, sender_party, proposal, valid).await;
                 },
                 bin_aa_msg = self.bin_aa_out_recv.recv() =>{
                     if bin_aa_msg.is_none(){
@@ -312,6 +381,16 @@ impl Context {
 
 
 
+/// Id of the BBA run in `round` of MVBA `instance_id`. RA instances of the round share it.
+pub fn bba_instance_id(instance_id: usize, round: usize) -> usize {
+    100*instance_id + round
+}
+
+/// MVBA instance and round of a BBA id
+pub fn split_bba_instance_id(bba_instance_id: usize) -> (usize, usize) {
+    (bba_instance_id/100, bba_instance_id % 100)
+}
+
 pub fn to_socket_address(ip_str: &str, port: u16) -> SocketAddr {
     let addr = SocketAddrV4::new(ip_str.parse().unwrap(), port);
     addr.into()
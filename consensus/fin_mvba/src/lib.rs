@@ -5,4 +5,7 @@ mod protocol;
 
 mod msg;
 
+mod validity;
+pub use validity::*;
+
 mod process;
\ No newline at end of file
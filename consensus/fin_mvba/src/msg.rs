@@ -3,7 +3,7 @@ use async_trait::async_trait;
 use consensus::LargeFieldSer;
 use network::Acknowledgement;
 use serde::{Serialize, Deserialize};
-use tokio::sync::mpsc::UnboundedSender;
+use consensus::{IngressSender, SessionMsg};
 use types::{Replica, WrapperMsg};
 
 #[derive(Debug,Serialize,Deserialize,Clone)]
@@ -13,25 +13,36 @@ pub enum ProtMsg{
     LeaderCoin(usize,usize,LargeFieldSer,Replica),
 }
 
+/// Instance ids of the reliable broadcasts of an MVBA round
+#[derive(Debug,Serialize,Deserialize,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum RBCId{
+    // Instance_id, round. Carries the proposal
+    L1(usize, usize),
+    // Instance_id, round. Carries the parties whose L1 broadcasts were delivered and validated
+    L2(usize, usize),
+}
+
 use futures_util::SinkExt;
 
 #[derive(Debug, Clone)]
 pub struct Handler {
-    consensus_tx: UnboundedSender<WrapperMsg<ProtMsg>>,
+    consensus_tx: IngressSender<WrapperMsg<SessionMsg<ProtMsg>>>,
 }
 
 impl Handler {
-    pub fn new(consensus_tx: UnboundedSender<WrapperMsg<ProtMsg>>) -> Self {
+    pub fn new(consensus_tx: IngressSender<WrapperMsg<SessionMsg<ProtMsg>>>) -> Self {
         Self { consensus_tx }
     }
 }
 
 #[async_trait]
-impl network::Handler<Acknowledgement, WrapperMsg<ProtMsg>> for Handler {
-    async fn dispatch(&self, msg: WrapperMsg<ProtMsg>, writer: &mut network::Writer<Acknowledgement>) {
+impl network::Handler<Acknowledgement, WrapperMsg<SessionMsg<ProtMsg>>> for Handler {
+    async fn dispatch(&self, msg: WrapperMsg<SessionMsg<ProtMsg>>, writer: &mut network::Writer<Acknowledgement>) {
         // Forward the message
+        // Waits if the queue of this sender is full, delaying the acknowledgement
         let status = self.consensus_tx
-            .send(msg);
+            .send(msg)
+            .await;
         if status.is_err(){
             log::error!("Failed to send consensus message to the channel because of {:?}", status.err().unwrap());
         }
@@ -1,21 +1,15 @@
 use std::{sync::Arc};
 
-use consensus::delay_message_processing;
-use crypto::hash::verf_mac;
+use consensus::{delay_message_processing, SessionMsg};
 use types::WrapperMsg;
 
 use crate::{context::Context, msg::ProtMsg};
 
 impl Context{
-    pub fn check_proposal(&self,wrapper_msg: Arc<WrapperMsg<ProtMsg>>) -> bool {
-        // validate MAC
-        let byte_val = bincode::serialize(&wrapper_msg.protmsg).expect("Failed to serialize object");
-        let sec_key = match self.sec_key_map.get(&wrapper_msg.clone().sender) {
-            Some(val) => {val},
-            None => {panic!("Secret key not available, this shouldn't happen")},
-        };
-        if !verf_mac(&byte_val,&sec_key.as_slice(),&wrapper_msg.mac){
-            log::warn!("MAC Verification failed.");
+    // The MAC was verified against the key of the sender when the message entered the ingress queue
+    pub fn check_proposal(&mut self, wrapper_msg: Arc<WrapperMsg<SessionMsg<ProtMsg>>>) -> bool {
+        // Reject messages of other sessions and replayed messages
+        if !self.session.accept(wrapper_msg.sender, &wrapper_msg.protmsg){
             return false;
         }
         true
@@ -23,12 +17,12 @@ impl Context{
     /**
      * Message deserialization happens here. Message is deserialized and passed to the appropriate handling function. 
      */
-    pub(crate) async fn process_msg(&mut self, wrapper_msg: WrapperMsg<ProtMsg>){
+    pub(crate) async fn process_msg(&mut self, wrapper_msg: WrapperMsg<SessionMsg<ProtMsg>>){
         log::debug!("Received protocol msg: {:?}",wrapper_msg);
         delay_message_processing().await;
         let msg = Arc::new(wrapper_msg.clone());
         if self.check_proposal(msg){
-            match wrapper_msg.clone().protmsg {
+            match wrapper_msg.clone().protmsg.msg {
                 
                 ProtMsg::LeaderCoin(instance_id,round, coin_share, share_sender) =>{
                     self.process_incoming_leader_coin(instance_id, round, coin_share, share_sender).await;
@@ -1,6 +1,6 @@
 use std::collections::HashSet;
 
-use consensus::{LargeFieldSer, LargeField};
+use consensus::{LargeFieldSer, LargeField, ReliableBroadcast};
 use lambdaworks_math::{traits::ByteConversion, polynomial::Polynomial};
 
 use rand::{Rng, rngs::StdRng};
@@ -8,13 +8,13 @@ use rand::{Rng, rngs::StdRng};
 use rand::SeedableRng;
 use types::Replica;
 
-use crate::{Context, protocol::{MVBAExecState, MVBARoundState}, msg::ProtMsg};
+use crate::{Context, bba_instance_id, split_bba_instance_id, protocol::{MVBAExecState, MVBARoundState}, msg::{ProtMsg, RBCId}};
 
 impl Context{
     pub async fn start_fin_mvba(&mut self, 
         instance_id: usize,
         round: usize,
-        rbc_value: Option<usize>,
+        rbc_value: Option<Vec<u8>>,
     ){
         log::debug!("Starting FIN MVBA for instance {} in round {} with value {:?}", instance_id, round, rbc_value);
         if !self.round_state.contains_key(&instance_id){
@@ -24,7 +24,7 @@ impl Context{
 
         let mvba_exec_state = self.round_state.get_mut(&instance_id).unwrap();
         if mvba_exec_state.inp_value.is_none() && rbc_value.is_some(){
-            mvba_exec_state.inp_value = Some(rbc_value.unwrap());
+            mvba_exec_state.inp_value = rbc_value;
         }
 
         if !mvba_exec_state.mvbas.contains_key(&round){
@@ -42,19 +42,49 @@ impl Context{
             return;
         }
 
-        let rbc_value = mvba_exec_state.inp_value.unwrap();
+        let rbc_value = mvba_exec_state.inp_value.clone().unwrap();
         
-        let ctrbc_msg = (instance_id, round as usize, 1 as usize, vec![rbc_value]);
-        let ser_msg = bincode::serialize(&ctrbc_msg).unwrap();
-        
-        let _status = self.ctrbc_req.send(ser_msg).await;
+        let status = self.rbc.broadcast(RBCId::L1(instance_id, round), rbc_value).await;
+        if status.is_err(){
+            log::error!("Failed to start L1 RBC for instance {} in round {}: {:?}", instance_id, round, status.err().unwrap());
+        }
+    }
+
+    /// Checks the external validity of a delivered L1 proposal without blocking the event loop.
+    /// The result comes back through the validation channel.
+    pub fn validate_l1_rbc(&mut self,
+        instance_id: usize,
+        round: usize,
+        rbc_broadcaster: usize,
+        broadcast_val: Vec<u8>
+    ){
+        let validity = self.validity.clone();
+        let validated_send = self.validated_send.clone();
+        tokio::spawn(async move {
+            let valid = validity.is_valid(instance_id, broadcast_val.as_slice()).await;
+            let _status = validated_send.send((instance_id, round, rbc_broadcaster, broadcast_val, valid)).await;
+        });
+    }
+
+    pub async fn process_validated_l1_rbc(&mut self,
+        instance_id: usize,
+        round: usize,
+        rbc_broadcaster: usize,
+        broadcast_val: Vec<u8>,
+        valid: bool
+    ){
+        if !valid{
+            log::warn!("L1 proposal of party {} in instance {} and round {} failed external validity, ignoring it", rbc_broadcaster, instance_id, round);
+            return;
+        }
+        self.process_l1_rbc_termination(instance_id, round, rbc_broadcaster, broadcast_val).await;
     }
 
     pub async fn process_l1_rbc_termination(&mut self, 
         instance_id: usize, 
         round: usize, 
         rbc_broadcaster: usize,
-        broadcast_val: usize
+        broadcast_val: Vec<u8>
     ){
         log::debug!("Received l1 RBC termination for instance {} and round {} from broadcaster {}",instance_id, round, rbc_broadcaster);
         if !self.round_state.contains_key(&instance_id){
@@ -91,12 +121,14 @@ impl Context{
             log::debug!("Initializing L2 RBC for instance {} and round {}, vec: {:?}", instance_id, round, l2_rbc_vec);
             
 
-            let ctrbc_msg = (instance_id, round, 2 as usize, l2_rbc_vec);
-            let ser_msg = bincode::serialize(&ctrbc_msg).unwrap();
-            let _status = self.ctrbc_req.send(ser_msg).await;
+            let ser_msg = bincode::serialize(&l2_rbc_vec).unwrap();
+            let status = self.rbc.broadcast(RBCId::L2(instance_id, round), ser_msg).await;
+            if status.is_err(){
+                log::error!("Failed to start L2 RBC for instance {} in round {}: {:?}", instance_id, round, status.err().unwrap());
+            }
         }
         // Also check change in l2/final agreement status because of l1 delivery
-        self.verify_l2_rbc_status_check(instance_id, round, Some((rbc_broadcaster,broadcast_val)), None).await;
+        self.verify_l2_rbc_status_check(instance_id, round, Some(rbc_broadcaster), None).await;
     }
 
     pub async fn process_l2_rbc_termination(&mut self,
@@ -147,7 +179,7 @@ impl Context{
     pub async fn verify_l2_rbc_status_check(&mut self, 
         instance_id: usize,
         round: usize,
-        new_l1_rbc: Option<(usize, usize)>,
+        new_l1_rbc: Option<usize>,
         
         new_l2_rbc: Option<usize>
     ){
@@ -162,7 +194,7 @@ impl Context{
         let mvba_round_state = mvba_exec_state.mvbas.get_mut(&round).unwrap();
 
         if new_l1_rbc.is_some(){
-            let l1_rbc_sender = new_l1_rbc.unwrap().0;
+            let l1_rbc_sender = new_l1_rbc.unwrap();
             // Iterate through l2 RBCs and check if the l1 list is empty
             for rep in 0..self.num_nodes{
                 if mvba_round_state.l2_rbcs.contains_key(&rep){
@@ -370,7 +402,7 @@ impl Context{
                 coin_shares_ba.push(coin_shares.pop_front().unwrap());
             }
 
-            let bin_aa_instance = 100*instance_id + round;
+            let bin_aa_instance = bba_instance_id(instance_id, round);
             if mvba_round_state.l2_approved_rbcs.contains(&leader_id){
                 log::debug!("Leader approved for Binary BA in instance {}", instance_id);
                 // Input this to BA
@@ -392,8 +424,7 @@ impl Context{
         output_val: usize,
     ){
         log::debug!("Received BBA termination for instance {} with output value {}", bin_aa_instance_id, output_val);
-        let instance_id = bin_aa_instance_id/100;
-        let round = bin_aa_instance_id % 100;
+        let (instance_id, round) = split_bba_instance_id(bin_aa_instance_id);
 
         if !self.round_state.contains_key(&instance_id){
             let mvba_round_state = MVBAExecState::new(instance_id);
@@ -425,8 +456,7 @@ impl Context{
         output_val: usize,
     ){
         log::debug!("Received RA termination for instance {} with output value {}", ra_instance_id, output_val);
-        let instance_id = ra_instance_id/100;
-        let round = ra_instance_id % 100;
+        let (instance_id, round) = split_bba_instance_id(ra_instance_id);
 
         if !self.round_state.contains_key(&instance_id){
             let mvba_round_state = MVBAExecState::new(instance_id);
@@ -0,0 +1,80 @@
+use async_trait::async_trait;
+
+/// External validity predicate of the MVBA.
+/// A proposal is only counted towards a round once the predicate accepts it, so the output always satisfies the predicate.
+/// The predicate must be deterministic across honest parties, though it may wait for local state such as the delivery of a broadcast.
+#[async_trait]
+pub trait ValidityPredicate: Send + Sync + 'static {
+    async fn is_valid(&self, instance_id: usize, proposal: &[u8]) -> bool;
+}
+
+/// Accepts every proposal
+pub struct AcceptAll;
+
+#[async_trait]
+impl ValidityPredicate for AcceptAll {
+    async fn is_valid(&self, _instance_id: usize, _proposal: &[u8]) -> bool {
+        true
+    }
+}
+
+/// Accepts proposals encoding a party index, used by the `usize` interface of the MVBA.
+pub struct PartyIndex {
+    pub num_nodes: usize,
+}
+
+#[async_trait]
+impl ValidityPredicate for PartyIndex {
+    async fn is_valid(&self, _instance_id: usize, proposal: &[u8]) -> bool {
+        match bincode::deserialize::<usize>(proposal) {
+            Ok(party) => party < self.num_nodes,
+            Err(_) => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    /// Accepts proposals that open with the instance id, as a predicate checking a proposal against local state would
+    struct TaggedWithInstance;
+
+    #[async_trait]
+    impl ValidityPredicate for TaggedWithInstance {
+        async fn is_valid(&self, instance_id: usize, proposal: &[u8]) -> bool {
+            proposal.first() == Some(&(instance_id as u8))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_party_index_accepts_indices_of_the_committee() {
+        let validity = PartyIndex { num_nodes: 4 };
+        for party in 0..4usize {
+            assert!(validity.is_valid(0, bincode::serialize(&party).unwrap().as_slice()).await);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_party_index_rejects_out_of_range_and_malformed_proposals() {
+        let validity = PartyIndex { num_nodes: 4 };
+        assert!(!validity.is_valid(0, bincode::serialize(&4usize).unwrap().as_slice()).await);
+        assert!(!validity.is_valid(0, bincode::serialize(&usize::MAX).unwrap().as_slice()).await);
+        // Too short to encode an index
+        assert!(!validity.is_valid(0, &[1u8, 0, 0]).await);
+        assert!(!validity.is_valid(0, &[]).await);
+    }
+
+    #[tokio::test]
+    async fn test_custom_predicate_behind_trait_object() {
+        let validity: Arc<dyn ValidityPredicate> = Arc::new(TaggedWithInstance);
+        assert!(validity.is_valid(3, &[3u8, 9, 9]).await);
+        assert!(!validity.is_valid(3, &[2u8, 9, 9]).await);
+        assert!(!validity.is_valid(3, &[]).await);
+
+        let accept_all: Arc<dyn ValidityPredicate> = Arc::new(AcceptAll);
+        assert!(accept_all.is_valid(3, &[]).await);
+    }
+}
@@ -11,10 +11,10 @@ use config::Node;
 use fnv::FnvHashMap;
 use lambdaworks_math::{ fft::cpu::roots_of_unity::get_powers_of_primitive_root, field::traits::RootsConfig};
 use network::{plaintcp::{CancelHandler, TcpReceiver, TcpReliableSender}, Acknowledgement, Message};
-use consensus::{rand_field_element, FoldingDZKContext, LargeField, LargeFieldSSS};
+use consensus::{rand_field_element, FoldingDZKContext, LargeField, LargeFieldSSS, ingress_channel, ingress_per_sender_capacity, service_channel, IngressReceiver, SessionMsg, SessionContext, session_id, RBCService, RBCBackend, ReliableBroadcast, rbc_backend, AvidThrottle};
 
 use tokio::{sync::{
-    mpsc::{Receiver, Sender, channel, unbounded_channel, UnboundedReceiver},
+    mpsc::{Receiver, Sender},
     oneshot,
 }};
 // use tokio_util::time::DelayQueue;
@@ -22,14 +22,16 @@ use types::{Replica, WrapperMsg};
 
 use ha_crypto::{aes_hash::HashState, hash::Hash};
 
+use asks::{ASKSOutput, ASKSService};
+
 use crate::{protocol::{ACSSABState, SymmetricKeyState}, msg::ProtMsg};
 
 use crate::Handler;
 
 pub struct Context {
     /// Networking context
-    pub net_send: TcpReliableSender<Replica, WrapperMsg<ProtMsg>, Acknowledgement>,
-    pub net_recv: UnboundedReceiver<WrapperMsg<ProtMsg>>,
+    pub net_send: TcpReliableSender<Replica, WrapperMsg<SessionMsg<ProtMsg>>, Acknowledgement>,
+    pub net_recv: IngressReceiver<WrapperMsg<SessionMsg<ProtMsg>>>,
     
     /// Data context
     pub num_nodes: usize,
@@ -38,6 +40,8 @@ pub struct Context {
 
     /// Secret Key map
     pub sec_key_map: HashMap<Replica, Vec<u8>>,
+    /// Session binding and replay protection
+    pub session: SessionContext,
 
     /// Hardware acceleration context
     pub hash_context: HashState,
@@ -68,12 +72,10 @@ pub struct Context {
     pub out_pub_rec_out: Sender<(usize, Replica, Vec<LargeField>)>,
 
     /// ASKS input and output channels
-    pub asks_inp_channel: Sender<(usize, usize, bool, bool, Option<Vec<LargeField>>, Option<usize>)>,
-    pub asks_recv_out: Receiver<(usize, Replica, Option<Vec<LargeField>>)>,
+    pub asks: ASKSService,
 
     /// CTRBC input and output channels
-    pub inp_ctrbc: Sender<Vec<u8>>,
-    pub recv_out_ctrbc: Receiver<(usize,usize, Vec<u8>)>,
+    pub rbc: RBCService<usize>,
 
     /// AVID input and output channels
     pub inp_avid_channel: Sender<Vec<(Replica,Option<Vec<u8>>)>>,
@@ -90,7 +92,8 @@ pub struct Context {
     pub roots_of_unity: Vec<LargeField>,
 
     pub avss_inst_id: usize,
-    pub avid_throttling_quant: usize,
+    /// AVID instances of this dealer, keyed by ACSS instance and batch
+    pub avid_throttle: AvidThrottle<(usize, usize), Vec<(Replica,Option<Vec<u8>>)>>,
 }
 
 impl Context {
@@ -138,8 +141,8 @@ impl Context {
         // syncer_map.insert(0, config.client_addr);
 
         // Setup networking
-        let (tx_net_to_consensus, rx_net_to_consensus) = unbounded_channel();
-        TcpReceiver::<Acknowledgement, WrapperMsg<ProtMsg>, _>::spawn(
+        let (tx_net_to_consensus, rx_net_to_consensus) = ingress_channel(ingress_per_sender_capacity(), config.sk_map.clone());
+        TcpReceiver::<Acknowledgement, WrapperMsg<SessionMsg<ProtMsg>>, _>::spawn(
             my_address,
             Handler::new(tx_net_to_consensus),
         );
@@ -154,7 +157,7 @@ impl Context {
         //     SyncHandler::new(tx_net_to_client),
         // );
 
-        let consensus_net = TcpReliableSender::<Replica, WrapperMsg<ProtMsg>, Acknowledgement>::with_peers(
+        let consensus_net = TcpReliableSender::<Replica, WrapperMsg<SessionMsg<ProtMsg>>, Acknowledgement>::with_peers(
             consensus_addrs.clone(),
         );
         // let sync_net =
@@ -200,21 +203,25 @@ impl Context {
             hash_context: hashstate2,
             poly_split_evaluation_map: ss_contexts,
             evaluation_points: (1..config.num_nodes+1).into_iter().collect(),
+            evaluation_domain: None,
             recon_threshold: config.num_faults+1,
             end_degree_threshold: end_degree,
         };
         
-        let (asks_req_send_channel, asks_req_recv_channel) = channel(10000);
-        let (asks_out_send_channel, asks_out_recv_channel) = channel(10000);
+        let (asks, asks_status) = ASKSService::spawn(asks_config, false);
 
-        let (ctrbc_req_send_channel, ctrbc_req_recv_channel) = channel(10000);
-        let (ctrbc_out_send_channel, ctrbc_out_recv_channel) = channel(10000);
+        let (rbc, ctrbc_status) = RBCService::spawn(
+            rbc_backend(RBCBackend::CCBRB),
+            ctrbc_config,
+            false
+        );
 
-        let (avid_req_send_channel, avid_req_recv_channel) = channel(10000);
-        let (avid_out_send_channel, avid_out_recv_channel) = channel(10000);
+        let (avid_req_send_channel, avid_req_recv_channel) = service_channel();
+        let (avid_out_send_channel, avid_out_recv_channel) = service_channel();
         
-        let (ra_req_send_channel, ra_req_recv_channel) = channel(10000);
-        let (ra_out_send_channel, ra_out_recv_channel) = channel(10000);
+        let (ra_req_send_channel, ra_req_recv_channel) = service_channel();
+        let (ra_out_send_channel, ra_out_recv_channel) = service_channel();
+        let session = SessionContext::new(session_id(), "g_acss", config.id, &consensus_addrs);
         tokio::spawn(async move {
             let mut c = Context {
                 net_send: consensus_net,
@@ -222,6 +229,7 @@ impl Context {
 
                 num_nodes: config.num_nodes,
                 sec_key_map: HashMap::default(),
+                session: session,
                 hash_context: hashstate,
                 myid: config.id,
                 
@@ -249,11 +257,9 @@ impl Context {
 
                 roots_of_unity: Self::gen_roots_of_unity(config.num_nodes),
 
-                asks_inp_channel: asks_req_send_channel,
-                asks_recv_out: asks_out_recv_channel,
+                asks: asks,
 
-                inp_ctrbc: ctrbc_req_send_channel,
-                recv_out_ctrbc: ctrbc_out_recv_channel,
+                rbc: rbc,
 
                 inp_avid_channel: avid_req_send_channel,
                 recv_out_avid: avid_out_recv_channel,
@@ -265,7 +271,7 @@ impl Context {
                 lin_or_quad: lin_or_quad,
 
                 avss_inst_id: 200,
-                avid_throttling_quant: config.num_nodes,
+                avid_throttle: AvidThrottle::new(config.num_nodes),
                 // Syncer related stuff
                 // sync_send: sync_net,
                 // sync_recv: rx_net_from_client,
@@ -283,22 +289,9 @@ impl Context {
         });
         let mut vector_statuses = Vec::new();
         
-        let _status =  asks::Context::spawn(
-            asks_config, 
-            asks_req_recv_channel, 
-            asks_out_send_channel, 
-            false
-        );
-        vector_statuses.push(_status);
+        vector_statuses.push(asks_status);
         
-        let _status =  ccbrb::Context::spawn(
-            ctrbc_config, 
-            ctrbc_req_recv_channel, 
-            ctrbc_out_send_channel, 
-            false
-        );
-
-        vector_statuses.push(_status);
+        vector_statuses.push(ctrbc_status);
         let _status =  avid::Context::spawn(
             avid_config, 
             avid_req_recv_channel, 
@@ -319,7 +312,7 @@ impl Context {
     pub async fn broadcast(&mut self, protmsg: ProtMsg) {
         let sec_key_map = self.sec_key_map.clone();
         for (replica, sec_key) in sec_key_map.into_iter() {
-            let wrapper_msg = WrapperMsg::new(protmsg.clone(), self.myid, &sec_key.as_slice());
+            let wrapper_msg = WrapperMsg::new(self.session.seal(replica, protmsg.clone()), self.myid, &sec_key.as_slice());
             let cancel_handler: CancelHandler<Acknowledgement> = self.net_send.send(replica, wrapper_msg).await;
             self.add_cancel_handler(cancel_handler);
         }
@@ -329,7 +322,7 @@ impl Context {
         let mut total_bytes = 0;
         let sec_key_map = self.sec_key_map.clone();
         for (replica, sec_key) in sec_key_map.into_iter() {
-            let wrapper_msg = WrapperMsg::new(protmsg.clone(), self.myid, &sec_key.as_slice());
+            let wrapper_msg = WrapperMsg::new(self.session.seal(replica, protmsg.clone()), self.myid, &sec_key.as_slice());
             total_bytes += Bytes::from(wrapper_msg.to_bytes()).len();
             let cancel_handler: CancelHandler<Acknowledgement> = self.net_send.send(replica, wrapper_msg).await;
             self.add_cancel_handler(cancel_handler);
@@ -338,7 +331,7 @@ impl Context {
     }
 
     pub fn add_cancel_handler(&mut self, canc: CancelHandler<Acknowledgement>) {
-        self.cancel_handlers.entry(0).or_default().push(canc);
+        consensus::add_cancel_handler_gc(&mut self.cancel_handlers, canc);
     }
 
     pub async fn run(&mut self) -> Result<()> {
@@ -373,29 +366,31 @@ impl Context {
                     self.acss_id = id;
                     self.init_acss_ab(secrets_field, id).await;
                 },
-                asks_msg = self.asks_recv_out.recv() => {
+                asks_msg = self.asks.next_output() => {
                     let asks_msg = asks_msg.ok_or_else(||
                         anyhow!("Networking layer has closed")
                     )?;
-                    if asks_msg.2.is_none() {
-                        log::debug!("Got ASKS termination event from party {:?}", asks_msg.clone());
-                        self.init_symmetric_key_reconstruction(asks_msg.1).await;
-                    }
-                    else{
-                        log::debug!("Got ASKS termination reconstruction event from party {:?}", asks_msg.clone());
-                        self.process_symmetric_key_reconstruction(asks_msg.1, asks_msg.2.unwrap()).await;
+                    match asks_msg {
+                        ASKSOutput::Shared { dealer, .. } => {
+                            log::debug!("Got ASKS termination event from party {}", dealer);
+                            self.init_symmetric_key_reconstruction(dealer).await;
+                        },
+                        ASKSOutput::Reconstructed { dealer, results, .. } => {
+                            log::debug!("Got ASKS termination reconstruction event from party {} {:?}", dealer, results);
+                            let secrets = results.iter().map(|result| result.secret_or_default()).collect();
+                            self.process_symmetric_key_reconstruction(dealer, secrets).await;
+                        }
                     }
                 },
-                ctrbc_msg = self.recv_out_ctrbc.recv() =>{
-                    let ctrbc_msg = ctrbc_msg.ok_or_else(||
+                rbc_msg = self.rbc.deliver() =>{
+                    let rbc_msg = rbc_msg.ok_or_else(||
                         anyhow!("Networking layer has closed")
                     )?;
-                    log::debug!("Received termination event from CTRBC channel from party {} at time: {:?}", ctrbc_msg.1, SystemTime::now()
+                    log::debug!("Received termination event from RBC channel from party {} at time: {:?}", rbc_msg.broadcaster, SystemTime::now()
                                 .duration_since(UNIX_EPOCH)
                                 .unwrap()
                                 .as_millis());
-                    // TODO: Change the -1 after fixing bug in CTRBC in ACSS.rs
-                    self.handle_ctrbc_termination(ctrbc_msg.0-1,ctrbc_msg.1,ctrbc_msg.2).await;
+                    self.handle_ctrbc_termination(rbc_msg.id, rbc_msg.broadcaster, rbc_msg.payload).await;
                 },
                 avid_msg = self.recv_out_avid.recv() =>{
                     let avid_msg = avid_msg.ok_or_else(||
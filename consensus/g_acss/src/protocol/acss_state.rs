@@ -1,4 +1,4 @@
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{HashMap, HashSet};
 
 use ha_crypto::hash::Hash;
 use lambdaworks_math::polynomial::Polynomial;
@@ -18,9 +18,6 @@ pub struct ACSSABState{
     // Commitments to shares, commitments to blinding polynomial, and DZK polynomial
     pub commitments: HashMap<Replica, CommDZKMsg>,
 
-    // AVID Throttling for bandwidth management
-    pub avid_instances: VecDeque<Vec<(Replica,Option<Vec<u8>>)>>,
-    
     // Reliable Agreement
     pub ra_outputs: HashSet<Replica>,
     
@@ -47,7 +44,6 @@ impl ACSSABState{
             batch_wise_shares: HashMap::default(),
             shares: HashMap::default(),
 
-            avid_instances: VecDeque::new(),
             commitments: HashMap::default(),
             ra_outputs: HashSet::default(),
             verification_status: HashMap::default(),
@@ -9,6 +9,11 @@ impl Context{
             // Decryption necessary here
             let (instance_id, batch,enc_shares) : (usize, usize, Vec<u8>) = bincode::deserialize(content.unwrap().as_slice()).unwrap();
             
+            if sender == self.myid && self.avid_throttle.complete(&(instance_id, batch)){
+                // Own dispersal terminated, schedule the next AVID instances
+                self.throttle_avid_instances().await;
+            }
+
             if !self.acss_ab_state.contains_key(&instance_id) {
                 let acss_state = ACSSABState::new();
                 self.acss_ab_state.insert(instance_id, acss_state);
@@ -1,4 +1,4 @@
-use consensus::{inverse_vandermonde, matrix_vector_multiply, vandermonde_matrix, LargeField};
+use consensus::{matrix_vector_multiply, LargeField, cached_inverse_vandermonde};
 use lambdaworks_math::{polynomial::Polynomial, unsigned_integer::element::UnsignedInteger};
 use rayon::iter::{IntoParallelIterator, IntoParallelRefIterator, IntoParallelRefMutIterator, ParallelIterator};
 
@@ -19,9 +19,7 @@ impl Context{
             evaluation_points.push(LargeField::new(UnsignedInteger::from(i as u64)));
         }
         
-        // Generate vandermonde matrix
-        let vandermonde = vandermonde_matrix(evaluation_points.clone());
-        let inverse_vandermonde = inverse_vandermonde(vandermonde);
+        let inverse_vandermonde = cached_inverse_vandermonde(&evaluation_points);
 
         grouped_polynomials.par_iter_mut().for_each(|bv_group|{
             // Each group is of size degree+1
@@ -1,5 +1,6 @@
-use consensus::LargeField;
+use consensus::{LargeField, Transcript};
 use ha_crypto::{aes_hash::HashState, hash::Hash};
+use types::Replica;
 
 use crate::Context;
 
@@ -42,4 +43,14 @@ impl Context{
         }
         return hc.do_hash_aes(agg_vector.as_slice());
     }
+
+    /// Challenge the shares and blinding shares are aggregated with in the DZK check, bound to the instance, the dealer and both commitments.
+    pub fn dzk_challenge(instance_id: usize, dealer: Replica, root_comm: &Hash, blinding_root_comm: &Hash)-> LargeField{
+        let mut transcript = Transcript::new(b"g-acss dzk");
+        transcript.append_u64(b"instance", instance_id as u64);
+        transcript.append_u64(b"dealer", dealer as u64);
+        transcript.append_hash(b"commitment", root_comm);
+        transcript.append_hash(b"blinding commitment", blinding_root_comm);
+        transcript.challenge_field_element(b"dzk aggregation")
+    }
 }
\ No newline at end of file
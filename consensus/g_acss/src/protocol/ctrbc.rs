@@ -1,12 +1,22 @@
 use crate::{protocol::ACSSABState, CommDZKMsg, Context};
 
 impl Context{
-    pub async fn handle_ctrbc_termination(&mut self, _inst_id: usize, sender_rep: usize, content: Vec<u8>){
+    pub async fn handle_ctrbc_termination(&mut self, inst_id: usize, sender_rep: usize, content: Vec<u8>){
         log::debug!("Received CTRBC termination message from sender {}",sender_rep);
 
         // Deserialize message
-        let commitment_msg: CommDZKMsg = bincode::deserialize(content.as_slice()).unwrap();
+        let commitment_msg: CommDZKMsg = match bincode::deserialize(content.as_slice()){
+            Ok(commitment_msg) => commitment_msg,
+            Err(e) => {
+                log::error!("Failed to deserialize CTRBC message from party {}: {}", sender_rep, e);
+                return;
+            }
+        };
         let instance_id = commitment_msg.instance_id;
+        if instance_id != inst_id{
+            log::error!("Party {} broadcast a commitment for instance {} under instance {}", sender_rep, instance_id, inst_id);
+            return;
+        }
         if !self.acss_ab_state.contains_key(&instance_id) {
             let acss_state = ACSSABState::new();
             self.acss_ab_state.insert(instance_id, acss_state);
@@ -3,7 +3,7 @@ use std::time::{SystemTime, UNIX_EPOCH};
 use crate::{msg::AcssSKEShares, CommDZKMsg, Context};
 use ha_crypto::encrypt;
 use lambdaworks_math::{unsigned_integer::element::UnsignedInteger, traits::ByteConversion};
-use consensus::{LargeField, LargeFieldSer, expand_sharing_to_n_evaluation_points, expand_sharing_to_n_evaluation_points_opt, sample_polynomials_from_prf, rand_field_element};
+use consensus::{LargeField, LargeFieldSer, expand_sharing_to_n_evaluation_points, expand_sharing_to_n_evaluation_points_opt, sample_polynomials_from_prf, rand_field_element, ReliableBroadcast};
 use types::Replica;
 
 use super::ACSSABState;
@@ -21,14 +21,7 @@ impl Context{
             }
             log::debug!("Symmetric keys generated: {:?}", symm_keys);
             // Now share these keys through ASKS
-            let _status = self.asks_inp_channel.send((
-                1,
-                self.num_nodes, 
-                false, 
-                false, 
-                Some(symm_keys), 
-                None
-            )).await;
+            let _status = self.asks.share(1, self.num_nodes, Some(symm_keys), false).await;
             if _status.is_err(){
                 log::error!("Failed to send ASKS init request");
                 return;
@@ -43,14 +36,7 @@ impl Context{
             self.symmetric_keys_avid.term_asks_sharing.insert(party);
             // Initiate reconstruction
 
-            let _status = self.asks_inp_channel.send((
-                1,
-                self.num_nodes, 
-                false, 
-                true, 
-                None, 
-                Some(party)
-            )).await;
+            let _status = self.asks.reconstruct(1, party, None, None).await;
             if _status.is_err(){
                 log::error!("Failed to send ASKS termination request for symmetric keys");
                 return;
@@ -95,8 +81,6 @@ impl Context{
                                 .as_millis();
         log::debug!("Starting sharing preparation");
         // Bivariate polynomials
-        let acss_state = self.acss_ab_state.get_mut(&instance_id).unwrap();
-
         let tot_sharings = secrets.len();
         let evaluations;
         let nonce_evaluations;
@@ -256,8 +240,7 @@ impl Context{
         let root_comm = Self::root_commitment(&commitments, &self.hash_context);
         let blinding_root_comm = Self::root_commitment(&blinding_commitments, &self.hash_context);
 
-        let fiat_shamir_root_comm = self.hash_context.hash_two(root_comm.clone(), blinding_root_comm.clone());
-        let fiat_shamir_root_fe = LargeField::from_bytes_be(fiat_shamir_root_comm.as_slice()).unwrap();
+        let fiat_shamir_root_fe = Self::dzk_challenge(instance_id, self.myid, &root_comm, &blinding_root_comm);
 
         let dzk_poly = Self::gen_dzk_proof_polynomial(
             &bv_evaluations, 
@@ -297,7 +280,10 @@ impl Context{
             .as_millis()-consensus_start_time
         );
         // Send shares in n batches through n independent AVID instances
-        let _rbc_status = self.inp_ctrbc.send(ser_comm_msg).await;
+        let rbc_status = self.rbc.broadcast(instance_id, ser_comm_msg).await;
+        if rbc_status.is_err(){
+            log::error!("Failed to broadcast commitment of instance {}: {:?}", instance_id, rbc_status.err().unwrap());
+        }
         for batch in 0..self.num_nodes{
             let mut shares: Vec<(Replica,Option<Vec<u8>>)> = Vec::new();
             for rep in 0..self.num_nodes{
@@ -335,27 +321,20 @@ impl Context{
                 let ser_enc_msg = bincode::serialize(&(instance_id,batch,enc_shares)).unwrap();
                 shares.push((rep, Some(ser_enc_msg)));
             }
-            acss_state.avid_instances.push_back(shares.clone());
+            let avid_bytes = shares.iter().map(|(_, share)| share.as_ref().map_or(0, |share| share.len())).sum();
+            self.avid_throttle.push((instance_id, batch), shares, avid_bytes);
             //let _inp_avid_status = self.inp_avid_channel.send(shares).await;
         }
-        self.throttle_avid_instances(instance_id).await;
+        self.throttle_avid_instances().await;
     }
 
-    pub async fn throttle_avid_instances(&mut self, instance_id: usize)-> bool{
-        // Run three at once
-        log::debug!("Throttling AVID instances for ACSS instance id {}, starting {} instances at once", instance_id, self.avid_throttling_quant);
-        let acss_state = self.acss_ab_state.get_mut(&instance_id).unwrap();
-        if acss_state.avid_instances.is_empty(){
-            return true;
-        }
-        
-        for _ in 0..self.avid_throttling_quant{
-            if !acss_state.avid_instances.is_empty(){
-                let shares = acss_state.avid_instances.pop_front().unwrap();
-                let _inp_avid_status = self.inp_avid_channel.send(shares).await;
-            }
+    /// Starts as many queued AVID instances as the congestion window and byte budget allow.
+    pub async fn throttle_avid_instances(&mut self){
+        let released = self.avid_throttle.release();
+        log::debug!("Throttling AVID instances, starting {} instances with window {}, {} bytes in flight, {} queued", released.len(), self.avid_throttle.window(), self.avid_throttle.outstanding_bytes(), self.avid_throttle.pending());
+        for shares in released.into_iter(){
+            let _inp_avid_status = self.inp_avid_channel.send(shares).await;
         }
-        return false;
     }
 
     pub async fn verify_shares(&mut self, sender: Replica, instance_id: usize){
@@ -413,8 +392,7 @@ impl Context{
         let root_commitment = Self::root_commitment(&va_commitment.comm, &self.hash_context);
         let blinding_root_commitment = Self::root_commitment(&va_commitment.blinding_comm, &self.hash_context);
 
-        let fiat_shamir_root_comm = self.hash_context.hash_two(root_commitment.clone(), blinding_root_commitment.clone());
-        let fiat_shamir_root_fe = LargeField::from_bytes_be(fiat_shamir_root_comm.as_slice()).unwrap();
+        let fiat_shamir_root_fe = Self::dzk_challenge(instance_id, sender, &root_commitment, &blinding_root_commitment);
 
         let mut accepted_shares= Vec::new();
         for batch in 0..self.num_nodes{
@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use consensus::{interpolate_shares, inverse_vandermonde, matrix_vector_multiply, vandermonde_matrix, LargeField};
+use consensus::{interpolate_shares, matrix_vector_multiply, LargeField, cached_inverse_vandermonde};
 use ha_crypto::{decrypt};
 use lambdaworks_math::{polynomial::Polynomial, unsigned_integer::element::UnsignedInteger};
 use rayon::iter::{IntoParallelRefMutIterator, ParallelIterator};
@@ -41,8 +41,7 @@ impl Context{
             let mut expanded_shares = shares.chunks(self.num_faults+1).map(|chunk| chunk.to_vec()).collect::<Vec<Vec<LargeField>>>();
             
             let evaluation_curr_points: Vec<LargeField> = (1..self.num_faults+2).into_iter().map(|i| LargeField::new(UnsignedInteger::from(i as u64))).collect();
-            let vdm_matrix = vandermonde_matrix(evaluation_curr_points);
-            let inv_vdm_matrix = inverse_vandermonde(vdm_matrix);
+            let inv_vdm_matrix = cached_inverse_vandermonde(&evaluation_curr_points);
             
             let evaluation_new_points: Vec<LargeField> = (self.num_faults+2..self.num_nodes+1).into_iter().map(|i| LargeField::new(UnsignedInteger::from(i as u64))).collect();
             
@@ -134,14 +133,7 @@ impl Context{
 
             enc_shares.remove(&batch);
         }
-        
-        if batch_wise_shares.len() % self.avid_throttling_quant == 0 && sender_rep == self.myid{
-            // Schedule next batch of AVID instances
-            let avid_status = self.throttle_avid_instances(instance_id).await;
-            if avid_status{
-                log::debug!("All AVID instances completed for ACSS instance id {}", instance_id);
-            }
-        }
+
         self.verify_shares(sender_rep, instance_id).await;
     }
 
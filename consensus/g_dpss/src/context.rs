@@ -13,21 +13,21 @@ use network::{
 };
 use signal_hook::{iterator::Signals, consts::{SIGINT, SIGTERM}};
 use tokio::{sync::{
-    mpsc::{UnboundedReceiver, Sender, Receiver, channel, unbounded_channel},
+    mpsc::{UnboundedReceiver, Sender, Receiver, unbounded_channel},
     oneshot,
 }};
 // use tokio_util::time::DelayQueue;
 use types::{Replica, SyncMsg, SyncState, WrapperMsg};
 
-use consensus::{SyncHandler, LargeFieldSSS, LargeField, LargeFieldSer};
+use consensus::{SyncHandler, LargeFieldSSS, LargeField, LargeFieldSer, ingress_channel, ingress_per_sender_capacity, service_channel, IngressReceiver, SessionMsg, SessionContext, session_id};
 use crypto::{aes_hash::HashState, hash::Hash};
 
 use crate::{msg::ProtMsg, Handler, protocol::{DPSSState, BAState}};
 
 pub struct Context {
     /// Networking context
-    pub net_send: TcpReliableSender<Replica, WrapperMsg<ProtMsg>, Acknowledgement>,
-    pub net_recv: UnboundedReceiver<WrapperMsg<ProtMsg>>,
+    pub net_send: TcpReliableSender<Replica, WrapperMsg<SessionMsg<ProtMsg>>, Acknowledgement>,
+    pub net_recv: IngressReceiver<WrapperMsg<SessionMsg<ProtMsg>>>,
     pub sync_send: TcpReliableSender<Replica, SyncMsg, Acknowledgement>,
     pub sync_recv: UnboundedReceiver<SyncMsg>,
     /// Data context
@@ -45,6 +45,8 @@ pub struct Context {
     pub large_field_shamir_ss: LargeFieldSSS,
     /// Secret Key map
     pub sec_key_map: HashMap<Replica, Vec<u8>>,
+    /// Session binding and replay protection
+    pub session: SessionContext,
 
     /// Hardware acceleration context
     pub hash_context: HashState,
@@ -146,8 +148,8 @@ impl Context {
         syncer_map.insert(0, config.client_addr);
 
         // Setup networking
-        let (tx_net_to_consensus, rx_net_to_consensus) = unbounded_channel();
-        TcpReceiver::<Acknowledgement, WrapperMsg<ProtMsg>, _>::spawn(
+        let (tx_net_to_consensus, rx_net_to_consensus) = ingress_channel(ingress_per_sender_capacity(), config.sk_map.clone());
+        TcpReceiver::<Acknowledgement, WrapperMsg<SessionMsg<ProtMsg>>, _>::spawn(
             my_address,
             Handler::new(tx_net_to_consensus),
         );
@@ -162,7 +164,7 @@ impl Context {
             SyncHandler::new(tx_net_to_client),
         );
 
-        let consensus_net = TcpReliableSender::<Replica, WrapperMsg<ProtMsg>, Acknowledgement>::with_peers(
+        let consensus_net = TcpReliableSender::<Replica, WrapperMsg<SessionMsg<ProtMsg>>, Acknowledgement>::with_peers(
             consensus_addrs.clone(),
         );
         let sync_net =
@@ -183,19 +185,20 @@ impl Context {
             config.num_nodes
         );
         // Prepare ACSS context
-        let (acss_req_send_channel, acss_req_recv_channel) = channel(10000);
-        let (acss_out_send_channel, acss_out_recv_channel) = channel(10000);
+        let (acss_req_send_channel, acss_req_recv_channel) = service_channel();
+        let (acss_out_send_channel, acss_out_recv_channel) = service_channel();
         
-        let (pub_rec_req_send_channel, pub_rec_req_recv_channel) = channel(10000);
-        let (pub_rec_out_send_channel, pub_rec_out_recv_channel) = channel(10000);
+        let (pub_rec_req_send_channel, pub_rec_req_recv_channel) = service_channel();
+        let (pub_rec_out_send_channel, pub_rec_out_recv_channel) = service_channel();
 
-        let (acs_req_send_channel, acs_req_recv_channel) = channel(10000);
-        let (acs_out_send_channel, acs_out_recv_channel) = channel(10000);
+        let (acs_req_send_channel, acs_req_recv_channel) = service_channel();
+        let (acs_out_send_channel, acs_out_recv_channel) = service_channel();
 
-        let (ra_req_send_channel, ra_req_recv_channel) = channel(10000);
-        let (ra_out_send_channel, ra_out_recv_channel) = channel(10000);        
+        let (ra_req_send_channel, ra_req_recv_channel) = service_channel();
+        let (ra_out_send_channel, ra_out_recv_channel) = service_channel();        
 
         let coin_secrets = (60/(config.num_faults+1))*(config.num_faults+1);
+        let session = SessionContext::new(session_id(), "g_dpss", config.id, &consensus_addrs);
         tokio::spawn(async move {
             let mut c = Context {
                 net_send: consensus_net,
@@ -204,6 +207,7 @@ impl Context {
                 sync_recv: rx_net_from_client,
                 num_nodes: config.num_nodes,
                 sec_key_map: HashMap::default(),
+                session: session,
                 hash_context: hashstate,
                 myid: config.id,
                 _byz: byz,
@@ -327,17 +331,17 @@ impl Context {
     pub async fn broadcast(&mut self, protmsg: ProtMsg) {
         let sec_key_map = self.sec_key_map.clone();
         for (replica, sec_key) in sec_key_map.into_iter() {
-            let wrapper_msg = WrapperMsg::new(protmsg.clone(), self.myid, &sec_key.as_slice());
+            let wrapper_msg = WrapperMsg::new(self.session.seal(replica, protmsg.clone()), self.myid, &sec_key.as_slice());
             let cancel_handler: CancelHandler<Acknowledgement> = self.net_send.send(replica, wrapper_msg).await;
             self.add_cancel_handler(cancel_handler);
         }
     }
 
     pub fn add_cancel_handler(&mut self, canc: CancelHandler<Acknowledgement>) {
-        self.cancel_handlers.entry(0).or_default().push(canc);
+        consensus::add_cancel_handler_gc(&mut self.cancel_handlers, canc);
     }
 
-    pub async fn send(&mut self, replica: Replica, wrapper_msg: WrapperMsg<ProtMsg>) {
+    pub async fn send(&mut self, replica: Replica, wrapper_msg: WrapperMsg<SessionMsg<ProtMsg>>) {
         let cancel_handler: CancelHandler<Acknowledgement> =
             self.net_send.send(replica, wrapper_msg).await;
         self.add_cancel_handler(cancel_handler);
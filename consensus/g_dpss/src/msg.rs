@@ -1,4 +1,4 @@
-use crypto::LargeFieldSer;
+use consensus::LargeFieldSer;
 use serde::{Serialize, Deserialize};
 use types::Replica;
 
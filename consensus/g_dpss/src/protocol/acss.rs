@@ -1,7 +1,7 @@
 use std::collections::{HashMap, HashSet};
 
-use consensus::LargeFieldSSS;
-use crypto::{LargeField, LargeFieldSer, hash::{Hash}, rand_field_element};
+use consensus::{LargeFieldSSS, LargeField, LargeFieldSer, rand_field_element, Transcript};
+use crypto::{hash::{Hash}};
 use lambdaworks_math::traits::ByteConversion;
 use types::Replica;
 
@@ -59,8 +59,13 @@ impl Context{
             let root_1 = first_comm_shares.1;
             let root_2 = second_comm_shares.1;
 
-            let core_root = self.hash_context.hash_two(root_1, root_2);
-            let root_comm_lf = LargeField::from_bytes_be(core_root.as_slice()).unwrap();
+            // Both sharings of the dealer are aggregated with the same challenge, derived from both commitments
+            let mut transcript = Transcript::new(b"g-dpss secret equivalence");
+            transcript.append_u64(b"instance", inst_key as u64);
+            transcript.append_u64(b"dealer", sender as u64);
+            transcript.append_hash(b"first commitment", &root_1);
+            transcript.append_hash(b"second commitment", &root_2);
+            let root_comm_lf: LargeField = transcript.challenge_field_element(b"secret equivalence");
 
             // Construct aggregated shares and broadcast them within committee
             let mut root_comm_mul = root_comm_lf.clone();
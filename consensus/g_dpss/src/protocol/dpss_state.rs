@@ -1,6 +1,7 @@
 use std::collections::{HashMap, HashSet};
 
-use crypto::{LargeField, hash::Hash};
+use crypto::hash::Hash;
+use consensus::LargeField;
 use types::Replica;
 
 pub struct DPSSState{
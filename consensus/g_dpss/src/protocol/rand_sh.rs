@@ -1,5 +1,4 @@
-use consensus::{LargeFieldSSS, RBCSyncMsg, inverse_vandermonde, matrix_vector_multiply, vandermonde_matrix};
-use crypto::{LargeField, LargeFieldSer};
+use consensus::{LargeFieldSSS, RBCSyncMsg, matrix_vector_multiply, cached_inverse_vandermonde, LargeField, LargeFieldSer};
 use lambdaworks_math::{traits::ByteConversion, polynomial::Polynomial};
 use rayon::prelude::IntoParallelIterator;
 use types::{WrapperMsg, Replica, SyncMsg, SyncState};
@@ -82,7 +81,7 @@ impl Context{
             let secret_key = self.sec_key_map.get(&rep).clone().unwrap();
             let shares_ser = shares.into_iter().map(|x| x.to_bytes_be()).collect();
             let prot_msg = ProtMsg::PubRecEcho1(shares_ser);
-            let wrapper = WrapperMsg::new(prot_msg, self.myid, secret_key.as_slice());
+            let wrapper = WrapperMsg::new(self.session.seal(rep, prot_msg), self.myid, secret_key.as_slice());
             let cancel_handler = self.net_send.send(rep, wrapper).await;
             self.add_cancel_handler(cancel_handler);
         }
@@ -116,9 +115,7 @@ impl Context{
             // Interpolate polynomials
             let secret_evaluation_point= LargeField::from(0 as u64);
 
-            // Generate vandermonde matrix
-            let vandermonde = vandermonde_matrix(evaluation_indices.clone());
-            let inverse_vandermonde = inverse_vandermonde(vandermonde);
+            let inverse_vandermonde = cached_inverse_vandermonde(&evaluation_indices);
 
             let l2_shares : Vec<LargeFieldSer> = vec_shares_indices.into_par_iter().map(|evals|{
                 let coefficients = matrix_vector_multiply(&inverse_vandermonde, &evals);
@@ -154,8 +151,7 @@ impl Context{
             }
 
             // Interpolate entire polynomial
-            let vandermonde_matrix = vandermonde_matrix(ht_indices);
-            let vandermonde_inverse = inverse_vandermonde(vandermonde_matrix);
+            let vandermonde_inverse = cached_inverse_vandermonde(&ht_indices);
             
             let secrets_blinded: Vec<LargeField> = vec_shares_indices.into_par_iter().map(|evals|{
                 let coefficients = matrix_vector_multiply(&vandermonde_inverse, &evals);
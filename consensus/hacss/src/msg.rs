@@ -1,5 +1,5 @@
-use consensus::{VACommitment};
-use ha_crypto::{hash::Hash, LargeFieldSer};
+use consensus::{VACommitment, LargeFieldSer};
+use ha_crypto::hash::Hash;
 use ctrbc::CTRBCMsg;
 use serde::{Serialize, Deserialize};
 use types::Replica;
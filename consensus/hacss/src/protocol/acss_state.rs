@@ -1,4 +1,4 @@
-use crypto::{SmallField, LargeField};
+use consensus::{SmallField, LargeField};
 use ctrbc::RBCState;
 use types::Replica;
 
@@ -1,4 +1,5 @@
-use ha_crypto::{LargeFieldSer, aes_hash::{Proof, MerkleTree}, hash::Hash, LargeField};
+use ha_crypto::{aes_hash::{Proof, MerkleTree}, hash::Hash};
+use consensus::{LargeFieldSer, LargeField};
 
 use crate::Context;
 
@@ -5,7 +5,7 @@ use network::{plaintcp::CancelHandler, Acknowledgement};
 
 use types::{Replica, WrapperMsg};
 
-use crate::{Context, ACSSVAState, ProtMsg};
+use crate::{Context, ACSSVAState, ProtMsg, protocol::COLUMN_DOMAIN};
 use consensus::PointBV;
 
 impl Context{
@@ -121,7 +121,7 @@ impl Context{
                                 enc_share.extend(encrypted_shares[rep].clone().1);
                             }
                             let ready_msg = ProtMsg::Ready(rbc_msg.clone(), enc_share, instance_id);
-                            let wrapper_msg = WrapperMsg::new(ready_msg, self.myid, &secret_key);
+                            let wrapper_msg = WrapperMsg::new(self.session.seal(rep, ready_msg), self.myid, &secret_key);
                             let cancel_handler: CancelHandler<Acknowledgement> = self.net_send.send(rep, wrapper_msg).await;
                             self.add_cancel_handler(cancel_handler);
                         }
@@ -130,11 +130,12 @@ impl Context{
                 else{
                     // Verify DZK proofs first
                     let bv_echo_points = acss_va_state.bv_echo_points.clone();
-                    let proof_status = self.folding_dzk_context.verify_dzk_proofs_column(
+                    let proof_status = self.dzk_context.verify_dzk_proofs_column(
                         comm.dzk_roots[self.myid].clone(), 
                             comm.polys[self.myid].clone(), 
                             bv_echo_points,
-                            instance_id
+                            instance_id,
+                            COLUMN_DOMAIN
                         );
                     if proof_status.is_none(){
                         log::error!("Error verifying distributed ZK proofs for points on column of {} in ACSS instance {}", self.myid, instance_id);
@@ -174,7 +175,7 @@ impl Context{
                             enc_share.extend(encrypted_shares[rep].clone().1);
                         }
                         let ready_msg = ProtMsg::Ready(rbc_msg.clone(), enc_share, instance_id);
-                        let wrapper_msg = WrapperMsg::new(ready_msg, self.myid, &secret_key);
+                        let wrapper_msg = WrapperMsg::new(self.session.seal(rep, ready_msg), self.myid, &secret_key);
                         let cancel_handler: CancelHandler<Acknowledgement> = self.net_send.send(rep, wrapper_msg).await;
                         self.add_cancel_handler(cancel_handler);
                     }
@@ -213,7 +214,7 @@ impl Context{
                         enc_share.extend(encrypted_shares[rep].clone().1);
                     }
                     let ready_msg = ProtMsg::Ready(ctrbc_msg.clone(), enc_share, instance_id);
-                    let wrapper_msg = WrapperMsg::new(ready_msg, self.myid, &secret_key);
+                    let wrapper_msg = WrapperMsg::new(self.session.seal(rep, ready_msg), self.myid, &secret_key);
                     let cancel_handler: CancelHandler<Acknowledgement> = self.net_send.send(rep, wrapper_msg).await;
                     self.add_cancel_handler(cancel_handler);
                 }
@@ -1,4 +1,5 @@
-use crypto::{encrypt, pseudorandom_lf, LargeField, decrypt};
+use crypto::{encrypt, decrypt};
+use consensus::{pseudorandom_lf, LargeField};
 use crypto::hash::{do_hash};
 use network::Acknowledgement;
 use network::plaintcp::CancelHandler;
@@ -232,7 +233,7 @@ impl Context{
                 self.myid, 
                 instance_id
             );
-            let wrapper_msg = WrapperMsg::new(prot_msg.clone(),self.myid,&secret_key);
+            let wrapper_msg = WrapperMsg::new(self.session.seal(rep, prot_msg.clone()),self.myid,&secret_key);
             let cancel_handler: CancelHandler<Acknowledgement> = self.net_send.send(rep, wrapper_msg).await;
             self.add_cancel_handler(cancel_handler);
         }
@@ -1,9 +1,9 @@
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use consensus::{get_shards, LargeFieldSSS, VACommitment, VAShare};
+use consensus::{get_shards, LargeFieldSSS, VACommitment, VAShare, dzk_aggregation_challenge, dzk_column_commitment, LargeField, LargeFieldSer, rand_field_element};
 use ha_crypto::aes_hash::{MerkleTree, Proof};
 use ha_crypto::hash::Hash;
-use ha_crypto::{LargeField, LargeFieldSer, encrypt, decrypt, rand_field_element};
+use ha_crypto::{encrypt, decrypt};
 use ctrbc::CTRBCMsg;
 use lambdaworks_math::polynomial::Polynomial;
 use lambdaworks_math::traits::ByteConversion;
@@ -11,7 +11,7 @@ use network::Acknowledgement;
 use network::plaintcp::CancelHandler;
 use types::{Replica, WrapperMsg};
 
-use crate::{Context, ProtMsg, ACSSVAState};
+use crate::{Context, ProtMsg, ACSSVAState, protocol::COLUMN_DOMAIN};
 use consensus::{DZKProof, PointBV};
 
 impl Context{
@@ -169,7 +169,7 @@ impl Context{
             |(mt,bmt)|{
                 column_share_roots.push(mt.root());
                 column_blinding_roots.push(bmt.root());
-                return self.hash_context.hash_two(mt.root(), bmt.root());
+                return dzk_column_commitment(COLUMN_DOMAIN, instance_id, &mt.root(), &bmt.root());
             }
         ).collect();
         // 4. Generate Distributed Zero Knowledge Proofs
@@ -184,7 +184,8 @@ impl Context{
         // 4.b. Create DZK Share polynomials
         let mut _rep = 0;
         for ((coefficient_vec, blinding_coefficient_vec), column_mr) in (aggregated_coefficients.into_iter().zip(blinding_coeffs_y_deg_t.into_iter())).zip(column_wise_roots.clone().into_iter()){
-            let column_root_bint =  LargeField::from_bytes_be(column_mr.clone().as_slice()).unwrap();
+            // Must match the challenge the shares were aggregated with
+            let column_root_bint: LargeField = dzk_aggregation_challenge(&column_mr);
             
             let dzk_poly: Vec<LargeField> = coefficient_vec.into_iter().zip(blinding_coefficient_vec.coefficients.into_iter()).map(
                 |(f_i,b_i)| {
@@ -224,7 +225,7 @@ impl Context{
             //merkle_roots.push(root.clone());
 
             // Reliably broadcast these coefficients
-            let coeffs_const_size: Vec<LargeFieldSer> = self.folding_dzk_context.gen_dzk_proof(
+            let coeffs_const_size: Vec<LargeFieldSer> = self.dzk_context.gen_dzk_proof(
                 &mut eval_points, 
                 &mut trees, 
                 coefficients, 
@@ -345,7 +346,7 @@ impl Context{
                 instance_id
             );
 
-            let wrapper_msg = WrapperMsg::new(prot_msg_va, self.myid, &secret_key);
+            let wrapper_msg = WrapperMsg::new(self.session.seal(rep, prot_msg_va), self.myid, &secret_key);
             let cancel_handler: CancelHandler<Acknowledgement> = self.net_send.send(rep, wrapper_msg).await;
             self.add_cancel_handler(cancel_handler);
         }
@@ -498,7 +499,7 @@ impl Context{
 
         let column_combined_roots: Vec<Hash> = comm.column_roots.clone().into_iter().zip(comm.blinding_column_roots.clone().into_iter()).map(
             |(root1,root2)|
-            self.hash_context.hash_two(root1, root2)
+            dzk_column_commitment(COLUMN_DOMAIN, instance_id, &root1, &root2)
         ).collect();
 
         let mut party_wise_row_shares = Vec::new();
@@ -513,18 +514,18 @@ impl Context{
         }
 
         let dzk_aggregated_points: Vec<LargeField> = party_wise_row_shares.clone().into_iter().zip(column_combined_roots.clone().into_iter()).map(
-            |(shares,root)| self.folding_dzk_context.gen_agg_poly_dzk(shares, root)
+            |(shares,root)| self.dzk_context.gen_agg_poly_dzk(shares, root)
         ).collect();
 
 
-        let verf_check = self.folding_dzk_context.verify_dzk_proof_row(
+        let verf_check = self.dzk_context.verify_dzk_proof_row(
             shares.dzk_iters.clone(), 
             comm.dzk_roots.clone(),
             comm.polys.clone(), 
             column_combined_roots, 
             dzk_aggregated_points.clone(),
             blinding_row_shares.clone(),
-            self.myid+1
+            LargeField::from((self.myid+1) as u64)
         );
         if verf_check{
             log::info!("Successfully verified shares for instance_id {}", instance_id);
@@ -611,7 +612,7 @@ impl Context{
                 origin: dealer
             };
             let echo_msg = ProtMsg::Echo(rbc_msg, enc_share, instance_id);
-            let wrapper_msg = WrapperMsg::new(echo_msg, self.myid, secret_key.as_slice());
+            let wrapper_msg = WrapperMsg::new(self.session.seal(rep, echo_msg), self.myid, secret_key.as_slice());
             let cancel_handler: CancelHandler<Acknowledgement> = self.net_send.send(rep, wrapper_msg).await;
             self.add_cancel_handler(cancel_handler);
         }
@@ -620,7 +621,7 @@ impl Context{
     fn agg_share_poly_dzk_batch(&self, coefficients: Vec<Vec<Vec<LargeField>>>, column_wise_roots: Vec<Hash>)-> Vec<Vec<LargeField>>{
 
         let mut root_mul_lf: Vec<LargeField> = column_wise_roots.iter().map(|root| 
-            LargeField::from_bytes_be(root).unwrap()
+            dzk_aggregation_challenge(root)
         ).collect();
         let roots_original = root_mul_lf.clone();
         let mut aggregated_coefficients = Vec::new();
@@ -1,3 +1,6 @@
+/// Transcript domain the dealer commits to its columns in
+pub(crate) const COLUMN_DOMAIN: &[u8] = b"hacss column";
+
 mod echo_vf;
 
 mod ready_vf;
@@ -1,10 +1,10 @@
-use consensus::{reconstruct_data, VACommitment};
-use ha_crypto::{decrypt, hash::{Hash, do_hash}, aes_hash::MerkleTree, LargeField};
+use consensus::{reconstruct_data, VACommitment, LargeField};
+use ha_crypto::{decrypt, hash::{Hash, do_hash}, aes_hash::MerkleTree};
 use ctrbc::CTRBCMsg;
 use network::{plaintcp::CancelHandler, Acknowledgement};
 use types::{Replica, WrapperMsg};
 
-use crate::{Context, ACSSVAState, ProtMsg};
+use crate::{Context, ACSSVAState, ProtMsg, protocol::COLUMN_DOMAIN};
 use consensus::{PointBV};
 
 impl Context{
@@ -95,11 +95,12 @@ impl Context{
                     let comm: VACommitment = bincode::deserialize(message.as_slice()).unwrap();
                     let bv_ready_points = acss_va_context.bv_ready_points.clone();
 
-                    let proof_status = self.folding_dzk_context.verify_dzk_proofs_column(
+                    let proof_status = self.dzk_context.verify_dzk_proofs_column(
                         comm.dzk_roots[self.myid].clone(), 
                         comm.polys[self.myid].clone(), 
                         bv_ready_points, 
-                        instance_id
+                        instance_id,
+                        COLUMN_DOMAIN
                     );
                     
                     if proof_status.is_none(){
@@ -145,7 +146,7 @@ impl Context{
                             enc_share.extend(encrypted_shares[rep].clone().1);
                         }
                         let ready_msg = ProtMsg::Ready(rbc_msg.clone(), enc_share, instance_id);
-                        let wrapper_msg = WrapperMsg::new(ready_msg, self.myid, &secret_key);
+                        let wrapper_msg = WrapperMsg::new(self.session.seal(rep, ready_msg), self.myid, &secret_key);
                         let cancel_handler: CancelHandler<Acknowledgement> = self.net_send.send(rep, wrapper_msg).await;
                         self.add_cancel_handler(cancel_handler);
                     }
@@ -1,10 +1,10 @@
 use std::collections::HashMap;
 
-use ha_crypto::{LargeField, hash::Hash, LargeFieldSer};
+use ha_crypto::hash::Hash;
 use ctrbc::RBCState;
 use types::Replica;
 
-use consensus::PointBV;
+use consensus::{PointBV, LargeField, LargeFieldSer};
 
 pub struct ACSSVAState{
     pub origin: Replica,
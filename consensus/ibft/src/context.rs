@@ -6,7 +6,7 @@ use std::{
 use anyhow::{anyhow, Result};
 use config::Node;
 
-use consensus::LargeFieldSer;
+use consensus::{LargeFieldSer, ingress_channel, ingress_per_sender_capacity, IngressReceiver, SessionMsg, SessionContext, session_id, RBCService, RBCBackend, ReliableBroadcast, rbc_backend};
 use fnv::FnvHashMap;
 use network::{
     plaintcp::{CancelHandler, TcpReceiver, TcpReliableSender},
@@ -14,7 +14,7 @@ use network::{
 };
 //use signal_hook::{iterator::Signals, consts::{SIGINT, SIGTERM}};
 use tokio::{sync::{
-    mpsc::{UnboundedReceiver, Sender, Receiver, channel, unbounded_channel},
+    mpsc::{Sender, Receiver},
     oneshot,
 }};
 // use tokio_util::time::DelayQueue;
@@ -24,8 +24,8 @@ use crate::{msg::ProtMsg, IBFTState, Handler};
 
 pub struct Context {
     /// Networking context
-    pub net_send: TcpReliableSender<Replica, WrapperMsg<ProtMsg>, Acknowledgement>,
-    pub net_recv: UnboundedReceiver<WrapperMsg<ProtMsg>>,
+    pub net_send: TcpReliableSender<Replica, WrapperMsg<SessionMsg<ProtMsg>>, Acknowledgement>,
+    pub net_recv: IngressReceiver<WrapperMsg<SessionMsg<ProtMsg>>>,
     //pub sync_send: TcpReliableSender<Replica, SyncMsg, Acknowledgement>,
     //pub sync_recv: UnboundedReceiver<SyncMsg>,
     /// Data context
@@ -39,6 +39,8 @@ pub struct Context {
     pub leader_id: usize,
     /// Secret Key map
     pub sec_key_map: HashMap<Replica, Vec<u8>>,
+    /// Session binding and replay protection
+    pub session: SessionContext,
 
     /// Cancel Handlers
     pub cancel_handlers: HashMap<u64, Vec<CancelHandler<Acknowledgement>>>,
@@ -54,8 +56,7 @@ pub struct Context {
     pub event_recv_channel: Receiver<(usize, usize, Vec<LargeFieldSer>)>,
     pub acs_out_channel: Sender<(usize, Vec<usize>)>,
 
-    pub ctrbc_req: Sender<Vec<u8>>,
-    pub ctrbc_out_recv: Receiver<(usize, usize, Vec<u8>)>,
+    pub rbc: RBCService<usize>,
 }
 
 // s = num_batches*per_batch
@@ -109,8 +110,8 @@ impl Context {
         syncer_map.insert(0, config.client_addr);
 
         // Setup networking
-        let (tx_net_to_consensus, rx_net_to_consensus) = unbounded_channel();
-        TcpReceiver::<Acknowledgement, WrapperMsg<ProtMsg>, _>::spawn(
+        let (tx_net_to_consensus, rx_net_to_consensus) = ingress_channel(ingress_per_sender_capacity(), config.sk_map.clone());
+        TcpReceiver::<Acknowledgement, WrapperMsg<SessionMsg<ProtMsg>>, _>::spawn(
             my_address,
             Handler::new(tx_net_to_consensus),
         );
@@ -125,7 +126,7 @@ impl Context {
         //    SyncHandler::new(tx_net_to_client),
         //);
 
-        let consensus_net = TcpReliableSender::<Replica, WrapperMsg<ProtMsg>, Acknowledgement>::with_peers(
+        let consensus_net = TcpReliableSender::<Replica, WrapperMsg<SessionMsg<ProtMsg>>, Acknowledgement>::with_peers(
             consensus_addrs.clone(),
         );
         //let sync_net =
@@ -136,8 +137,13 @@ impl Context {
         //let (acss_req_send_channel, acss_req_recv_channel) = channel(10000);
         //let (acss_out_send_channel, acss_out_recv_channel) = channel(10000);
         // Prepare RBC config
-        let (ctrbc_req_send_channel, ctrbc_req_recv_channel) = channel(10000);
-        let (ctrbc_out_send_channel, ctrbc_out_recv_channel) = channel(10000);
+        let (rbc, _rbc_serv_status) = RBCService::spawn(
+            rbc_backend(RBCBackend::CCBRB),
+            rbc_config,
+            false
+        );
+        
+        let session = SessionContext::new(session_id(), "ibft", config.id, &consensus_addrs);
         
         tokio::spawn(async move {
             let mut c = Context {
@@ -147,6 +153,7 @@ impl Context {
                 //sync_recv: rx_net_from_client,
                 num_nodes: config.num_nodes,
                 sec_key_map: HashMap::default(),
+                session: session,
 
                 myid: config.id,
                 _byz: byz,
@@ -169,8 +176,7 @@ impl Context {
                 event_recv_channel: term_event_channel,
                 acs_out_channel: acs_out_channel,
 
-                ctrbc_req: ctrbc_req_send_channel,
-                ctrbc_out_recv: ctrbc_out_recv_channel,
+                rbc: rbc,
             };
 
             // Populate secret keys from config
@@ -186,13 +192,6 @@ impl Context {
         // This is so that the inner contexts are not dropped by the compiler
         let mut statuses = Vec::new();
 
-        let _rbc_serv_status = ccbrb::Context::spawn(
-            rbc_config,
-            ctrbc_req_recv_channel, 
-            ctrbc_out_send_channel, 
-            false
-        );
-
         statuses.push(_rbc_serv_status);
         // let mut signals = Signals::new(&[SIGINT, SIGTERM])?;
         // signals.forever().next();
@@ -203,17 +202,17 @@ impl Context {
     pub async fn broadcast(&mut self, protmsg: ProtMsg) {
         let sec_key_map = self.sec_key_map.clone();
         for (replica, sec_key) in sec_key_map.into_iter() {
-            let wrapper_msg = WrapperMsg::new(protmsg.clone(), self.myid, &sec_key.as_slice());
+            let wrapper_msg = WrapperMsg::new(self.session.seal(replica, protmsg.clone()), self.myid, &sec_key.as_slice());
             let cancel_handler: CancelHandler<Acknowledgement> = self.net_send.send(replica, wrapper_msg).await;
             self.add_cancel_handler(cancel_handler);
         }
     }
 
     pub fn add_cancel_handler(&mut self, canc: CancelHandler<Acknowledgement>) {
-        self.cancel_handlers.entry(0).or_default().push(canc);
+        consensus::add_cancel_handler_gc(&mut self.cancel_handlers, canc);
     }
 
-    pub async fn send(&mut self, replica: Replica, wrapper_msg: WrapperMsg<ProtMsg>) {
+    pub async fn send(&mut self, replica: Replica, wrapper_msg: WrapperMsg<SessionMsg<ProtMsg>>) {
         let cancel_handler: CancelHandler<Acknowledgement> =
             self.net_send.send(replica, wrapper_msg).await;
         self.add_cancel_handler(cancel_handler);
@@ -246,12 +245,12 @@ impl Context {
                     // Process the termination event
                     self.init_acss_term_procedure(term_party, instance_id).await;
                 },
-                ctrbc_msg = self.ctrbc_out_recv.recv() => {
-                    let ctrbc_msg = ctrbc_msg.ok_or_else(||
+                rbc_msg = self.rbc.deliver() => {
+                    let rbc_msg = rbc_msg.ok_or_else(||
                         anyhow!("Networking layer has closed")
                     )?;
-                    log::debug!("Received message from CTRBC channel {:?}", ctrbc_msg);
-                    self.process_ctrbc_termination(ctrbc_msg.2).await;
+                    log::debug!("Received message from RBC channel {:?}", rbc_msg);
+                    self.process_ctrbc_termination(rbc_msg.id, rbc_msg.payload).await;
                 },
             };
         }
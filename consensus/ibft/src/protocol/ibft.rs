@@ -1,3 +1,4 @@
+use consensus::ReliableBroadcast;
 use network::{Acknowledgement, plaintcp::CancelHandler};
 use types::{Replica, WrapperMsg};
 
@@ -9,7 +10,7 @@ impl Context{
         let prot_msg = ProtMsg::ACSSTerm(term_party, instance_id);
 
         let secret_key = self.sec_key_map.get(&self.leader_id).unwrap().clone();
-        let wrapper_msg = WrapperMsg::new(prot_msg,self.myid, &secret_key);
+        let wrapper_msg = WrapperMsg::new(self.session.seal(self.leader_id, prot_msg),self.myid, &secret_key);
 
         let cancel_handler: CancelHandler<Acknowledgement> = self.net_send.send(self.leader_id, wrapper_msg).await;
         self.add_cancel_handler(cancel_handler);
@@ -41,12 +42,11 @@ impl Context{
             let mut inp_set_vec = Vec::new();
             inp_set_vec.extend(ibft_state.consensus_inp_set.iter().cloned());
 
-            let ctrbc_msg = (instance_id, inp_set_vec);
-            let ser_msg = bincode::serialize(&ctrbc_msg).expect("Failed to serialize CTRBC message");
+            let ser_msg = bincode::serialize(&inp_set_vec).expect("Failed to serialize CTRBC message");
 
-            let ctrbc_status = self.ctrbc_req.send(ser_msg).await;
+            let ctrbc_status = self.rbc.broadcast(instance_id, ser_msg).await;
             if ctrbc_status.is_err() {
-                log::error!("Failed to send CTRBC request for instance {}", instance_id);
+                log::error!("Failed to send CTRBC request for instance {}: {:?}", instance_id, ctrbc_status.err().unwrap());
             } else {
                 log::info!("CTRBC request sent successfully for instance {}", instance_id);
             }
@@ -54,9 +54,14 @@ impl Context{
         }
     }
 
-    pub async fn process_ctrbc_termination(&mut self, ctrbc_msg: Vec<u8>){
-        let (instance_id,party_set): ( usize, Vec<Replica>) = bincode::deserialize(&ctrbc_msg)
-            .expect("Failed to deserialize CTRBC message");
+    pub async fn process_ctrbc_termination(&mut self, instance_id: usize, ctrbc_msg: Vec<u8>){
+        let party_set: Vec<Replica> = match bincode::deserialize(&ctrbc_msg){
+            Ok(party_set) => party_set,
+            Err(e) => {
+                log::error!("Failed to deserialize CTRBC message for instance {}: {:?}", instance_id, e);
+                return;
+            }
+        };
         log::info!("Received CTRBC termination for instance id {} with party set {:?}", instance_id, party_set);
         if !self.ibft_state_map.contains_key(&instance_id){
             let ibft_state = IBFTState::new();
@@ -7,6 +7,10 @@ use crate::{context::Context, msg::ProtMsg};
 impl Context{
     pub async fn init_ra(&mut self, instance_id: usize, representative_rep: Replica, value: usize){
         log::debug!("Request to start Reliable Agreement for instance {} corresponding to replica {}", instance_id, representative_rep);
+        if self.gc.is_collected(&instance_id){
+            // Instance terminated and its state was pruned
+            return;
+        }
         if !self.ra_state.contains_key(&instance_id){
             let rbc_context = RBCState::new(representative_rep);
             self.ra_state.insert(instance_id, rbc_context);
@@ -28,6 +32,10 @@ impl Context{
     }
 
     pub async fn process_echo_ra(&mut self, instance_id: usize, echo_sender: Replica, value: usize){
+        if self.gc.is_collected(&instance_id){
+            // Instance terminated and its state was pruned
+            return;
+        }
         // Broadcast ECHO message
         if !self.ra_state.contains_key(&instance_id){
             let (_inst, representative_rep) = replica_from_inst_id(self.threshold, instance_id);
@@ -9,6 +9,10 @@ use super::echo::replica_from_inst_id;
 
 impl Context{
     pub async fn process_ra_ready(&mut self, instance_id: usize, ready_sender: Replica, value: usize){
+        if self.gc.is_collected(&instance_id){
+            // Instance terminated and its state was pruned
+            return;
+        }
         if !self.ra_state.contains_key(&instance_id){
             let (_inst, representative_rep) = replica_from_inst_id(self.threshold, instance_id);
             let ra_context = RBCState::new(representative_rep);
@@ -59,5 +63,8 @@ impl Context{
         let msg = (rep, instance, value);
         let status = self.out_ra_values.send(msg).await;
         log::debug!("Sent result back to original channel {:?}", status);
+
+        self.gc.mark_terminated(instance_id);
+        self.gc.prune(&mut self.ra_state);
     }
 }
\ No newline at end of file
@@ -0,0 +1,121 @@
+use std::{
+    collections::HashMap,
+    convert::TryInto,
+    sync::{Arc, OnceLock},
+};
+
+use config::Node;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use types::Replica;
+
+pub const ED25519_SECRET_KEY_LENGTH: usize = 32;
+pub const ED25519_PUBLIC_KEY_LENGTH: usize = 32;
+pub const ED25519_SIGNATURE_LENGTH: usize = 64;
+
+static SIGNATURE_KEYS: OnceLock<Arc<SignatureKeys>> = OnceLock::new();
+
+/// Switch every service spawned after this call to signature-based authentication.
+/// Pairwise MACs are still checked on every link, signatures are verified on top of them.
+pub fn enable_signatures(keys: SignatureKeys) {
+    if SIGNATURE_KEYS.set(Arc::new(keys)).is_err() {
+        log::warn!("Signature keys were already set, ignoring the new keys");
+    }
+}
+
+/// Returns the signing keys of this node if signature-based authentication is enabled.
+pub fn signature_keys() -> Option<Arc<SignatureKeys>> {
+    SIGNATURE_KEYS.get().cloned()
+}
+
+/// Ed25519 keys of this node and the verification keys of all nodes, as generated by genconfig.
+pub struct SignatureKeys {
+    pub myid: Replica,
+    signing_key: SigningKey,
+    verifying_keys: HashMap<Replica, VerifyingKey>,
+}
+
+impl SignatureKeys {
+    /// Reads the secret key from `secret_key_bytes` and the public keys from `pk_map` of the config.
+    pub fn from_config(config: &Node) -> Result<SignatureKeys, String> {
+        let secret: [u8; ED25519_SECRET_KEY_LENGTH] = config.secret_key_bytes.as_slice().try_into()
+            .map_err(|_| format!("Invalid Ed25519 secret key length {}, was the config generated with -A ED25519?", config.secret_key_bytes.len()))?;
+        let mut verifying_keys = HashMap::default();
+        for (rep, pk_bytes) in config.pk_map.iter() {
+            let pk: [u8; ED25519_PUBLIC_KEY_LENGTH] = pk_bytes.as_slice().try_into()
+                .map_err(|_| format!("Invalid Ed25519 public key length {} for node {}", pk_bytes.len(), rep))?;
+            let verifying_key = VerifyingKey::from_bytes(&pk)
+                .map_err(|e| format!("Invalid Ed25519 public key for node {}: {}", rep, e))?;
+            verifying_keys.insert(*rep, verifying_key);
+        }
+        for rep in 0..config.num_nodes {
+            if !verifying_keys.contains_key(&rep) {
+                return Err(format!("Public key of node {} missing from pk_map", rep));
+            }
+        }
+        let signing_key = SigningKey::from_bytes(&secret);
+        if verifying_keys.get(&config.id) != Some(&signing_key.verifying_key()) {
+            return Err(format!("Secret key does not match the public key of node {}", config.id));
+        }
+        Ok(SignatureKeys {
+            myid: config.id,
+            signing_key: signing_key,
+            verifying_keys: verifying_keys,
+        })
+    }
+
+    /// Keys of a committee whose secret keys are all known locally, e.g. in tests.
+    pub fn from_secret_keys(myid: Replica, secret_keys: &HashMap<Replica, [u8; ED25519_SECRET_KEY_LENGTH]>) -> SignatureKeys {
+        let verifying_keys = secret_keys.iter()
+            .map(|(rep, secret)| (*rep, SigningKey::from_bytes(secret).verifying_key()))
+            .collect();
+        SignatureKeys {
+            myid: myid,
+            signing_key: SigningKey::from_bytes(secret_keys.get(&myid).expect("Own secret key missing")),
+            verifying_keys: verifying_keys,
+        }
+    }
+
+    pub fn sign(&self, bytes: &[u8]) -> Vec<u8> {
+        self.signing_key.sign(bytes).to_bytes().to_vec()
+    }
+
+    pub fn verify(&self, signer: Replica, bytes: &[u8], signature: &[u8]) -> bool {
+        let verifying_key = match self.verifying_keys.get(&signer) {
+            Some(key) => key,
+            None => {
+                return false;
+            }
+        };
+        let signature: [u8; ED25519_SIGNATURE_LENGTH] = match signature.try_into() {
+            Ok(sig) => sig,
+            Err(_) => {
+                return false;
+            }
+        };
+        verifying_key.verify(bytes, &Signature::from_bytes(&signature)).is_ok()
+    }
+}
+
+/// Bytes covered by a signature. The recipient is deliberately not covered so that the message can be forwarded as evidence.
+pub fn signed_bytes<M: Serialize>(session_id: u64, service: &str, signer: Replica, msg: &M) -> Vec<u8> {
+    bincode::serialize(&(session_id, service, signer, msg)).expect("Failed to serialize object")
+}
+
+/// A message signed by `signer`, verifiable by any node holding the public keys.
+/// Used as transferable evidence, e.g. to prove the misbehavior of a dealer to nodes that did not receive the message.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SignedEvidence<M> {
+    pub signer: Replica,
+    pub session_id: u64,
+    pub service: String,
+    pub msg: M,
+    pub signature: Vec<u8>,
+}
+
+impl<M: Serialize> SignedEvidence<M> {
+    pub fn verify(&self, keys: &SignatureKeys) -> bool {
+        let bytes = signed_bytes(self.session_id, self.service.as_str(), self.signer, &self.msg);
+        keys.verify(self.signer, bytes.as_slice(), self.signature.as_slice())
+    }
+}
@@ -0,0 +1,55 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Default number of secrets dealt per chunk by ACSS. Zero deals every batch in a single chunk.
+pub const DEFAULT_DEALING_CHUNK_SIZE: usize = 0;
+
+static DEALING_CHUNK_SIZE: AtomicUsize = AtomicUsize::new(DEFAULT_DEALING_CHUNK_SIZE);
+
+/// Set the chunk size used by every service spawned after this call.
+/// Batches larger than a chunk are committed to and dispersed chunk by chunk, so the dealer holds the evaluations of a single chunk at a time.
+pub fn set_dealing_chunk_size(chunk_size: usize) {
+    DEALING_CHUNK_SIZE.store(chunk_size, Ordering::Relaxed);
+}
+
+pub fn dealing_chunk_size() -> usize {
+    DEALING_CHUNK_SIZE.load(Ordering::Relaxed)
+}
+
+/// Sub-instances (chunks of a dealing, views of a session) share the id space of their parent instance.
+/// Sub-instance 0 keeps the id of its parent, so protocols that never split an instance see unchanged ids.
+/// Parent ids must stay below `1 << SUB_INSTANCE_SHIFT`.
+pub const SUB_INSTANCE_SHIFT: usize = 32;
+
+pub fn sub_instance_id(instance: usize, sub: usize) -> usize {
+    instance | (sub << SUB_INSTANCE_SHIFT)
+}
+
+/// Returns the parent instance and the sub-instance of an id built by `sub_instance_id`
+pub fn split_sub_instance_id(id: usize) -> (usize, usize) {
+    (id & ((1 << SUB_INSTANCE_SHIFT) - 1), id >> SUB_INSTANCE_SHIFT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sub_instance_zero_keeps_parent_id() {
+        for instance in vec![0, 1, 7, 1 << 20] {
+            assert_eq!(sub_instance_id(instance, 0), instance);
+            assert_eq!(split_sub_instance_id(instance), (instance, 0));
+        }
+    }
+
+    #[test]
+    fn test_sub_instance_ids_round_trip_and_are_disjoint() {
+        let mut seen = std::collections::HashSet::new();
+        for instance in 0..16 {
+            for sub in 0..16 {
+                let id = sub_instance_id(instance, sub);
+                assert!(seen.insert(id));
+                assert_eq!(split_sub_instance_id(id), (instance, sub));
+            }
+        }
+    }
+}
@@ -0,0 +1,467 @@
+use std::collections::{HashMap, HashSet};
+
+use ha_crypto::{aes_hash::{HashState, MerkleTree}, decrypt, hash::{verf_mac, Hash}};
+use lambdaworks_math::polynomial::Polynomial;
+use types::{Replica, WrapperMsg};
+
+use crate::{gc_retention, online_error_correction, session_id, share_commitment, ClientOutputMsg, ClientShare, ClientShares, FieldType, InstanceGC, LargeField, ProtocolField};
+
+/// Outcome of the reconstruction of one secret by the client.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientSecret {
+    /// Position of the secret in the output instance
+    pub index: usize,
+    /// None if the shares do not match the commitment agreed upon by the committee
+    pub secret: Option<LargeField>,
+    /// Parties whose shares of this secret were rejected
+    pub faulty_parties: Vec<Replica>,
+}
+
+/// Secrets of one output instance reconstructed by the client, in the order their shares completed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientOutput {
+    pub protocol: String,
+    pub instance: usize,
+    pub results: Vec<ClientSecret>,
+}
+
+/// Share of a party and, for committed outputs, its nonce share
+type ReceivedShare = (LargeField, Option<LargeField>);
+
+#[derive(Default)]
+struct ClientInstance {
+    /// Commitments reported by each party. The committee agreed upon them once t+1 parties report the same.
+    commitment_votes: HashMap<Replica, Option<Vec<Hash>>>,
+    commitments: Option<Option<Vec<Hash>>>,
+    /// Shares received before the commitments were decided
+    pending: Vec<(Replica, Vec<ClientShare>)>,
+    shares: HashMap<usize, HashMap<Replica, ReceivedShare>>,
+    faulty_shares: HashMap<usize, HashSet<Replica>>,
+    secrets: HashMap<usize, Option<LargeField>>,
+}
+
+impl ClientInstance {
+    /// Every secret of the instance is decided
+    fn decided(&self, num_nodes: usize) -> bool {
+        if let Some(Some(roots)) = self.commitments.as_ref() {
+            return (0..roots.len()).all(|index| self.secrets.contains_key(&index));
+        }
+        // Without commitments the secrets of the instance are only known once every party delivered
+        self.commitment_votes.len() == num_nodes
+            && self.shares.keys().chain(self.faulty_shares.keys()).all(|index| self.secrets.contains_key(index))
+    }
+}
+
+/// Client side of private output delivery.
+/// Verifies the shares sent by the parties against the commitments agreed upon by the committee and reconstructs the secrets.
+/// Secrets without commitments are reconstructed with online error correction.
+/// Instances are pruned once every secret is decided and the retention window has elapsed.
+pub struct ClientReconstructor {
+    num_nodes: usize,
+    num_faults: usize,
+    /// Point at which each party holds its shares, 1..n unless the protocol registered other points
+    default_evaluation_points: Vec<LargeField>,
+    evaluation_points: HashMap<String, Vec<LargeField>>,
+    /// Key shared with each party
+    keys: HashMap<Replica, Vec<u8>>,
+    hash_context: HashState,
+    instances: HashMap<(String, usize), ClientInstance>,
+    gc: InstanceGC<(String, usize)>,
+}
+
+impl ClientReconstructor {
+    pub fn new(num_nodes: usize, num_faults: usize, keys: HashMap<Replica, Vec<u8>>, hash_context: HashState) -> ClientReconstructor {
+        ClientReconstructor {
+            num_nodes: num_nodes,
+            num_faults: num_faults,
+            default_evaluation_points: (1..num_nodes as u64 + 1).map(LargeField::from).collect(),
+            evaluation_points: HashMap::default(),
+            keys: keys,
+            hash_context: hash_context,
+            instances: HashMap::default(),
+            gc: InstanceGC::new(gc_retention()),
+        }
+    }
+
+    /// Registers the points at which the parties hold their shares of the outputs of `protocol`, e.g. the roots of unity when sharing with FFTs.
+    pub fn set_evaluation_points(&mut self, protocol: &str, evaluation_points: Vec<LargeField>) {
+        self.evaluation_points.insert(protocol.to_string(), evaluation_points);
+    }
+
+    fn evaluation_points(&self, protocol: &str) -> &Vec<LargeField> {
+        self.evaluation_points.get(protocol).unwrap_or(&self.default_evaluation_points)
+    }
+
+    /// Processes the shares sent by one party. Returns the secrets that could be reconstructed with them, if any.
+    pub fn process_msg(&mut self, wrapper_msg: WrapperMsg<ClientOutputMsg>) -> Option<ClientOutput> {
+        let sender = wrapper_msg.sender;
+        let key = match self.keys.get(&sender) {
+            Some(key) => key,
+            None => {
+                log::error!("Received client output from unknown party {}", sender);
+                return None;
+            }
+        };
+        let byte_val = bincode::serialize(&wrapper_msg.protmsg).expect("Failed to serialize object");
+        if !verf_mac(&byte_val, key.as_slice(), &wrapper_msg.mac) {
+            log::warn!("MAC Verification failed.");
+            return None;
+        }
+        let msg = wrapper_msg.protmsg;
+        if msg.session != session_id() {
+            log::warn!("Client output from {} belongs to a different session, dropping it", sender);
+            return None;
+        }
+        let shares: ClientShares = match bincode::deserialize(decrypt(key.as_slice(), msg.ciphertext).as_slice()) {
+            Ok(shares) => shares,
+            Err(e) => {
+                log::error!("Unable to decrypt client output from party {}: {}", sender, e);
+                return None;
+            }
+        };
+
+        self.gc.prune(&mut self.instances);
+        let instance_key = (msg.protocol.clone(), msg.instance);
+        if self.gc.is_terminated(&instance_key) {
+            log::debug!("Client output instance {} of {} is already decided, dropping shares of party {}", msg.instance, msg.protocol, sender);
+            return None;
+        }
+        let mut instance = self.instances.remove(&instance_key).unwrap_or_default();
+        if !instance.commitment_votes.contains_key(&sender) {
+            instance.commitment_votes.insert(sender, shares.roots.clone());
+            let votes = instance.commitment_votes.values().filter(|roots| **roots == shares.roots).count();
+            if instance.commitments.is_none() && votes == self.num_faults + 1 {
+                instance.commitments = Some(shares.roots.clone());
+            }
+        }
+        instance.pending.push((sender, shares.shares));
+
+        let mut results = Vec::new();
+        if instance.commitments.is_some() {
+            for (share_sender, shares) in std::mem::take(&mut instance.pending) {
+                results.extend(self.process_shares(&msg.protocol, &mut instance, share_sender, shares));
+            }
+            if instance.decided(self.num_nodes) {
+                self.gc.mark_terminated(instance_key.clone());
+            }
+        }
+        self.instances.insert(instance_key, instance);
+        if results.is_empty() {
+            return None;
+        }
+        log::debug!("Client reconstructed {} secrets of {} instance {}", results.len(), msg.protocol, msg.instance);
+        Some(ClientOutput {
+            protocol: msg.protocol,
+            instance: msg.instance,
+            results: results,
+        })
+    }
+
+    fn process_shares(&self, protocol: &str, instance: &mut ClientInstance, share_sender: Replica, shares: Vec<ClientShare>) -> Vec<ClientSecret> {
+        let roots = instance.commitments.clone().unwrap();
+        let evaluation_points = self.evaluation_points(protocol);
+        let mut results = Vec::new();
+        for client_share in shares.into_iter() {
+            let index = client_share.index;
+            if instance.secrets.contains_key(&index) {
+                // Already reconstructed
+                continue;
+            }
+            if instance.shares.get(&index).map(|shares| shares.contains_key(&share_sender)).unwrap_or(false) {
+                continue;
+            }
+            let received = match roots.as_ref() {
+                Some(roots) => self.verify_committed_share(roots, share_sender, client_share),
+                None => FieldType::from_ser(&client_share.share).map(|share| (share, None)),
+            };
+            let received = match received {
+                Some(received) => received,
+                None => {
+                    log::error!("Share of secret {} sent by party {} failed verification at the client", index, share_sender);
+                    instance.faulty_shares.entry(index).or_default().insert(share_sender);
+                    continue;
+                }
+            };
+            instance.shares.entry(index).or_default().insert(share_sender, received);
+
+            let secret = match roots.as_ref() {
+                Some(roots) => self.reconstruct_committed(instance, evaluation_points, index, roots[index]),
+                None => self.reconstruct_with_error_correction(instance, evaluation_points, index),
+            };
+            if let Some(secret) = secret {
+                instance.secrets.insert(index, secret.clone());
+                let mut faulty_parties: Vec<Replica> = instance.faulty_shares.get(&index).map(|parties| parties.iter().cloned().collect()).unwrap_or_default();
+                faulty_parties.sort();
+                results.push(ClientSecret {
+                    index: index,
+                    secret: secret,
+                    faulty_parties: faulty_parties,
+                });
+            }
+        }
+        results
+    }
+
+    /// Checks the Merkle proof of a share against the agreed commitment of its secret, at the position of the sender.
+    fn verify_committed_share(&self, roots: &Vec<Hash>, share_sender: Replica, client_share: ClientShare) -> Option<ReceivedShare> {
+        if client_share.index >= roots.len() || share_sender >= self.num_nodes {
+            return None;
+        }
+        let (nonce_ser, proof) = client_share.opening?;
+        let share = FieldType::from_ser(&client_share.share)?;
+        let nonce = FieldType::from_ser(&nonce_ser)?;
+        let commitment = share_commitment(&self.hash_context, share_sender, &client_share.share, &nonce_ser);
+        if !proof.validate(&self.hash_context) || proof.item() != commitment || proof.root() != roots[client_share.index] {
+            return None;
+        }
+        Some((share, Some(nonce)))
+    }
+
+    /// Interpolates the verified shares and checks that the dealer committed to shares of a polynomial of degree t.
+    /// Every verified share is the value committed to at the position of its sender, so shares of degree t that do not match the commitment
+    /// wait for the shares of the other positions until n-f shares are verified. Their t+1 honest shares fix the polynomial, so the secret then fails,
+    /// as it does once the verified shares are not of degree t.
+    fn reconstruct_committed(&self, instance: &ClientInstance, evaluation_points: &Vec<LargeField>, index: usize, root: Hash) -> Option<Option<LargeField>> {
+        let shares = instance.shares.get(&index).unwrap();
+        if shares.len() < self.num_faults + 1 {
+            return None;
+        }
+        let mut evaluation_indices = Vec::new();
+        let mut share_poly_shares = Vec::new();
+        let mut nonce_poly_shares = Vec::new();
+        for (rep, (share, nonce)) in shares.iter() {
+            evaluation_indices.push(evaluation_points[*rep].clone());
+            share_poly_shares.push(share.clone());
+            nonce_poly_shares.push(nonce.clone().unwrap());
+        }
+        let share_poly = Polynomial::interpolate(&evaluation_indices, &share_poly_shares).unwrap();
+        let nonce_poly = Polynomial::interpolate(&evaluation_indices, &nonce_poly_shares).unwrap();
+        if share_poly.degree() > self.num_faults || nonce_poly.degree() > self.num_faults {
+            log::error!("Committed shares of secret {} do not lie on a polynomial of degree {}", index, self.num_faults);
+            return Some(None);
+        }
+
+        // Compute and match commitments
+        let all_commitments: Vec<Hash> = evaluation_points.iter().enumerate().map(|(rep, point)| {
+            share_commitment(&self.hash_context, rep, &FieldType::to_ser(&share_poly.evaluate(point)), &FieldType::to_ser(&nonce_poly.evaluate(point)))
+        }).collect();
+        if MerkleTree::new(all_commitments, &self.hash_context).root() == root {
+            Some(Some(share_poly.evaluate(&LargeField::zero())))
+        }
+        else if shares.len() >= self.num_nodes - self.num_faults {
+            log::error!("Reconstructed commitment of secret {} does not match with {} shares, the dealing is inconsistent", index, shares.len());
+            Some(None)
+        }
+        else {
+            log::warn!("Reconstructed commitment of secret {} does not match with {} shares, waiting for more shares", index, shares.len());
+            None
+        }
+    }
+
+    /// Decodes the sharing of degree t once enough shares agree on it.
+    fn reconstruct_with_error_correction(&self, instance: &mut ClientInstance, evaluation_points: &Vec<LargeField>, index: usize) -> Option<Option<LargeField>> {
+        let shares = instance.shares.get(&index).unwrap();
+        let mut senders = Vec::new();
+        let mut evaluation_indices = Vec::new();
+        let mut evaluations = Vec::new();
+        for (rep, (share, _)) in shares.iter() {
+            if *rep >= self.num_nodes {
+                continue;
+            }
+            senders.push(*rep);
+            evaluation_indices.push(evaluation_points[*rep].clone());
+            evaluations.push(share.clone());
+        }
+        match online_error_correction(&evaluation_indices, &evaluations, self.num_faults, self.num_faults) {
+            Some((polynomial, wrong)) => {
+                let faulty_shares = instance.faulty_shares.entry(index).or_default();
+                faulty_shares.extend(wrong.into_iter().map(|pos| senders[pos]));
+                Some(Some(polynomial.evaluate(&LargeField::zero())))
+            },
+            None => {
+                if shares.len() == self.num_nodes {
+                    // All parties delivered and the shares still do not determine a polynomial of degree t
+                    log::error!("Shares of secret {} do not decode to a polynomial of degree {}", index, self.num_faults);
+                    return Some(None);
+                }
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LargeFieldSer;
+    use ha_crypto::encrypt;
+    use std::convert::TryInto;
+
+    const NUM_NODES: usize = 4;
+    const NUM_FAULTS: usize = 1;
+
+    fn hash_context() -> HashState {
+        HashState::new([5u8; 16], [29u8; 16], [23u8; 16])
+    }
+
+    fn keys() -> HashMap<Replica, Vec<u8>> {
+        (0..NUM_NODES).map(|rep| (rep, vec![rep as u8 + 1; 32])).collect()
+    }
+
+    fn client() -> ClientReconstructor {
+        ClientReconstructor::new(NUM_NODES, NUM_FAULTS, keys(), hash_context())
+    }
+
+    fn ser(element: LargeField) -> LargeFieldSer {
+        FieldType::to_ser(&element).try_into().unwrap()
+    }
+
+    fn seal(sender: Replica, instance: usize, shares: ClientShares) -> WrapperMsg<ClientOutputMsg> {
+        seal_protocol("test", sender, instance, shares)
+    }
+
+    fn seal_protocol(protocol: &str, sender: Replica, instance: usize, shares: ClientShares) -> WrapperMsg<ClientOutputMsg> {
+        let key = keys().get(&sender).unwrap().clone();
+        let msg = ClientOutputMsg {
+            session: session_id(),
+            protocol: protocol.to_string(),
+            instance: instance,
+            ciphertext: encrypt(key.as_slice(), bincode::serialize(&shares).unwrap()),
+        };
+        WrapperMsg::new(msg, sender, key.as_slice())
+    }
+
+    /// Shares of `secret` on a polynomial of degree t, with nonces and Merkle proofs of the commitments
+    fn committed_sharing(secret: u64) -> (Vec<ClientShare>, Hash) {
+        inconsistent_sharing(secret, None)
+    }
+
+    /// Like `committed_sharing`, with the share of party `corrupt`, if any, moved off the polynomial before committing
+    fn inconsistent_sharing(secret: u64, corrupt: Option<Replica>) -> (Vec<ClientShare>, Hash) {
+        let hash_context = hash_context();
+        let share_poly = Polynomial::new(&[LargeField::from(secret), LargeField::from(11)]);
+        let nonce_poly = Polynomial::new(&[LargeField::from(3), LargeField::from(5)]);
+        let points: Vec<LargeField> = (1..NUM_NODES as u64 + 1).map(LargeField::from).collect();
+        let mut shares: Vec<LargeField> = points.iter().map(|x| share_poly.evaluate(x)).collect();
+        if let Some(corrupt) = corrupt {
+            shares[corrupt] = shares[corrupt] + LargeField::one();
+        }
+        let nonces: Vec<LargeField> = points.iter().map(|x| nonce_poly.evaluate(x)).collect();
+        let commitments: Vec<Hash> = shares.iter().zip(nonces.iter()).enumerate().map(|(rep, (share, nonce))| {
+            share_commitment(&hash_context, rep, &FieldType::to_ser(share), &FieldType::to_ser(nonce))
+        }).collect();
+        let tree = MerkleTree::new(commitments, &hash_context);
+        let client_shares = (0..NUM_NODES).map(|rep| ClientShare {
+            index: 0,
+            share: ser(shares[rep]),
+            opening: Some((ser(nonces[rep]), tree.gen_proof(rep))),
+        }).collect();
+        (client_shares, tree.root())
+    }
+
+    #[test]
+    fn test_client_rejects_forged_and_tampered_messages() {
+        let mut client = client();
+        let shares = ClientShares { shares: vec![], roots: None };
+
+        let mut forged = seal(1, 0, shares.clone());
+        forged.sender = 2;
+        assert!(client.process_msg(forged).is_none());
+
+        let mut tampered = seal(1, 0, shares);
+        tampered.protmsg.instance = 1;
+        assert!(client.process_msg(tampered).is_none());
+        assert!(client.instances.is_empty());
+    }
+
+    #[test]
+    fn test_client_verifies_shares_against_agreed_commitments() {
+        let mut client = client();
+        let (mut shares, root) = committed_sharing(42);
+        let roots = Some(vec![root]);
+
+        // Party 3 reports a different commitment and a wrong share, party 0 replays the share of party 1
+        let replayed = shares[1].clone();
+        shares[3].share = ser(LargeField::from(7));
+        assert!(client.process_msg(seal(3, 0, ClientShares { shares: vec![shares[3].clone()], roots: Some(vec![[0u8; 32]]) })).is_none());
+        assert!(client.process_msg(seal(1, 0, ClientShares { shares: vec![shares[1].clone()], roots: roots.clone() })).is_none());
+        // Commitments decided by t+1 votes, the buffered shares are verified against them
+        assert!(client.process_msg(seal(0, 0, ClientShares { shares: vec![replayed], roots: roots.clone() })).is_none());
+        let output = client.process_msg(seal(2, 0, ClientShares { shares: vec![shares[2].clone()], roots: roots })).unwrap();
+        assert_eq!(output.results, vec![ClientSecret { index: 0, secret: Some(LargeField::from(42)), faulty_parties: vec![0, 3] }]);
+    }
+
+    #[test]
+    fn test_client_fails_inconsistent_dealing_with_n_minus_f_shares() {
+        let mut client = client();
+        // The off-polynomial share committed at the position of party 3 is never sent
+        let (shares, root) = inconsistent_sharing(42, Some(3));
+        let roots = Some(vec![root]);
+        assert!(client.process_msg(seal(0, 0, ClientShares { shares: vec![shares[0].clone()], roots: roots.clone() })).is_none());
+        assert!(client.process_msg(seal(1, 0, ClientShares { shares: vec![shares[1].clone()], roots: roots.clone() })).is_none());
+        let output = client.process_msg(seal(2, 0, ClientShares { shares: vec![shares[2].clone()], roots: roots })).unwrap();
+        assert_eq!(output.results, vec![ClientSecret { index: 0, secret: None, faulty_parties: vec![] }]);
+    }
+
+    #[test]
+    fn test_client_corrects_errors_without_commitments() {
+        let mut client = client();
+        let share_poly = Polynomial::new(&[LargeField::from(42), LargeField::from(9)]);
+        let mut shares: Vec<LargeField> = (1..NUM_NODES as u64 + 1).map(|x| share_poly.evaluate(&LargeField::from(x))).collect();
+        shares[0] = shares[0] + LargeField::one();
+
+        let mut output = None;
+        for rep in 0..NUM_NODES {
+            let share = ClientShare { index: 0, share: ser(shares[rep]), opening: None };
+            output = client.process_msg(seal(rep, 5, ClientShares { shares: vec![share], roots: None }));
+            if rep < NUM_NODES - 1 {
+                // 2t+1 shares with one of them wrong do not determine the polynomial
+                assert!(output.is_none());
+            }
+        }
+        let output = output.unwrap();
+        assert_eq!((output.protocol.as_str(), output.instance), ("test", 5));
+        assert_eq!(output.results, vec![ClientSecret { index: 0, secret: Some(LargeField::from(42)), faulty_parties: vec![0] }]);
+    }
+
+    /// Shares of `secret` at `points`, without commitments
+    fn uncommitted_shares(secret: u64, points: &[LargeField]) -> Vec<ClientShare> {
+        let share_poly = Polynomial::new(&[LargeField::from(secret), LargeField::from(9)]);
+        points.iter().map(|x| ClientShare { index: 0, share: ser(share_poly.evaluate(x)), opening: None }).collect()
+    }
+
+    #[test]
+    fn test_client_uses_the_evaluation_points_of_each_protocol() {
+        let mut client = client();
+        let roots_of_unity = crate::roots_of_unity::<FieldType>(NUM_NODES);
+        client.set_evaluation_points("fft", roots_of_unity.clone());
+        let fft_shares = uncommitted_shares(42, &roots_of_unity);
+        let default_shares = uncommitted_shares(43, &(1..NUM_NODES as u64 + 1).map(LargeField::from).collect::<Vec<LargeField>>());
+
+        let mut outputs = Vec::new();
+        for rep in 0..NUM_NODES {
+            outputs.extend(client.process_msg(seal_protocol("fft", rep, 1, ClientShares { shares: vec![fft_shares[rep].clone()], roots: None })));
+            outputs.extend(client.process_msg(seal_protocol("test", rep, 1, ClientShares { shares: vec![default_shares[rep].clone()], roots: None })));
+        }
+        let secrets: Vec<(String, Option<LargeField>)> = outputs.into_iter().map(|output| (output.protocol, output.results[0].secret.clone())).collect();
+        assert!(secrets.contains(&("fft".to_string(), Some(LargeField::from(42)))));
+        assert!(secrets.contains(&("test".to_string(), Some(LargeField::from(43)))));
+    }
+
+    #[test]
+    fn test_client_prunes_decided_instances() {
+        let mut client = client();
+        client.gc = InstanceGC::new(std::time::Duration::from_millis(0));
+        let (shares, root) = committed_sharing(42);
+        let roots = Some(vec![root]);
+        assert!(client.process_msg(seal(0, 2, ClientShares { shares: vec![shares[0].clone()], roots: roots.clone() })).is_none());
+        let output = client.process_msg(seal(1, 2, ClientShares { shares: vec![shares[1].clone()], roots: roots.clone() })).unwrap();
+        assert_eq!(output.results[0].secret, Some(LargeField::from(42)));
+        assert!(client.gc.is_terminated(&("test".to_string(), 2)));
+
+        // Late shares of the decided instance do not recreate its state, and its state is dropped
+        assert!(client.process_msg(seal(2, 2, ClientShares { shares: vec![shares[2].clone()], roots: roots })).is_none());
+        assert!(client.instances.is_empty());
+        assert!(client.gc.is_collected(&("test".to_string(), 2)));
+    }
+}
@@ -0,0 +1,202 @@
+use std::ops::{Mul, Sub};
+
+use lambdaworks_math::{field::element::FieldElement, polynomial::Polynomial};
+
+use crate::ProtocolField;
+
+// Decoding of Reed-Solomon codewords with errors, used by the client to reconstruct secrets from shares that carry no commitment.
+
+/// Berlekamp-Welch decoding of a polynomial of degree at most `degree` from evaluations of which at most `max_errors` are wrong.
+/// Requires `points.len() >= degree + 2*max_errors + 1`, returns None if no such polynomial exists.
+pub fn berlekamp_welch<F: ProtocolField>(points: &[FieldElement<F>], evaluations: &[FieldElement<F>], degree: usize, max_errors: usize) -> Option<Polynomial<FieldElement<F>>> {
+    if points.len() != evaluations.len() || points.len() < degree + 2*max_errors + 1 {
+        return None;
+    }
+    // Unknowns: coefficients of Q of degree degree+e, then the coefficients of the monic error locator E of degree e except the leading one
+    // Q(x_i) - y_i*E(x_i) = y_i*x_i^e for every point
+    let q_len = degree + max_errors + 1;
+    let mut matrix = Vec::with_capacity(points.len());
+    let mut rhs = Vec::with_capacity(points.len());
+    for (x, y) in points.iter().zip(evaluations.iter()) {
+        let mut row = Vec::with_capacity(q_len + max_errors);
+        let mut power = FieldElement::<F>::one();
+        for _ in 0..q_len {
+            row.push(power.clone());
+            power = power.mul(x);
+        }
+        let mut power = FieldElement::<F>::one();
+        for _ in 0..max_errors {
+            row.push(FieldElement::<F>::zero().sub(y.mul(&power)));
+            power = power.mul(x);
+        }
+        matrix.push(row);
+        rhs.push(y.mul(&power));
+    }
+    let solution = solve_linear_system(matrix, rhs)?;
+
+    let mut error_locator = solution[q_len..].to_vec();
+    error_locator.push(FieldElement::<F>::one());
+    let (quotient, remainder) = divide_by_monic(&solution[..q_len], &error_locator);
+    if remainder.iter().any(|coeff| *coeff != FieldElement::<F>::zero()) {
+        return None;
+    }
+    Some(Polynomial::new(&quotient))
+}
+
+/// Online error correction of a sharing of degree `degree` among parties of which at most `max_faults` are corrupt.
+/// Succeeds once the evaluations determine the polynomial uniquely, that is when a polynomial of degree `degree` agrees with at least `degree + max_faults + 1` of them.
+/// Returns the polynomial and the positions of the evaluations that do not lie on it.
+pub fn online_error_correction<F: ProtocolField>(points: &[FieldElement<F>], evaluations: &[FieldElement<F>], degree: usize, max_faults: usize) -> Option<(Polynomial<FieldElement<F>>, Vec<usize>)> {
+    if points.len() != evaluations.len() || points.len() < degree + max_faults + 1 {
+        return None;
+    }
+    // With r evaluations, a polynomial agreeing with r-e of them is only guaranteed to be the honest one if r-e >= degree+t+1
+    let max_errors = std::cmp::min(max_faults, points.len() - degree - max_faults - 1);
+    let polynomial = berlekamp_welch(points, evaluations, degree, max_errors)?;
+    let wrong: Vec<usize> = points.iter().zip(evaluations.iter()).enumerate()
+        .filter(|(_, (x, y))| polynomial.evaluate(x) != **y)
+        .map(|(pos, _)| pos)
+        .collect();
+    if points.len() - wrong.len() < degree + max_faults + 1 {
+        return None;
+    }
+    Some((polynomial, wrong))
+}
+
+/// Solves `matrix * x = rhs` with Gaussian elimination, setting free variables to zero. Returns None if the system is inconsistent.
+fn solve_linear_system<F: ProtocolField>(mut matrix: Vec<Vec<FieldElement<F>>>, mut rhs: Vec<FieldElement<F>>) -> Option<Vec<FieldElement<F>>> {
+    let rows = matrix.len();
+    let cols = if rows == 0 { 0 } else { matrix[0].len() };
+    let mut pivot_cols = Vec::new();
+    let mut pivot_row = 0;
+    for col in 0..cols {
+        if pivot_row == rows {
+            break;
+        }
+        let pivot = match (pivot_row..rows).find(|row| matrix[*row][col] != FieldElement::<F>::zero()) {
+            Some(row) => row,
+            None => continue,
+        };
+        matrix.swap(pivot_row, pivot);
+        rhs.swap(pivot_row, pivot);
+
+        // Normalize pivot row
+        let inv = matrix[pivot_row][col].inv().unwrap();
+        for k in col..cols {
+            matrix[pivot_row][k] = matrix[pivot_row][k].mul(&inv);
+        }
+        rhs[pivot_row] = rhs[pivot_row].mul(&inv);
+
+        // Eliminate other rows
+        for row in 0..rows {
+            if row != pivot_row && matrix[row][col] != FieldElement::<F>::zero() {
+                let factor = matrix[row][col].clone();
+                for k in col..cols {
+                    matrix[row][k] = matrix[row][k].sub(factor.mul(&matrix[pivot_row][k]));
+                }
+                rhs[row] = rhs[row].sub(factor.mul(&rhs[pivot_row]));
+            }
+        }
+        pivot_cols.push(col);
+        pivot_row += 1;
+    }
+    // Rows without a pivot must have been reduced to 0 = 0
+    if rhs[pivot_row..].iter().any(|val| *val != FieldElement::<F>::zero()) {
+        return None;
+    }
+    let mut solution = vec![FieldElement::<F>::zero(); cols];
+    for (row, col) in pivot_cols.into_iter().enumerate() {
+        solution[col] = rhs[row].clone();
+    }
+    Some(solution)
+}
+
+/// Long division of `dividend` by the monic polynomial `divisor`, both given by their coefficients in increasing degree.
+fn divide_by_monic<F: ProtocolField>(dividend: &[FieldElement<F>], divisor: &[FieldElement<F>]) -> (Vec<FieldElement<F>>, Vec<FieldElement<F>>) {
+    let divisor_degree = divisor.len() - 1;
+    if dividend.len() <= divisor_degree {
+        return (vec![FieldElement::<F>::zero()], dividend.to_vec());
+    }
+    let mut remainder = dividend.to_vec();
+    let mut quotient = vec![FieldElement::<F>::zero(); dividend.len() - divisor_degree];
+    for pos in (0..quotient.len()).rev() {
+        let coeff = remainder[pos + divisor_degree].clone();
+        for (offset, divisor_coeff) in divisor.iter().enumerate() {
+            remainder[pos + offset] = remainder[pos + offset].sub(coeff.mul(divisor_coeff));
+        }
+        quotient[pos] = coeff;
+    }
+    remainder.truncate(divisor_degree);
+    (quotient, remainder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LargeField;
+    use proptest::{collection, prelude::*};
+
+    fn evaluations_of(coefficients: &[u64], num_nodes: usize) -> (Vec<LargeField>, Vec<LargeField>) {
+        let polynomial = Polynomial::new(&coefficients.iter().map(|c| LargeField::from(*c)).collect::<Vec<LargeField>>());
+        let points: Vec<LargeField> = (1..num_nodes as u64 + 1).map(LargeField::from).collect();
+        let evaluations = points.iter().map(|x| polynomial.evaluate(x)).collect();
+        (points, evaluations)
+    }
+
+    #[test]
+    fn test_online_error_correction_waits_for_enough_agreeing_shares() {
+        // n = 7, t = 2
+        let (points, mut evaluations) = evaluations_of(&[42, 7, 9], 7);
+        evaluations[1] = evaluations[1] + LargeField::one();
+
+        // Five shares with one error cannot be decoded with certainty
+        assert!(online_error_correction(&points[..5], &evaluations[..5], 2, 2).is_none());
+
+        let (polynomial, wrong) = online_error_correction(&points[..6], &evaluations[..6], 2, 2).unwrap();
+        assert_eq!(polynomial.evaluate(&LargeField::zero()), LargeField::from(42));
+        assert_eq!(wrong, vec![1]);
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn prop_online_error_correction_corrects_up_to_t_errors(
+            coefficients in collection::vec(any::<u64>(), 1..5),
+            errors in collection::hash_set(0usize..13, 0..5),
+            offset in 1u64..u32::MAX as u64,
+        ) {
+            let degree = coefficients.len() - 1;
+            let max_faults = 4;
+            let num_nodes = 3*max_faults + 1;
+            let (points, mut evaluations) = evaluations_of(&coefficients, num_nodes);
+            for pos in errors.iter() {
+                evaluations[*pos] = evaluations[*pos] + LargeField::from(offset);
+            }
+            let (polynomial, mut wrong) = online_error_correction(&points, &evaluations, degree, max_faults).unwrap();
+            prop_assert_eq!(polynomial.evaluate(&LargeField::zero()), LargeField::from(coefficients[0]));
+            wrong.sort();
+            let mut expected: Vec<usize> = errors.into_iter().collect();
+            expected.sort();
+            prop_assert_eq!(wrong, expected);
+        }
+
+        #[test]
+        fn prop_berlekamp_welch_output_is_within_error_bound(
+            coefficients in collection::vec(any::<u64>(), 2..4),
+            errors in collection::hash_set(0usize..6, 0..4),
+            offset in 1u64..u32::MAX as u64,
+        ) {
+            // A decoded polynomial may differ from the dealt one beyond the error bound, but never from more than `max_errors` evaluations
+            let degree = coefficients.len() - 1;
+            let (points, mut evaluations) = evaluations_of(&coefficients, degree + 3);
+            for pos in errors.into_iter().filter(|pos| *pos < degree + 3) {
+                evaluations[pos] = evaluations[pos] + LargeField::from(offset);
+            }
+            if let Some(polynomial) = berlekamp_welch(&points, &evaluations, degree, 1) {
+                prop_assert!(polynomial.coefficients.len() <= degree + 1);
+                prop_assert!(points.iter().zip(evaluations.iter()).filter(|(x, y)| polynomial.evaluate(x) != **y).count() <= 1);
+            }
+        }
+    }
+}
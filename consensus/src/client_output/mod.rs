@@ -0,0 +1,97 @@
+use std::{
+    net::{SocketAddr, SocketAddrV4},
+    sync::OnceLock,
+};
+
+use config::Node;
+use fnv::FnvHashMap;
+use ha_crypto::encrypt;
+use network::{
+    plaintcp::{CancelHandler, TcpReceiver, TcpReliableSender},
+    Acknowledgement,
+};
+use tokio::sync::mpsc::Receiver;
+use types::{Replica, WrapperMsg};
+
+use crate::{service_channel, session_id, ClientOutputHandler};
+
+mod msg;
+pub use msg::*;
+
+mod decode;
+pub use decode::*;
+
+mod client;
+pub use client::*;
+
+// Private output delivery. Instead of opening secrets to the committee, parties send their shares encrypted and MAC'd
+// under a key shared with an external client, which verifies them and reconstructs the secrets on its own.
+
+/// The client receives outputs on the port of its `client_addr` endpoint plus this offset.
+pub const CLIENT_OUTPUT_PORT_OFFSET: u16 = 1000;
+
+static CLIENT_OUTPUT_KEY: OnceLock<Vec<u8>> = OnceLock::new();
+
+/// Deliver outputs of services spawned after this call to the client registered as `client_addr` in the config.
+/// `key` is the key this node shares with the client, generated by genconfig with `--client_output`.
+pub fn enable_client_output(key: Vec<u8>) {
+    if CLIENT_OUTPUT_KEY.set(key).is_err() {
+        log::warn!("Client output key was already set, ignoring the new key");
+    }
+}
+
+/// Returns the key shared with the client if private output delivery is enabled.
+pub fn client_output_key() -> Option<Vec<u8>> {
+    CLIENT_OUTPUT_KEY.get().cloned()
+}
+
+pub fn client_output_address(client_addr: &SocketAddr) -> SocketAddr {
+    SocketAddr::new(client_addr.ip(), client_addr.port() + CLIENT_OUTPUT_PORT_OFFSET)
+}
+
+/// Listens for shares sent to the client registered at `client_addr`.
+/// The queue is bounded, so parties sending faster than the client reconstructs are held back at the network layer.
+pub fn listen_for_client_output(client_addr: &SocketAddr) -> Receiver<WrapperMsg<ClientOutputMsg>> {
+    let port = client_output_address(client_addr).port();
+    let listen_address = SocketAddrV4::new("0.0.0.0".parse().unwrap(), port);
+    let (tx_net_to_client, rx_net_to_client) = service_channel();
+    TcpReceiver::<Acknowledgement, WrapperMsg<ClientOutputMsg>, _>::spawn(
+        SocketAddr::V4(listen_address),
+        ClientOutputHandler::new(tx_net_to_client),
+    );
+    rx_net_to_client
+}
+
+/// Sends this node's shares to the client, encrypted and authenticated under the key they share.
+pub struct ClientOutputChannel {
+    myid: Replica,
+    key: Vec<u8>,
+    net_send: TcpReliableSender<Replica, WrapperMsg<ClientOutputMsg>, Acknowledgement>,
+}
+
+impl ClientOutputChannel {
+    /// Connects to the client registered as `client_addr` in the config.
+    /// Returns None unless private output delivery was enabled with `enable_client_output`.
+    pub fn from_config(config: &Node) -> Option<ClientOutputChannel> {
+        let key = client_output_key()?;
+        let mut client_map: FnvHashMap<Replica, SocketAddr> = FnvHashMap::default();
+        client_map.insert(0, client_output_address(&config.client_addr));
+        Some(ClientOutputChannel {
+            myid: config.id,
+            key: key,
+            net_send: TcpReliableSender::<Replica, WrapperMsg<ClientOutputMsg>, Acknowledgement>::with_peers(client_map),
+        })
+    }
+
+    pub async fn send(&mut self, protocol: &str, instance: usize, shares: ClientShares) -> CancelHandler<Acknowledgement> {
+        let shares_ser = bincode::serialize(&shares).expect("Failed to serialize client shares");
+        let msg = ClientOutputMsg {
+            session: session_id(),
+            protocol: protocol.to_string(),
+            instance: instance,
+            ciphertext: encrypt(self.key.as_slice(), shares_ser),
+        };
+        let wrapper_msg = WrapperMsg::new(msg, self.myid, self.key.as_slice());
+        self.net_send.send(0, wrapper_msg).await
+    }
+}
@@ -0,0 +1,43 @@
+use ha_crypto::{aes_hash::{HashState, Proof}, hash::Hash};
+use serde::{Deserialize, Serialize};
+use types::Replica;
+
+use crate::LargeFieldSer;
+
+/// Commitment to the share and nonce share held by the party at `position`, the Merkle leaf of a committed secret.
+/// Hashing the position into the leaf binds every opening to the party the share belongs to.
+pub fn share_commitment(hash_context: &HashState, position: Replica, share: &[u8], nonce: &[u8]) -> Hash {
+    let mut appended_vec = (position as u64).to_be_bytes().to_vec();
+    appended_vec.extend_from_slice(share);
+    appended_vec.extend_from_slice(nonce);
+    hash_context.do_hash_aes(appended_vec.as_slice())
+}
+
+/// Share of one secret delivered to the client.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ClientShare {
+    /// Position of the secret in the output instance
+    pub index: usize,
+    pub share: LargeFieldSer,
+    /// Nonce share and Merkle proof opening the commitment to this share, for protocols that commit to shares
+    pub opening: Option<(LargeFieldSer, Proof)>,
+}
+
+/// Shares of one party in one output instance. Encrypted under the key this party shares with the client.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ClientShares {
+    pub shares: Vec<ClientShare>,
+    /// Merkle roots of the commitments to each secret, as agreed upon by the committee. None for protocols without commitments.
+    pub roots: Option<Vec<Hash>>,
+}
+
+/// Message sent by a party to the client, authenticated by the MAC of `WrapperMsg`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ClientOutputMsg {
+    pub session: u64,
+    /// Name of the protocol delivering the output, instances of different protocols are reconstructed separately
+    pub protocol: String,
+    pub instance: usize,
+    /// `ClientShares`, serialized and encrypted
+    pub ciphertext: Vec<u8>,
+}
@@ -0,0 +1,141 @@
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use ha_crypto::hash::Hash;
+use lambdaworks_math::field::element::FieldElement;
+
+use crate::{DZKProof, FieldType, LargeFieldSer, ProtocolField};
+
+/// Default number of dealings verified together.
+pub const DEFAULT_DZK_BATCH_SIZE: usize = 32;
+
+/// Longest time a dealing waits for its batch to fill up before it is verified anyway.
+pub const DZK_BATCH_DELAY: Duration = Duration::from_millis(5);
+
+static DZK_BATCH_SIZE: AtomicUsize = AtomicUsize::new(DEFAULT_DZK_BATCH_SIZE);
+
+/// Set the batch size used by every service spawned after this call. A batch size of one verifies each dealing on arrival.
+pub fn set_dzk_batch_size(batch_size: usize) {
+    DZK_BATCH_SIZE.store(batch_size.max(1), Ordering::Relaxed);
+}
+
+pub fn dzk_batch_size() -> usize {
+    DZK_BATCH_SIZE.load(Ordering::Relaxed)
+}
+
+/// Arguments of a single DZK check, the proof of one column at the evaluation point of one party.
+#[derive(Debug, Clone)]
+pub struct DZKBatchItem<F: ProtocolField = FieldType> {
+    pub dzk_proof: DZKProof,
+    pub dzk_roots: Vec<Hash>,
+    pub dzk_poly: Vec<LargeFieldSer>,
+    pub column_root: Hash,
+    pub row_share: FieldElement<F>,
+    pub blinding_row_share: FieldElement<F>,
+    pub evaluation_point: FieldElement<F>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, convert::TryInto};
+
+    use ha_crypto::aes_hash::{HashState, MerkleTree};
+    use lambdaworks_math::polynomial::Polynomial;
+
+    use super::*;
+    use crate::{DZKContext, FoldingDZKContext, LargeFieldSSS, LinDZKContext};
+
+    fn folding_context(num_nodes: usize, num_faults: usize) -> FoldingDZKContext {
+        let mut start_degree = num_faults as isize;
+        let mut poly_split_evaluation_map = HashMap::default();
+        while start_degree > 0 {
+            let split_point = if start_degree % 2 == 0 { start_degree / 2 } else { (start_degree + 1) / 2 };
+            start_degree = start_degree - split_point;
+            poly_split_evaluation_map.insert(start_degree, split_point);
+        }
+        FoldingDZKContext {
+            large_field_uv_sss: LargeFieldSSS::new_with_vandermonde(num_faults + 1, num_nodes),
+            hash_context: HashState::new([5u8; 16], [29u8; 16], [23u8; 16]),
+            poly_split_evaluation_map,
+            evaluation_points: (1..num_nodes + 1).collect(),
+            evaluation_domain: None,
+            recon_threshold: num_faults + 1,
+            end_degree_threshold: 1,
+        }
+    }
+
+    fn ser(element: &FieldElement<FieldType>) -> LargeFieldSer {
+        FieldType::to_ser(element).try_into().unwrap()
+    }
+
+    /// Proofs of `num_dealers` dealings at the evaluation point of party `rep`, laid out as in the ACSS dealing
+    fn batch_items(context: &dyn DZKContext, num_dealers: usize, rep: usize) -> Vec<DZKBatchItem> {
+        (0..num_dealers).map(|dealer| {
+            let coefficients: Vec<FieldElement<FieldType>> = (0..context.recon_threshold()).map(|_| FieldType::random_element()).collect();
+            let column_root = context.hash_context().do_hash_aes(format!("column commitment {}", dealer).as_bytes());
+
+            let mut eval_points = Vec::new();
+            let mut trees: Vec<MerkleTree> = Vec::new();
+            let dzk_poly = context.gen_dzk_proof(&mut eval_points, &mut trees, coefficients.clone(), 1, column_root)
+                .iter().map(ser).collect();
+            let evaluation_point = FieldElement::<FieldType>::from((rep + 1) as u64);
+            DZKBatchItem {
+                dzk_proof: DZKProof {
+                    g_0_x: eval_points.iter().map(|points| ser(&points[rep].0)).collect(),
+                    g_1_x: eval_points.iter().map(|points| ser(&points[rep].1)).collect(),
+                    proof: trees.iter().map(|tree| tree.gen_proof(rep)).collect(),
+                },
+                dzk_roots: trees.iter().map(|tree| tree.root()).collect(),
+                dzk_poly: dzk_poly,
+                column_root: column_root,
+                // With a zero share, the aggregated polynomial is the blinding polynomial itself
+                row_share: FieldElement::<FieldType>::zero(),
+                blinding_row_share: Polynomial::new(&coefficients).evaluate(&evaluation_point),
+                evaluation_point: evaluation_point,
+            }
+        }).collect()
+    }
+
+    fn check_batch(context: &dyn DZKContext) {
+        let mut items = batch_items(context, 8, 2);
+        assert!(context.verify_dzk_batch(items.clone()).into_iter().all(|status| status));
+        assert!(context.verify_dzk_batch(Vec::new()).is_empty());
+
+        // Faulty dealings are identified
+        items[3].row_share = FieldElement::<FieldType>::one();
+        items[6].dzk_poly[0] = ser(&FieldType::random_element());
+        let mut expected = vec![true; 8];
+        expected[3] = false;
+        expected[6] = false;
+        assert_eq!(context.verify_dzk_batch(items), expected);
+
+        // Proofs at different evaluation points are combined per point
+        let mut items = batch_items(context, 4, 1);
+        items.extend(batch_items(context, 4, 5));
+        assert!(context.verify_dzk_batch(items.clone()).into_iter().all(|status| status));
+        items[5].blinding_row_share = FieldType::random_element();
+        let mut expected = vec![true; 8];
+        expected[5] = false;
+        assert_eq!(context.verify_dzk_batch(items), expected);
+    }
+
+    #[test]
+    fn test_folding_batch_verification() {
+        let context = folding_context(16, 5);
+        check_batch(&context);
+
+        // A tampered Merkle proof fails the batch
+        let mut items = batch_items(&context, 4, 0);
+        assert!(!items[1].dzk_proof.proof.is_empty());
+        items[1].dzk_proof.g_1_x[0] = ser(&FieldType::random_element());
+        assert_eq!(context.verify_dzk_batch(items), vec![true, false, true, true]);
+    }
+
+    #[test]
+    fn test_linear_batch_verification() {
+        let context = LinDZKContext::from(folding_context(16, 5));
+        check_batch(&context);
+    }
+}
@@ -0,0 +1,313 @@
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+use ha_crypto::{aes_hash::{HashState, MerkleTree}, hash::Hash};
+use lambdaworks_math::field::element::FieldElement;
+use rayon::prelude::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator};
+use types::Replica;
+
+use crate::{cached_inverse_vandermonde, DZKBatchItem, DZKProof, FieldType, FoldingDZKContext, LargeFieldSSS, LargeFieldSer, LinDZKContext, PointBV, ProtocolField, Transcript};
+
+/// Distributed Zero Knowledge proof used by the ACSS dealers to prove the degree of the committed polynomials.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DZKScheme {
+    /// Logarithmic number of folding iterations, constant size broadcast polynomial
+    Folding,
+    /// Broadcasts the aggregated polynomial itself, verified with a single evaluation
+    Linear,
+}
+
+impl FromStr for DZKScheme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<DZKScheme, String> {
+        match s.to_lowercase().as_str() {
+            "folding" => Ok(DZKScheme::Folding),
+            "linear" => Ok(DZKScheme::Linear),
+            _ => Err(format!("Unknown DZK scheme {}, expected folding or linear", s)),
+        }
+    }
+}
+
+static DZK_SCHEME: AtomicU8 = AtomicU8::new(0);
+
+/// Select the DZK scheme used by every service spawned after this call.
+pub fn set_dzk_scheme(scheme: DZKScheme) {
+    let val = match scheme {
+        DZKScheme::Folding => 0,
+        DZKScheme::Linear => 1,
+    };
+    DZK_SCHEME.store(val, Ordering::Relaxed);
+}
+
+pub fn dzk_scheme() -> DZKScheme {
+    match DZK_SCHEME.load(Ordering::Relaxed) {
+        1 => DZKScheme::Linear,
+        _ => DZKScheme::Folding,
+    }
+}
+
+/// Context of the selected DZK scheme, built from the folding context of the service.
+pub fn select_dzk_context<F: ProtocolField>(folding_context: FoldingDZKContext<F>) -> Box<dyn DZKContext<F>> where FieldElement<F>: Send + Sync {
+    match dzk_scheme() {
+        DZKScheme::Folding => Box::new(folding_context),
+        DZKScheme::Linear => Box::new(LinDZKContext::from(folding_context)),
+    }
+}
+
+/// Commitment to a column and its blinding column in a transcript of `domain`, binding the instance.
+/// Each protocol passes its own domain, so the aggregation challenges of different protocols never coincide.
+pub fn dzk_column_commitment(domain: &[u8], instance_id: usize, root: &Hash, blinding_root: &Hash) -> Hash {
+    let mut transcript = Transcript::new(domain);
+    transcript.append_u64(b"instance", instance_id as u64);
+    transcript.append_hash(b"column root", root);
+    transcript.append_hash(b"blinding column root", blinding_root);
+    transcript.state()
+}
+
+/// Challenge the shares of a column are aggregated with.
+/// `commitment` is the state of the transcript in which the dealer committed to the column, and also starts the transcript of the folding iterations.
+pub fn dzk_aggregation_challenge<F: ProtocolField>(commitment: &Hash) -> FieldElement<F> {
+    Transcript::from_state(*commitment).challenge_field_element(b"dzk aggregation")
+}
+
+/// Common interface of the DZK schemes.
+/// A dealer commits to `dzk_roots` and broadcasts `dzk_poly`, and each party checks its `DZKProof` against its own shares.
+pub trait DZKContext<F: ProtocolField = FieldType>: Send + Sync {
+    fn large_field_uv_sss(&self) -> &LargeFieldSSS<F>;
+
+    fn hash_context(&self) -> &HashState;
+
+    fn evaluation_points(&self) -> &Vec<usize>;
+
+    fn recon_threshold(&self) -> usize;
+
+    /// Returns the polynomial to broadcast. The evaluations and Merkle trees of the folding iterations, if any, are pushed into `eval_points` and `trees`.
+    fn gen_dzk_proof(&self,
+        eval_points: &mut Vec<Vec<(FieldElement<F>,FieldElement<F>)>>,
+        trees: &mut Vec<MerkleTree>,
+        coefficients: Vec<FieldElement<F>>,
+        iteration: usize,
+        root: Hash
+    ) -> Vec<FieldElement<F>>;
+
+    fn verify_dzk_proof(&self,
+        dzk_proof: DZKProof,
+        dzk_roots: Vec<Hash>,
+        dzk_poly: Vec<LargeFieldSer>,
+        column_root: Hash,
+        row_share: FieldElement<F>,
+        blinding_row_share: FieldElement<F>,
+        evaluation_point: FieldElement<F>
+    ) -> bool;
+
+    /// Verifies the proofs of all columns at the evaluation point of one party.
+    fn verify_dzk_proof_row(&self,
+        dzk_proofs: Vec<DZKProof>,
+        dzk_roots: Vec<Vec<Hash>>,
+        dzk_polys: Vec<Vec<LargeFieldSer>>,
+        column_roots: Vec<Hash>,
+        row_shares: Vec<FieldElement<F>>,
+        blinding_row_shares: Vec<FieldElement<F>>,
+        evaluation_point: FieldElement<F>
+    ) -> bool {
+        let columns = dzk_proofs.len();
+        if dzk_roots.len() != columns || dzk_polys.len() != columns || column_roots.len() != columns
+            || row_shares.len() != columns || blinding_row_shares.len() != columns {
+            log::error!("DZK proof verification failed because of a mismatch in the number of columns");
+            return false;
+        }
+        for ((dzk_proof, (roots, poly)), (column_root, (share, blinding))) in
+                dzk_proofs.into_iter().zip(dzk_roots.into_iter().zip(dzk_polys.into_iter())).zip(
+                    column_roots.into_iter().zip(row_shares.into_iter().zip(blinding_row_shares.into_iter()))
+            ){
+            if !self.verify_dzk_proof(dzk_proof, roots, poly, column_root, share, blinding, evaluation_point.clone()){
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Verifies the points sent by other parties for this party's column and interpolates the column polynomials from the first `recon_threshold` valid points.
+    /// `domain` is the transcript domain the dealer committed to the columns in.
+    fn verify_dzk_proofs_column(&self,
+        dzk_roots: Vec<Hash>,
+        dzk_poly: Vec<LargeFieldSer>,
+        bv_ready_points: HashMap<Replica,PointBV>,
+        instance_id: usize,
+        domain: &[u8],
+    )-> Option<(Vec<Vec<FieldElement<F>>>, Vec<FieldElement<F>>, Vec<FieldElement<F>>, Vec<FieldElement<F>>)>{
+        let mut column_evaluation_points = Vec::new();
+        let mut nonce_evaluation_points = Vec::new();
+
+        let mut blinding_evaluation_points = Vec::new();
+        let mut blinding_nonce_points = Vec::new();
+
+        let mut valid_indices = Vec::new();
+        for rep in self.evaluation_points().clone().into_iter(){
+            if bv_ready_points.contains_key(&rep){
+                let (column_share,bcolumn_share, dzk_iter) = bv_ready_points.get(&rep).unwrap();
+                // Combine column and blinding column roots
+                let combined_root = dzk_column_commitment(domain, instance_id, &column_share.2.root(), &bcolumn_share.2.root());
+
+                let deser_points: Option<Vec<FieldElement<F>>> = column_share.0.iter().map(|el| F::from_ser(el.as_slice())).collect();
+                let (deser_points, nonce, blinding_point, blinding_nonce) = match (deser_points, F::from_ser(column_share.1.as_slice()), F::from_ser(bcolumn_share.0.as_slice()), F::from_ser(bcolumn_share.1.as_slice())){
+                    (Some(deser_points), Some(nonce), Some(blinding_point), Some(blinding_nonce)) => (deser_points, nonce, blinding_point, blinding_nonce),
+                    _ => {
+                        log::error!("Malformed column points from party {} in ACSS {}", rep, instance_id);
+                        continue;
+                    }
+                };
+                let agg_point = self.gen_agg_poly_dzk(deser_points.clone(), combined_root.clone());
+
+                if self.verify_dzk_proof(dzk_iter.clone() ,
+                                        dzk_roots.clone(),
+                                        dzk_poly.clone(),
+                                        combined_root,
+                                        agg_point,
+                                        blinding_point.clone(),
+                                        FieldElement::<F>::from(rep as u64)){
+                    valid_indices.push(FieldElement::<F>::from(rep as u64));
+                    column_evaluation_points.push(deser_points);
+                    nonce_evaluation_points.push(nonce);
+
+                    blinding_evaluation_points.push(blinding_point);
+                    blinding_nonce_points.push(blinding_nonce);
+                }
+
+                if column_evaluation_points.len() == self.recon_threshold(){
+                    break;
+                }
+            }
+        }
+
+        if column_evaluation_points.len() < self.recon_threshold() {
+            log::error!("Did not receive enough valid points from other parties, abandoning ACSS {}", instance_id);
+            return None;
+        }
+        log::info!("Successfully verified commitments and dZK proofs for column polynomial of ACSS instance {}",instance_id);
+
+        // Interpolate column
+        // The inverse Vandermonde matrix of the valid indices is computed once and cached, interpolating the entire column costs O(n^3) operations otherwise
+        let inverse_vandermonde = cached_inverse_vandermonde(&valid_indices);
+        let uv_sss = self.large_field_uv_sss();
+
+        let poly_coeffs: Vec<Vec<FieldElement<F>>> = column_evaluation_points.into_iter().map(|poly| uv_sss.polynomial_coefficients_with_vandermonde_matrix(&inverse_vandermonde, &poly)).collect();
+        let nonce_coeffs = uv_sss.polynomial_coefficients_with_vandermonde_matrix(&inverse_vandermonde,&nonce_evaluation_points);
+
+        let bpoly_coeffs = uv_sss.polynomial_coefficients_with_precomputed_vandermonde_matrix(&blinding_evaluation_points);
+        let bnonce_coeffs = uv_sss.polynomial_coefficients_with_precomputed_vandermonde_matrix(&blinding_nonce_points);
+
+        return Some((poly_coeffs,nonce_coeffs,bpoly_coeffs,bnonce_coeffs));
+    }
+
+    /// Checks proofs that share their evaluation point and shape with a single combined equation, see `verify_dzk_batch`.
+    /// Schemes without a combined check verify every proof directly.
+    fn verify_dzk_group(&self, items: &[&DZKBatchItem<F>]) -> bool {
+        items.iter().all(|item| {
+            let item = (*item).clone();
+            self.verify_dzk_proof(item.dzk_proof, item.dzk_roots, item.dzk_poly, item.column_root, item.row_share, item.blinding_row_share, item.evaluation_point)
+        })
+    }
+
+    /// Verifies many DZK proofs, possibly of different dealers, together.
+    /// Proofs at the same evaluation point and with the same number of folding iterations are combined with fresh random coefficients,
+    /// so their broadcast polynomials are evaluated once and a faulty proof passes with probability 1/|F|.
+    /// If the combined check of a group fails, every proof of the group is verified individually to identify the faulty ones.
+    fn verify_dzk_batch(&self, items: Vec<DZKBatchItem<F>>) -> Vec<bool> where FieldElement<F>: Send + Sync {
+        let mut groups: HashMap<(Vec<u8>, usize, usize), Vec<usize>> = HashMap::default();
+        for (index, item) in items.iter().enumerate(){
+            groups.entry((F::to_ser(&item.evaluation_point), item.dzk_poly.len(), item.dzk_roots.len())).or_default().push(index);
+        }
+        let groups: Vec<Vec<usize>> = groups.into_iter().map(|(_, group)| group).collect();
+        let failed: Vec<usize> = groups.into_par_iter().filter(|group| {
+            let group_items: Vec<&DZKBatchItem<F>> = group.iter().map(|index| &items[*index]).collect();
+            !self.verify_dzk_group(&group_items)
+        }).flatten().collect();
+
+        let mut statuses = vec![true; items.len()];
+        if failed.is_empty(){
+            return statuses;
+        }
+        log::warn!("Batch verification of {} DZK proofs failed, verifying proofs individually", failed.len());
+        let failed_statuses: Vec<bool> = failed.par_iter().map(|index| {
+            let item = items[*index].clone();
+            self.verify_dzk_proof(item.dzk_proof, item.dzk_roots, item.dzk_poly, item.column_root, item.row_share, item.blinding_row_share, item.evaluation_point)
+        }).collect();
+        for (index, status) in failed.into_iter().zip(failed_statuses.into_iter()){
+            statuses[index] = status;
+        }
+        statuses
+    }
+
+    /// Aggregates the evaluations of a group of polynomials with powers of the challenge derived from `root`.
+    fn gen_agg_poly_dzk(&self, evaluations: Vec<FieldElement<F>>, root: Hash)-> FieldElement<F>{
+        let root_original: FieldElement<F> = dzk_aggregation_challenge::<F>(&root);
+        let mut root_mul_lf = FieldElement::<F>::from(1);
+        let mut aggregated_val = FieldElement::<F>::from(0);
+        for share in evaluations{
+            aggregated_val += &root_mul_lf*share;
+            root_mul_lf = &root_mul_lf*&root_original;
+        }
+        aggregated_val
+    }
+}
+
+impl<F: ProtocolField> DZKContext<F> for FoldingDZKContext<F> where FieldElement<F>: Send + Sync {
+    fn large_field_uv_sss(&self) -> &LargeFieldSSS<F> {
+        &self.large_field_uv_sss
+    }
+
+    fn hash_context(&self) -> &HashState {
+        &self.hash_context
+    }
+
+    fn evaluation_points(&self) -> &Vec<usize> {
+        &self.evaluation_points
+    }
+
+    fn recon_threshold(&self) -> usize {
+        self.recon_threshold
+    }
+
+    fn gen_dzk_proof(&self,
+        eval_points: &mut Vec<Vec<(FieldElement<F>,FieldElement<F>)>>,
+        trees: &mut Vec<MerkleTree>,
+        coefficients: Vec<FieldElement<F>>,
+        iteration: usize,
+        root: Hash
+    ) -> Vec<FieldElement<F>> {
+        FoldingDZKContext::gen_dzk_proof(self, eval_points, trees, coefficients, iteration, root)
+    }
+
+    fn verify_dzk_proof(&self,
+        dzk_proof: DZKProof,
+        dzk_roots: Vec<Hash>,
+        dzk_poly: Vec<LargeFieldSer>,
+        column_root: Hash,
+        row_share: FieldElement<F>,
+        blinding_row_share: FieldElement<F>,
+        evaluation_point: FieldElement<F>
+    ) -> bool {
+        FoldingDZKContext::verify_dzk_proof(self, dzk_proof, dzk_roots, dzk_poly, column_root, row_share, blinding_row_share, evaluation_point)
+    }
+
+    fn verify_dzk_proof_row(&self,
+        dzk_proofs: Vec<DZKProof>,
+        dzk_roots: Vec<Vec<Hash>>,
+        dzk_polys: Vec<Vec<LargeFieldSer>>,
+        column_roots: Vec<Hash>,
+        row_shares: Vec<FieldElement<F>>,
+        blinding_row_shares: Vec<FieldElement<F>>,
+        evaluation_point: FieldElement<F>
+    ) -> bool {
+        FoldingDZKContext::verify_dzk_proof_row(self, dzk_proofs, dzk_roots, dzk_polys, column_roots, row_shares, blinding_row_shares, evaluation_point)
+    }
+
+    fn verify_dzk_group(&self, items: &[&DZKBatchItem<F>]) -> bool {
+        FoldingDZKContext::verify_dzk_group(self, items)
+    }
+}
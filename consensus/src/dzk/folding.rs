@@ -1,40 +1,63 @@
 use std::collections::HashMap;
 
-use ha_crypto::{aes_hash::{MerkleTree, Proof, HashState}, LargeField, hash::Hash, LargeFieldSer};
-use lambdaworks_math::{traits::ByteConversion, unsigned_integer::element::UnsignedInteger};
-use types::Replica;
+use ha_crypto::{aes_hash::{MerkleTree, Proof, HashState}, hash::Hash};
+use lambdaworks_math::field::element::FieldElement;
 
-use crate::{LargeFieldSSS, DZKProof, PointBV};
+use crate::{LargeFieldSSS, DZKBatchItem, DZKProof, LargeFieldSer, FieldType, ProtocolField, Transcript, dzk_aggregation_challenge};
 
 
-pub struct FoldingDZKContext{
-    pub large_field_uv_sss: LargeFieldSSS,
+pub struct FoldingDZKContext<F: ProtocolField = FieldType>{
+    pub large_field_uv_sss: LargeFieldSSS<F>,
     pub hash_context: HashState,
     pub poly_split_evaluation_map: HashMap<isize,isize>,
     pub evaluation_points: Vec<usize>,
+    /// Field points the folded polynomials are evaluated at instead of `evaluation_points`, the roots of unity on the FFT sharing path
+    pub evaluation_domain: Option<Vec<FieldElement<F>>>,
     pub recon_threshold: usize,
     pub end_degree_threshold: usize,
 }
 
-impl FoldingDZKContext{
+impl<F: ProtocolField> FoldingDZKContext<F> where FieldElement<F>: Send + Sync{
+    /// Evaluation point of each party, in party order
+    pub fn domain(&self) -> Vec<FieldElement<F>>{
+        match self.evaluation_domain.as_ref(){
+            Some(domain) => domain.clone(),
+            None => self.evaluation_points.iter().map(|x| FieldElement::<F>::from(*x as u64)).collect()
+        }
+    }
+
+    /// Absorbs the Merkle root of a folding iteration and squeezes the challenge the polynomial is folded with.
+    fn folding_challenge(transcript: &mut Transcript, merkle_root: &Hash) -> FieldElement<F>{
+        transcript.append_hash(b"dzk folding root", merkle_root);
+        transcript.challenge_field_element(b"dzk folding challenge")
+    }
+
+    /// Folding challenges of a proof for the column committed to by `column_root`, last iteration first.
+    fn rev_folding_challenges(column_root: Hash, dzk_roots: &Vec<Hash>) -> Vec<FieldElement<F>>{
+        let mut transcript = Transcript::from_state(column_root);
+        let mut challenges: Vec<FieldElement<F>> = dzk_roots.iter().map(|root| Self::folding_challenge(&mut transcript, root)).collect();
+        challenges.reverse();
+        challenges
+    }
+
     // Distributed Zero Knowledge Proofs follow a recursive structure. 
     pub fn gen_dzk_proof(&self, 
-        eval_points: &mut Vec<Vec<(LargeField,LargeField)>>, 
+        eval_points: &mut Vec<Vec<(FieldElement<F>,FieldElement<F>)>>, 
         trees: &mut Vec<MerkleTree>, 
-        coefficients: Vec<LargeField>, 
+        coefficients: Vec<FieldElement<F>>, 
         iteration: usize, 
         root: Hash
-    ) -> Vec<LargeField>{
+    ) -> Vec<FieldElement<F>>{
         if coefficients.len()-1 <= self.end_degree_threshold{
             return coefficients;
         }
         
         // 1. Create a Merkle Tree if the polynomial is big enough
-        let evaluations: Vec<LargeField> = self.evaluation_points.clone().into_iter().map(|x| self.large_field_uv_sss.mod_evaluate_at(&coefficients, x)).collect();
-        let hashes: Vec<Hash> = evaluations.iter().map(|x| self.hash_context.do_hash_aes(x.to_bytes_be().as_slice())).collect();
+        let domain = self.domain();
+        let evaluations: Vec<FieldElement<F>> = domain.iter().map(|x| self.large_field_uv_sss.mod_evaluate_at_lf(&coefficients, x.clone())).collect();
+        let hashes: Vec<Hash> = evaluations.iter().map(|x| self.hash_context.do_hash_aes(F::to_ser(x).as_slice())).collect();
         let merkle_tree = MerkleTree::new(hashes, &self.hash_context);
         let next_root = merkle_tree.root();
-        let aggregated_root_hash = self.hash_context.hash_two(root, merkle_tree.root().clone());
         trees.push(merkle_tree);
 
         // 2. Split polynomial in half
@@ -50,19 +73,20 @@ impl FoldingDZKContext{
         let second_half_coeff = first_half_coeff.split_off(split_point);
         
         // 3. Calculate evaluation points on both split polynomials
-        let g_vals: Vec<(LargeField,LargeField)> = self.evaluation_points.clone().into_iter().map(|rep| 
-            (self.large_field_uv_sss.mod_evaluate_at(&first_half_coeff, rep),
-            self.large_field_uv_sss.mod_evaluate_at(&second_half_coeff, rep))
+        let g_vals: Vec<(FieldElement<F>,FieldElement<F>)> = domain.into_iter().map(|rep| 
+            (self.large_field_uv_sss.mod_evaluate_at_lf(&first_half_coeff, rep.clone()),
+            self.large_field_uv_sss.mod_evaluate_at_lf(&second_half_coeff, rep))
         ).collect();
         eval_points.push(g_vals.clone());
         
         // 4. Compute coefficients for next iteration
         
-        // 4.a. Compute updated Merkle root
-        let next_root = self.hash_context.hash_two(root, next_root);
-        let root_bint = LargeField::from_bytes_be(next_root.as_slice()).unwrap();
+        // 4.a. Absorb the Merkle root into the transcript and derive the folding challenge
+        let mut transcript = Transcript::from_state(root);
+        let root_bint = Self::folding_challenge(&mut transcript, &next_root);
+        let aggregated_root_hash = transcript.state();
         
-        let mut poly_folded:Vec<LargeField> = second_half_coeff.into_iter().map(|coeff| (coeff*&root_bint)).collect();
+        let mut poly_folded:Vec<FieldElement<F>> = second_half_coeff.into_iter().map(|coeff| (coeff*&root_bint)).collect();
         for (index, coeff) in (0..first_half_coeff.len()).into_iter().zip(first_half_coeff.into_iter()){
             poly_folded[index] += coeff;
         }
@@ -72,114 +96,33 @@ impl FoldingDZKContext{
         return self.gen_dzk_proof(eval_points, trees, poly_folded, iteration+1, aggregated_root_hash);
     }
 
-    pub fn verify_dzk_proofs_column(&self, 
-        dzk_roots: Vec<Hash>, 
-        dzk_poly: Vec<LargeFieldSer>, 
-        bv_ready_points: HashMap<Replica,PointBV>,
-        instance_id: usize,
-    )-> Option<(Vec<Vec<LargeField>>, Vec<LargeField>, Vec<LargeField>, Vec<LargeField>)>{
-        let mut column_evaluation_points = Vec::new();
-        let mut nonce_evaluation_points = Vec::new();
-
-        let mut blinding_evaluation_points = Vec::new();
-        let mut blinding_nonce_points = Vec::new();
-
-        //let bv_echo_points = acss_va_state.bv_echo_points.clone();
-        //let dzk_roots = comm.dzk_roots[self.myid].clone();
-        //let dzk_poly = comm.polys[self.myid].clone();
-        let mut valid_indices = Vec::new();
-        for rep in self.evaluation_points.clone().into_iter(){
-            if bv_ready_points.contains_key(&rep){
-                let (column_share,bcolumn_share, dzk_iter) = bv_ready_points.get(&rep).unwrap();
-                // Combine column and blinding column roots
-                let combined_root = self.hash_context.hash_two(column_share.2.root(), bcolumn_share.2.root());
-                
-                let deser_points: Vec<LargeField> = column_share.0.clone().into_iter().map(|el| LargeField::from_bytes_be(el.as_slice()).unwrap()).collect();
-                let agg_point = self.gen_agg_poly_dzk(deser_points.clone(), combined_root.clone());
-
-                let nonce = LargeField::from_bytes_be(column_share.1.as_slice()).unwrap();
-
-                let blinding_point = LargeField::from_bytes_be(bcolumn_share.0.as_slice()).unwrap(); 
-                let blinding_nonce = LargeField::from_bytes_be(bcolumn_share.1.as_slice()).unwrap();
-
-                if self.verify_dzk_proof(dzk_iter.clone() , 
-                                        dzk_roots.clone(), 
-                                        dzk_poly.clone(), 
-                                        combined_root, 
-                                        agg_point.clone(), 
-                                        blinding_point.clone(), 
-                                        rep){
-                    valid_indices.push(LargeField::from(rep as u64));
-                    column_evaluation_points.push(deser_points);
-                    nonce_evaluation_points.push(nonce);
-
-                    blinding_evaluation_points.push(blinding_point.clone());
-                    blinding_nonce_points.push( blinding_nonce);
-                }
-                
-                if column_evaluation_points.len() == self.recon_threshold{
-                    break;
-                }
-            }
-        }
-
-        if column_evaluation_points.len() < self.recon_threshold {
-            log::error!("Did not receive enough valid points from other parties, abandoning ACSS {}", instance_id);
-            return None;
-        }
-        log::info!("Successfully verified commitments and dZK proofs for column polynomial of ACSS instance {}",instance_id);
-        // Re borrow here
-        //let acss_va_state = self.acss_state.get_mut(&instance_id).unwrap();
-
-        // Interpolate column
-        // Compute Vandermonde matrix here once. No other choice but to compute. If we have to interpolate the entire column, then it must cost O(n^3) operations
-        let vandermonde_matrix_lt =  LargeFieldSSS::vandermonde_matrix(valid_indices);
-        let inverse_vandermonde = LargeFieldSSS::inverse_vandermonde(vandermonde_matrix_lt);
-
-        let poly_coeffs: Vec<Vec<LargeField>> = column_evaluation_points.into_iter().map(|poly| self.large_field_uv_sss.polynomial_coefficients_with_vandermonde_matrix(&inverse_vandermonde, &poly)).collect();
-        //let poly_coeffs = self.large_field_uv_sss.polynomial_coefficients_with_precomputed_vandermonde_matrix(&column_evaluation_points);
-        let nonce_coeffs = self.large_field_uv_sss.polynomial_coefficients_with_vandermonde_matrix(&inverse_vandermonde,&nonce_evaluation_points);
-
-        let bpoly_coeffs = self.large_field_uv_sss.polynomial_coefficients_with_precomputed_vandermonde_matrix(&blinding_evaluation_points);
-        let bnonce_coeffs = self.large_field_uv_sss.polynomial_coefficients_with_precomputed_vandermonde_matrix(&blinding_nonce_points);
-
-        return Some((poly_coeffs,nonce_coeffs,bpoly_coeffs,bnonce_coeffs));
-    }
-
     pub fn verify_dzk_proof_alt(&self,
         dzk_proof: DZKProof, 
         dzk_roots: Vec<Hash>, 
         dzk_poly: Vec<LargeFieldSer>, 
         column_root: Hash, 
-        row_share: LargeField,
+        row_share: FieldElement<F>,
         evaluation_point: usize
     ) -> bool{
         // Verify dzk proof finally
         // Start from the lowest level
-        // Calculate aggregated roots first
-        let mut rev_agg_roots: Vec<Hash> = Vec::new();
+        // Calculate the folding challenges first
         let mut rev_roots: Vec<Hash> = Vec::new();
 
         let dzk_share = row_share;
         
-        // First root comes from the share and blinding polynomials
-        let mut agg_root = column_root;
-        let mut aggregated_roots = Vec::new();
-        for index in 0..dzk_roots.len(){
-            agg_root = self.hash_context.hash_two(agg_root , dzk_roots[index]);
-            aggregated_roots.push(agg_root.clone());
-        }
-        rev_agg_roots.extend(aggregated_roots.into_iter().rev());
+        // The transcript starts from the commitment to the share and blinding polynomials
+        let rev_challenges = Self::rev_folding_challenges(column_root, &dzk_roots);
         rev_roots.extend(dzk_roots.into_iter().rev());
         
-        let first_poly: Vec<LargeField> = dzk_poly.into_iter().map(|x| LargeField::from_bytes_be(x.as_slice()).unwrap()).collect();
+        let first_poly: Vec<FieldElement<F>> = dzk_poly.into_iter().map(|x| F::from_ser(x.as_slice()).unwrap()).collect();
         let mut degree_poly = first_poly.len()-1;
 
         // Evaluate points according to this polynomial
         let mut point = self.large_field_uv_sss.mod_evaluate_at(first_poly.as_slice(), evaluation_point);
 
-        let g_0_pts: Vec<LargeField> = dzk_proof.g_0_x.into_iter().rev().map(|x | LargeField::from_bytes_be(x.as_slice()).unwrap()).collect();
-        let g_1_pts: Vec<LargeField> = dzk_proof.g_1_x.into_iter().rev().map(|x| LargeField::from_bytes_be(x.as_slice()).unwrap()).collect();
+        let g_0_pts: Vec<FieldElement<F>> = dzk_proof.g_0_x.into_iter().rev().map(|x | F::from_ser(x.as_slice()).unwrap()).collect();
+        let g_1_pts: Vec<FieldElement<F>> = dzk_proof.g_1_x.into_iter().rev().map(|x| F::from_ser(x.as_slice()).unwrap()).collect();
         let proofs: Vec<Proof> = dzk_proof.proof.into_iter().rev().collect();
         
         for (index, (g_0, g_1)) in (0..g_0_pts.len()).into_iter().zip(g_0_pts.into_iter().zip(g_1_pts.into_iter())){
@@ -187,7 +130,7 @@ impl FoldingDZKContext{
             
             // First, Compute Fiat-Shamir Heuristic point
             // log::info!("Aggregated Root Hash: {:?}, g_0: {:?}, g_1: {:?}, poly_folded: {:?}", rev_agg_root_vec[index], g_0, g_1, first_poly);
-            let root = LargeField::from_bytes_be(rev_agg_roots[index].as_slice()).unwrap();
+            let root = rev_challenges[index].clone();
             
             let fiat_shamir_hs_point = &g_0 + &root*&g_1;
             if point != fiat_shamir_hs_point{
@@ -199,8 +142,8 @@ impl FoldingDZKContext{
             // Where was the polynomial split?
             let split_point = *self.poly_split_evaluation_map.get(&(degree_poly as isize)).unwrap() as usize;
 
-            let pt_bigint = LargeField::from(evaluation_point as u64);
-            let pow_bigint = pt_bigint.pow(UnsignedInteger::<4>::from(split_point as u64));
+            let pt_bigint = FieldElement::<F>::from(evaluation_point as u64);
+            let pow_bigint = pt_bigint.pow(split_point as u64);
             //let pow_bigint = LargeFieldSSS::mod_pow(&pt_bigint,&LargeField::from(split_point), &self.large_field_uv_sss.prime);
             let agg_point = &g_0 + &pow_bigint*&g_1;
             point = agg_point;
@@ -211,11 +154,11 @@ impl FoldingDZKContext{
             let merkle_proof = &proofs[index];
             if !merkle_proof.validate(
                 &self.hash_context) || 
-                    self.hash_context.do_hash_aes(point.to_bytes_be().as_slice()) !=  merkle_proof.item()|| 
+                    self.hash_context.do_hash_aes(F::to_ser(&point).as_slice()) !=  merkle_proof.item()|| 
                     rev_roots[index] != merkle_proof.root(){
                 log::error!("DZK Proof verification failed while verifying Merkle Proof validity at iteration {}", index);
                 log::error!("Merkle root matching: computed: {:?}  given: {:?}",rev_roots[index].clone(),merkle_proof.root());
-                log::error!("Items: {:?}  given: {:?}",merkle_proof.item(),self.hash_context.do_hash_aes(point.to_bytes_be().as_slice()));
+                log::error!("Items: {:?}  given: {:?}",merkle_proof.item(),self.hash_context.do_hash_aes(F::to_ser(&point).as_slice()));
                 return false; 
             }
         }
@@ -233,37 +176,30 @@ impl FoldingDZKContext{
         dzk_roots: Vec<Hash>, 
         dzk_poly: Vec<LargeFieldSer>, 
         column_root: Hash, 
-        row_share: LargeField, 
-        blinding_row_share: LargeField, 
-        evaluation_point: usize
+        row_share: FieldElement<F>, 
+        blinding_row_share: FieldElement<F>, 
+        evaluation_point: FieldElement<F>
     ) -> bool{
         // Verify dzk proof finally
         // Start from the lowest level
-        // Calculate aggregated roots first
-        let mut rev_agg_roots: Vec<Hash> = Vec::new();
+        // Calculate the folding challenges first
         let mut rev_roots: Vec<Hash> = Vec::new();
 
-        let root_bint = LargeField::from_bytes_be(column_root.as_slice()).unwrap();
+        let root_bint = dzk_aggregation_challenge::<F>(&column_root);
         let dzk_share = blinding_row_share + root_bint*row_share;
         
-        // First root comes from the share and blinding polynomials
-        let mut agg_root = column_root;
-        let mut aggregated_roots = Vec::new();
-        for index in 0..dzk_roots.len(){
-            agg_root = self.hash_context.hash_two(agg_root , dzk_roots[index]);
-            aggregated_roots.push(agg_root.clone());
-        }
-        rev_agg_roots.extend(aggregated_roots.into_iter().rev());
+        // The transcript starts from the commitment to the share and blinding polynomials
+        let rev_challenges = Self::rev_folding_challenges(column_root, &dzk_roots);
         rev_roots.extend(dzk_roots.into_iter().rev());
         
-        let first_poly: Vec<LargeField> = dzk_poly.into_iter().map(|x| LargeField::from_bytes_be(x.as_slice()).unwrap()).collect();
+        let first_poly: Vec<FieldElement<F>> = dzk_poly.into_iter().map(|x| F::from_ser(x.as_slice()).unwrap()).collect();
         let mut degree_poly = first_poly.len()-1;
 
         // Evaluate points according to this polynomial
-        let mut point = self.large_field_uv_sss.mod_evaluate_at(first_poly.as_slice(), evaluation_point);
+        let mut point = self.large_field_uv_sss.mod_evaluate_at_lf(first_poly.as_slice(), evaluation_point.clone());
 
-        let g_0_pts: Vec<LargeField> = dzk_proof.g_0_x.into_iter().rev().map(|x | LargeField::from_bytes_be(x.as_slice()).unwrap()).collect();
-        let g_1_pts: Vec<LargeField> = dzk_proof.g_1_x.into_iter().rev().map(|x| LargeField::from_bytes_be(x.as_slice()).unwrap()).collect();
+        let g_0_pts: Vec<FieldElement<F>> = dzk_proof.g_0_x.into_iter().rev().map(|x | F::from_ser(x.as_slice()).unwrap()).collect();
+        let g_1_pts: Vec<FieldElement<F>> = dzk_proof.g_1_x.into_iter().rev().map(|x| F::from_ser(x.as_slice()).unwrap()).collect();
         let proofs: Vec<Proof> = dzk_proof.proof.into_iter().rev().collect();
         
         for (index, (g_0, g_1)) in (0..g_0_pts.len()).into_iter().zip(g_0_pts.into_iter().zip(g_1_pts.into_iter())){
@@ -271,7 +207,7 @@ impl FoldingDZKContext{
             
             // First, Compute Fiat-Shamir Heuristic point
             // log::info!("Aggregated Root Hash: {:?}, g_0: {:?}, g_1: {:?}, poly_folded: {:?}", rev_agg_root_vec[index], g_0, g_1, first_poly);
-            let root = LargeField::from_bytes_be(rev_agg_roots[index].as_slice()).unwrap();
+            let root = rev_challenges[index].clone();
             
             let fiat_shamir_hs_point = &g_0 + &root*&g_1;
             if point != fiat_shamir_hs_point{
@@ -283,8 +219,8 @@ impl FoldingDZKContext{
             // Where was the polynomial split?
             let split_point = *self.poly_split_evaluation_map.get(&(degree_poly as isize)).unwrap() as usize;
 
-            let pt_bigint = LargeField::from(evaluation_point as u64);
-            let pow_bigint = pt_bigint.pow(UnsignedInteger::<4>::from(split_point as u64));
+            let pt_bigint = evaluation_point.clone();
+            let pow_bigint = pt_bigint.pow(split_point as u64);
             //let pow_bigint = LargeFieldSSS::mod_pow(&pt_bigint,&LargeField::from(split_point), &self.large_field_uv_sss.prime);
             let agg_point = &g_0 + &pow_bigint*&g_1;
             point = agg_point;
@@ -295,36 +231,113 @@ impl FoldingDZKContext{
             let merkle_proof = &proofs[index];
             if !merkle_proof.validate(
                 &self.hash_context) || 
-                    self.hash_context.do_hash_aes(point.to_bytes_be().as_slice()) !=  merkle_proof.item()|| 
+                    self.hash_context.do_hash_aes(F::to_ser(&point).as_slice()) !=  merkle_proof.item()|| 
                     rev_roots[index] != merkle_proof.root(){
                 log::error!("DZK Proof verification failed while verifying Merkle Proof validity at iteration {}", index);
                 log::error!("Merkle root matching: computed: {:?}  given: {:?}",rev_roots[index].clone(),merkle_proof.root());
-                log::error!("Items: {:?}  given: {:?}",merkle_proof.item(),self.hash_context.do_hash_aes(point.to_bytes_be().as_slice()));
+                log::error!("Items: {:?}  given: {:?}",merkle_proof.item(),self.hash_context.do_hash_aes(F::to_ser(&point).as_slice()));
                 return false; 
             }
         }
         // Verify final point's equality with the original accumulated point
         if point != dzk_share{
-            log::error!("DZK Point does not match the first level point {:?} {:?} for {}'s column", point, dzk_share, evaluation_point);
+            log::error!("DZK Point does not match the first level point {:?} {:?} for {:?}'s column", point, dzk_share, evaluation_point);
             return false;
         }
         true
     }
 
+    /// Verifies proofs at the same evaluation point and with the same number of folding iterations together.
+    /// The Merkle proofs are bound to each proof and are checked one by one, while the broadcast polynomials and the folded points of every iteration
+    /// are combined with random coefficients, so the broadcast polynomial is evaluated once and every folding equation is checked once for the whole group.
+    pub fn verify_dzk_group(&self, items: &[&DZKBatchItem<F>]) -> bool{
+        let first = match items.first(){
+            Some(first) => first,
+            None => return true,
+        };
+        let evaluation_point = first.evaluation_point.clone();
+        let num_iterations = first.dzk_roots.len();
+        let poly_len = first.dzk_poly.len();
+        if poly_len == 0{
+            return false;
+        }
+        // The polynomials of the group have the same degree, hence the same split points in every iteration
+        let mut degree_poly = poly_len-1;
+        let mut split_powers = Vec::with_capacity(num_iterations);
+        for _ in 0..num_iterations{
+            let split_point = match self.poly_split_evaluation_map.get(&(degree_poly as isize)){
+                Some(split_point) => *split_point as usize,
+                None => return false,
+            };
+            split_powers.push(evaluation_point.pow(split_point as u64));
+            degree_poly = degree_poly + split_point;
+        }
+
+        let zero = FieldElement::<F>::zero();
+        let mut combined_poly = vec![zero.clone(); poly_len];
+        let mut combined_g_0 = vec![zero.clone(); num_iterations];
+        let mut combined_g_1 = vec![zero.clone(); num_iterations];
+        let mut combined_challenge_g_1 = vec![zero.clone(); num_iterations];
+        let mut combined_share = zero;
+        for item in items.iter(){
+            if item.evaluation_point != evaluation_point || item.dzk_poly.len() != poly_len || item.dzk_roots.len() != num_iterations
+                    || item.dzk_proof.g_0_x.len() != num_iterations || item.dzk_proof.g_1_x.len() != num_iterations || item.dzk_proof.proof.len() != num_iterations{
+                return false;
+            }
+            let first_poly: Option<Vec<FieldElement<F>>> = item.dzk_poly.iter().map(|x| F::from_ser(x.as_slice())).collect();
+            let g_0_pts: Option<Vec<FieldElement<F>>> = item.dzk_proof.g_0_x.iter().rev().map(|x| F::from_ser(x.as_slice())).collect();
+            let g_1_pts: Option<Vec<FieldElement<F>>> = item.dzk_proof.g_1_x.iter().rev().map(|x| F::from_ser(x.as_slice())).collect();
+            let (first_poly, g_0_pts, g_1_pts) = match (first_poly, g_0_pts, g_1_pts){
+                (Some(first_poly), Some(g_0_pts), Some(g_1_pts)) => (first_poly, g_0_pts, g_1_pts),
+                _ => return false
+            };
+            let rev_challenges = Self::rev_folding_challenges(item.column_root, &item.dzk_roots);
+            let rho = F::random_element();
+
+            for (combined, coefficient) in combined_poly.iter_mut().zip(first_poly.into_iter()){
+                *combined += &rho*coefficient;
+            }
+            for (index, (g_0, g_1)) in g_0_pts.into_iter().zip(g_1_pts.into_iter()).enumerate(){
+                // The point before folding is committed to in the Merkle tree of the iteration
+                let point = &g_0 + &split_powers[index]*&g_1;
+                let merkle_proof = &item.dzk_proof.proof[num_iterations-1-index];
+                if !merkle_proof.validate(&self.hash_context) ||
+                        self.hash_context.do_hash_aes(F::to_ser(&point).as_slice()) != merkle_proof.item() ||
+                        item.dzk_roots[num_iterations-1-index] != merkle_proof.root(){
+                    return false;
+                }
+                combined_challenge_g_1[index] += &rho*&rev_challenges[index]*&g_1;
+                combined_g_0[index] += &rho*g_0;
+                combined_g_1[index] += &rho*g_1;
+            }
+            let root_bint = dzk_aggregation_challenge::<F>(&item.column_root);
+            combined_share += rho*(&item.blinding_row_share + root_bint*&item.row_share);
+        }
+
+        let mut point = self.large_field_uv_sss.mod_evaluate_at_lf(combined_poly.as_slice(), evaluation_point);
+        for index in 0..num_iterations{
+            if point != &combined_g_0[index] + &combined_challenge_g_1[index]{
+                return false;
+            }
+            point = &combined_g_0[index] + &split_powers[index]*&combined_g_1[index];
+        }
+        point == combined_share
+    }
+
     pub fn verify_dzk_proof_row(&self, 
                         dzk_proofs: Vec<DZKProof>, 
                         dzk_roots: Vec<Vec<Hash>>,
                         dzk_polys: Vec<Vec<LargeFieldSer>>, 
                         column_roots: Vec<Hash>, 
-                        row_shares: Vec<LargeField>, 
-                        blinding_row_shares: Vec<LargeField>,
-                        evaluation_point: usize
+                        row_shares: Vec<FieldElement<F>>, 
+                        blinding_row_shares: Vec<FieldElement<F>>,
+                        evaluation_point: FieldElement<F>
                     )-> bool{
         // Verify dzk proof finally
         // Start from the lowest level
         let roots = dzk_roots.clone();
-        // Calculate aggregated roots first
-        let mut rev_agg_roots: Vec<Vec<Hash>> = Vec::new();
+        // Calculate the folding challenges first
+        let mut rev_challenges: Vec<Vec<FieldElement<F>>> = Vec::new();
         let mut rev_roots: Vec<Vec<Hash>> = Vec::new();
 
         let mut dzk_shares = Vec::new();
@@ -332,34 +345,28 @@ impl FoldingDZKContext{
                 (roots.into_iter().zip(column_roots.into_iter())).zip(
                     row_shares.into_iter().zip(blinding_row_shares.into_iter())
             ){
-            let root_bint = LargeField::from_bytes_be(first_root.as_slice()).unwrap();
+            let root_bint = dzk_aggregation_challenge::<F>(&first_root);
             let dzk_share = blinding + root_bint*share;
             
             dzk_shares.push(dzk_share);
-            // First root comes from the share and blinding polynomials
-            let mut agg_root = first_root;
-            let mut aggregated_roots = Vec::new();
-            for index in 0..ind_roots.len(){
-                agg_root = self.hash_context.hash_two(agg_root , ind_roots[index]);
-                aggregated_roots.push(agg_root.clone());
-            }
-            rev_agg_roots.push(aggregated_roots.into_iter().rev().collect());
+            // The transcript starts from the commitment to the share and blinding polynomials
+            rev_challenges.push(Self::rev_folding_challenges(first_root, &ind_roots));
             rev_roots.push(ind_roots.into_iter().rev().collect());
         }
         let mut _rep = 0;
-        for ((dzk_proof, first_poly),((rev_agg_root_vec,rev_root_vec),dzk_share)) in 
+        for ((dzk_proof, first_poly),((rev_challenge_vec,rev_root_vec),dzk_share)) in 
                     (dzk_proofs.into_iter().zip(dzk_polys.into_iter())).zip(
-                        (rev_agg_roots.into_iter().zip(rev_roots.into_iter())).zip(dzk_shares.into_iter())
+                        (rev_challenges.into_iter().zip(rev_roots.into_iter())).zip(dzk_shares.into_iter())
                     ){
             // These are the coefficients of the polynomial
             //log::info!("DZK verification Hashes {:?} for rep {}", rev_agg_root_vec, rep);
-            let first_poly: Vec<LargeField> = first_poly.into_iter().map(|x| LargeField::from_bytes_be(x.as_slice()).unwrap()).collect();
+            let first_poly: Vec<FieldElement<F>> = first_poly.into_iter().map(|x| F::from_ser(x.as_slice()).unwrap()).collect();
             let mut degree_poly = first_poly.len()-1;
             // Evaluate points according to this polynomial
-            let mut point = self.large_field_uv_sss.mod_evaluate_at(first_poly.as_slice(), evaluation_point);
+            let mut point = self.large_field_uv_sss.mod_evaluate_at_lf(first_poly.as_slice(), evaluation_point.clone());
 
-            let g_0_pts: Vec<LargeField> = dzk_proof.g_0_x.into_iter().rev().map(|x | LargeField::from_bytes_be(x.as_slice()).unwrap()).collect();
-            let g_1_pts: Vec<LargeField> = dzk_proof.g_1_x.into_iter().rev().map(|x| LargeField::from_bytes_be(x.as_slice()).unwrap()).collect();
+            let g_0_pts: Vec<FieldElement<F>> = dzk_proof.g_0_x.into_iter().rev().map(|x | F::from_ser(x.as_slice()).unwrap()).collect();
+            let g_1_pts: Vec<FieldElement<F>> = dzk_proof.g_1_x.into_iter().rev().map(|x| F::from_ser(x.as_slice()).unwrap()).collect();
             let proofs: Vec<Proof> = dzk_proof.proof.into_iter().rev().collect();
             
             for (index, (g_0, g_1)) in (0..g_0_pts.len()).into_iter().zip(g_0_pts.into_iter().zip(g_1_pts.into_iter())){
@@ -367,7 +374,7 @@ impl FoldingDZKContext{
                 
                 // First, Compute Fiat-Shamir Heuristic point
                 // log::info!("Aggregated Root Hash: {:?}, g_0: {:?}, g_1: {:?}, poly_folded: {:?}", rev_agg_root_vec[index], g_0, g_1, first_poly);
-                let root = LargeField::from_bytes_be(rev_agg_root_vec[index].as_slice()).unwrap();
+                let root = rev_challenge_vec[index].clone();
                 
                 let fiat_shamir_hs_point = &g_0 + &root*&g_1;
                 if point != fiat_shamir_hs_point{
@@ -379,8 +386,8 @@ impl FoldingDZKContext{
                 // Where was the polynomial split?
                 let split_point = *self.poly_split_evaluation_map.get(&(degree_poly as isize)).unwrap() as usize;
 
-                let pt_bigint = LargeField::from(evaluation_point as u64);
-                let pow_bigint = pt_bigint.pow(UnsignedInteger::<4>::from(split_point as u64));
+                let pt_bigint = evaluation_point.clone();
+                let pow_bigint = pt_bigint.pow(split_point as u64);
                 let agg_point = &g_0 + &pow_bigint*&g_1;
                 
                 point = agg_point;
@@ -391,11 +398,11 @@ impl FoldingDZKContext{
                 let merkle_proof = &proofs[index];
                 if !merkle_proof.validate(
                     &self.hash_context) || 
-                        self.hash_context.do_hash_aes(point.to_bytes_be().as_slice()) !=  merkle_proof.item()|| 
+                        self.hash_context.do_hash_aes(F::to_ser(&point).as_slice()) !=  merkle_proof.item()|| 
                         rev_root_vec[index] != merkle_proof.root(){
                     log::error!("DZK Proof verification failed while verifying Merkle Proof validity at iteration {}", index);
                     log::error!("Merkle root matching: computed: {:?}  given: {:?}",rev_root_vec[index].clone(),merkle_proof.root());
-                    log::error!("Items: {:?}  given: {:?}",merkle_proof.item(),self.hash_context.do_hash_aes(point.to_bytes_be().as_slice()));
+                    log::error!("Items: {:?}  given: {:?}",merkle_proof.item(),self.hash_context.do_hash_aes(F::to_ser(&point).as_slice()));
                     return false; 
                 }
             }
@@ -408,19 +415,4 @@ impl FoldingDZKContext{
         }
         true
     }
-    
-    pub fn gen_agg_poly_dzk(&self, evaluations: Vec<LargeField>, root: Hash)-> LargeField{
-
-        let mut root_mul_lf: LargeField = LargeField::from_bytes_be(root.as_slice()).unwrap();
-        let root_original = root_mul_lf.clone();
-        let mut aggregated_val = LargeField::from(0);
-
-        root_mul_lf = LargeField::from(1);
-        for share in evaluations{
-            aggregated_val += &root_mul_lf*share;
-            
-            root_mul_lf = &root_mul_lf*&root_original;
-        }
-        aggregated_val
-    }
 }
\ No newline at end of file
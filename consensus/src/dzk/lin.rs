@@ -1,20 +1,177 @@
-use ha_crypto::{aes_hash::HashState};
+use ha_crypto::{aes_hash::{HashState, MerkleTree}, hash::Hash};
+use lambdaworks_math::field::element::FieldElement;
 
-use crate::LargeFieldSSS;
+use crate::{LargeFieldSSS, FieldType, ProtocolField, DZKBatchItem, DZKContext, DZKProof, FoldingDZKContext, LargeFieldSer, dzk_aggregation_challenge};
 
-pub struct LinDZKContext{
-    pub large_field_uv_sss: LargeFieldSSS,
+pub struct LinDZKContext<F: ProtocolField = FieldType>{
+    pub large_field_uv_sss: LargeFieldSSS<F>,
     pub hash_context: HashState,
     pub evaluation_points: Vec<usize>,
     pub recon_threshold: usize,
 }
 
-impl LinDZKContext{
-    // Linear sized Distributed ZK proof 
+impl<F: ProtocolField> From<FoldingDZKContext<F>> for LinDZKContext<F>{
+    fn from(folding_context: FoldingDZKContext<F>) -> LinDZKContext<F>{
+        LinDZKContext {
+            large_field_uv_sss: folding_context.large_field_uv_sss,
+            hash_context: folding_context.hash_context,
+            evaluation_points: folding_context.evaluation_points,
+            recon_threshold: folding_context.recon_threshold,
+        }
+    }
+}
+
+// Linear sized Distributed ZK proof
+// The dealer broadcasts the aggregated polynomial itself as `VACommitment::polys`, without any folding iterations.
+// Each party evaluates it at its own point and compares with its aggregated share, so the proof of a party is empty and the broadcast grows linearly with the degree.
+impl<F: ProtocolField> DZKContext<F> for LinDZKContext<F> where FieldElement<F>: Send + Sync{
+    fn large_field_uv_sss(&self) -> &LargeFieldSSS<F> {
+        &self.large_field_uv_sss
+    }
+
+    fn hash_context(&self) -> &HashState {
+        &self.hash_context
+    }
+
+    fn evaluation_points(&self) -> &Vec<usize> {
+        &self.evaluation_points
+    }
+
+    fn recon_threshold(&self) -> usize {
+        self.recon_threshold
+    }
+
+    fn gen_dzk_proof(&self,
+        _eval_points: &mut Vec<Vec<(FieldElement<F>,FieldElement<F>)>>,
+        _trees: &mut Vec<MerkleTree>,
+        coefficients: Vec<FieldElement<F>>,
+        _iteration: usize,
+        _root: Hash
+    ) -> Vec<FieldElement<F>>{
+        coefficients
+    }
+
+    fn verify_dzk_proof(&self,
+        dzk_proof: DZKProof,
+        dzk_roots: Vec<Hash>,
+        dzk_poly: Vec<LargeFieldSer>,
+        column_root: Hash,
+        row_share: FieldElement<F>,
+        blinding_row_share: FieldElement<F>,
+        evaluation_point: FieldElement<F>
+    ) -> bool{
+        if !dzk_roots.is_empty() || !dzk_proof.g_0_x.is_empty() || !dzk_proof.g_1_x.is_empty() || !dzk_proof.proof.is_empty(){
+            log::error!("Linear DZK proof verification failed because the proof contains folding iterations");
+            return false;
+        }
+        // The aggregated polynomial must have the degree of the shared polynomials
+        if dzk_poly.is_empty() || dzk_poly.len() > self.recon_threshold{
+            log::error!("Linear DZK proof verification failed because the broadcast polynomial has {} coefficients", dzk_poly.len());
+            return false;
+        }
+        let coefficients: Option<Vec<FieldElement<F>>> = dzk_poly.iter().map(|x| F::from_ser(x.as_slice())).collect();
+        let coefficients = match coefficients{
+            Some(coefficients) => coefficients,
+            None => {
+                log::error!("Linear DZK proof verification failed because the broadcast polynomial is malformed");
+                return false;
+            }
+        };
+
+        let root_bint = dzk_aggregation_challenge::<F>(&column_root);
+        let dzk_share = blinding_row_share + root_bint*row_share;
+        let point = self.large_field_uv_sss.mod_evaluate_at_lf(coefficients.as_slice(), evaluation_point.clone());
+        if point != dzk_share{
+            log::error!("Linear DZK point {:?} does not match the aggregated share {:?} at {:?}", point, dzk_share, evaluation_point);
+            return false;
+        }
+        true
+    }
+
+    fn verify_dzk_group(&self, items: &[&DZKBatchItem<F>]) -> bool {
+        let evaluation_point = match items.first(){
+            Some(item) => item.evaluation_point.clone(),
+            None => return true,
+        };
+        // The broadcast polynomials are combined with random coefficients and the combination is evaluated once
+        let mut combined_poly = vec![FieldElement::<F>::zero(); self.recon_threshold];
+        let mut combined_share = FieldElement::<F>::zero();
+        for item in items.iter(){
+            if item.evaluation_point != evaluation_point || !item.dzk_roots.is_empty() || !item.dzk_proof.g_0_x.is_empty()
+                    || !item.dzk_proof.g_1_x.is_empty() || !item.dzk_proof.proof.is_empty(){
+                return false;
+            }
+            if item.dzk_poly.is_empty() || item.dzk_poly.len() > self.recon_threshold{
+                return false;
+            }
+            let coefficients: Option<Vec<FieldElement<F>>> = item.dzk_poly.iter().map(|x| F::from_ser(x.as_slice())).collect();
+            let coefficients = match coefficients{
+                Some(coefficients) => coefficients,
+                None => return false,
+            };
+            let rho = F::random_element();
+            for (combined, coefficient) in combined_poly.iter_mut().zip(coefficients.into_iter()){
+                *combined += &rho*coefficient;
+            }
+            let root_bint = dzk_aggregation_challenge::<F>(&item.column_root);
+            combined_share += rho*(&item.blinding_row_share + root_bint*&item.row_share);
+        }
+        self.large_field_uv_sss.mod_evaluate_at_lf(combined_poly.as_slice(), evaluation_point) == combined_share
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryInto;
+
+    use lambdaworks_math::polynomial::Polynomial;
+
+    use super::*;
+
+    fn lin_context(num_nodes: usize, num_faults: usize) -> LinDZKContext {
+        LinDZKContext {
+            large_field_uv_sss: LargeFieldSSS::new(num_faults + 1, num_nodes),
+            hash_context: HashState::new([5u8; 16], [29u8; 16], [23u8; 16]),
+            evaluation_points: (1..num_nodes + 1).collect(),
+            recon_threshold: num_faults + 1,
+        }
+    }
 
-    // pub fn gen_dzk_proof(
-    //     &self,
-    //     root: Hash,
+    fn empty_proof() -> DZKProof {
+        DZKProof { g_0_x: Vec::new(), g_1_x: Vec::new(), proof: Vec::new() }
+    }
 
-    // )
-}
\ No newline at end of file
+    #[test]
+    fn test_linear_dzk_proof() {
+        let (num_nodes, num_faults) = (7, 2);
+        let context = lin_context(num_nodes, num_faults);
+        let shares: Vec<FieldElement<FieldType>> = (0..num_faults + 1).map(|_| FieldType::random_element()).collect();
+        let blinding: Vec<FieldElement<FieldType>> = (0..num_faults + 1).map(|_| FieldType::random_element()).collect();
+        let column_root: Hash = context.hash_context.do_hash_aes(b"column commitment");
+
+        // Aggregated polynomial blinding + r * shares, as built by the dealer
+        let root = dzk_aggregation_challenge::<FieldType>(&column_root);
+        let agg_poly: Vec<FieldElement<FieldType>> = blinding.iter().zip(shares.iter()).map(|(b, s)| b + &root * s).collect();
+        let dzk_poly: Vec<LargeFieldSer> = context.gen_dzk_proof(&mut Vec::new(), &mut Vec::new(), agg_poly.clone(), 1, column_root)
+            .iter().map(|x| FieldType::to_ser(x).try_into().unwrap()).collect();
+        assert_eq!(dzk_poly.len(), num_faults + 1);
+
+        let share_poly = Polynomial::new(&shares);
+        let blinding_poly = Polynomial::new(&blinding);
+        for rep in 1..num_nodes + 1 {
+            let point = FieldElement::<FieldType>::from(rep as u64);
+            assert!(context.verify_dzk_proof(empty_proof(), Vec::new(), dzk_poly.clone(), column_root,
+                share_poly.evaluate(&point), blinding_poly.evaluate(&point), point.clone()));
+            // A wrong share is caught
+            assert!(!context.verify_dzk_proof(empty_proof(), Vec::new(), dzk_poly.clone(), column_root,
+                share_poly.evaluate(&point) + FieldElement::<FieldType>::one(), blinding_poly.evaluate(&point), point));
+        }
+
+        // Polynomials above the reconstruction degree are rejected
+        let mut high_degree = dzk_poly.clone();
+        high_degree.push(FieldType::to_ser(&FieldType::random_element()).try_into().unwrap());
+        let point = FieldElement::<FieldType>::from(1u64);
+        assert!(!context.verify_dzk_proof(empty_proof(), Vec::new(), high_degree, column_root,
+            share_poly.evaluate(&point), blinding_poly.evaluate(&point), point));
+    }
+}
@@ -5,6 +5,13 @@ pub mod proof;
 pub use proof::*;
 
 pub mod lin;
+pub use lin::*;
+
+pub mod context;
+pub use context::*;
+
+pub mod batch;
+pub use batch::*;
 
 pub mod msg;
 pub use msg::*;
\ No newline at end of file
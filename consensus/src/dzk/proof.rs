@@ -1,8 +1,10 @@
 // Verifiable Abort
 
-use ha_crypto::{LargeFieldSer, aes_hash::Proof};
+use ha_crypto::aes_hash::Proof;
 use serde::{Deserialize, Serialize};
 
+use crate::LargeFieldSer;
+
 pub type PointBV = ((Vec<LargeFieldSer>, LargeFieldSer, Proof), (LargeFieldSer,LargeFieldSer, Proof), DZKProof);
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -0,0 +1,393 @@
+use lambdaworks_math::{field::element::FieldElement, polynomial::Polynomial};
+use rayon::prelude::{IntoParallelIterator, ParallelIterator};
+
+use crate::{ProtocolField, cached_inverse_vandermonde, matrix_vector_multiply};
+
+/// Number of points from which interpolation and multipoint evaluation go through product trees instead of dense linear algebra
+pub const FAST_POLY_THRESHOLD: usize = 256;
+
+/// Number of points per leaf of a product tree. Leaves are evaluated and interpolated directly.
+const LEAF_SIZE: usize = 32;
+
+/// Polynomials with at most this many coefficients are multiplied and divided schoolbook style
+const NAIVE_LEN: usize = 32;
+
+type Coefficients<F> = Vec<FieldElement<F>>;
+
+/// Product tree over a set of distinct points.
+/// The leaves are the vanishing polynomials of consecutive blocks of `LEAF_SIZE` points, every other node is the product of its children
+/// and the root is the vanishing polynomial of all points.
+/// Multipoint evaluation reduces a polynomial down the tree and costs O(M(n) log n) field operations, where M(n) is the cost of an FFT multiplication.
+pub struct ProductTree<F: ProtocolField> {
+    points: Vec<FieldElement<F>>,
+    /// layers[0] holds the leaves, the last layer the root
+    layers: Vec<Vec<Coefficients<F>>>,
+    /// Inverses of the reversed node polynomials modulo the largest quotient degree seen by the node, for division by Newton iteration
+    inverses: Vec<Vec<Coefficients<F>>>,
+}
+
+impl<F: ProtocolField> ProductTree<F> where FieldElement<F>: Send + Sync {
+    pub fn new(points: &[FieldElement<F>]) -> ProductTree<F> {
+        let leaves: Vec<Coefficients<F>> = points.chunks(LEAF_SIZE).map(|block| {
+            block.iter().fold(vec![FieldElement::<F>::one()], |acc, point| mul_naive(&acc, &[-point.clone(), FieldElement::<F>::one()]))
+        }).collect();
+        let mut layers = vec![leaves];
+        while layers.last().unwrap().len() > 1 {
+            let layer = layers.last().unwrap();
+            let next: Vec<Coefficients<F>> = (0..(layer.len() + 1) / 2).into_par_iter().map(|index| {
+                match layer.get(2 * index + 1) {
+                    Some(right) => mul(&layer[2 * index], right),
+                    None => layer[2 * index].clone(),
+                }
+            }).collect();
+            layers.push(next);
+        }
+        let inverses: Vec<Vec<Coefficients<F>>> = (0..layers.len()).map(|level| {
+            (0..layers[level].len()).into_par_iter().map(|index| {
+                let node = &layers[level][index];
+                // Remainders handed down by the parent have fewer coefficients than the parent
+                let quotient_len = match layers.get(level + 1) {
+                    Some(parent_layer) => parent_layer[index / 2].len().saturating_sub(node.len()),
+                    None => 0,
+                };
+                if node.len() <= NAIVE_LEN || quotient_len <= NAIVE_LEN {
+                    Vec::new()
+                } else {
+                    inverse_series(&reversed(node), quotient_len)
+                }
+            }).collect()
+        }).collect();
+        ProductTree { points: points.to_vec(), layers, inverses }
+    }
+
+    pub fn points(&self) -> &[FieldElement<F>] {
+        &self.points
+    }
+
+    /// Coefficients of the vanishing polynomial of all points
+    pub fn vanishing_polynomial(&self) -> &[FieldElement<F>] {
+        &self.layers.last().unwrap()[0]
+    }
+
+    /// Evaluates the polynomial with `coefficients` at every point of the tree
+    pub fn evaluate(&self, coefficients: &[FieldElement<F>]) -> Vec<FieldElement<F>> {
+        let top = self.layers.len() - 1;
+        let mut remainders = vec![rem(coefficients, &self.layers[top][0], &self.inverses[top][0])];
+        for level in (0..top).rev() {
+            remainders = (0..self.layers[level].len()).map(|index| {
+                rem(&remainders[index / 2], &self.layers[level][index], &self.inverses[level][index])
+            }).collect();
+        }
+        self.points.chunks(LEAF_SIZE).zip(remainders.iter()).flat_map(|(block, remainder)| {
+            block.iter().map(move |point| horner(remainder, point))
+        }).collect()
+    }
+}
+
+/// Interpolation over a fixed set of distinct points, precomputed once and reused for every set of evaluations.
+/// With M the vanishing polynomial of the points, the interpolant of values y_i is the sum of y_i M(x)/(M'(x_i)(x - x_i)),
+/// which is assembled bottom up along the product tree in O(M(n) log n) field operations.
+pub struct Interpolator<F: ProtocolField> {
+    tree: ProductTree<F>,
+    /// Barycentric weights 1/M'(x_i)
+    weights: Vec<FieldElement<F>>,
+    /// Weighted Lagrange basis of every point within its leaf, w_i * M_leaf(x)/(x - x_i)
+    leaf_basis: Vec<Coefficients<F>>,
+}
+
+impl<F: ProtocolField> Interpolator<F> where FieldElement<F>: Send + Sync {
+    pub fn new(points: &[FieldElement<F>]) -> Interpolator<F> {
+        let tree = ProductTree::new(points);
+        let derivative = derivative(tree.vanishing_polynomial());
+        let weights: Vec<FieldElement<F>> = tree.evaluate(&derivative).into_iter().map(|eval| eval.inv().unwrap()).collect();
+        let leaf_basis: Vec<Coefficients<F>> = points.chunks(LEAF_SIZE).zip(tree.layers[0].iter()).enumerate().flat_map(|(block, (block_points, leaf))| {
+            let weights = &weights;
+            block_points.iter().enumerate().map(move |(index, point)| {
+                let weight = &weights[block * LEAF_SIZE + index];
+                divide_by_linear(leaf, point).into_iter().map(|coeff| coeff * weight).collect()
+            })
+        }).collect();
+        Interpolator { tree, weights, leaf_basis }
+    }
+
+    pub fn points(&self) -> &[FieldElement<F>] {
+        self.tree.points()
+    }
+
+    /// Coefficients of the polynomial through `values` at the points, padded to one coefficient per point
+    pub fn interpolate(&self, values: &[FieldElement<F>]) -> Vec<FieldElement<F>> {
+        let num_points = self.tree.points.len();
+        let mut polys: Vec<Coefficients<F>> = self.tree.layers[0].iter().enumerate().map(|(block, leaf)| {
+            let mut acc = vec![FieldElement::<F>::zero(); leaf.len() - 1];
+            let start = block * LEAF_SIZE;
+            for index in start..std::cmp::min(start + LEAF_SIZE, num_points) {
+                for (coeff, basis) in acc.iter_mut().zip(self.leaf_basis[index].iter()) {
+                    *coeff = &*coeff + &values[index] * basis;
+                }
+            }
+            acc
+        }).collect();
+        for level in 0..self.tree.layers.len() - 1 {
+            let layer = &self.tree.layers[level];
+            polys = (0..(polys.len() + 1) / 2).map(|index| {
+                if 2 * index + 1 < polys.len() {
+                    add(&mul(&polys[2 * index], &layer[2 * index + 1]), &mul(&polys[2 * index + 1], &layer[2 * index]))
+                } else {
+                    polys[2 * index].clone()
+                }
+            }).collect();
+        }
+        let mut coefficients = polys.pop().unwrap();
+        coefficients.resize(num_points, FieldElement::<F>::zero());
+        coefficients
+    }
+
+    /// Lagrange coefficients of the points at `x`: the value at `x` of the interpolant through y_i is the sum of y_i times the i-th coefficient.
+    pub fn lagrange_coefficients_at(&self, x: &FieldElement<F>) -> Vec<FieldElement<F>> {
+        if let Some(index) = self.tree.points.iter().position(|point| point == x) {
+            let mut coefficients = vec![FieldElement::<F>::zero(); self.tree.points.len()];
+            coefficients[index] = FieldElement::<F>::one();
+            return coefficients;
+        }
+        let vanishing_at_x = horner(self.tree.vanishing_polynomial(), x);
+        self.tree.points.iter().zip(self.weights.iter()).map(|(point, weight)| {
+            &vanishing_at_x * weight * (x - point).inv().unwrap()
+        }).collect()
+    }
+}
+
+/// Coefficients of the polynomials through `evaluations` at `points`, one coefficient per point.
+/// Uses the cached inverse Vandermonde matrix of the points for small point sets and an `Interpolator` otherwise.
+pub fn interpolate_polynomials<F: ProtocolField>(
+    points: &[FieldElement<F>],
+    evaluations: Vec<Vec<FieldElement<F>>>
+) -> Vec<Vec<FieldElement<F>>> where FieldElement<F>: Send + Sync {
+    if points.len() < FAST_POLY_THRESHOLD {
+        let inverse_vandermonde = cached_inverse_vandermonde(points);
+        return evaluations.into_par_iter().map(|evals| matrix_vector_multiply(&inverse_vandermonde, &evals)).collect();
+    }
+    let interpolator = Interpolator::new(points);
+    evaluations.into_par_iter().map(|evals| interpolator.interpolate(&evals)).collect()
+}
+
+/// Evaluations of each polynomial at every point of `points`.
+/// Evaluates point by point for small point sets and through a `ProductTree` otherwise.
+pub fn evaluate_polynomials<F: ProtocolField>(
+    polynomials: &[Polynomial<FieldElement<F>>],
+    points: &[FieldElement<F>]
+) -> Vec<Vec<FieldElement<F>>> where FieldElement<F>: Send + Sync {
+    if points.len() < FAST_POLY_THRESHOLD {
+        return polynomials.into_par_iter().map(|polynomial| {
+            points.iter().map(|point| polynomial.evaluate(point)).collect()
+        }).collect();
+    }
+    let tree = ProductTree::new(points);
+    polynomials.into_par_iter().map(|polynomial| tree.evaluate(polynomial.coefficients())).collect()
+}
+
+/// Values at `x` of the polynomials through `evaluations` at `points`.
+/// Each value is a linear combination of the evaluations, so interpolating the polynomials is not necessary.
+pub fn interpolate_at_point<F: ProtocolField>(
+    points: &[FieldElement<F>],
+    evaluations: Vec<Vec<FieldElement<F>>>,
+    x: &FieldElement<F>
+) -> Vec<FieldElement<F>> where FieldElement<F>: Send + Sync {
+    let lagrange_coefficients = if points.len() < FAST_POLY_THRESHOLD {
+        // The powers of x times the inverse Vandermonde matrix
+        let inverse_vandermonde = cached_inverse_vandermonde(points);
+        let powers: Vec<FieldElement<F>> = (0..points.len()).scan(FieldElement::<F>::one(), |power, _| {
+            let current = power.clone();
+            *power = &*power * x;
+            Some(current)
+        }).collect();
+        (0..points.len()).map(|column| {
+            powers.iter().zip(inverse_vandermonde.iter()).fold(FieldElement::<F>::zero(), |sum, (power, row)| sum + power * &row[column])
+        }).collect()
+    } else {
+        Interpolator::new(points).lagrange_coefficients_at(x)
+    };
+    evaluations.into_par_iter().map(|evals| {
+        evals.iter().zip(lagrange_coefficients.iter()).fold(FieldElement::<F>::zero(), |sum, (eval, coeff)| sum + eval * coeff)
+    }).collect()
+}
+
+fn horner<F: ProtocolField>(coefficients: &[FieldElement<F>], x: &FieldElement<F>) -> FieldElement<F> {
+    coefficients.iter().rev().fold(FieldElement::<F>::zero(), |acc, coeff| acc * x + coeff)
+}
+
+fn trim<F: ProtocolField>(mut coefficients: Coefficients<F>) -> Coefficients<F> {
+    while coefficients.last().map_or(false, |coeff| *coeff == FieldElement::<F>::zero()) {
+        coefficients.pop();
+    }
+    coefficients
+}
+
+fn reversed<F: ProtocolField>(coefficients: &[FieldElement<F>]) -> Coefficients<F> {
+    coefficients.iter().rev().cloned().collect()
+}
+
+fn add<F: ProtocolField>(a: &[FieldElement<F>], b: &[FieldElement<F>]) -> Coefficients<F> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut sum = long.to_vec();
+    for (coeff, other) in sum.iter_mut().zip(short.iter()) {
+        *coeff = &*coeff + other;
+    }
+    sum
+}
+
+fn derivative<F: ProtocolField>(coefficients: &[FieldElement<F>]) -> Coefficients<F> {
+    coefficients.iter().enumerate().skip(1).map(|(power, coeff)| coeff * FieldElement::<F>::from(power as u64)).collect()
+}
+
+/// Quotient of `coefficients` by (x - point), dropping the remainder
+fn divide_by_linear<F: ProtocolField>(coefficients: &[FieldElement<F>], point: &FieldElement<F>) -> Coefficients<F> {
+    let mut quotient = vec![FieldElement::<F>::zero(); coefficients.len() - 1];
+    let mut carry = FieldElement::<F>::zero();
+    for index in (1..coefficients.len()).rev() {
+        carry = &coefficients[index] + &carry * point;
+        quotient[index - 1] = carry.clone();
+    }
+    quotient
+}
+
+fn mul_naive<F: ProtocolField>(a: &[FieldElement<F>], b: &[FieldElement<F>]) -> Coefficients<F> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let mut product = vec![FieldElement::<F>::zero(); a.len() + b.len() - 1];
+    for (i, x) in a.iter().enumerate() {
+        for (j, y) in b.iter().enumerate() {
+            product[i + j] = &product[i + j] + x * y;
+        }
+    }
+    product
+}
+
+/// Product of two polynomials, through FFTs once both factors are large
+fn mul<F: ProtocolField>(a: &[FieldElement<F>], b: &[FieldElement<F>]) -> Coefficients<F> {
+    if std::cmp::min(a.len(), b.len()) <= NAIVE_LEN {
+        return mul_naive(a, b);
+    }
+    let product_len = a.len() + b.len() - 1;
+    let (poly_a, poly_b) = (Polynomial::new(a), Polynomial::new(b));
+    if poly_a.coefficients.is_empty() || poly_b.coefficients.is_empty() {
+        return vec![FieldElement::<F>::zero(); product_len];
+    }
+    let evals_a = Polynomial::evaluate_fft::<F>(&poly_a, 1, Some(product_len)).unwrap();
+    let evals_b = Polynomial::evaluate_fft::<F>(&poly_b, 1, Some(product_len)).unwrap();
+    let evals: Vec<FieldElement<F>> = evals_a.iter().zip(evals_b.iter()).map(|(x, y)| x * y).collect();
+    let mut product = Polynomial::interpolate_fft::<F>(&evals).unwrap().coefficients;
+    product.resize(product_len, FieldElement::<F>::zero());
+    product
+}
+
+/// Inverse of the power series `series` modulo x^len by Newton iteration
+fn inverse_series<F: ProtocolField>(series: &[FieldElement<F>], len: usize) -> Coefficients<F> {
+    let mut inverse = vec![series[0].inv().unwrap()];
+    let mut precision = 1;
+    while precision < len {
+        precision = std::cmp::min(2 * precision, len);
+        // inverse <- inverse * (2 - series * inverse) mod x^precision
+        let mut error = mul(&series[..std::cmp::min(series.len(), precision)], &inverse);
+        error.resize(precision, FieldElement::<F>::zero());
+        let mut correction: Coefficients<F> = error.into_iter().map(|coeff| -coeff).collect();
+        correction[0] = &correction[0] + FieldElement::<F>::from(2u64);
+        inverse = mul(&inverse, &correction);
+        inverse.truncate(precision);
+    }
+    inverse
+}
+
+/// Remainder of `dividend` by the monic polynomial `modulus`.
+/// `inverse` is the inverse of the reversed modulus up to some precision, it is extended when the quotient needs more.
+fn rem<F: ProtocolField>(dividend: &[FieldElement<F>], modulus: &[FieldElement<F>], inverse: &[FieldElement<F>]) -> Coefficients<F> {
+    let dividend = trim(dividend.to_vec());
+    if dividend.len() < modulus.len() {
+        return dividend;
+    }
+    let quotient_len = dividend.len() - modulus.len() + 1;
+    if modulus.len() <= NAIVE_LEN || quotient_len <= NAIVE_LEN {
+        return rem_naive(dividend, modulus);
+    }
+    let inverse = if inverse.len() >= quotient_len {
+        inverse[..quotient_len].to_vec()
+    } else {
+        inverse_series(&reversed(modulus), quotient_len)
+    };
+    let reversed_dividend = reversed(&dividend);
+    let mut reversed_quotient = mul(&reversed_dividend[..quotient_len], &inverse);
+    reversed_quotient.resize(quotient_len, FieldElement::<F>::zero());
+    let quotient = reversed(&reversed_quotient);
+    let product = mul(&quotient, modulus);
+    (0..modulus.len() - 1).map(|index| &dividend[index] - &product[index]).collect()
+}
+
+fn rem_naive<F: ProtocolField>(mut dividend: Coefficients<F>, modulus: &[FieldElement<F>]) -> Coefficients<F> {
+    let degree = modulus.len() - 1;
+    for index in (degree..dividend.len()).rev() {
+        let lead = dividend[index].clone();
+        if lead == FieldElement::<F>::zero() {
+            continue;
+        }
+        for (offset, coeff) in modulus.iter().enumerate() {
+            dividend[index - degree + offset] = &dividend[index - degree + offset] - &lead * coeff;
+        }
+    }
+    dividend.truncate(degree);
+    dividend
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FieldType, LargeField, inverse_vandermonde, vandermonde_matrix};
+
+    fn random_polys(count: usize, len: usize) -> Vec<Polynomial<LargeField>> {
+        (0..count).map(|_| Polynomial::new(&(0..len).map(|_| FieldType::random_element()).collect::<Vec<LargeField>>())).collect()
+    }
+
+    #[test]
+    fn test_product_tree_matches_naive_evaluation() {
+        // Arbitrary points, not a subgroup
+        let points: Vec<LargeField> = (0..300u64).map(|i| LargeField::from(i * i + 7)).collect();
+        for poly in random_polys(3, 200).iter().chain(random_polys(1, 700).iter()) {
+            let tree = ProductTree::new(&points);
+            let expected: Vec<LargeField> = points.iter().map(|point| poly.evaluate(point)).collect();
+            assert_eq!(tree.evaluate(poly.coefficients()), expected);
+        }
+    }
+
+    #[test]
+    fn test_interpolator_recovers_polynomial() {
+        for num_points in [1usize, 5, 33, 100, 257].iter() {
+            let points: Vec<LargeField> = (0..*num_points as u64).map(|i| LargeField::from(3 * i + 1)).collect();
+            let poly = &random_polys(1, *num_points)[0];
+            let values: Vec<LargeField> = points.iter().map(|point| poly.evaluate(point)).collect();
+            let interpolator = Interpolator::new(&points);
+            let coefficients = interpolator.interpolate(&values);
+            assert_eq!(Polynomial::new(&coefficients), *poly);
+            assert_eq!(coefficients, matrix_vector_multiply(&inverse_vandermonde(vandermonde_matrix(points.clone())), &values));
+
+            let x = LargeField::zero();
+            let at_x = interpolator.lagrange_coefficients_at(&x).iter().zip(values.iter()).fold(LargeField::zero(), |sum, (c, v)| sum + c * v);
+            assert_eq!(at_x, poly.evaluate(&x));
+        }
+    }
+
+    #[test]
+    fn test_dispatch_agrees_across_threshold() {
+        for num_points in [FAST_POLY_THRESHOLD - 1, FAST_POLY_THRESHOLD + 1].iter() {
+            let points: Vec<LargeField> = (1..*num_points as u64 + 1).map(LargeField::from).collect();
+            let polys = random_polys(2, *num_points);
+            let evaluations = evaluate_polynomials(&polys, &points);
+            let coefficients = interpolate_polynomials(&points, evaluations.clone());
+            for (poly, coeffs) in polys.iter().zip(coefficients.iter()) {
+                assert_eq!(Polynomial::new(coeffs), *poly);
+            }
+            let secrets = interpolate_at_point(&points, evaluations, &LargeField::zero());
+            for (poly, secret) in polys.iter().zip(secrets.iter()) {
+                assert_eq!(poly.evaluate(&LargeField::zero()), *secret);
+            }
+        }
+    }
+}
@@ -0,0 +1,163 @@
+use std::{convert::TryInto, fmt::Debug};
+
+use lambdaworks_math::{
+    elliptic_curve::short_weierstrass::curves::bls12_381::default_types::FrField,
+    field::{
+        element::FieldElement,
+        fields::{fft_friendly::stark_252_prime_field::MontgomeryConfigStark252PrimeField, montgomery_backed_prime_fields::MontgomeryBackendPrimeField, u64_goldilocks_field::Goldilocks64Field},
+        traits::{IsFFTField, IsPrimeField},
+    },
+    traits::ByteConversion,
+    unsigned_integer::element::UnsignedInteger,
+};
+use rand::random;
+use rand_core::RngCore;
+
+pub type Stark252Field = MontgomeryBackendPrimeField<MontgomeryConfigStark252PrimeField, 4>;
+pub type Bls12381ScalarField = FrField;
+pub type GoldilocksField = Goldilocks64Field;
+
+/// Prime field over which secrets are shared.
+/// Protocol code is written against this trait, the serialized length of elements is derived from the field.
+pub trait ProtocolField: IsFFTField + IsPrimeField + Clone + Debug + Send + Sync + 'static {
+    /// Length of a serialized element in bytes
+    const SER_LEN: usize;
+
+    fn to_ser(element: &FieldElement<Self>) -> Vec<u8>;
+
+    /// Returns None if the bytes do not encode a canonical element
+    fn from_ser(bytes: &[u8]) -> Option<FieldElement<Self>>;
+
+    /// Samples an element from a seeded generator, used for shares expanded from PRF keys
+    fn sample<R: RngCore>(rng: &mut R) -> FieldElement<Self>;
+
+    fn random_element() -> FieldElement<Self>;
+}
+
+macro_rules! impl_montgomery_protocol_field {
+    ($field: ty) => {
+        impl ProtocolField for $field {
+            const SER_LEN: usize = 32;
+
+            fn to_ser(element: &FieldElement<Self>) -> Vec<u8> {
+                element.to_bytes_be().to_vec()
+            }
+
+            fn from_ser(bytes: &[u8]) -> Option<FieldElement<Self>> {
+                if bytes.len() != Self::SER_LEN {
+                    return None;
+                }
+                FieldElement::<Self>::from_bytes_be(bytes).ok()
+            }
+
+            fn sample<R: RngCore>(rng: &mut R) -> FieldElement<Self> {
+                let mut limbs = [0u64; 4];
+                for j in 0..4 {
+                    limbs[j] = rng.next_u64();
+                }
+                FieldElement::<Self>::new(UnsignedInteger { limbs: limbs })
+            }
+
+            fn random_element() -> FieldElement<Self> {
+                FieldElement::<Self>::new(UnsignedInteger { limbs: random() })
+            }
+        }
+    };
+}
+
+impl_montgomery_protocol_field!(Stark252Field);
+impl_montgomery_protocol_field!(Bls12381ScalarField);
+
+impl ProtocolField for GoldilocksField {
+    const SER_LEN: usize = 8;
+
+    fn to_ser(element: &FieldElement<Self>) -> Vec<u8> {
+        element.representative().to_be_bytes().to_vec()
+    }
+
+    fn from_ser(bytes: &[u8]) -> Option<FieldElement<Self>> {
+        let value = u64::from_be_bytes(bytes.try_into().ok()?);
+        let element = FieldElement::<Self>::from(value);
+        if element.representative() != value {
+            return None;
+        }
+        Some(element)
+    }
+
+    fn sample<R: RngCore>(rng: &mut R) -> FieldElement<Self> {
+        FieldElement::<Self>::from(rng.next_u64())
+    }
+
+    fn random_element() -> FieldElement<Self> {
+        FieldElement::<Self>::from(random::<u64>())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::SeedableRng;
+
+    fn check_round_trip<F: ProtocolField>(seed: [u8; 32]) {
+        let mut rng = ChaCha20Rng::from_seed(seed);
+        for element in vec![FieldElement::<F>::zero(), FieldElement::<F>::one(), -FieldElement::<F>::one(), F::sample(&mut rng)] {
+            let bytes = F::to_ser(&element);
+            assert_eq!(bytes.len(), F::SER_LEN);
+            assert_eq!(F::from_ser(&bytes), Some(element));
+        }
+        let element = F::random_element();
+        assert_eq!(F::from_ser(&F::to_ser(&element)), Some(element));
+    }
+
+    fn check_rejects_wrong_length<F: ProtocolField>() {
+        let bytes = F::to_ser(&FieldElement::<F>::one());
+        assert!(F::from_ser(&bytes[1..]).is_none());
+        let mut longer = bytes.clone();
+        longer.push(0);
+        assert!(F::from_ser(&longer).is_none());
+    }
+
+    #[test]
+    fn test_ser_len_matches_field_size() {
+        assert_eq!(Stark252Field::SER_LEN, 32);
+        assert_eq!(Bls12381ScalarField::SER_LEN, 32);
+        assert_eq!(GoldilocksField::SER_LEN, 8);
+    }
+
+    #[test]
+    fn test_from_ser_rejects_wrong_length() {
+        check_rejects_wrong_length::<Stark252Field>();
+        check_rejects_wrong_length::<Bls12381ScalarField>();
+        check_rejects_wrong_length::<GoldilocksField>();
+    }
+
+    #[test]
+    fn test_goldilocks_rejects_non_canonical_encoding() {
+        // p = 2^64 - 2^32 + 1
+        let modulus: u64 = 0xffff_ffff_0000_0001;
+        assert!(GoldilocksField::from_ser(&modulus.to_be_bytes()).is_none());
+        assert!(GoldilocksField::from_ser(&u64::MAX.to_be_bytes()).is_none());
+        assert_eq!(GoldilocksField::from_ser(&(modulus - 1).to_be_bytes()), Some(-FieldElement::<GoldilocksField>::one()));
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn prop_stark252_round_trip(seed in any::<[u8; 32]>()) {
+            check_round_trip::<Stark252Field>(seed);
+        }
+
+        #[test]
+        fn prop_bls12_381_round_trip(seed in any::<[u8; 32]>()) {
+            check_round_trip::<Bls12381ScalarField>(seed);
+        }
+
+        #[test]
+        fn prop_goldilocks_round_trip(seed in any::<[u8; 32]>()) {
+            check_round_trip::<GoldilocksField>(seed);
+        }
+    }
+}
@@ -0,0 +1,399 @@
+use std::{
+    collections::{BTreeSet, HashMap, HashSet, VecDeque},
+    hash::Hash,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+use network::{plaintcp::CancelHandler, Acknowledgement};
+
+/// Default time an instance's state is kept around after it terminates.
+/// Late messages for the instance may still need this state (e.g. to answer echoes or reconstruction requests).
+pub const DEFAULT_GC_RETENTION_MS: u64 = 30_000;
+
+/// Number of outstanding cancel handlers after which acknowledged handlers are pruned.
+pub const CANCEL_HANDLER_PRUNE_THRESHOLD: usize = 4096;
+
+/// Number of collected instances tracked individually per stream before the low-water mark is forced past the oldest of them.
+/// The mark is never forced past an instance that still has state, so a stream with a live instance may track more.
+pub const MAX_COLLECTED_ABOVE_LOW_WATER: usize = 1024;
+
+static GC_RETENTION_MS: AtomicU64 = AtomicU64::new(DEFAULT_GC_RETENTION_MS);
+
+/// Set the retention window used by every service spawned after this call.
+pub fn set_gc_retention(retention: Duration) {
+    GC_RETENTION_MS.store(retention.as_millis() as u64, Ordering::Relaxed);
+}
+
+pub fn gc_retention() -> Duration {
+    Duration::from_millis(GC_RETENTION_MS.load(Ordering::Relaxed))
+}
+
+/// Instance ids tracked by `InstanceGC`.
+/// Ids are split into a stream (e.g. the broadcaster) and a sequence number within the stream.
+/// Collected ids of a stream are remembered by a low-water mark below which every sequence number is collected, plus the collected sequence numbers above it.
+pub trait GCKey: Eq + Hash + Clone {
+    type Stream: Eq + Hash + Clone;
+    fn stream(&self) -> Self::Stream;
+    fn seq(&self) -> usize;
+}
+
+impl GCKey for usize {
+    type Stream = ();
+    fn stream(&self) -> () {}
+    fn seq(&self) -> usize {
+        *self
+    }
+}
+
+/// (Stream, sequence number), e.g. (broadcaster, broadcast id) or (dealer, instance id)
+impl GCKey for (usize, usize) {
+    type Stream = usize;
+    fn stream(&self) -> usize {
+        self.0
+    }
+    fn seq(&self) -> usize {
+        self.1
+    }
+}
+
+/// (Protocol, instance id), the outputs delivered to a client
+impl GCKey for (String, usize) {
+    type Stream = String;
+    fn stream(&self) -> String {
+        self.0.clone()
+    }
+    fn seq(&self) -> usize {
+        self.1
+    }
+}
+
+/// Collected sequence numbers of one stream
+#[derive(Default)]
+struct CollectedSeqs {
+    // Every sequence number below this one is collected
+    low_water: usize,
+    above: BTreeSet<usize>,
+}
+
+impl CollectedSeqs {
+    fn contains(&self, seq: usize) -> bool {
+        seq < self.low_water || self.above.contains(&seq)
+    }
+
+    fn insert(&mut self, seq: usize) {
+        if seq < self.low_water {
+            return;
+        }
+        self.above.insert(seq);
+        self.advance();
+    }
+
+    fn advance(&mut self) {
+        while self.above.remove(&self.low_water) {
+            self.low_water += 1;
+        }
+    }
+
+    /// Gaps that stay open for this long belong to instances that never started here or were abandoned.
+    /// Forces the mark past the oldest half of the collected ids, but never past `floor`, the oldest instance that still has state.
+    fn compact(&mut self, floor: Option<usize>) {
+        let mut target = *self.above.iter().nth(MAX_COLLECTED_ABOVE_LOW_WATER / 2).unwrap();
+        if let Some(floor) = floor {
+            target = std::cmp::min(target, floor);
+        }
+        if target > self.low_water {
+            self.low_water = target;
+            self.above = self.above.split_off(&target);
+            self.advance();
+        }
+    }
+}
+
+/// Tracks the lifecycle of protocol instances.
+/// Instances are marked terminated once they produce their output.
+/// After the retention window elapses, their ids are handed back to the caller for removal from the state maps.
+/// The ids of collected instances are remembered so that late messages do not recreate state for them.
+pub struct InstanceGC<K: GCKey> {
+    pub retention: Duration,
+    terminated: VecDeque<(K, Instant)>,
+    terminated_set: HashSet<K>,
+    collected: HashMap<K::Stream, CollectedSeqs>,
+}
+
+impl<K: GCKey> InstanceGC<K> {
+    pub fn new(retention: Duration) -> InstanceGC<K> {
+        InstanceGC {
+            retention: retention,
+            terminated: VecDeque::new(),
+            terminated_set: HashSet::default(),
+            collected: HashMap::default(),
+        }
+    }
+
+    pub fn mark_terminated(&mut self, key: K) {
+        self.mark_terminated_at(key, Instant::now());
+    }
+
+    pub fn mark_terminated_at(&mut self, key: K, now: Instant) {
+        if self.terminated_set.contains(&key) || self.is_collected(&key) {
+            return;
+        }
+        self.terminated_set.insert(key.clone());
+        self.terminated.push_back((key, now));
+    }
+
+    pub fn is_terminated(&self, key: &K) -> bool {
+        self.terminated_set.contains(key) || self.is_collected(key)
+    }
+
+    /// Returns true if the state of this instance has already been pruned.
+    /// Messages for such instances must be dropped instead of reinitializing state.
+    pub fn is_collected(&self, key: &K) -> bool {
+        match self.collected.get(&key.stream()) {
+            Some(seqs) => seqs.contains(key.seq()),
+            None => false,
+        }
+    }
+
+    /// Number of collected ids remembered individually, i.e. not yet covered by a low-water mark.
+    pub fn collected_len(&self) -> usize {
+        self.collected.values().map(|seqs| seqs.above.len()).sum()
+    }
+
+    pub fn pending(&self) -> usize {
+        self.terminated.len()
+    }
+
+    /// Returns the instances whose retention window has elapsed. The caller removes their state.
+    /// `live_floor` returns the lowest sequence number of a stream whose instance still has state and has not terminated, if any.
+    /// Instances in progress are not known here, and the low-water mark of their stream is never forced past them.
+    pub fn collect_expired<F>(&mut self, live_floor: F) -> Vec<K> where F: Fn(&K::Stream) -> Option<usize> {
+        self.collect_expired_at(Instant::now(), live_floor)
+    }
+
+    pub fn collect_expired_at<F>(&mut self, now: Instant, live_floor: F) -> Vec<K> where F: Fn(&K::Stream) -> Option<usize> {
+        let (expired, crowded) = self.take_expired_at(now);
+        self.compact_streams(crowded, live_floor);
+        expired
+    }
+
+    /// Removes the state of all expired instances from the given map.
+    /// The instances left in the map bound the low-water marks of their streams.
+    pub fn prune<V>(&mut self, state: &mut HashMap<K, V>) -> Vec<K> {
+        self.prune_at(state, Instant::now())
+    }
+
+    pub fn prune_at<V>(&mut self, state: &mut HashMap<K, V>, now: Instant) -> Vec<K> {
+        let (expired, crowded) = self.take_expired_at(now);
+        for key in expired.iter() {
+            state.remove(key);
+        }
+        if !crowded.is_empty() {
+            self.compact_streams(crowded, |stream| {
+                state.keys().filter(|key| key.stream() == *stream).map(|key| key.seq()).min()
+            });
+        }
+        expired
+    }
+
+    /// Moves the expired instances to the collected ids. Returns them, with the streams tracking too many collected ids individually.
+    fn take_expired_at(&mut self, now: Instant) -> (Vec<K>, HashSet<K::Stream>) {
+        let mut expired = Vec::new();
+        let mut crowded = HashSet::default();
+        while let Some((_, terminated_at)) = self.terminated.front() {
+            if now.duration_since(*terminated_at) < self.retention {
+                break;
+            }
+            let (key, _) = self.terminated.pop_front().unwrap();
+            self.terminated_set.remove(&key);
+            let stream = key.stream();
+            let seqs = self.collected.entry(stream.clone()).or_default();
+            seqs.insert(key.seq());
+            if seqs.above.len() > MAX_COLLECTED_ABOVE_LOW_WATER {
+                crowded.insert(stream);
+            }
+            expired.push(key);
+        }
+        (expired, crowded)
+    }
+
+    /// Forces the low-water marks of the given streams forward, stopping at the oldest instance still within its retention window and at `live_floor`.
+    fn compact_streams<F>(&mut self, streams: HashSet<K::Stream>, live_floor: F) where F: Fn(&K::Stream) -> Option<usize> {
+        for stream in streams.into_iter() {
+            let pending_floor = self.terminated.iter()
+                .filter(|(pending, _)| pending.stream() == stream)
+                .map(|(pending, _)| pending.seq())
+                .min();
+            let floor = match (pending_floor, live_floor(&stream)) {
+                (Some(pending), Some(live)) => Some(std::cmp::min(pending, live)),
+                (pending, live) => pending.or(live),
+            };
+            if let Some(seqs) = self.collected.get_mut(&stream) {
+                seqs.compact(floor);
+            }
+        }
+    }
+}
+
+/// Drops cancel handlers whose messages were already acknowledged (or whose connection was closed).
+/// Returns the number of handlers removed.
+pub fn prune_cancel_handlers(cancel_handlers: &mut HashMap<u64, Vec<CancelHandler<Acknowledgement>>>) -> usize {
+    let mut pruned = 0;
+    for (_, handlers) in cancel_handlers.iter_mut() {
+        let before = handlers.len();
+        handlers.retain_mut(|handler| {
+            match handler.try_recv() {
+                Err(tokio::sync::oneshot::error::TryRecvError::Empty) => true,
+                _ => false,
+            }
+        });
+        pruned += before - handlers.len();
+    }
+    cancel_handlers.retain(|_, handlers| !handlers.is_empty());
+    pruned
+}
+
+/// Pushes a cancel handler into the default bucket and prunes acknowledged handlers once the bucket grows large.
+pub fn add_cancel_handler_gc(cancel_handlers: &mut HashMap<u64, Vec<CancelHandler<Acknowledgement>>>, canc: CancelHandler<Acknowledgement>) {
+    let bucket = cancel_handlers.entry(0).or_default();
+    bucket.push(canc);
+    if bucket.len() >= CANCEL_HANDLER_PRUNE_THRESHOLD && bucket.len().is_power_of_two() {
+        let pruned = prune_cancel_handlers(cancel_handlers);
+        log::debug!("Pruned {} acknowledged cancel handlers", pruned);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::oneshot;
+
+    #[test]
+    fn test_instance_gc_bounded_state() {
+        let mut gc: InstanceGC<usize> = InstanceGC::new(Duration::from_millis(10));
+        let mut state: HashMap<usize, Vec<u8>> = HashMap::default();
+        let start = Instant::now();
+        for instance in 0..100_000 {
+            state.insert(instance, vec![0u8; 64]);
+            gc.mark_terminated_at(instance, start + Duration::from_millis(instance as u64 / 100));
+            let now = start + Duration::from_millis(instance as u64 / 100);
+            for key in gc.collect_expired_at(now, |_| None) {
+                state.remove(&key);
+            }
+            // Only the instances terminated within the last retention window are live
+            assert!(state.len() <= 1100);
+        }
+        assert!(gc.is_collected(&0));
+        assert!(gc.is_terminated(&99_999));
+        assert!(!gc.is_collected(&99_999));
+        for key in gc.collect_expired_at(start + Duration::from_secs(3600), |_| None) {
+            state.remove(&key);
+        }
+        assert!(state.is_empty());
+        assert_eq!(gc.pending(), 0);
+    }
+
+    #[test]
+    fn test_collected_ids_are_compacted() {
+        let mut gc: InstanceGC<usize> = InstanceGC::new(Duration::from_millis(0));
+        let start = Instant::now();
+        // Instances terminate out of order, the mark advances once the gaps are filled
+        for instance in (0..10_000).rev() {
+            gc.mark_terminated_at(instance, start);
+        }
+        gc.collect_expired_at(start, |_| None);
+        assert_eq!(gc.collected_len(), 0);
+        assert!(gc.is_collected(&0));
+        assert!(gc.is_collected(&9_999));
+        assert!(!gc.is_collected(&10_000));
+
+        // Instance 10_000 never runs here, the ids above it stay bounded
+        for instance in 10_001..20_000 {
+            gc.mark_terminated_at(instance, start);
+            gc.collect_expired_at(start, |_| None);
+            assert!(gc.collected_len() <= MAX_COLLECTED_ABOVE_LOW_WATER);
+        }
+        assert!(gc.is_collected(&10_000));
+        assert!(gc.is_collected(&19_999));
+        assert!(!gc.is_collected(&20_000));
+    }
+
+    #[test]
+    fn test_compaction_keeps_pending_instances() {
+        let mut gc: InstanceGC<(usize, usize)> = InstanceGC::new(Duration::from_millis(10));
+        let start = Instant::now();
+        // Broadcaster 1 terminates instance 0 late, broadcaster 2 never terminates instance 0
+        for seq in 1..2000 {
+            gc.mark_terminated_at((1, seq), start);
+            gc.mark_terminated_at((2, seq), start);
+        }
+        gc.mark_terminated_at((1, 0), start + Duration::from_millis(5));
+        gc.collect_expired_at(start + Duration::from_millis(10), |_| None);
+        assert!(!gc.is_collected(&(1, 0)));
+        assert!(gc.is_terminated(&(1, 0)));
+        assert!(gc.is_collected(&(1, 1999)));
+        // Without state for it, the gap of broadcaster 2 belongs to an instance that never started here and may be forced shut
+        assert!(gc.is_collected(&(2, 0)));
+        assert!(gc.collected_len() <= 1999 + MAX_COLLECTED_ABOVE_LOW_WATER);
+
+        gc.collect_expired_at(start + Duration::from_millis(20), |_| None);
+        assert!(gc.is_collected(&(1, 0)));
+        assert_eq!(gc.collected_len(), 0);
+    }
+
+    #[test]
+    fn test_compaction_keeps_live_instances() {
+        let mut gc: InstanceGC<(usize, usize)> = InstanceGC::new(Duration::from_millis(0));
+        let mut state: HashMap<(usize, usize), ()> = HashMap::default();
+        let start = Instant::now();
+        // Broadcaster 2 is still delivering instance 0 while its later instances terminate
+        state.insert((2, 0), ());
+        for seq in 1..2000 {
+            state.insert((2, seq), ());
+            gc.mark_terminated_at((2, seq), start);
+            gc.prune_at(&mut state, start);
+            assert!(!gc.is_collected(&(2, 0)));
+        }
+        assert_eq!(state.len(), 1);
+        assert!(!gc.is_terminated(&(2, 0)));
+        assert!(gc.is_collected(&(2, 1999)));
+
+        // Once it terminates, the whole stream is covered by the low-water mark
+        gc.mark_terminated_at((2, 0), start);
+        gc.prune_at(&mut state, start);
+        assert!(state.is_empty());
+        assert_eq!(gc.collected_len(), 0);
+
+        // An explicit floor holds the mark the same way
+        let mut gc: InstanceGC<usize> = InstanceGC::new(Duration::from_millis(0));
+        for instance in 1..2000 {
+            gc.mark_terminated_at(instance, start);
+            gc.collect_expired_at(start, |_| Some(0));
+        }
+        assert!(!gc.is_collected(&0));
+        assert_eq!(gc.collected_len(), 1999);
+    }
+
+    #[test]
+    fn test_prune_acknowledged_cancel_handlers() {
+        let mut cancel_handlers: HashMap<u64, Vec<CancelHandler<Acknowledgement>>> = HashMap::default();
+        let mut senders = Vec::new();
+        for i in 0..1000 {
+            let (tx, rx) = oneshot::channel();
+            cancel_handlers.entry(0).or_default().push(rx);
+            if i % 2 == 0 {
+                tx.send(Acknowledgement::Pong).unwrap();
+            }
+            else {
+                senders.push(tx);
+            }
+        }
+        let pruned = prune_cancel_handlers(&mut cancel_handlers);
+        assert_eq!(pruned, 500);
+        assert_eq!(cancel_handlers.get(&0).unwrap().len(), 500);
+        drop(senders);
+        prune_cancel_handlers(&mut cancel_handlers);
+        assert!(cancel_handlers.is_empty());
+    }
+}
@@ -0,0 +1,35 @@
+use async_trait::async_trait;
+use futures_util::SinkExt;
+use network::Acknowledgement;
+use tokio::sync::mpsc::Sender;
+use types::WrapperMsg;
+
+use crate::ClientOutputMsg;
+
+#[derive(Debug, Clone)]
+pub struct ClientOutputHandler {
+    client_tx: Sender<WrapperMsg<ClientOutputMsg>>,
+}
+
+impl ClientOutputHandler {
+    pub fn new(client_tx: Sender<WrapperMsg<ClientOutputMsg>>) -> Self {
+        Self { client_tx }
+    }
+}
+
+#[async_trait]
+impl network::Handler<Acknowledgement, WrapperMsg<ClientOutputMsg>> for ClientOutputHandler {
+    async fn dispatch(&self, msg: WrapperMsg<ClientOutputMsg>, writer: &mut network::Writer<Acknowledgement>) {
+        // Forward the message, waiting for room in the client queue before acknowledging
+        self.client_tx
+            .send(msg)
+            .await
+            .expect("Failed to send message to the client channel");
+
+        // Acknowledge
+        writer
+            .send(Acknowledgement::Pong)
+            .await
+            .expect("Failed to send an acknowledgement");
+    }
+}
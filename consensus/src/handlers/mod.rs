@@ -1,2 +1,5 @@
 pub mod sync_handler;
-pub use sync_handler::*;
\ No newline at end of file
+pub use sync_handler::*;
+
+pub mod client_output_handler;
+pub use client_output_handler::*;
@@ -0,0 +1,366 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use ha_crypto::hash::verf_mac;
+use serde::Serialize;
+use tokio::sync::{
+    mpsc::{channel, Receiver, Sender},
+    Notify, Semaphore,
+};
+use types::{Replica, WrapperMsg};
+
+/// Default number of messages buffered per peer on the network ingress path.
+pub const DEFAULT_INGRESS_PER_SENDER_CAPACITY: usize = 10000;
+/// Default capacity of the channels between protocol services (ACSS, ACS, BBA, ...).
+pub const DEFAULT_SERVICE_CHANNEL_CAPACITY: usize = 10000;
+/// Number of received messages between two queue depth reports.
+const METRICS_REPORT_INTERVAL: u64 = 10000;
+
+static INGRESS_PER_SENDER_CAPACITY: AtomicUsize = AtomicUsize::new(DEFAULT_INGRESS_PER_SENDER_CAPACITY);
+static SERVICE_CHANNEL_CAPACITY: AtomicUsize = AtomicUsize::new(DEFAULT_SERVICE_CHANNEL_CAPACITY);
+
+/// Set the queue capacities used by every service spawned after this call.
+pub fn set_channel_capacities(ingress_per_sender: usize, service: usize) {
+    INGRESS_PER_SENDER_CAPACITY.store(ingress_per_sender.max(1), Ordering::Relaxed);
+    SERVICE_CHANNEL_CAPACITY.store(service.max(1), Ordering::Relaxed);
+}
+
+pub fn ingress_per_sender_capacity() -> usize {
+    INGRESS_PER_SENDER_CAPACITY.load(Ordering::Relaxed)
+}
+
+pub fn service_channel_capacity() -> usize {
+    SERVICE_CHANNEL_CAPACITY.load(Ordering::Relaxed)
+}
+
+/// Bounded channel between two protocol services.
+pub fn service_channel<T>() -> (Sender<T>, Receiver<T>) {
+    channel(service_channel_capacity())
+}
+
+/// Messages on the ingress path are queued per sender.
+/// The MAC is checked against the key of the claimed sender before the message is queued,
+/// so that a peer cannot fill or starve the queues of other senders by spoofing their ids.
+pub trait IngressMsg {
+    fn ingress_sender(&self) -> Replica;
+    fn authenticate(&self, key: &[u8]) -> bool;
+}
+
+impl<T: Serialize> IngressMsg for WrapperMsg<T> {
+    fn ingress_sender(&self) -> Replica {
+        self.sender
+    }
+
+    fn authenticate(&self, key: &[u8]) -> bool {
+        let byte_val = bincode::serialize(&self.protmsg).expect("Failed to serialize object");
+        verf_mac(&byte_val, key, &self.mac)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct IngressMetrics {
+    pub depth: usize,
+    pub max_depth: usize,
+    pub per_sender: HashMap<Replica, usize>,
+    pub backpressure_events: u64,
+    pub rejected: u64,
+    pub received: u64,
+}
+
+struct SenderQueue<T> {
+    queue: VecDeque<T>,
+    permits: Arc<Semaphore>,
+}
+
+struct IngressQueues<T> {
+    queues: HashMap<Replica, SenderQueue<T>>,
+    // Senders with pending messages, served in round robin order
+    ready: VecDeque<Replica>,
+    depth: usize,
+    max_depth: usize,
+}
+
+struct IngressShared<T> {
+    queues: Mutex<IngressQueues<T>>,
+    notify: Notify,
+    per_sender_capacity: usize,
+    // Keys shared with each peer, messages from senders without a key are rejected
+    keys: HashMap<Replica, Vec<u8>>,
+    senders: AtomicUsize,
+    receiver_closed: AtomicBool,
+    backpressure_events: AtomicU64,
+    rejected: AtomicU64,
+}
+
+/// Network ingress channel with a bounded queue per sender.
+/// A full queue blocks the handler of that sender, which withholds the acknowledgement and pushes back on the peer.
+/// The receiver serves senders in round robin so that a single noisy peer cannot starve the others.
+/// `keys` are the keys shared with the peers, as in the `sk_map` of the config.
+pub fn ingress_channel<T: IngressMsg>(per_sender_capacity: usize, keys: impl IntoIterator<Item = (Replica, Vec<u8>)>) -> (IngressSender<T>, IngressReceiver<T>) {
+    let shared = Arc::new(IngressShared {
+        queues: Mutex::new(IngressQueues {
+            queues: HashMap::default(),
+            ready: VecDeque::new(),
+            depth: 0,
+            max_depth: 0,
+        }),
+        notify: Notify::new(),
+        per_sender_capacity: per_sender_capacity.max(1),
+        keys: keys.into_iter().collect(),
+        senders: AtomicUsize::new(1),
+        receiver_closed: AtomicBool::new(false),
+        backpressure_events: AtomicU64::new(0),
+        rejected: AtomicU64::new(0),
+    });
+    (
+        IngressSender { shared: shared.clone() },
+        IngressReceiver { shared: shared, received: 0 },
+    )
+}
+
+#[derive(Debug)]
+pub struct IngressSendError<T>(pub T);
+
+impl<T> std::fmt::Display for IngressSendError<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ingress channel closed")
+    }
+}
+
+pub struct IngressSender<T: IngressMsg> {
+    shared: Arc<IngressShared<T>>,
+}
+
+impl<T: IngressMsg> Clone for IngressSender<T> {
+    fn clone(&self) -> Self {
+        self.shared.senders.fetch_add(1, Ordering::SeqCst);
+        IngressSender { shared: self.shared.clone() }
+    }
+}
+
+impl<T: IngressMsg> Drop for IngressSender<T> {
+    fn drop(&mut self) {
+        if self.shared.senders.fetch_sub(1, Ordering::SeqCst) == 1 {
+            // Wake up the receiver so that it observes the closed channel
+            self.shared.notify.notify_one();
+        }
+    }
+}
+
+impl<T: IngressMsg> std::fmt::Debug for IngressSender<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IngressSender")
+            .field("per_sender_capacity", &self.shared.per_sender_capacity)
+            .finish()
+    }
+}
+
+impl<T: IngressMsg> IngressSender<T> {
+    /// Enqueues a message, waiting while the queue of its sender is full.
+    /// Messages from unknown senders or with an invalid MAC are dropped without taking queue space.
+    pub async fn send(&self, msg: T) -> Result<(), IngressSendError<T>> {
+        if self.shared.receiver_closed.load(Ordering::SeqCst) {
+            return Err(IngressSendError(msg));
+        }
+        let sender = msg.ingress_sender();
+        let authentic = match self.shared.keys.get(&sender) {
+            Some(key) => msg.authenticate(key.as_slice()),
+            None => false,
+        };
+        if !authentic {
+            self.shared.rejected.fetch_add(1, Ordering::Relaxed);
+            log::warn!("Dropping ingress message with an invalid MAC or unknown sender {}", sender);
+            return Ok(());
+        }
+        let permits = {
+            let mut queues = self.shared.queues.lock().unwrap();
+            let per_sender_capacity = self.shared.per_sender_capacity;
+            queues.queues.entry(sender).or_insert_with(|| SenderQueue {
+                queue: VecDeque::new(),
+                permits: Arc::new(Semaphore::new(per_sender_capacity)),
+            }).permits.clone()
+        };
+        if permits.available_permits() == 0 {
+            self.shared.backpressure_events.fetch_add(1, Ordering::Relaxed);
+            log::warn!("Ingress queue of sender {} is full, applying backpressure", sender);
+        }
+        match permits.acquire().await {
+            Ok(permit) => permit.forget(),
+            Err(_) => return Err(IngressSendError(msg)),
+        }
+        {
+            let mut queues = self.shared.queues.lock().unwrap();
+            let sender_queue = queues.queues.get_mut(&sender).unwrap();
+            sender_queue.queue.push_back(msg);
+            if sender_queue.queue.len() == 1 {
+                queues.ready.push_back(sender);
+            }
+            queues.depth += 1;
+            if queues.depth > queues.max_depth {
+                queues.max_depth = queues.depth;
+            }
+        }
+        self.shared.notify.notify_one();
+        Ok(())
+    }
+}
+
+pub struct IngressReceiver<T: IngressMsg> {
+    shared: Arc<IngressShared<T>>,
+    received: u64,
+}
+
+impl<T: IngressMsg> Drop for IngressReceiver<T> {
+    fn drop(&mut self) {
+        self.shared.receiver_closed.store(true, Ordering::SeqCst);
+        // Release blocked handlers
+        let queues = self.shared.queues.lock().unwrap();
+        for (_, sender_queue) in queues.queues.iter() {
+            sender_queue.permits.close();
+        }
+    }
+}
+
+impl<T: IngressMsg> IngressReceiver<T> {
+    /// Receives the next message, serving senders in round robin order.
+    /// Returns None once all senders are dropped and the queues are drained.
+    pub async fn recv(&mut self) -> Option<T> {
+        loop {
+            if let Some(msg) = self.try_recv() {
+                return Some(msg);
+            }
+            if self.shared.senders.load(Ordering::SeqCst) == 0 {
+                return self.try_recv();
+            }
+            self.shared.notify.notified().await;
+        }
+    }
+
+    pub fn try_recv(&mut self) -> Option<T> {
+        let msg = {
+            let mut queues = self.shared.queues.lock().unwrap();
+            let sender = queues.ready.pop_front()?;
+            let sender_queue = queues.queues.get_mut(&sender).unwrap();
+            let msg = sender_queue.queue.pop_front().unwrap();
+            sender_queue.permits.add_permits(1);
+            let pending = !sender_queue.queue.is_empty();
+            if pending {
+                queues.ready.push_back(sender);
+            }
+            queues.depth -= 1;
+            msg
+        };
+        self.received += 1;
+        if self.received % METRICS_REPORT_INTERVAL == 0 {
+            let metrics = self.metrics();
+            log::debug!(
+                "Ingress queue depth {} (max {}), backpressure events {}, per sender {:?}",
+                metrics.depth,
+                metrics.max_depth,
+                metrics.backpressure_events,
+                metrics.per_sender
+            );
+        }
+        Some(msg)
+    }
+
+    pub fn depth(&self) -> usize {
+        self.shared.queues.lock().unwrap().depth
+    }
+
+    pub fn metrics(&self) -> IngressMetrics {
+        let queues = self.shared.queues.lock().unwrap();
+        IngressMetrics {
+            depth: queues.depth,
+            max_depth: queues.max_depth,
+            per_sender: queues.queues.iter().map(|(rep, sender_queue)| (*rep, sender_queue.queue.len())).collect(),
+            backpressure_events: self.shared.backpressure_events.load(Ordering::Relaxed),
+            rejected: self.shared.rejected.load(Ordering::Relaxed),
+            received: self.received,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    const NUM_NODES: usize = 4;
+
+    fn keys() -> HashMap<Replica, Vec<u8>> {
+        (0..NUM_NODES).map(|rep| (rep, vec![rep as u8 + 1; 32])).collect()
+    }
+
+    fn msg(sender: Replica, value: usize) -> WrapperMsg<usize> {
+        WrapperMsg::new(value, sender, keys().get(&sender).unwrap().as_slice())
+    }
+
+    #[tokio::test]
+    async fn test_rejects_spoofed_and_unknown_senders() {
+        let (tx, mut rx) = ingress_channel::<WrapperMsg<usize>>(4, keys());
+        // MAC computed under the key of party 1 but claiming to come from party 0
+        let mut spoofed = msg(1, 7);
+        spoofed.sender = 0;
+        tx.send(spoofed).await.unwrap();
+        // Sender outside of the committee
+        let mut unknown = msg(1, 8);
+        unknown.sender = NUM_NODES;
+        tx.send(unknown).await.unwrap();
+        tx.send(msg(2, 9)).await.unwrap();
+
+        assert_eq!(rx.recv().await.unwrap().protmsg, 9);
+        assert!(rx.try_recv().is_none());
+        let metrics = rx.metrics();
+        assert_eq!(metrics.rejected, 2);
+        assert!(!metrics.per_sender.contains_key(&0));
+        assert!(!metrics.per_sender.contains_key(&NUM_NODES));
+    }
+
+    #[tokio::test]
+    async fn test_round_robin_across_senders() {
+        let (tx, mut rx) = ingress_channel::<WrapperMsg<usize>>(100, keys());
+        // Party 0 floods the queue before the others send anything
+        for value in 0..50 {
+            tx.send(msg(0, value)).await.unwrap();
+        }
+        for sender in 1..NUM_NODES {
+            tx.send(msg(sender, 100 + sender)).await.unwrap();
+        }
+        // Every other sender is served within the first round
+        let first_round: Vec<Replica> = (0..NUM_NODES).map(|_| rx.try_recv().unwrap().sender).collect();
+        for sender in 0..NUM_NODES {
+            assert!(first_round.contains(&sender));
+        }
+        let rest: Vec<usize> = std::iter::from_fn(|| rx.try_recv()).map(|msg| msg.protmsg).collect();
+        assert_eq!(rest, (1..50).collect::<Vec<usize>>());
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_blocks_only_its_sender() {
+        let (tx, mut rx) = ingress_channel::<WrapperMsg<usize>>(2, keys());
+        tx.send(msg(0, 0)).await.unwrap();
+        tx.send(msg(0, 1)).await.unwrap();
+
+        // The queue of party 0 is full, its next message waits for the receiver
+        let blocked_tx = tx.clone();
+        let blocked = tokio::spawn(async move { blocked_tx.send(msg(0, 2)).await.unwrap() });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!blocked.is_finished());
+        assert_eq!(rx.metrics().backpressure_events, 1);
+
+        // Other senders are not affected
+        tokio::time::timeout(Duration::from_secs(1), tx.send(msg(1, 10))).await.unwrap().unwrap();
+        assert_eq!(rx.depth(), 3);
+
+        assert_eq!(rx.try_recv().unwrap().protmsg, 0);
+        tokio::time::timeout(Duration::from_secs(1), blocked).await.unwrap().unwrap();
+        assert_eq!(rx.depth(), 3);
+        assert_eq!(rx.metrics().per_sender.get(&0), Some(&2));
+    }
+}
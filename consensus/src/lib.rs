@@ -13,8 +13,50 @@ pub use handlers::*;
 mod types;
 pub use types::*;
 
+pub mod field;
+pub use field::*;
+
 pub mod poly;
 pub use poly::*;
 
+pub mod vandermonde;
+pub use vandermonde::*;
+
+pub mod packed;
+pub use packed::*;
+
+pub mod matrix_cache;
+pub use matrix_cache::*;
+
+pub mod fast_poly;
+pub use fast_poly::*;
+
 pub mod timer;
-pub use timer::*;
\ No newline at end of file
+pub use timer::*;
+
+pub mod gc;
+pub use gc::*;
+
+pub mod chunk;
+pub use chunk::*;
+
+pub mod throttle;
+pub use throttle::*;
+
+pub mod ingress;
+pub use ingress::*;
+
+pub mod session;
+pub use session::*;
+
+pub mod auth;
+pub use auth::*;
+
+pub mod rbc;
+pub use rbc::*;
+
+pub mod transcript;
+pub use transcript::*;
+
+pub mod client_output;
+pub use client_output::*;
@@ -0,0 +1,266 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    path::Path,
+    sync::{atomic::{AtomicU64, Ordering}, Arc, OnceLock, RwLock},
+};
+
+use lambdaworks_math::field::element::FieldElement;
+
+use crate::{inverse_vandermonde, vandermonde_matrix, ProtocolField};
+
+/// Maximum memory held by the cached matrices, least recently used matrices are evicted beyond it.
+/// Public reconstruction interpolates over whichever t+1 parties answer first, so the number of point sets is not bounded by the protocol.
+pub const MAX_MATRIX_CACHE_BYTES: usize = 256 << 20;
+
+pub type Matrix<F> = Vec<Vec<FieldElement<F>>>;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum MatrixKey {
+    /// Inverse Vandermonde matrix of the serialized evaluation points
+    InverseVandermonde(Vec<u8>),
+    /// Lagrange coefficients of `LargeFieldSSS` for (threshold, share_amount)
+    Lagrange(usize, usize),
+}
+
+struct CacheEntry {
+    matrix: Arc<dyn Any + Send + Sync>,
+    bytes: usize,
+    last_used: AtomicU64,
+}
+
+#[derive(Default)]
+struct MatrixCache {
+    entries: HashMap<(TypeId, MatrixKey), CacheEntry>,
+    bytes: usize,
+    clock: AtomicU64,
+}
+
+impl MatrixCache {
+    fn tick(&self) -> u64 {
+        self.clock.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Inserts a matrix, evicting least recently used matrices until it fits under `capacity` bytes
+    fn insert(&mut self, key: (TypeId, MatrixKey), matrix: Arc<dyn Any + Send + Sync>, bytes: usize, capacity: usize) {
+        if bytes > capacity {
+            log::debug!("Matrix of {} bytes exceeds the cache capacity, not caching {:?}", bytes, key.1);
+            return;
+        }
+        if let Some(entry) = self.entries.remove(&key) {
+            self.bytes -= entry.bytes;
+        }
+        while self.bytes + bytes > capacity {
+            let oldest = self.entries.iter()
+                .min_by_key(|(_key, entry)| entry.last_used.load(Ordering::Relaxed))
+                .map(|(key, _entry)| key.clone());
+            match oldest {
+                Some(oldest) => {
+                    let entry = self.entries.remove(&oldest).unwrap();
+                    self.bytes -= entry.bytes;
+                },
+                None => break,
+            }
+        }
+        let last_used = AtomicU64::new(self.tick());
+        self.bytes += bytes;
+        self.entries.insert(key, CacheEntry { matrix, bytes, last_used });
+    }
+}
+
+static MATRIX_CACHE: OnceLock<RwLock<MatrixCache>> = OnceLock::new();
+
+fn matrix_cache() -> &'static RwLock<MatrixCache> {
+    MATRIX_CACHE.get_or_init(|| RwLock::new(MatrixCache::default()))
+}
+
+fn matrix_bytes<F: ProtocolField>(matrix: &Matrix<F>) -> usize {
+    matrix.iter().map(|row| std::mem::size_of::<Vec<FieldElement<F>>>() + row.len() * std::mem::size_of::<FieldElement<F>>()).sum()
+}
+
+/// Returns the matrix cached under `key`, computing and caching it on the first request.
+/// Hits share the cached matrix instead of copying it. The matrix is computed outside the lock, concurrent misses on the same key compute the same matrix.
+pub(crate) fn cached_matrix<F: ProtocolField>(key: MatrixKey, compute: impl FnOnce() -> Matrix<F>) -> Arc<Matrix<F>>
+where FieldElement<F>: Send + Sync {
+    let key = (TypeId::of::<F>(), key);
+    {
+        let cache = matrix_cache().read().unwrap();
+        if let Some(entry) = cache.entries.get(&key) {
+            if let Ok(matrix) = entry.matrix.clone().downcast::<Matrix<F>>() {
+                entry.last_used.store(cache.tick(), Ordering::Relaxed);
+                return matrix;
+            }
+        }
+    }
+    let matrix = Arc::new(compute());
+    insert_matrix(key, matrix.clone());
+    matrix
+}
+
+fn insert_matrix<F: ProtocolField>(key: (TypeId, MatrixKey), matrix: Arc<Matrix<F>>)
+where FieldElement<F>: Send + Sync {
+    let bytes = matrix_bytes(matrix.as_ref());
+    matrix_cache().write().unwrap().insert(key, matrix, bytes, MAX_MATRIX_CACHE_BYTES);
+}
+
+fn points_key<F: ProtocolField>(x_values: &[FieldElement<F>]) -> MatrixKey {
+    let mut key = Vec::with_capacity(x_values.len() * F::SER_LEN);
+    for x in x_values {
+        key.extend(F::to_ser(x));
+    }
+    MatrixKey::InverseVandermonde(key)
+}
+
+/// Inverse of the Vandermonde matrix of `x_values`, computed once per set of points and shared by all services of the process.
+pub fn cached_inverse_vandermonde<F: ProtocolField>(x_values: &[FieldElement<F>]) -> Arc<Matrix<F>>
+where FieldElement<F>: Send + Sync {
+    cached_matrix(points_key(x_values), || inverse_vandermonde(vandermonde_matrix(x_values.to_vec())))
+}
+
+pub fn cached_matrices() -> usize {
+    matrix_cache().read().unwrap().entries.len()
+}
+
+pub fn cached_matrix_bytes() -> usize {
+    matrix_cache().read().unwrap().bytes
+}
+
+/// Evaluation points of the precomputed matrices in `data/`.
+/// `lt` holds the inverse for the low-threshold points 1..t+1, `ht` for the high-threshold points -t..t.
+pub fn precomputed_points<F: ProtocolField>(threshold_dir: &str, num_nodes: usize) -> Option<Vec<FieldElement<F>>> {
+    let num_faults = (num_nodes - 1) / 3;
+    match threshold_dir {
+        "lt" => Some((1..num_faults as u64 + 2).map(FieldElement::<F>::from).collect()),
+        "ht" => Some((0..2 * num_faults as u64 + 1).map(|index| {
+            FieldElement::<F>::from(index) - FieldElement::<F>::from(num_faults as u64)
+        }).collect()),
+        _ => None,
+    }
+}
+
+/// Loads the precomputed inverse Vandermonde matrices for `num_nodes` parties from `dir/lt` and `dir/ht` into the cache.
+/// Elements are big-endian byte strings reduced into the field. The matrices in `data/` are generated over the default Stark252 field.
+/// A matrix that does not invert the Vandermonde matrix of its points over the configured field, for example one generated over another field, is an error.
+/// Returns the number of matrices loaded, missing files are skipped.
+pub fn load_precomputed_matrices<F: ProtocolField>(dir: &Path, num_nodes: usize) -> Result<usize, String>
+where FieldElement<F>: Send + Sync {
+    let mut loaded = 0;
+    for threshold_dir in ["lt", "ht"].iter() {
+        let path = dir.join(threshold_dir).join(format!("vandermonde_inverse-{}.json", num_nodes));
+        let contents = match std::fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) => {
+                log::warn!("No precomputed matrix at {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        let rows: Vec<Vec<Vec<u8>>> = match serde_json::from_str(contents.as_str()) {
+            Ok(rows) => rows,
+            Err(e) => {
+                return Err(format!("Unable to parse precomputed matrix {}: {}", path.display(), e));
+            }
+        };
+        let points = precomputed_points::<F>(threshold_dir, num_nodes).unwrap();
+        let matrix: Matrix<F> = rows.iter().map(|row| row.iter().map(|bytes| reduce_be_bytes::<F>(bytes)).collect()).collect();
+        if !is_inverse_vandermonde(&matrix, &points) {
+            return Err(format!("Precomputed matrix {} is not the inverse Vandermonde matrix of its points over the configured field", path.display()));
+        }
+        insert_matrix((TypeId::of::<F>(), points_key(&points)), Arc::new(matrix));
+        loaded += 1;
+    }
+    Ok(loaded)
+}
+
+fn reduce_be_bytes<F: ProtocolField>(bytes: &[u8]) -> FieldElement<F> {
+    let base = FieldElement::<F>::from(256u64);
+    bytes.iter().fold(FieldElement::<F>::zero(), |acc, byte| acc * &base + FieldElement::<F>::from(*byte as u64))
+}
+
+fn is_inverse_vandermonde<F: ProtocolField>(matrix: &Matrix<F>, points: &[FieldElement<F>]) -> bool {
+    let size = points.len();
+    if matrix.len() != size || matrix.iter().any(|row| row.len() != size) {
+        return false;
+    }
+    let vandermonde = vandermonde_matrix(points.to_vec());
+    for i in 0..size {
+        for j in 0..size {
+            let entry = (0..size).fold(FieldElement::<F>::zero(), |sum, k| sum + &matrix[i][k] * &vandermonde[k][j]);
+            let expected = if i == j { FieldElement::<F>::one() } else { FieldElement::<F>::zero() };
+            if entry != expected {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FieldType, LargeField};
+    use lambdaworks_math::traits::ByteConversion;
+
+    #[test]
+    fn test_cached_inverse_matches_fresh_inverse() {
+        let points: Vec<LargeField> = (1..8u64).map(LargeField::from).collect();
+        let first = cached_inverse_vandermonde(&points);
+        let second = cached_inverse_vandermonde(&points);
+        assert_eq!(*first, inverse_vandermonde(vandermonde_matrix(points.clone())));
+        // Hits share the cached matrix
+        assert!(Arc::ptr_eq(&first, &second));
+        // A different point set is a different entry
+        let shifted: Vec<LargeField> = (2..9u64).map(LargeField::from).collect();
+        assert_ne!(cached_inverse_vandermonde(&shifted), first);
+    }
+
+    #[test]
+    fn test_load_precomputed_matrices_checks_the_field() {
+        let num_nodes = 10;
+        let dir = std::env::temp_dir().join(format!("matrix_cache_test_{}", std::process::id()));
+        for threshold_dir in ["lt", "ht"].iter() {
+            let points = precomputed_points::<FieldType>(threshold_dir, num_nodes).unwrap();
+            let mut matrix = inverse_vandermonde(vandermonde_matrix(points));
+            if *threshold_dir == "ht" {
+                // Corrupt the high-threshold matrix, as if generated over another field
+                matrix[0][0] = matrix[0][0].clone() + LargeField::one();
+            }
+            let rows: Vec<Vec<Vec<u8>>> = matrix.iter().map(|row| row.iter().map(|el| el.to_bytes_be().to_vec()).collect()).collect();
+            std::fs::create_dir_all(dir.join(threshold_dir)).unwrap();
+            std::fs::write(
+                dir.join(threshold_dir).join(format!("vandermonde_inverse-{}.json", num_nodes)),
+                serde_json::to_string(&rows).unwrap()
+            ).unwrap();
+        }
+        assert!(load_precomputed_matrices::<FieldType>(&dir, num_nodes).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "bls12_381", feature = "goldilocks")))]
+    fn test_shipped_matrices_match_the_default_field() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("..").join("data");
+        for num_nodes in vec![4, 10, 97] {
+            assert_eq!(load_precomputed_matrices::<FieldType>(&dir, num_nodes), Ok(2));
+        }
+    }
+
+    #[test]
+    fn test_cache_evicts_least_recently_used_matrices() {
+        let mut cache = MatrixCache::default();
+        let key = |index: usize| (TypeId::of::<FieldType>(), MatrixKey::Lagrange(index, index));
+        let matrix = || Arc::new(vec![vec![LargeField::one()]]) as Arc<dyn Any + Send + Sync>;
+        for index in 0..3 {
+            cache.insert(key(index), matrix(), 10, 30);
+        }
+        // Touch the oldest entry, the second one becomes the least recently used
+        cache.entries.get(&key(0)).unwrap().last_used.store(cache.tick(), Ordering::Relaxed);
+        cache.insert(key(3), matrix(), 10, 30);
+        assert_eq!(cache.bytes, 30);
+        assert!(cache.entries.contains_key(&key(0)));
+        assert!(!cache.entries.contains_key(&key(1)));
+        // A matrix larger than the capacity is never cached
+        cache.insert(key(4), matrix(), 31, 30);
+        assert!(!cache.entries.contains_key(&key(4)));
+        assert_eq!(cache.entries.len(), 3);
+    }
+}
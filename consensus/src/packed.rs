@@ -0,0 +1,161 @@
+use ha_crypto::{aes_hash::{HashState, MerkleTree, Proof}, hash::Hash};
+use lambdaworks_math::field::element::FieldElement;
+use rayon::prelude::{IntoParallelIterator, ParallelIterator};
+use serde::{Deserialize, Serialize};
+
+use crate::ProtocolField;
+
+/// Bytes absorbed by a single AES hash invocation
+pub const HASH_LEAF_BYTES: usize = 32;
+
+/// Number of elements packed into a Merkle leaf so that a leaf fills one hash block.
+/// One element per leaf for 252-bit fields, four elements per leaf for 64-bit fields.
+pub fn packing_factor<F: ProtocolField>() -> usize {
+    std::cmp::max(1, HASH_LEAF_BYTES / F::SER_LEN)
+}
+
+/// Serializes `elements` into leaves of `pack` consecutive elements. The last leaf may be shorter.
+pub fn pack_elements<F: ProtocolField>(elements: &[FieldElement<F>], pack: usize) -> Vec<Vec<u8>> {
+    elements.chunks(pack).map(|chunk| {
+        let mut leaf = Vec::with_capacity(chunk.len() * F::SER_LEN);
+        for element in chunk {
+            leaf.extend(F::to_ser(element));
+        }
+        leaf
+    }).collect()
+}
+
+pub fn unpack_elements<F: ProtocolField>(leaf: &[u8]) -> Option<Vec<FieldElement<F>>> {
+    if leaf.len() % F::SER_LEN != 0 {
+        return None;
+    }
+    leaf.chunks(F::SER_LEN).map(|bytes| F::from_ser(bytes)).collect()
+}
+
+/// Merkle commitment to a vector of field elements with `pack` elements per leaf.
+/// Packing cuts the number of hashes and the depth of the tree for small fields, where a single element does not fill a hash block.
+pub struct PackedMerkleTree {
+    pub tree: MerkleTree,
+    pub pack: usize,
+    pub num_elements: usize,
+}
+
+impl PackedMerkleTree {
+    pub fn new<F: ProtocolField>(elements: &[FieldElement<F>], pack: usize, hash_context: &HashState) -> PackedMerkleTree {
+        assert!(pack > 0, "Packing factor must be positive");
+        let hashes: Vec<Hash> = pack_elements(elements, pack).into_par_iter().map(|leaf| {
+            hash_context.do_hash_aes(leaf.as_slice())
+        }).collect();
+        PackedMerkleTree {
+            tree: MerkleTree::new(hashes, hash_context),
+            pack: pack,
+            num_elements: elements.len(),
+        }
+    }
+
+    pub fn root(&self) -> Hash {
+        self.tree.root()
+    }
+
+    /// Proof for the leaf holding element `index`. The proof carries all elements of the leaf.
+    pub fn gen_proof<F: ProtocolField>(&self, elements: &[FieldElement<F>], index: usize) -> PackedProof {
+        let leaf_index = index / self.pack;
+        let start = leaf_index * self.pack;
+        let end = std::cmp::min(start + self.pack, self.num_elements);
+        PackedProof {
+            leaf_index: leaf_index,
+            leaf: pack_elements(&elements[start..end], self.pack).pop().unwrap_or_default(),
+            proof: self.tree.gen_proof(leaf_index),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PackedProof {
+    pub leaf_index: usize,
+    pub leaf: Vec<u8>,
+    pub proof: Proof,
+}
+
+impl PackedProof {
+    /// Verifies the proof against `root` and returns the element at `index` of the committed vector.
+    pub fn verify<F: ProtocolField>(&self, index: usize, pack: usize, root: Hash, hash_context: &HashState) -> Option<FieldElement<F>> {
+        if pack == 0 || index / pack != self.leaf_index {
+            return None;
+        }
+        if !self.proof.validate(hash_context)
+            || self.proof.root() != root
+            || self.proof.item() != hash_context.do_hash_aes(self.leaf.as_slice()) {
+            return None;
+        }
+        let elements = unpack_elements::<F>(self.leaf.as_slice())?;
+        elements.get(index % pack).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LargeField, SmallField, SmallFieldType, FieldType};
+    use proptest::{collection, prelude::*};
+
+    fn hash_context() -> HashState {
+        HashState::new([5u8; 16], [29u8; 16], [23u8; 16])
+    }
+
+    #[test]
+    fn test_packing_factor_fills_a_hash_block() {
+        assert_eq!(packing_factor::<SmallFieldType>(), 4);
+        assert_eq!(packing_factor::<FieldType>(), 1);
+    }
+
+    #[test]
+    fn test_packed_proof_rejects_other_positions_and_tampering() {
+        let hash_context = hash_context();
+        let elements: Vec<SmallField> = (0..10u64).map(SmallField::from).collect();
+        let tree = PackedMerkleTree::new(&elements, 4, &hash_context);
+        let proof = tree.gen_proof(&elements, 5);
+
+        assert_eq!(proof.verify::<SmallFieldType>(5, 4, tree.root(), &hash_context), Some(SmallField::from(5)));
+        // Elements of the same leaf are opened by the same proof
+        assert_eq!(proof.verify::<SmallFieldType>(7, 4, tree.root(), &hash_context), Some(SmallField::from(7)));
+        // Elements of another leaf are not
+        assert!(proof.verify::<SmallFieldType>(1, 4, tree.root(), &hash_context).is_none());
+        assert!(proof.verify::<SmallFieldType>(5, 2, tree.root(), &hash_context).is_none());
+
+        let other_root = PackedMerkleTree::new(&elements[1..], 4, &hash_context).root();
+        assert!(proof.verify::<SmallFieldType>(5, 4, other_root, &hash_context).is_none());
+
+        let mut tampered = proof.clone();
+        tampered.leaf[SmallFieldType::SER_LEN - 1] ^= 1;
+        assert!(tampered.verify::<SmallFieldType>(4, 4, tree.root(), &hash_context).is_none());
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn prop_packed_commitment_opens_every_element(
+            values in collection::vec(any::<u64>(), 1..40),
+            pack in 1usize..6,
+        ) {
+            let hash_context = hash_context();
+            let small: Vec<SmallField> = values.iter().map(|value| SmallField::from(*value)).collect();
+            let tree = PackedMerkleTree::new(&small, pack, &hash_context);
+            prop_assert_eq!(pack_elements(&small, pack).len(), (values.len() + pack - 1) / pack);
+            for index in 0..small.len() {
+                let proof = tree.gen_proof(&small, index);
+                prop_assert_eq!(proof.verify::<SmallFieldType>(index, pack, tree.root(), &hash_context), Some(small[index].clone()));
+            }
+
+            // One element per leaf over the large field
+            let large: Vec<LargeField> = values.iter().map(|value| LargeField::from(*value)).collect();
+            let pack = packing_factor::<FieldType>();
+            let tree = PackedMerkleTree::new(&large, pack, &hash_context);
+            for index in 0..large.len() {
+                let proof = tree.gen_proof(&large, index);
+                prop_assert_eq!(proof.verify::<FieldType>(index, pack, tree.root(), &hash_context), Some(large[index].clone()));
+            }
+        }
+    }
+}
@@ -1,22 +1,21 @@
-use std::{collections::HashMap, ops::{Mul, Sub, Add}};
+use std::collections::HashMap;
 
 use ha_crypto::hash::do_hash;
-use lambdaworks_math::{unsigned_integer::element::UnsignedInteger, polynomial::Polynomial, field::fields::{montgomery_backed_prime_fields::MontgomeryBackendPrimeField, fft_friendly::stark_252_prime_field::MontgomeryConfigStark252PrimeField}};
-use rand::random;
+use lambdaworks_math::{polynomial::Polynomial, field::{element::FieldElement, traits::RootsConfig}, fft::cpu::roots_of_unity::get_powers_of_primitive_root};
 use rand_chacha::ChaCha20Rng;
-use rand_core::{SeedableRng, RngCore};
+use rand_core::SeedableRng;
 use rayon::prelude::{IntoParallelIterator, ParallelIterator, IntoParallelRefIterator};
 use types::Replica;
 
-use crate::LargeField;
+use crate::{LargeField, FieldType, ProtocolField, interpolate_polynomials, evaluate_polynomials};
 
-pub fn sample_polynomials_from_prf(
-    secrets: Vec<LargeField>, 
+pub fn sample_polynomials_from_prf<F: ProtocolField>(
+    secrets: Vec<FieldElement<F>>, 
     sec_key_map: HashMap<Replica, Vec<u8>>, 
     degree: usize,
     is_nonce: bool,
     nonce: u8
-)-> Vec<Vec<LargeField>>{
+)-> Vec<Vec<FieldElement<F>>>{
     let tot_evaluations = secrets.len();
     let mut evaluations = Vec::new();
     for secret in secrets{
@@ -27,7 +26,7 @@ pub fn sample_polynomials_from_prf(
         if is_nonce{
             sec_key.push(nonce);
         }
-        let samples = pseudorandom_lf(&sec_key, tot_evaluations);
+        let samples = pseudorandom_field::<F>(&sec_key, tot_evaluations);
         for (i,sample) in samples.into_iter().enumerate() {
             evaluations[i].push(sample);
         }
@@ -35,23 +34,23 @@ pub fn sample_polynomials_from_prf(
     evaluations
 }
 
-pub fn expand_sharing_to_n_evaluation_points(
-    evaluations_prf: Vec<Vec<LargeField>>, 
+pub fn expand_sharing_to_n_evaluation_points<F: ProtocolField>(
+    evaluations_prf: Vec<Vec<FieldElement<F>>>, 
     degree: usize,
     shares_total: usize,
-) -> (Vec<Vec<LargeField>>, 
-    Vec<Polynomial<LargeField>>
-){
+) -> (Vec<Vec<FieldElement<F>>>, 
+    Vec<Polynomial<FieldElement<F>>>
+) where FieldElement<F>: Send + Sync{
 
     // The first evaluation is always at 0
     let mut evaluation_points = Vec::new();
-    evaluation_points.push(LargeField::new(UnsignedInteger::from(0u64)));
+    evaluation_points.push(FieldElement::<F>::zero());
     for i in 0..degree{
-        evaluation_points.push(LargeField::new(UnsignedInteger::from((i+1) as u64)));
+        evaluation_points.push(FieldElement::<F>::from((i+1) as u64));
     }
     
     // Generate coefficients of polynomial and then evaluate the polynomial at n points
-    let coefficients: Vec<Polynomial<LargeField>> = evaluations_prf.into_par_iter().map(|evals| {
+    let coefficients: Vec<Polynomial<FieldElement<F>>> = evaluations_prf.into_par_iter().map(|evals| {
         return Polynomial::interpolate(evaluation_points.as_slice(), evals.as_slice()).unwrap()
     }).collect();
 
@@ -59,198 +58,172 @@ pub fn expand_sharing_to_n_evaluation_points(
     let evaluations_full = coefficients.par_iter().map(|polynomial|{
         let mut eval_vec_ind = Vec::new();
         for index in 0..shares_total{
-            eval_vec_ind.push(polynomial.evaluate(&LargeField::new(UnsignedInteger::from((index+1) as u64))));
+            eval_vec_ind.push(polynomial.evaluate(&FieldElement::<F>::from((index+1) as u64)));
         }
         return eval_vec_ind;
     }).collect();
     (evaluations_full,coefficients)
 }
 
-pub fn expand_sharing_to_n_evaluation_points_opt(
-    evaluations_prf: Vec<Vec<LargeField>>, 
+pub fn expand_sharing_to_n_evaluation_points_opt<F: ProtocolField>(
+    evaluations_prf: Vec<Vec<FieldElement<F>>>, 
     degree: usize,
     shares_total: usize,
-) -> (Vec<Vec<LargeField>>, 
-    Vec<Polynomial<LargeField>>
-){
+) -> (Vec<Vec<FieldElement<F>>>, 
+    Vec<Polynomial<FieldElement<F>>>
+) where FieldElement<F>: Send + Sync{
 
     // The first evaluation is always at 0
     let mut evaluation_points = Vec::new();
-    evaluation_points.push(LargeField::new(UnsignedInteger::from(0u64)));
+    evaluation_points.push(FieldElement::<F>::zero());
     for i in 0..degree{
-        evaluation_points.push(LargeField::new(UnsignedInteger::from((i+1) as u64)));
+        evaluation_points.push(FieldElement::<F>::from((i+1) as u64));
     }
     
-    // Generate vandermonde matrix
-    let vandermonde = vandermonde_matrix(evaluation_points.clone());
-    let inverse_vandermonde = inverse_vandermonde(vandermonde);
-
-    let coefficients : Vec<Polynomial<LargeField>> = evaluations_prf.into_par_iter().map(|evals|{
-        let coefficients = matrix_vector_multiply(&inverse_vandermonde, &evals);
+    // Dense linear algebra for small committees, product trees for large ones
+    let coefficients : Vec<Polynomial<FieldElement<F>>> = interpolate_polynomials(&evaluation_points, evaluations_prf).into_iter().map(|coefficients|{
         return Polynomial::new(&coefficients);
     }).collect();
 
     // Evaluate the polynomial at n points
-    let evaluations_full = coefficients.par_iter().map(|polynomial|{
-        let mut eval_vec_ind = Vec::new();
-        for index in 0..shares_total{
-            eval_vec_ind.push(polynomial.evaluate(&LargeField::new(UnsignedInteger::from((index+1) as u64))));
-        }
-        return eval_vec_ind;
-    }).collect();
+    let share_points: Vec<FieldElement<F>> = (1..shares_total as u64 + 1).map(FieldElement::<F>::from).collect();
+    let evaluations_full = evaluate_polynomials(&coefficients, &share_points);
     (evaluations_full,coefficients)
 }
 
-pub async fn generate_evaluation_points_fft(
-    secrets: Vec<LargeField>,
+pub async fn generate_evaluation_points_fft<F: ProtocolField>(
+    secrets: Vec<FieldElement<F>>,
     degree_poly: usize,
     shares_total: usize,
-)-> (Vec<Vec<LargeField>>, 
-    Vec<Polynomial<LargeField>>
-){
+)-> (Vec<Vec<FieldElement<F>>>, 
+    Vec<Polynomial<FieldElement<F>>>
+) where FieldElement<F>: Send + Sync{
     // For FFT evaluations, first sample coefficients of polynomial and then interpolate all n points
-    let coefficients: Vec<Polynomial<LargeField>> = secrets.into_par_iter().map(|secret| {
+    let coefficients: Vec<Polynomial<FieldElement<F>>> = secrets.into_par_iter().map(|secret| {
         let mut coeffs_single_poly = Vec::new();
         coeffs_single_poly.push(secret);
         for _ in 0..degree_poly{
-            coeffs_single_poly.push(rand_field_element());
+            coeffs_single_poly.push(F::random_element());
         }
         return Polynomial::new(&coeffs_single_poly);
     }).collect();
 
     let evaluations = coefficients.par_iter().map(|poly_coeffs|{
-        let poly_evaluations_fft = Polynomial::evaluate_fft::<MontgomeryBackendPrimeField<MontgomeryConfigStark252PrimeField, 4>>(poly_coeffs, 1, Some(shares_total)).unwrap();
-        poly_evaluations_fft
+        evaluate_at_roots_of_unity(poly_coeffs, shares_total)
     }).collect();
     (evaluations, coefficients)
 }
 
+/// Same as `expand_sharing_to_n_evaluation_points_opt`, but the i-th party's share is the evaluation at `roots_of_unity[i]`.
+/// The PRF-derived values are evaluations at 0 and at the first `degree` roots, so the first t parties can still expand their shares from the PRF keys.
+/// The polynomials are interpolated through `interpolate_polynomials` and evaluated at all n points with an FFT.
+pub fn expand_sharing_to_n_evaluation_points_fft<F: ProtocolField>(
+    evaluations_prf: Vec<Vec<FieldElement<F>>>, 
+    degree: usize,
+    shares_total: usize,
+    roots_of_unity: &[FieldElement<F>],
+) -> (Vec<Vec<FieldElement<F>>>, 
+    Vec<Polynomial<FieldElement<F>>>
+) where FieldElement<F>: Send + Sync{
+
+    // The first evaluation is always at 0
+    let mut evaluation_points = Vec::new();
+    evaluation_points.push(FieldElement::<F>::zero());
+    evaluation_points.extend(roots_of_unity[0..degree].iter().cloned());
+    
+    let coefficients : Vec<Polynomial<FieldElement<F>>> = interpolate_polynomials(&evaluation_points, evaluations_prf).into_iter().map(|coefficients|{
+        return Polynomial::new(&coefficients);
+    }).collect();
+
+    let evaluations_full = coefficients.par_iter().map(|polynomial|{
+        evaluate_at_roots_of_unity(polynomial, shares_total)
+    }).collect();
+    (evaluations_full,coefficients)
+}
+
+/// Evaluates `polynomial` at the first `shares_total` powers of the primitive root of order `shares_total.next_power_of_two()`.
+pub fn evaluate_at_roots_of_unity<F: ProtocolField>(polynomial: &Polynomial<FieldElement<F>>, shares_total: usize) -> Vec<FieldElement<F>>{
+    let mut evaluations = Polynomial::evaluate_fft::<F>(polynomial, 1, Some(shares_total)).unwrap();
+    // The FFT evaluates at the whole power-of-two domain
+    evaluations.truncate(shares_total);
+    evaluations
+}
+
+/// The evaluation points of the FFT sharing path, the powers of the primitive root of order `n.next_power_of_two()`
+pub fn roots_of_unity<F: ProtocolField>(n: usize) -> Vec<FieldElement<F>>{
+    let len = n.next_power_of_two();
+    let order = len.trailing_zeros();
+    let mut roots = get_powers_of_primitive_root::<F>(order.into(), len, RootsConfig::Natural).unwrap();
+    roots.truncate(n);
+    roots
+}
+
 pub fn pseudorandom_lf(rng_seed: &[u8], num: usize)->Vec<LargeField>{
+    pseudorandom_field::<FieldType>(rng_seed, num)
+}
+
+pub fn pseudorandom_field<F: ProtocolField>(rng_seed: &[u8], num: usize)->Vec<FieldElement<F>>{
     let mut rng = ChaCha20Rng::from_seed(do_hash(rng_seed));
-    let mut random_numbers: Vec<LargeField> = Vec::new();
+    let mut random_numbers: Vec<FieldElement<F>> = Vec::new();
     for _i in 0..num{
-        let mut limbs = [0u64;4];
-        for j in 0..4{
-            limbs[j] = rng.next_u64();
-        }
-        let bigint_rand = UnsignedInteger{ 
-            limbs: limbs
-        };
-        random_numbers.push(LargeField::new( bigint_rand));
+        random_numbers.push(F::sample(&mut rng));
     }
     random_numbers
 }
 
 pub fn rand_field_element() -> LargeField {
-    let rand_big = UnsignedInteger { limbs: random() };
-    LargeField::new(rand_big)
+    FieldType::random_element()
 }
 
 
-pub fn interpolate_shares( mut secret_key: Vec<u8>, num_shares: usize, is_nonce: bool, padding: u8) -> Vec<LargeField>{
+pub fn interpolate_shares( secret_key: Vec<u8>, num_shares: usize, is_nonce: bool, padding: u8) -> Vec<LargeField>{
+    interpolate_shares_field::<FieldType>(secret_key, num_shares, is_nonce, padding)
+}
+
+pub fn interpolate_shares_field<F: ProtocolField>( mut secret_key: Vec<u8>, num_shares: usize, is_nonce: bool, padding: u8) -> Vec<FieldElement<F>>{
     if is_nonce{
         secret_key.push(padding);
     }
-    let prf_values = pseudorandom_lf(&secret_key, num_shares);
+    let prf_values = pseudorandom_field::<F>(&secret_key, num_shares);
     prf_values
 }
 
-pub fn check_if_all_points_lie_on_degree_x_polynomial(eval_points: Vec<LargeField>, polys_vector: Vec<Vec<LargeField>>, degree: usize) -> (bool,Option<Vec<Polynomial<LargeField>>>){
-    //log::info!("Checking evaluations on points :{:?}, eval_points: {:?}", eval_points, polys_vector);
-    let inverse_vandermonde = inverse_vandermonde(vandermonde_matrix(eval_points[0..degree].to_vec()));
-    let polys = polys_vector.into_par_iter().map(|points| {
-        let coeffs = matrix_vector_multiply(&inverse_vandermonde, &points[0..degree].to_vec());
-        let polynomial = Polynomial::new(&coeffs);
-        let all_points_match =  eval_points[degree..].iter().zip(points[degree..].iter()).map(|(eval_point, share)|{
-            return polynomial.evaluate(eval_point) == *share;
-        }).fold(true, |acc,x| acc && x);
-
-        if all_points_match{
-            Some(polynomial)
-        }
-        else{
-            None
-        }
-    }).fold(|| Vec::new(), |mut acc_vec, vec: Option<Polynomial<LargeField>>|{
-        acc_vec.push(vec);
-        acc_vec
-    }).reduce(|| Vec::new(), |mut acc_vec, vec: Vec<Option<Polynomial<LargeField>>>|{
-        acc_vec.extend(vec);
-        acc_vec
-    });
-    let all_polys_positive = polys.par_iter().all(|poly| poly.is_some());
-    if all_polys_positive{
-        let polys_vec = polys.into_iter().map(|x| x.unwrap()).collect();
-        (true, Some(polys_vec))
-    }
-    else{
-        (false, None)
-    }
-}
-
-
-/// Constructs the Vandermonde matrix for a given set of x-values.
-pub fn vandermonde_matrix(x_values: Vec<LargeField>) -> Vec<Vec<LargeField>> {
-    let n = x_values.len();
-    let mut matrix = vec![vec![LargeField::zero(); n]; n];
-
-    for (row, x) in x_values.iter().enumerate() {
-        let mut value = LargeField::one();
-        for col in 0..n {
-            matrix[row][col] = value.clone();
-            value = value.mul(x);
-        }
-    }
-
-    matrix
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-/// Computes the inverse of a Vandermonde matrix modulo prime using Gaussian elimination.
-pub fn inverse_vandermonde(matrix: Vec<Vec<LargeField>>) -> Vec<Vec<LargeField>> {
-    let n = matrix.len();
-    let mut augmented = matrix.clone();
+    const NUM_NODES: usize = 16;
+    const NUM_FAULTS: usize = 5;
 
-    // Extend the matrix with an identity matrix on the right
-    for i in 0..n {
-        augmented[i].extend((0..n).map(|j| if i == j { LargeField::one() } else { LargeField::zero() }));
+    fn prf_keys(num_nodes: usize) -> HashMap<Replica, Vec<u8>> {
+        (0..num_nodes).map(|rep| (rep, vec![rep as u8; 32])).collect()
     }
 
-    // Perform Gaussian elimination
-    for col in 0..n {
-        // Normalize pivot row
-        let inv = &augmented[col][col].inv().unwrap();
-        for k in col..2 * n {
-            augmented[col][k] = augmented[col][k].mul(inv);
-        }
-
-        // Eliminate other rows
-        for row in 0..n {
-            if row != col {
-                let factor = augmented[row][col].clone();
-                for k in col..2 * n {
-                    augmented[row][k] = augmented[row][k].sub(factor.mul(augmented[col][k]));
-                }
+    #[test]
+    fn test_fft_sharing_matches_prf_and_reconstructs_at_zero() {
+        let roots = roots_of_unity::<FieldType>(NUM_NODES);
+        assert_eq!(roots.len(), NUM_NODES);
+        let secrets: Vec<LargeField> = (0..10).map(|_| rand_field_element()).collect();
+        let keys = prf_keys(NUM_NODES);
+        let evaluations_prf = sample_polynomials_from_prf(secrets.clone(), keys.clone(), NUM_FAULTS, false, 1u8);
+        let (evaluations, coefficients) = expand_sharing_to_n_evaluation_points_fft(evaluations_prf, NUM_FAULTS, NUM_NODES, &roots);
+
+        for (index, (shares, polynomial)) in evaluations.iter().zip(coefficients.iter()).enumerate() {
+            assert_eq!(shares.len(), NUM_NODES);
+            // The share of party i is the evaluation at the i-th root
+            for (share, root) in shares.iter().zip(roots.iter()) {
+                assert_eq!(*share, polynomial.evaluate(root));
             }
+            // The first t parties can expand their shares from the PRF keys alone
+            for rep in 0..NUM_FAULTS {
+                let prf_share = interpolate_shares(keys.get(&rep).unwrap().clone(), secrets.len(), false, 1u8)[index].clone();
+                assert_eq!(shares[rep], prf_share);
+            }
+            // Any t+1 shares reconstruct the secret at 0
+            let points: Vec<LargeField> = roots[NUM_NODES-NUM_FAULTS-1..].to_vec();
+            let values: Vec<LargeField> = shares[NUM_NODES-NUM_FAULTS-1..].to_vec();
+            let interpolated = Polynomial::interpolate(&points, &values).unwrap();
+            assert_eq!(interpolated.evaluate(&LargeField::zero()), secrets[index]);
         }
     }
-
-    // Extract the right half as the inverse
-    augmented
-        .into_iter()
-        .map(|row| row[n..2 * n].to_vec())
-        .collect()
 }
-
-pub fn matrix_vector_multiply(
-    matrix: &Vec<Vec<LargeField>>,
-    vector: &Vec<LargeField>,
-) -> Vec<LargeField> {
-    matrix
-        .par_iter()
-        .map(|row| {
-            row.iter()
-                .zip(vector)
-                .fold(LargeField::zero(), |sum, (a, b)| sum.add(a.mul(b)))
-        })
-        .collect()
-}
\ No newline at end of file
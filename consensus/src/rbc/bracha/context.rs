@@ -0,0 +1,160 @@
+use std::{
+    collections::HashMap,
+    net::{SocketAddr, SocketAddrV4},
+};
+
+use config::Node;
+use fnv::FnvHashMap;
+use network::{plaintcp::{CancelHandler, TcpReceiver, TcpReliableSender}, Acknowledgement};
+use tokio::sync::{
+    mpsc::{Receiver, Sender},
+    oneshot,
+};
+use types::{Replica, WrapperMsg};
+
+use crate::{gc_retention, ingress_channel, ingress_per_sender_capacity, session_id, IngressReceiver, InstanceGC, SessionContext, SessionMsg};
+
+use super::{BrachaState, Handler, ProtMsg};
+
+pub struct Context {
+    /// Networking context
+    pub net_send: TcpReliableSender<Replica, WrapperMsg<SessionMsg<ProtMsg>>, Acknowledgement>,
+    pub net_recv: IngressReceiver<WrapperMsg<SessionMsg<ProtMsg>>>,
+
+    /// Data context
+    pub num_nodes: usize,
+    pub myid: usize,
+    pub num_faults: usize,
+    _byz: bool,
+
+    /// Secret Key map
+    pub sec_key_map: HashMap<Replica, Vec<u8>>,
+    /// Session binding and replay protection
+    pub session: SessionContext,
+
+    /// Cancel Handlers
+    pub cancel_handlers: HashMap<u64, Vec<CancelHandler<Acknowledgement>>>,
+    exit_rx: oneshot::Receiver<()>,
+
+    /// Sequence number of the last broadcast initiated by this node
+    pub max_id: usize,
+
+    /// State of each broadcast, keyed by the broadcaster and its sequence number
+    pub bracha_state: HashMap<(Replica, usize), BrachaState>,
+    /// Lifecycle of delivered broadcasts, pruned after the retention window
+    pub gc: InstanceGC<(Replica, usize)>,
+
+    /// Input and output request channels
+    pub inp_rbc_requests: Receiver<Vec<u8>>,
+    pub out_rbc_values: Sender<(usize, Replica, Vec<u8>)>,
+}
+
+impl Context {
+    pub fn spawn(config: Node,
+        input_reqs: Receiver<Vec<u8>>,
+        output_values: Sender<(usize, Replica, Vec<u8>)>,
+        byz: bool) -> anyhow::Result<oneshot::Sender<()>> {
+        let mut consensus_addrs: FnvHashMap<Replica, SocketAddr> = FnvHashMap::default();
+        for (replica, address) in config.net_map.iter() {
+            let address: SocketAddr = address.parse().expect("Unable to parse address");
+            consensus_addrs.insert(*replica, SocketAddr::from(address.clone()));
+        }
+        let my_port = consensus_addrs.get(&config.id).unwrap();
+        let my_address = to_socket_address("0.0.0.0", my_port.port());
+
+        // Setup networking
+        let (tx_net_to_consensus, rx_net_to_consensus) = ingress_channel(ingress_per_sender_capacity(), config.sk_map.clone());
+        TcpReceiver::<Acknowledgement, WrapperMsg<SessionMsg<ProtMsg>>, _>::spawn(
+            my_address,
+            Handler::new(tx_net_to_consensus),
+        );
+
+        let consensus_net = TcpReliableSender::<Replica, WrapperMsg<SessionMsg<ProtMsg>>, Acknowledgement>::with_peers(
+            consensus_addrs.clone(),
+        );
+
+        let (exit_tx, exit_rx) = oneshot::channel();
+
+        let session = SessionContext::new(session_id(), "bracha", config.id, &consensus_addrs);
+
+        tokio::spawn(async move {
+            let mut c = Context {
+                net_send: consensus_net,
+                net_recv: rx_net_to_consensus,
+
+                num_nodes: config.num_nodes,
+                sec_key_map: HashMap::default(),
+                session: session,
+                myid: config.id,
+                _byz: byz,
+                num_faults: config.num_faults,
+                cancel_handlers: HashMap::default(),
+                exit_rx: exit_rx,
+
+                max_id: 0,
+
+                bracha_state: HashMap::default(),
+                gc: InstanceGC::new(gc_retention()),
+
+                inp_rbc_requests: input_reqs,
+                out_rbc_values: output_values,
+            };
+
+            // Populate secret keys from config
+            for (id, sk_data) in config.sk_map.clone() {
+                c.sec_key_map.insert(id, sk_data.clone());
+            }
+
+            // Run the consensus context
+            c.run().await;
+        });
+
+        Ok(exit_tx)
+    }
+
+    pub async fn broadcast(&mut self, protmsg: ProtMsg) {
+        let sec_key_map = self.sec_key_map.clone();
+        for (replica, sec_key) in sec_key_map.into_iter() {
+            let wrapper_msg = WrapperMsg::new(self.session.seal(replica, protmsg.clone()), self.myid, &sec_key.as_slice());
+            let cancel_handler: CancelHandler<Acknowledgement> = self.net_send.send(replica, wrapper_msg).await;
+            self.add_cancel_handler(cancel_handler);
+        }
+    }
+
+    pub fn add_cancel_handler(&mut self, canc: CancelHandler<Acknowledgement>) {
+        crate::add_cancel_handler_gc(&mut self.cancel_handlers, canc);
+    }
+
+    pub async fn run(&mut self) {
+        loop {
+            tokio::select! {
+                // Receive exit handlers
+                _exit_tx = &mut self.exit_rx => {
+                    log::debug!("Termination signal received by the server. Exiting.");
+                    break
+                },
+                msg = self.net_recv.recv() => {
+                    // Received messages are processed here
+                    log::trace!("Got a consensus message from the network: {:?}", msg);
+                    if msg.is_none(){
+                        log::error!("Got none from the consensus layer, most likely it closed");
+                        return;
+                    }
+                    self.process_msg(msg.unwrap()).await;
+                },
+                req_msg = self.inp_rbc_requests.recv() => {
+                    if req_msg.is_none(){
+                        log::error!("Request channel closed");
+                        return;
+                    }
+                    self.init_broadcast(req_msg.unwrap()).await;
+                },
+            };
+        }
+    }
+}
+
+pub fn to_socket_address(ip_str: &str, port: u16) -> SocketAddr {
+    let addr = SocketAddrV4::new(ip_str.parse().unwrap(), port);
+    addr.into()
+}
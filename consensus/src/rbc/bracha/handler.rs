@@ -0,0 +1,40 @@
+use async_trait::async_trait;
+use futures_util::SinkExt;
+use network::Acknowledgement;
+use types::WrapperMsg;
+
+use crate::{IngressSender, SessionMsg};
+
+use super::ProtMsg;
+
+#[derive(Debug, Clone)]
+pub struct Handler {
+    consensus_tx: IngressSender<WrapperMsg<SessionMsg<ProtMsg>>>,
+}
+
+impl Handler {
+    pub fn new(consensus_tx: IngressSender<WrapperMsg<SessionMsg<ProtMsg>>>) -> Self {
+        Self { consensus_tx }
+    }
+}
+
+#[async_trait]
+impl network::Handler<Acknowledgement, WrapperMsg<SessionMsg<ProtMsg>>> for Handler {
+    async fn dispatch(&self, msg: WrapperMsg<SessionMsg<ProtMsg>>, writer: &mut network::Writer<Acknowledgement>) {
+        // Forward the message
+        // Waits if the queue of this sender is full, delaying the acknowledgement
+        let status = self.consensus_tx
+            .send(msg)
+            .await;
+        if status.is_err(){
+            log::error!("Failed to send consensus message to the channel because of {:?}", status.err().unwrap());
+        }
+        // Acknowledge
+        let status = writer
+            .send(Acknowledgement::Pong)
+            .await;
+        if status.is_err(){
+            log::error!("Failed to send consensus message to the channel because of {:?}", status.err().unwrap());
+        }
+    }
+}
@@ -0,0 +1,15 @@
+mod context;
+pub use context::*;
+
+mod msg;
+pub use msg::*;
+
+mod handler;
+pub use handler::*;
+
+mod process;
+
+mod state;
+pub use state::*;
+
+mod protocol;
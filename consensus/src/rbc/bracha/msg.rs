@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+use types::Replica;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum ProtMsg {
+    // Sequence number of the broadcast and the payload
+    Init(usize, Vec<u8>),
+    // Broadcaster, sequence number and the echoed payload
+    Echo(Replica, usize, Vec<u8>),
+    Ready(Replica, usize, Vec<u8>),
+}
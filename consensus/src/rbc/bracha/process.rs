@@ -0,0 +1,48 @@
+use std::sync::Arc;
+
+use types::WrapperMsg;
+
+use crate::{delay_message_processing, SessionMsg};
+
+use super::{Context, ProtMsg};
+
+impl Context {
+    // The MAC was verified against the key of the sender when the message entered the ingress queue
+    pub fn check_proposal(&mut self, wrapper_msg: Arc<WrapperMsg<SessionMsg<ProtMsg>>>) -> bool {
+        // Reject messages of other sessions and replayed messages
+        if !self.session.accept(wrapper_msg.sender, &wrapper_msg.protmsg){
+            return false;
+        }
+        true
+    }
+
+    pub(crate) async fn process_msg(&mut self, wrapper_msg: WrapperMsg<SessionMsg<ProtMsg>>) {
+        log::trace!("Received protocol msg: {:?}", wrapper_msg);
+        delay_message_processing().await;
+        let msg = Arc::new(wrapper_msg.clone());
+
+        // Drop messages of other sessions and replays before proceeding
+        if self.check_proposal(msg) {
+            match wrapper_msg.clone().protmsg.msg {
+                ProtMsg::Init(seq, payload) => {
+                    log::debug!("Received Init for broadcast {} from node : {}", seq, wrapper_msg.sender);
+                    // The sender of the INIT message is the broadcaster
+                    self.process_init(wrapper_msg.sender, seq, payload).await;
+                }
+                ProtMsg::Echo(broadcaster, seq, payload) => {
+                    log::debug!("Received Echo for broadcast ({},{}) from node : {}", broadcaster, seq, wrapper_msg.sender);
+                    self.process_echo(broadcaster, seq, wrapper_msg.sender, payload).await;
+                }
+                ProtMsg::Ready(broadcaster, seq, payload) => {
+                    log::debug!("Received Ready for broadcast ({},{}) from node : {}", broadcaster, seq, wrapper_msg.sender);
+                    self.process_ready(broadcaster, seq, wrapper_msg.sender, payload).await;
+                }
+            }
+        } else {
+            log::warn!(
+                "MAC Verification failed for message {:?}",
+                wrapper_msg.protmsg
+            );
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use ha_crypto::hash::do_hash;
+use types::Replica;
+
+use super::{BrachaState, Context, ProtMsg};
+
+impl Context {
+    pub async fn init_broadcast(&mut self, payload: Vec<u8>) {
+        self.max_id += 1;
+        let seq = self.max_id;
+        log::debug!("Starting Bracha broadcast {} of {} bytes", seq, payload.len());
+        let init = ProtMsg::Init(seq, payload);
+        self.broadcast(init).await;
+    }
+
+    pub async fn process_init(&mut self, broadcaster: Replica, seq: usize, payload: Vec<u8>) {
+        let bracha_state = match self.get_state(broadcaster, seq) {
+            Some(bracha_state) => bracha_state,
+            None => return,
+        };
+        if bracha_state.echo_sent {
+            // Echo only the first payload sent by the broadcaster
+            return;
+        }
+        bracha_state.echo_sent = true;
+        let echo = ProtMsg::Echo(broadcaster, seq, payload);
+        self.broadcast(echo).await;
+    }
+
+    pub async fn process_echo(&mut self, broadcaster: Replica, seq: usize, echo_sender: Replica, payload: Vec<u8>) {
+        let num_nodes = self.num_nodes;
+        let num_faults = self.num_faults;
+        let bracha_state = match self.get_state(broadcaster, seq) {
+            Some(bracha_state) => bracha_state,
+            None => return,
+        };
+        let root = do_hash(payload.as_slice());
+        if bracha_state.add_echo(root, echo_sender, num_nodes, num_faults) {
+            log::debug!("Received n-f ECHO messages for broadcast ({},{}), sending READY message", broadcaster, seq);
+            let ready = ProtMsg::Ready(broadcaster, seq, payload);
+            self.broadcast(ready).await;
+        }
+    }
+
+    pub async fn process_ready(&mut self, broadcaster: Replica, seq: usize, ready_sender: Replica, payload: Vec<u8>) {
+        let num_nodes = self.num_nodes;
+        let num_faults = self.num_faults;
+        let bracha_state = match self.get_state(broadcaster, seq) {
+            Some(bracha_state) => bracha_state,
+            None => return,
+        };
+        let root = do_hash(payload.as_slice());
+        let (send_ready, deliver) = bracha_state.add_ready(root, ready_sender, num_nodes, num_faults);
+
+        if send_ready {
+            // Amplify READY messages, at least one honest node received n-f ECHOs for this payload
+            log::debug!("Received f+1 READY messages for broadcast ({},{}), sending READY message", broadcaster, seq);
+            let ready = ProtMsg::Ready(broadcaster, seq, payload.clone());
+            self.broadcast(ready).await;
+        }
+        if deliver {
+            log::debug!("Received n-f READY messages for broadcast ({},{}), delivering", broadcaster, seq);
+            self.terminate(broadcaster, seq, payload).await;
+        }
+    }
+
+    /// Returns the state of the broadcast, or None if it was already garbage collected.
+    fn get_state(&mut self, broadcaster: Replica, seq: usize) -> Option<&mut BrachaState> {
+        if self.gc.is_collected(&(broadcaster, seq)) {
+            // Instance terminated and its state was pruned
+            return None;
+        }
+        let bracha_state = self.bracha_state.entry((broadcaster, seq)).or_insert_with(BrachaState::new);
+        if bracha_state.terminated {
+            // Broadcast already delivered, skip processing this message
+            return None;
+        }
+        Some(bracha_state)
+    }
+
+    pub async fn terminate(&mut self, broadcaster: Replica, seq: usize, payload: Vec<u8>) {
+        let status = self.out_rbc_values.send((seq, broadcaster, payload)).await;
+        if status.is_err() {
+            log::error!("Failed to send Bracha output of broadcast ({},{}) back to the protocol", broadcaster, seq);
+        }
+
+        self.gc.mark_terminated((broadcaster, seq));
+        self.gc.prune(&mut self.bracha_state);
+    }
+}
@@ -0,0 +1,180 @@
+use std::collections::{HashMap, HashSet};
+
+use ha_crypto::hash::Hash;
+use types::Replica;
+
+/// State of the broadcast instance identified by (broadcaster, sequence number).
+#[derive(Default)]
+pub struct BrachaState {
+    /// Senders of ECHO and READY messages, keyed by the hash of the payload
+    pub echos: HashMap<Hash, HashSet<Replica>>,
+    pub readys: HashMap<Hash, HashSet<Replica>>,
+
+    pub echo_sent: bool,
+    pub ready_sent: bool,
+    pub terminated: bool,
+}
+
+impl BrachaState {
+    pub fn new() -> BrachaState {
+        BrachaState::default()
+    }
+
+    /// Records the ECHO of `sender` for the payload hashing to `root`.
+    /// Returns true if this node has to send its READY, once n-f nodes echoed the same payload.
+    pub fn add_echo(&mut self, root: Hash, sender: Replica, num_nodes: usize, num_faults: usize) -> bool {
+        let echo_senders = self.echos.entry(root).or_default();
+        if !echo_senders.insert(sender) {
+            return false;
+        }
+        if echo_senders.len() == num_nodes - num_faults && !self.ready_sent {
+            self.ready_sent = true;
+            return true;
+        }
+        false
+    }
+
+    /// Records the READY of `sender` for the payload hashing to `root`.
+    /// Returns whether this node has to send its READY, after f+1 READYs, and whether it delivers the payload, after n-f READYs.
+    pub fn add_ready(&mut self, root: Hash, sender: Replica, num_nodes: usize, num_faults: usize) -> (bool, bool) {
+        let ready_senders = self.readys.entry(root).or_default();
+        if !ready_senders.insert(sender) {
+            return (false, false);
+        }
+        let size = ready_senders.len();
+        let send_ready = size >= num_faults + 1 && !self.ready_sent;
+        if send_ready {
+            self.ready_sent = true;
+        }
+        let deliver = size >= num_nodes - num_faults && !self.terminated;
+        if deliver {
+            self.terminated = true;
+        }
+        (send_ready, deliver)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use ha_crypto::hash::do_hash;
+
+    use super::*;
+
+    const NUM_NODES: usize = 4;
+    const NUM_FAULTS: usize = 1;
+    const BYZANTINE: Replica = 3;
+
+    #[derive(Clone, Debug)]
+    enum Msg {
+        Echo(Vec<u8>),
+        Ready(Vec<u8>),
+    }
+
+    /// Runs the ECHO and READY phases of one broadcast among the honest nodes, delivering queued messages in order.
+    /// `initial` holds the messages in flight after the INIT phase, as (recipient, sender, message).
+    /// Returns the payload each honest node delivered.
+    fn run(initial: Vec<(Replica, Replica, Msg)>, honest: &[Replica]) -> HashMap<Replica, Vec<u8>> {
+        let mut states: HashMap<Replica, BrachaState> = honest.iter().map(|rep| (*rep, BrachaState::new())).collect();
+        let mut delivered = HashMap::new();
+        let mut queue: VecDeque<(Replica, Replica, Msg)> = initial.into_iter().collect();
+        while let Some((recipient, sender, msg)) = queue.pop_front() {
+            let state = match states.get_mut(&recipient) {
+                Some(state) => state,
+                None => continue,
+            };
+            match msg {
+                Msg::Echo(payload) => {
+                    let echo_count = state.echos.get(&do_hash(&payload)).map_or(0, |senders| senders.len());
+                    if state.add_echo(do_hash(&payload), sender, NUM_NODES, NUM_FAULTS) {
+                        assert_eq!(echo_count + 1, NUM_NODES - NUM_FAULTS, "READY sent before n-f ECHOs");
+                        queue.extend((0..NUM_NODES).map(|rep| (rep, recipient, Msg::Ready(payload.clone()))));
+                    }
+                },
+                Msg::Ready(payload) => {
+                    let (send_ready, deliver) = state.add_ready(do_hash(&payload), sender, NUM_NODES, NUM_FAULTS);
+                    let ready_count = state.readys.get(&do_hash(&payload)).unwrap().len();
+                    if send_ready {
+                        assert!(ready_count >= NUM_FAULTS + 1);
+                        queue.extend((0..NUM_NODES).map(|rep| (rep, recipient, Msg::Ready(payload.clone()))));
+                    }
+                    if deliver {
+                        assert!(ready_count >= NUM_NODES - NUM_FAULTS, "delivered before n-f READYs");
+                        assert!(delivered.insert(recipient, payload).is_none(), "delivered twice");
+                    }
+                },
+            }
+        }
+        delivered
+    }
+
+    #[test]
+    fn test_honest_nodes_deliver_despite_byzantine_echo() {
+        let payload = b"honest payload".to_vec();
+        let forged = b"forged payload".to_vec();
+        let honest = [0, 1, 2];
+        let mut initial = Vec::new();
+        // The Byzantine node echoes and readies a different payload first
+        for rep in honest.iter() {
+            initial.push((*rep, BYZANTINE, Msg::Echo(forged.clone())));
+            initial.push((*rep, BYZANTINE, Msg::Ready(forged.clone())));
+        }
+        // Honest nodes echo the payload of the honest broadcaster
+        for sender in honest.iter() {
+            for rep in 0..NUM_NODES {
+                initial.push((rep, *sender, Msg::Echo(payload.clone())));
+            }
+        }
+        let delivered = run(initial, &honest);
+        assert_eq!(delivered.len(), honest.len());
+        assert!(delivered.values().all(|value| *value == payload));
+    }
+
+    #[test]
+    fn test_byzantine_echo_and_ready_alone_do_not_deliver() {
+        // Only node 1 echoes the payload, the Byzantine node echoes it too and readies it
+        let payload = b"payload".to_vec();
+        let honest = [0, 1, 2];
+        let mut initial = Vec::new();
+        for rep in honest.iter() {
+            initial.push((*rep, 1, Msg::Echo(payload.clone())));
+            initial.push((*rep, BYZANTINE, Msg::Echo(payload.clone())));
+            initial.push((*rep, BYZANTINE, Msg::Ready(payload.clone())));
+        }
+        // Two ECHOs and one READY are below the n-f and f+1 thresholds
+        assert!(run(initial, &honest).is_empty());
+    }
+
+    #[test]
+    fn test_ready_amplification_delivers_at_nodes_without_echoes() {
+        // Nodes 0 and 1 saw n-f ECHOs, node 2 saw none of them
+        let payload = b"payload".to_vec();
+        let honest = [0, 1, 2];
+        let mut initial = Vec::new();
+        for sender in vec![0, 1, BYZANTINE] {
+            for rep in vec![0, 1] {
+                initial.push((rep, sender, Msg::Echo(payload.clone())));
+            }
+        }
+        let delivered = run(initial, &honest);
+        // Node 2 amplifies after f+1 READYs and every honest node delivers
+        assert_eq!(delivered.len(), honest.len());
+        assert!(delivered.values().all(|value| *value == payload));
+    }
+
+    #[test]
+    fn test_duplicate_messages_are_ignored() {
+        let mut state = BrachaState::new();
+        let root = do_hash(b"payload");
+        assert!(!state.add_echo(root, 0, NUM_NODES, NUM_FAULTS));
+        assert!(!state.add_echo(root, 0, NUM_NODES, NUM_FAULTS));
+        assert!(!state.add_echo(root, 1, NUM_NODES, NUM_FAULTS));
+        assert_eq!(state.echos.get(&root).unwrap().len(), 2);
+        assert_eq!(state.add_ready(root, 0, NUM_NODES, NUM_FAULTS), (false, false));
+        assert_eq!(state.add_ready(root, 0, NUM_NODES, NUM_FAULTS), (false, false));
+        assert_eq!(state.add_ready(root, 1, NUM_NODES, NUM_FAULTS), (true, false));
+        assert_eq!(state.add_ready(root, 2, NUM_NODES, NUM_FAULTS), (false, true));
+        assert_eq!(state.add_ready(root, 3, NUM_NODES, NUM_FAULTS), (false, false));
+    }
+}
@@ -0,0 +1,237 @@
+use std::{
+    collections::HashSet,
+    fmt::Debug,
+    marker::PhantomData,
+    str::FromStr,
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+use async_trait::async_trait;
+use config::Node;
+use serde::{de::DeserializeOwned, Serialize};
+use tokio::sync::{
+    mpsc::{Receiver, Sender},
+    oneshot,
+};
+use types::Replica;
+
+use crate::service_channel;
+
+pub mod bracha;
+
+/// Reliable broadcast protocol backing the `ReliableBroadcast` interface.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RBCBackend {
+    /// Bracha's broadcast, every node relays the full payload
+    Bracha,
+    /// Erasure coded broadcast of Das, Xiang and Ren, with Merkle commitments to the shards
+    CTRBC,
+    /// Erasure coded broadcast with cross checksums of the shards
+    CCBRB,
+}
+
+impl RBCBackend {
+    fn to_u8(self) -> u8 {
+        match self {
+            RBCBackend::Bracha => 0,
+            RBCBackend::CTRBC => 1,
+            RBCBackend::CCBRB => 2,
+        }
+    }
+
+    fn from_u8(val: u8) -> Option<RBCBackend> {
+        match val {
+            0 => Some(RBCBackend::Bracha),
+            1 => Some(RBCBackend::CTRBC),
+            2 => Some(RBCBackend::CCBRB),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for RBCBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<RBCBackend, String> {
+        match s.to_lowercase().as_str() {
+            "bracha" => Ok(RBCBackend::Bracha),
+            "ctrbc" => Ok(RBCBackend::CTRBC),
+            "ccbrb" => Ok(RBCBackend::CCBRB),
+            _ => Err(format!("Unknown reliable broadcast backend {}, expected one of bracha, ctrbc, ccbrb", s)),
+        }
+    }
+}
+
+/// No backend selected for the deployment, each protocol uses its own default.
+const RBC_BACKEND_UNSET: u8 = u8::MAX;
+
+static RBC_BACKEND: AtomicU8 = AtomicU8::new(RBC_BACKEND_UNSET);
+
+/// Select the reliable broadcast used by every service spawned after this call.
+pub fn set_rbc_backend(backend: RBCBackend) {
+    RBC_BACKEND.store(backend.to_u8(), Ordering::Relaxed);
+}
+
+/// Backend selected for the deployment, or `default` if none was selected.
+pub fn rbc_backend(default: RBCBackend) -> RBCBackend {
+    RBCBackend::from_u8(RBC_BACKEND.load(Ordering::Relaxed)).unwrap_or(default)
+}
+
+/// Payload delivered by a reliable broadcast instance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RBCDelivery<I> {
+    pub id: I,
+    pub broadcaster: Replica,
+    pub payload: Vec<u8>,
+}
+
+/// Reliable broadcast of byte payloads, tagged with an instance id chosen by the caller.
+/// Every node delivers at most one payload per (id, broadcaster) pair.
+/// For an honest broadcaster all honest nodes deliver the same payload, or none of them do.
+#[async_trait]
+pub trait ReliableBroadcast {
+    type Id: Send;
+
+    async fn broadcast(&mut self, id: Self::Id, payload: Vec<u8>) -> anyhow::Result<()>;
+
+    /// Waits for the next delivered payload. Returns None once the backend has shut down.
+    /// Cancel safe, so it can be used as a `tokio::select!` branch.
+    async fn deliver(&mut self) -> Option<RBCDelivery<Self::Id>>;
+}
+
+/// Handle to a reliable broadcast service running on its own port.
+/// Ids are carried inside the broadcast payload, so every backend delivers them back unchanged.
+/// Backends number their instances on their own, so a faulty broadcaster can send the same id in several instances.
+/// Only the first of them is delivered.
+pub struct RBCService<I> {
+    pub backend: RBCBackend,
+    req: Sender<Vec<u8>>,
+    out: Receiver<(usize, Replica, Vec<u8>)>,
+    /// Serialized ids delivered so far, with their broadcasters
+    delivered: HashSet<(Replica, Vec<u8>)>,
+    _id: PhantomData<I>,
+}
+
+impl<I> RBCService<I> {
+    /// Spawns the backend on the address of `config`.
+    pub fn spawn(backend: RBCBackend, config: Node, byz: bool) -> (RBCService<I>, anyhow::Result<oneshot::Sender<()>>) {
+        let (req_send_channel, req_recv_channel) = service_channel();
+        let (out_send_channel, out_recv_channel) = service_channel();
+
+        let status = match backend {
+            RBCBackend::Bracha => bracha::Context::spawn(config, req_recv_channel, out_send_channel, byz),
+            RBCBackend::CTRBC => ctrbc::Context::spawn(config, req_recv_channel, out_send_channel, byz),
+            RBCBackend::CCBRB => ccbrb::Context::spawn(config, req_recv_channel, out_send_channel, byz),
+        };
+        (RBCService::new(backend, req_send_channel, out_recv_channel), status)
+    }
+
+    /// Wraps the channels of an already running backend.
+    pub fn new(backend: RBCBackend, req: Sender<Vec<u8>>, out: Receiver<(usize, Replica, Vec<u8>)>) -> RBCService<I> {
+        RBCService {
+            backend: backend,
+            req: req,
+            out: out,
+            delivered: HashSet::default(),
+            _id: PhantomData,
+        }
+    }
+}
+
+#[async_trait]
+impl<I> ReliableBroadcast for RBCService<I>
+where
+    I: Serialize + DeserializeOwned + Debug + Send + Sync,
+{
+    type Id = I;
+
+    async fn broadcast(&mut self, id: I, payload: Vec<u8>) -> anyhow::Result<()> {
+        let ser_msg = bincode::serialize(&(id, payload))?;
+        self.req
+            .send(ser_msg)
+            .await
+            .map_err(|_| anyhow::anyhow!("{:?} broadcast service has shut down", self.backend))
+    }
+
+    async fn deliver(&mut self) -> Option<RBCDelivery<I>> {
+        loop {
+            let (instance, broadcaster, msg) = self.out.recv().await?;
+            match bincode::deserialize::<(I, Vec<u8>)>(msg.as_slice()) {
+                Ok((id, payload)) => {
+                    let id_ser = bincode::serialize(&id).expect("Failed to serialize broadcast id");
+                    if !self.delivered.insert((broadcaster, id_ser)) {
+                        // Only a faulty broadcaster reuses an id
+                        log::error!("Party {} broadcast id {:?} again in instance {}, dropping it", broadcaster, id, instance);
+                        continue;
+                    }
+                    return Some(RBCDelivery {
+                        id: id,
+                        broadcaster: broadcaster,
+                        payload: payload,
+                    });
+                }
+                Err(e) => {
+                    // Only a faulty broadcaster delivers a malformed envelope
+                    log::error!("Unable to deserialize broadcast of party {}: {:?}", broadcaster, e);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_service_delivers_typed_ids() {
+        let (req_send, mut req_recv) = service_channel();
+        let (out_send, out_recv) = service_channel();
+        let mut rbc: RBCService<(usize, usize)> = RBCService::new(RBCBackend::Bracha, req_send, out_recv);
+
+        rbc.broadcast((4, 2), vec![7u8; 3]).await.unwrap();
+        let envelope = req_recv.recv().await.unwrap();
+
+        // Malformed envelopes are skipped
+        out_send.send((1, 0, vec![1u8])).await.unwrap();
+        out_send.send((1, 3, envelope)).await.unwrap();
+        let delivery = rbc.deliver().await.unwrap();
+        assert_eq!(delivery, RBCDelivery { id: (4, 2), broadcaster: 3, payload: vec![7u8; 3] });
+
+        drop(out_send);
+        assert!(rbc.deliver().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_service_drops_equivocating_broadcasts() {
+        let (req_send, _req_recv) = service_channel();
+        let (out_send, out_recv) = service_channel();
+        let mut rbc: RBCService<usize> = RBCService::new(RBCBackend::Bracha, req_send, out_recv);
+
+        // Party 3 broadcasts two payloads under id 5 in separate backend instances
+        out_send.send((1, 3, bincode::serialize(&(5usize, vec![1u8])).unwrap())).await.unwrap();
+        out_send.send((2, 3, bincode::serialize(&(5usize, vec![2u8])).unwrap())).await.unwrap();
+        // The same id of another broadcaster, and another id of the same broadcaster, are delivered
+        out_send.send((1, 2, bincode::serialize(&(5usize, vec![3u8])).unwrap())).await.unwrap();
+        out_send.send((3, 3, bincode::serialize(&(6usize, vec![4u8])).unwrap())).await.unwrap();
+        drop(out_send);
+
+        let mut deliveries = Vec::new();
+        while let Some(delivery) = rbc.deliver().await {
+            deliveries.push(delivery);
+        }
+        assert_eq!(deliveries, vec![
+            RBCDelivery { id: 5, broadcaster: 3, payload: vec![1u8] },
+            RBCDelivery { id: 5, broadcaster: 2, payload: vec![3u8] },
+            RBCDelivery { id: 6, broadcaster: 3, payload: vec![4u8] },
+        ]);
+    }
+
+    #[test]
+    fn test_backend_from_str() {
+        assert_eq!("Bracha".parse::<RBCBackend>(), Ok(RBCBackend::Bracha));
+        assert_eq!("ctrbc".parse::<RBCBackend>(), Ok(RBCBackend::CTRBC));
+        assert_eq!("CCBRB".parse::<RBCBackend>(), Ok(RBCBackend::CCBRB));
+        assert!("avid".parse::<RBCBackend>().is_err());
+    }
+}
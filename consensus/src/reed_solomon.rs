@@ -3,19 +3,14 @@ use reed_solomon_erasure::{galois_8::ReedSolomon, Error};
 pub fn get_shards(data:Vec<u8>,shards:usize,parity_shards:usize)->Vec<Vec<u8>>{
     let reed_solomon:ReedSolomon<> = ReedSolomon::new(shards,parity_shards).unwrap();
     let mut vec_vecs = Vec::new();
-    let size_of_vec = (data.len()/shards)+1;
+    // Pad the payload only up to the next multiple of the number of data shards
+    let size_of_vec = std::cmp::max(1, (data.len() + shards - 1)/shards);
     for b in 0..shards{
-        let mut indi_vec:Vec<u8> = Vec::new();
-        for x in 0..size_of_vec{
-            if b*size_of_vec+x >= data.len(){
-                // Padding until filling up all shards
-                indi_vec.push(0);
-            }
-            else {
-                // Fill each shard
-                indi_vec.push(data[b*size_of_vec+x]);
-            }
-        }
+        let start = std::cmp::min(b*size_of_vec, data.len());
+        let end = std::cmp::min(start+size_of_vec, data.len());
+        let mut indi_vec:Vec<u8> = Vec::with_capacity(size_of_vec);
+        indi_vec.extend_from_slice(&data[start..end]);
+        indi_vec.resize(size_of_vec, 0);
         vec_vecs.push(indi_vec);
     }
     // Fill parity shards with zeros
@@ -0,0 +1,254 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use config::Node;
+use fnv::FnvHashMap;
+use ha_crypto::hash::{do_hash, Hash};
+use serde::{Deserialize, Serialize};
+use types::Replica;
+
+use crate::{signature_keys, signed_bytes, SignatureKeys, SignedEvidence};
+
+/// Number of counters below the highest received counter that are still accepted out of order.
+pub const REPLAY_WINDOW: u64 = 4096;
+
+static SESSION_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Set the session (epoch) identifier bound into every message sent by services spawned after this call.
+/// All nodes of a run must use the same identifier, and each run must use a fresh one.
+pub fn set_session_id(session_id: u64) {
+    SESSION_ID.store(session_id, Ordering::Relaxed);
+}
+
+pub fn session_id() -> u64 {
+    SESSION_ID.load(Ordering::Relaxed)
+}
+
+/// Session identifier of a run, derived from `nonce` and the committee and client addresses in the config.
+/// `nonce` must be fresh for each run and the same at all nodes of the run, so restarts with the same config do not reuse a session.
+/// Distinct deployments get distinct identifiers even if they pick the same nonce.
+pub fn session_id_from_config(config: &Node, nonce: u64) -> u64 {
+    let mut peers: Vec<(&Replica, &String)> = config.net_map.iter().collect();
+    peers.sort();
+    let mut session_bytes = Vec::new();
+    session_bytes.extend(nonce.to_be_bytes());
+    session_bytes.extend(config.num_nodes.to_be_bytes());
+    for (rep, addr) in peers {
+        session_bytes.extend(rep.to_be_bytes());
+        session_bytes.extend(addr.as_bytes());
+    }
+    session_bytes.extend(config.client_addr.to_string().as_bytes());
+    let hash = do_hash(session_bytes.as_slice());
+    let mut id_bytes = [0u8; 8];
+    id_bytes.copy_from_slice(&hash[..8]);
+    u64::from_be_bytes(id_bytes)
+}
+
+/// Envelope authenticated by the MAC of `WrapperMsg`.
+/// Binds a protocol message to a session and to the service endpoint of the recipient, and carries a per-peer monotonic counter.
+/// With signature-based authentication, `signature` covers the message, the session identifier and the service, but not the recipient.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SessionMsg<M> {
+    pub session: Hash,
+    pub counter: u64,
+    pub msg: M,
+    pub signature: Option<Vec<u8>>,
+}
+
+/// Tracks the counters received from one peer.
+/// Counters below `floor` are either delivered or too old, counters in `seen` are delivered.
+#[derive(Default)]
+struct ReplayWindow {
+    floor: u64,
+    seen: BTreeSet<u64>,
+}
+
+impl ReplayWindow {
+    fn accept(&mut self, counter: u64) -> bool {
+        if counter < self.floor || self.seen.contains(&counter) {
+            return false;
+        }
+        self.seen.insert(counter);
+        // Advance the floor over delivered counters
+        while self.seen.remove(&self.floor) {
+            self.floor += 1;
+        }
+        // Bound the window: counters too far behind the highest one are no longer accepted
+        let highest = *self.seen.iter().next_back().unwrap_or(&self.floor);
+        if highest >= self.floor + REPLAY_WINDOW {
+            self.floor = highest + 1 - REPLAY_WINDOW;
+            self.seen = self.seen.split_off(&self.floor);
+            while self.seen.remove(&self.floor) {
+                self.floor += 1;
+            }
+        }
+        true
+    }
+}
+
+pub struct SessionContext {
+    pub session_id: u64,
+    pub service: String,
+    // Session binding expected on messages addressed to this node
+    pub session: Hash,
+    // Session binding of messages addressed to each peer
+    peer_sessions: HashMap<Replica, Hash>,
+    send_counters: HashMap<Replica, u64>,
+    recv_windows: HashMap<Replica, ReplayWindow>,
+    // Signing keys, set if signature-based authentication is enabled
+    pub signature_keys: Option<Arc<SignatureKeys>>,
+    // Broadcasts seal the same message for every recipient, the last signature is reused for them
+    last_signed: Option<(Vec<u8>, Vec<u8>)>,
+}
+
+impl SessionContext {
+    /// Services of a node share pairwise keys, so the binding covers the service name and the recipient's endpoint.
+    pub fn new(session_id: u64, service: &str, myid: Replica, addrs: &FnvHashMap<Replica, SocketAddr>) -> SessionContext {
+        let peer_sessions: HashMap<Replica, Hash> = addrs.iter().map(|(rep, addr)| {
+            (*rep, Self::session_hash(session_id, service, addr))
+        }).collect();
+        let session = peer_sessions.get(&myid).expect("Own address missing from the address map").clone();
+        SessionContext {
+            session_id: session_id,
+            service: service.to_string(),
+            session: session,
+            peer_sessions: peer_sessions,
+            send_counters: HashMap::default(),
+            recv_windows: HashMap::default(),
+            signature_keys: signature_keys(),
+            last_signed: None,
+        }
+    }
+
+    fn session_hash(session_id: u64, service: &str, addr: &SocketAddr) -> Hash {
+        let mut session_bytes = Vec::new();
+        session_bytes.extend(session_id.to_be_bytes());
+        session_bytes.extend(service.as_bytes());
+        session_bytes.extend(addr.to_string().as_bytes());
+        do_hash(session_bytes.as_slice())
+    }
+
+    /// Wraps a message for the given recipient with the next counter, and signs it if signatures are enabled.
+    pub fn seal<M: Serialize>(&mut self, recipient: Replica, msg: M) -> SessionMsg<M> {
+        let session = self.peer_sessions.get(&recipient).expect("Unknown recipient").clone();
+        let signature = self.sign(&msg);
+        let counter = self.send_counters.entry(recipient).or_insert(0);
+        let session_msg = SessionMsg {
+            session: session,
+            counter: *counter,
+            msg: msg,
+            signature: signature,
+        };
+        *counter += 1;
+        session_msg
+    }
+
+    fn sign<M: Serialize>(&mut self, msg: &M) -> Option<Vec<u8>> {
+        let keys = self.signature_keys.as_ref()?;
+        let bytes = signed_bytes(self.session_id, self.service.as_str(), keys.myid, msg);
+        if let Some((last_bytes, last_signature)) = self.last_signed.as_ref() {
+            if *last_bytes == bytes {
+                return Some(last_signature.clone());
+            }
+        }
+        let signature = keys.sign(bytes.as_slice());
+        self.last_signed = Some((bytes, signature.clone()));
+        Some(signature)
+    }
+
+    /// Checks the session binding, the signature (if signatures are enabled) and the counter of an authenticated message.
+    /// Must only be called after the MAC has been verified.
+    pub fn accept<M: Serialize>(&mut self, sender: Replica, session_msg: &SessionMsg<M>) -> bool {
+        if session_msg.session != self.session {
+            log::warn!("Message from {} belongs to a different session, dropping it", sender);
+            return false;
+        }
+        if let Some(keys) = self.signature_keys.as_ref() {
+            let valid = match session_msg.signature.as_ref() {
+                Some(signature) => {
+                    let bytes = signed_bytes(self.session_id, self.service.as_str(), sender, &session_msg.msg);
+                    keys.verify(sender, bytes.as_slice(), signature.as_slice())
+                },
+                None => false,
+            };
+            if !valid {
+                log::warn!("Signature verification failed for message from {}, dropping it", sender);
+                return false;
+            }
+        }
+        if !self.recv_windows.entry(sender).or_default().accept(session_msg.counter) {
+            log::warn!("Replayed message with counter {} from {}, dropping it", session_msg.counter, sender);
+            return false;
+        }
+        true
+    }
+
+    /// Extracts transferable evidence from a message accepted from `sender`.
+    /// Returns None if the message is not signed.
+    pub fn evidence<M: Clone>(&self, sender: Replica, session_msg: &SessionMsg<M>) -> Option<SignedEvidence<M>> {
+        let signature = session_msg.signature.clone()?;
+        Some(SignedEvidence {
+            signer: sender,
+            session_id: self.session_id,
+            service: self.service.clone(),
+            msg: session_msg.msg.clone(),
+            signature: signature,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replay_window_rejects_duplicates() {
+        let mut window = ReplayWindow::default();
+        for counter in 0..10 {
+            assert!(window.accept(counter));
+        }
+        assert_eq!(window.floor, 10);
+        assert!(window.seen.is_empty());
+        for counter in 0..10 {
+            assert!(!window.accept(counter));
+        }
+    }
+
+    #[test]
+    fn test_replay_window_accepts_out_of_order() {
+        let mut window = ReplayWindow::default();
+        assert!(window.accept(5));
+        assert!(window.accept(2));
+        assert!(!window.accept(5));
+        assert_eq!(window.floor, 0);
+        for counter in vec![0, 1, 3, 4] {
+            assert!(window.accept(counter));
+        }
+        // The gap is closed and the floor moves past every delivered counter
+        assert_eq!(window.floor, 6);
+        assert!(window.seen.is_empty());
+        assert!(!window.accept(2));
+    }
+
+    #[test]
+    fn test_replay_window_is_bounded() {
+        let mut window = ReplayWindow::default();
+        // Counter 0 never arrives
+        for counter in 1..3 * REPLAY_WINDOW {
+            assert!(window.accept(counter));
+            assert!(window.seen.len() < REPLAY_WINDOW as usize);
+        }
+        assert_eq!(window.floor, 3 * REPLAY_WINDOW);
+        // Too old to be told apart from a replay
+        assert!(!window.accept(0));
+        assert!(window.accept(3 * REPLAY_WINDOW + 10));
+        assert!(window.accept(3 * REPLAY_WINDOW + 1));
+        assert!(!window.accept(3 * REPLAY_WINDOW + 10));
+    }
+}
@@ -1,50 +1,49 @@
-use std::ops::{Sub, Add};
-use std::{ops::Mul};
+use std::sync::Arc;
 
-use ha_crypto::{rand_field_element};
-use lambdaworks_math::{ polynomial::Polynomial, unsigned_integer::element::UnsignedInteger};
+use lambdaworks_math::{ polynomial::Polynomial, field::element::FieldElement};
 
-use rayon::prelude::{ParallelIterator, IntoParallelRefIterator, IntoParallelIterator};
-
-use ha_crypto::LargeField;
+use crate::{FieldType, Matrix, ProtocolField, SmallFieldType, cached_inverse_vandermonde, matrix_cache::{cached_matrix, MatrixKey}, vandermonde};
 
 /// The `ShamirSecretSharing` stores threshold, share_amount and the prime of finite field.
+/// Generic over the protocol field, defaults to the field selected for the services.
 #[derive(Clone, Debug)]
-pub struct LargeFieldSSS {
+pub struct LargeFieldSSS<F: ProtocolField = FieldType> {
     /// the threshold of shares to recover the secret.
     pub threshold: usize,
     /// the total number of shares to generate from the secret.
     pub share_amount: usize,
-    /// Lagrange coefficients for points 1 through 2f
-    pub lag_coeffs: Vec<Vec<LargeField>>,
-    /// Vandermonde inverse matrix for points -f to f
-    pub vandermonde_matrix: Vec<Vec<LargeField>>
+    /// Lagrange coefficients for points 1 through 2f, shared through the matrix cache
+    pub lag_coeffs: Arc<Matrix<F>>,
+    /// Vandermonde inverse matrix for points -f to f, shared through the matrix cache
+    pub vandermonde_matrix: Arc<Matrix<F>>
 }
 
+/// Shamir secret sharing over the 64-bit field
+pub type SmallFieldSSS = LargeFieldSSS<SmallFieldType>;
+
 // 64-bit variant of shamir SS mainly because of efficiency
-impl LargeFieldSSS {
+impl<F: ProtocolField> LargeFieldSSS<F> where FieldElement<F>: Send + Sync {
 
-    pub fn new(threshold: usize, share_amount: usize)-> LargeFieldSSS{
+    pub fn new(threshold: usize, share_amount: usize)-> LargeFieldSSS<F>{
 
-        let lag_coeffs = Self::lagrange_coefficients(threshold, share_amount);
+        let lag_coeffs = Self::cached_lagrange_coefficients(threshold, share_amount);
         LargeFieldSSS { 
             threshold: threshold, 
             share_amount: share_amount, 
             lag_coeffs: lag_coeffs ,
-            vandermonde_matrix: Vec::new()
+            vandermonde_matrix: Arc::new(Vec::new())
         }
     }
 
-    pub fn new_with_vandermonde(threshold: usize, share_amount: usize)-> LargeFieldSSS{
-        let lag_coeffs = Self::lagrange_coefficients(threshold, share_amount);
+    pub fn new_with_vandermonde(threshold: usize, share_amount: usize)-> LargeFieldSSS<F>{
+        let lag_coeffs = Self::cached_lagrange_coefficients(threshold, share_amount);
         let mut x_values = Vec::new();
         for index in (1..threshold+1).into_iter(){
-            x_values.push(LargeField::from(index as u64));
+            x_values.push(FieldElement::<F>::from(index as u64));
         }
 
-        // Compute Vandermonde matrix
-        let vandermonde = Self::vandermonde_matrix(x_values);
-        let vandermonde_inverse = Self::inverse_vandermonde(vandermonde);
+        // Inverse Vandermonde matrix, shared with every other context over the same points
+        let vandermonde_inverse = cached_inverse_vandermonde(&x_values);
 
         LargeFieldSSS { 
             threshold: threshold, 
@@ -55,7 +54,7 @@ impl LargeFieldSSS {
     }
     
     /// Split a secret according to the config.
-    pub fn split(&self, secret: LargeField) -> Vec<LargeField> {
+    pub fn split(&self, secret: FieldElement<F>) -> Vec<FieldElement<F>> {
         assert!(self.threshold < self.share_amount);
         let polynomial = self.sample_polynomial(secret);
         // println!("polynomial: {:?}", polynomial);
@@ -65,17 +64,17 @@ impl LargeFieldSSS {
         
         for i in 0..self.share_amount{
             evaluation_points.push(
-                polynomial.evaluate(&LargeField::new(UnsignedInteger::from((i+1) as u64)))
+                polynomial.evaluate(&FieldElement::<F>::from((i+1) as u64))
             );
         }
 
         evaluation_points
     }
 
-    pub fn fill_evaluation_at_all_points(&self, values: &mut Vec<LargeField>){
+    pub fn fill_evaluation_at_all_points(&self, values: &mut Vec<FieldElement<F>>){
         let mut all_values = Vec::new();
         for coefficients in self.lag_coeffs.iter(){
-            let mut sum: LargeField = LargeField::zero();
+            let mut sum: FieldElement<F> = FieldElement::<F>::zero();
             for (coefficient,point) in coefficients.into_iter().zip(values.clone().into_iter()){
                 sum += coefficient*point;
             }
@@ -84,7 +83,7 @@ impl LargeFieldSSS {
         values.extend(all_values);
     }
 
-    pub fn verify_degree(&self, values: &mut Vec<LargeField>) -> bool{
+    pub fn verify_degree(&self, values: &mut Vec<FieldElement<F>>) -> bool{
         let mut shares_interp = Vec::new();
         
         for rep in self.share_amount - self.threshold .. self.share_amount{
@@ -96,61 +95,65 @@ impl LargeFieldSSS {
         secret == values[0].clone()
     }
 
-    fn sample_polynomial(&self, secret: LargeField) -> Vec<LargeField> {
-        let mut coefficients: Vec<LargeField> = vec![secret];
-        let random_coefficients: Vec<LargeField> = (0..(self.threshold - 1))
-            .map(|_| rand_field_element())
+    fn sample_polynomial(&self, secret: FieldElement<F>) -> Vec<FieldElement<F>> {
+        let mut coefficients: Vec<FieldElement<F>> = vec![secret];
+        let random_coefficients: Vec<FieldElement<F>> = (0..(self.threshold - 1))
+            .map(|_| F::random_element())
             .collect();
         coefficients.extend(random_coefficients);
         coefficients
     }
 
     /// Recover the secret by the shares.
-    pub fn recover(&self, shares: &[(usize, LargeField)]) -> LargeField {
+    pub fn recover(&self, shares: &[(usize, FieldElement<F>)]) -> FieldElement<F> {
         assert!(shares.len() == self.threshold, "wrong shares number");
-        let (xs, ys): (Vec<usize>, Vec<LargeField>) = shares.iter().cloned().unzip();
-        let result = self.lagrange_interpolation(LargeField::zero(), xs, ys);
+        let (xs, ys): (Vec<usize>, Vec<FieldElement<F>>) = shares.iter().cloned().unzip();
+        let result = self.lagrange_interpolation(FieldElement::<F>::zero(), xs, ys);
         result
     }
 
-    fn lagrange_interpolation(&self, x: LargeField, xs: Vec<usize>, ys: Vec<LargeField>) -> LargeField {
-        let xs = xs.into_iter().map(|x| LargeField::new(UnsignedInteger::from(x as u64))).collect::<Vec<LargeField>>();
+    fn lagrange_interpolation(&self, x: FieldElement<F>, xs: Vec<usize>, ys: Vec<FieldElement<F>>) -> FieldElement<F> {
+        let xs = xs.into_iter().map(|x| FieldElement::<F>::from(x as u64)).collect::<Vec<FieldElement<F>>>();
         let poly = Polynomial::interpolate(&xs, &ys).unwrap();
 
         poly.evaluate(&x)
     }
 
-    pub fn mod_evaluate_at_lf(&self, polynomial: &[LargeField], x: LargeField) -> LargeField {
+    pub fn mod_evaluate_at_lf(&self, polynomial: &[FieldElement<F>], x: FieldElement<F>) -> FieldElement<F> {
         let poly = Polynomial::new(polynomial);
         poly.evaluate(&x)
     }
 
-    pub fn mod_evaluate_at(&self, polynomial: &[LargeField], x: usize) -> LargeField {
+    pub fn mod_evaluate_at(&self, polynomial: &[FieldElement<F>], x: usize) -> FieldElement<F> {
         let poly = Polynomial::new(polynomial);
-        poly.evaluate(&LargeField::new(UnsignedInteger::from(x as u64)))
+        poly.evaluate(&FieldElement::<F>::from(x as u64))
     }
 
-    pub fn polynomial_coefficients_with_precomputed_vandermonde_matrix(&self, y_values: &Vec<LargeField>) -> Vec<LargeField> {
+    pub fn polynomial_coefficients_with_precomputed_vandermonde_matrix(&self, y_values: &Vec<FieldElement<F>>) -> Vec<FieldElement<F>> {
         // Multiply Vandermonde inverse by the y-values vector to solve for coefficients
         Self::matrix_vector_multiply(&self.vandermonde_matrix, y_values)
     }
 
-    pub fn polynomial_coefficients_with_vandermonde_matrix(&self, matrix: &Vec<Vec<LargeField>>, y_values: &Vec<LargeField>) -> Vec<LargeField>{
+    pub fn polynomial_coefficients_with_vandermonde_matrix(&self, matrix: &Vec<Vec<FieldElement<F>>>, y_values: &Vec<FieldElement<F>>) -> Vec<FieldElement<F>>{
         Self::matrix_vector_multiply(matrix, y_values)
     }
 
-    fn lagrange_coefficients(threshold: usize, tot_shares: usize)->Vec<Vec<LargeField>>{
+    fn cached_lagrange_coefficients(threshold: usize, tot_shares: usize)->Arc<Matrix<F>>{
+        cached_matrix(MatrixKey::Lagrange(threshold, tot_shares), || Self::lagrange_coefficients(threshold, tot_shares))
+    }
+
+    fn lagrange_coefficients(threshold: usize, tot_shares: usize)->Vec<Vec<FieldElement<F>>>{
         // Construct denominators first
         let mut denominators = Vec::new();
         
         let xs: Vec<u64> = (0 as u64 .. threshold as u64).into_iter().collect();
         let ys: Vec<u64> = (threshold as u64 .. tot_shares as u64+1u64).into_iter().collect();
 
-        let xs_lf: Vec<LargeField> = xs.iter().map(|x| LargeField::from(*x as u64)).collect();
-        let ys_lf: Vec<LargeField> = ys.iter().map(|x| LargeField::from(*x as u64)).collect();
+        let xs_lf: Vec<FieldElement<F>> = xs.iter().map(|x| FieldElement::<F>::from(*x as u64)).collect();
+        let ys_lf: Vec<FieldElement<F>> = ys.iter().map(|x| FieldElement::<F>::from(*x as u64)).collect();
         
         for i in xs_lf.iter(){
-            let mut denominator_prod: LargeField = LargeField::one();
+            let mut denominator_prod: FieldElement<F> = FieldElement::<F>::one();
             for j in xs_lf.clone().into_iter(){
                 if j != i.clone(){
                     denominator_prod = denominator_prod * (i - j);
@@ -161,7 +164,7 @@ impl LargeFieldSSS {
         let mut numerators = Vec::new();
         for i in ys_lf.iter(){
 
-            let mut num_prod:LargeField = LargeField::one();
+            let mut num_prod:FieldElement<F> = FieldElement::<F>::one();
             for j in xs_lf.iter(){
                 num_prod = num_prod * (i - j);
             }
@@ -184,101 +187,158 @@ impl LargeFieldSSS {
     }
 
     /// Constructs the Vandermonde matrix for a given set of x-values.
-    pub fn vandermonde_matrix(x_values: Vec<LargeField>) -> Vec<Vec<LargeField>> {
-        let n = x_values.len();
-        let mut matrix = vec![vec![LargeField::zero(); n]; n];
-
-        for (row, x) in x_values.iter().enumerate() {
-            let mut value = LargeField::one();
-            for col in 0..n {
-                matrix[row][col] = value.clone();
-                value = value.mul(x);
-            }
-        }
-
-        matrix
+    pub fn vandermonde_matrix(x_values: Vec<FieldElement<F>>) -> Vec<Vec<FieldElement<F>>> {
+        vandermonde::vandermonde_matrix(x_values)
     }
 
     /// Computes the inverse of a Vandermonde matrix modulo prime using Gaussian elimination.
-    pub fn inverse_vandermonde(matrix: Vec<Vec<LargeField>>) -> Vec<Vec<LargeField>> {
-        let n = matrix.len();
-        let mut augmented = matrix.clone();
+    pub fn inverse_vandermonde(matrix: Vec<Vec<FieldElement<F>>>) -> Vec<Vec<FieldElement<F>>> {
+        vandermonde::inverse_vandermonde(matrix)
+    }
 
-        // Extend the matrix with an identity matrix on the right
-        for i in 0..n {
-            augmented[i].extend((0..n).map(|j| if i == j { LargeField::one() } else { LargeField::zero() }));
-        }
+    pub fn matrix_vector_multiply(
+        matrix: &Vec<Vec<FieldElement<F>>>,
+        vector: &Vec<FieldElement<F>>,
+    ) -> Vec<FieldElement<F>> {
+        vandermonde::matrix_vector_multiply(matrix, vector)
+    }
 
-        // Perform Gaussian elimination
-        for col in 0..n {
-            // Normalize pivot row
-            let inv = &augmented[col][col].inv().unwrap();
-            for k in col..2 * n {
-                augmented[col][k] = augmented[col][k].mul(inv);
-            }
+    pub fn check_if_all_points_lie_on_degree_x_polynomial(eval_points: Vec<FieldElement<F>>, polys_vector: Vec<Vec<FieldElement<F>>>, degree: usize) -> (bool,Option<Vec<Polynomial<FieldElement<F>>>>){
+        vandermonde::check_if_all_points_lie_on_degree_x_polynomial(eval_points, polys_vector, degree)
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LargeField, SmallField};
+    use proptest::{collection, prelude::*, sample::subsequence};
+
+    /// Threshold and number of shares, with `threshold < share_amount` as `split` requires
+    fn sharing_parameters() -> impl Strategy<Value = (usize, usize)> {
+        (2usize..16).prop_flat_map(|share_amount| (1..share_amount, Just(share_amount)))
+    }
 
-            // Eliminate other rows
-            for row in 0..n {
-                if row != col {
-                    let factor = augmented[row][col].clone();
-                    for k in col..2 * n {
-                        augmented[row][k] = augmented[row][k].sub(factor.mul(augmented[col][k]));
-                    }
-                }
+    fn field_elements(len: usize) -> impl Strategy<Value = Vec<LargeField>> {
+        collection::vec(any::<u64>(), len).prop_map(|values| values.into_iter().map(LargeField::from).collect())
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn prop_split_recover_round_trip(
+            (threshold, share_amount, indices) in sharing_parameters().prop_flat_map(|(threshold, share_amount)| {
+                (Just(threshold), Just(share_amount), subsequence((1..share_amount + 1).collect::<Vec<usize>>(), threshold))
+            }),
+            secret in any::<u64>(),
+        ) {
+            let sss = LargeFieldSSS::<FieldType>::new_with_vandermonde(threshold, share_amount);
+            let secret = LargeField::from(secret);
+            let shares = sss.split(secret.clone());
+            prop_assert_eq!(shares.len(), share_amount);
+
+            // Any `threshold` shares recover the secret
+            let subset: Vec<(usize, LargeField)> = indices.iter().map(|index| (*index, shares[index - 1].clone())).collect();
+            prop_assert_eq!(sss.recover(&subset), secret.clone());
+
+            // The first `threshold` shares determine the polynomial through the precomputed inverse
+            let coefficients = sss.polynomial_coefficients_with_precomputed_vandermonde_matrix(&shares[0..threshold].to_vec());
+            prop_assert_eq!(&coefficients[0], &secret);
+            for (index, share) in shares.iter().enumerate() {
+                prop_assert_eq!(&sss.mod_evaluate_at(&coefficients, index + 1), share);
             }
         }
 
-        // Extract the right half as the inverse
-        augmented
-            .into_iter()
-            .map(|row| row[n..2 * n].to_vec())
-            .collect()
-    }
+        #[test]
+        fn prop_small_field_split_recover_round_trip(
+            (threshold, share_amount, indices) in sharing_parameters().prop_flat_map(|(threshold, share_amount)| {
+                (Just(threshold), Just(share_amount), subsequence((1..share_amount + 1).collect::<Vec<usize>>(), threshold))
+            }),
+            secret in any::<u64>(),
+        ) {
+            let sss = SmallFieldSSS::new_with_vandermonde(threshold, share_amount);
+            let secret = SmallField::from(secret);
+            let shares = sss.split(secret.clone());
+            let subset: Vec<(usize, SmallField)> = indices.iter().map(|index| (*index, shares[index - 1].clone())).collect();
+            prop_assert_eq!(sss.recover(&subset), secret.clone());
+
+            // Expansion from the evaluations at 0..threshold-1 reaches the same shares
+            let mut evaluations = vec![secret];
+            evaluations.extend(shares[0..threshold - 1].iter().cloned());
+            let (expanded, _) = crate::expand_sharing_to_n_evaluation_points_opt(vec![evaluations], threshold - 1, share_amount);
+            prop_assert_eq!(&expanded[0], &shares);
+        }
 
-    pub fn matrix_vector_multiply(
-        matrix: &Vec<Vec<LargeField>>,
-        vector: &Vec<LargeField>,
-    ) -> Vec<LargeField> {
-        matrix
-            .par_iter()
-            .map(|row| {
-                row.iter()
-                    .zip(vector)
-                    .fold(LargeField::zero(), |sum, (a, b)| sum.add(a.mul(b)))
-            })
-            .collect()
-    }
+        #[test]
+        fn prop_fill_evaluation_at_all_points_extends_the_polynomial(
+            (threshold, share_amount, coefficients) in sharing_parameters().prop_flat_map(|(threshold, share_amount)| {
+                (Just(threshold), Just(share_amount), field_elements(threshold))
+            }),
+        ) {
+            let sss = LargeFieldSSS::<FieldType>::new(threshold, share_amount);
+            let polynomial = Polynomial::new(&coefficients);
+            // Evaluations at 0..threshold are extended to 0..=share_amount
+            let mut values: Vec<LargeField> = (0..threshold).map(|x| sss.mod_evaluate_at(&coefficients, x)).collect();
+            sss.fill_evaluation_at_all_points(&mut values);
+            let expected: Vec<LargeField> = (0..share_amount as u64 + 1).map(|x| polynomial.evaluate(&LargeField::from(x))).collect();
+            prop_assert_eq!(values, expected);
+        }
 
-    pub fn check_if_all_points_lie_on_degree_x_polynomial(eval_points: Vec<LargeField>, polys_vector: Vec<Vec<LargeField>>, degree: usize) -> (bool,Option<Vec<Polynomial<LargeField>>>){
-        //log::info!("Checking evaluations on points :{:?}, eval_points: {:?}", eval_points, polys_vector);
-        let inverse_vandermonde = Self::inverse_vandermonde(Self::vandermonde_matrix(eval_points[0..degree].to_vec()));
-        let polys = polys_vector.into_par_iter().map(|points| {
-            let coeffs = Self::matrix_vector_multiply(&inverse_vandermonde, &points[0..degree].to_vec());
-            let polynomial = Polynomial::new(&coeffs);
-            let all_points_match =  eval_points[degree..].iter().zip(points[degree..].iter()).map(|(eval_point, share)|{
-                return polynomial.evaluate(eval_point) == *share;
-            }).fold(true, |acc,x| acc && x);
-    
-            if all_points_match{
-                Some(polynomial)
-            }
-            else{
-                None
-            }
-        }).fold(|| Vec::new(), |mut acc_vec, vec: Option<Polynomial<LargeField>>|{
-            acc_vec.push(vec);
-            acc_vec
-        }).reduce(|| Vec::new(), |mut acc_vec, vec: Vec<Option<Polynomial<LargeField>>>|{
-            acc_vec.extend(vec);
-            acc_vec
-        });
-        let all_polys_positive = polys.par_iter().all(|poly| poly.is_some());
-        if all_polys_positive{
-            let polys_vec = polys.into_iter().map(|x| x.unwrap()).collect();
-            (true, Some(polys_vec))
+        #[test]
+        fn prop_verify_degree_rejects_higher_degree(
+            (threshold, share_amount, coefficients) in sharing_parameters().prop_flat_map(|(threshold, share_amount)| {
+                (Just(threshold), Just(share_amount), field_elements(threshold))
+            }),
+            leading in 1u64..,
+        ) {
+            let sss = LargeFieldSSS::<FieldType>::new(threshold, share_amount);
+            let evaluations = |coefficients: &Vec<LargeField>| -> Vec<LargeField> {
+                (0..share_amount + 1).map(|x| sss.mod_evaluate_at(coefficients, x)).collect()
+            };
+            prop_assert!(sss.verify_degree(&mut evaluations(&coefficients)));
+
+            // One coefficient too many, with a nonzero leading coefficient
+            let mut higher_degree = coefficients.clone();
+            higher_degree.push(LargeField::from(leading));
+            prop_assert!(!sss.verify_degree(&mut evaluations(&higher_degree)));
         }
-        else{
-            (false, None)
+
+        #[test]
+        fn prop_associated_functions_agree_with_interpolation(
+            (points, extra_point, values) in collection::hash_set(any::<u64>(), 2..13).prop_flat_map(|points| {
+                let mut points: Vec<LargeField> = points.into_iter().map(LargeField::from).collect();
+                let extra_point = points.pop().unwrap();
+                let len = points.len();
+                (Just(points), Just(extra_point), field_elements(len))
+            }),
+        ) {
+            let degree = points.len();
+            // Row i holds the powers of point i
+            let vandermonde = LargeFieldSSS::<FieldType>::vandermonde_matrix(points.clone());
+            for (row, point) in vandermonde.iter().zip(points.iter()) {
+                for (power, entry) in row.iter().enumerate() {
+                    prop_assert_eq!(entry, &point.pow(power as u64));
+                }
+            }
+
+            // The inverse maps evaluations to the coefficients of the interpolating polynomial
+            let inverse = LargeFieldSSS::<FieldType>::inverse_vandermonde(vandermonde);
+            let coefficients = LargeFieldSSS::<FieldType>::matrix_vector_multiply(&inverse, &values);
+            let interpolated = Polynomial::interpolate(&points, &values).unwrap();
+            let mut expected = interpolated.coefficients().to_vec();
+            expected.resize(degree, LargeField::zero());
+            prop_assert_eq!(&coefficients, &expected);
+
+            // An evaluation on the interpolated polynomial passes the degree check, any other value fails it
+            let mut all_points = points.clone();
+            all_points.push(extra_point.clone());
+            let mut evaluations = values.clone();
+            evaluations.push(interpolated.evaluate(&extra_point));
+            let (on_polynomial, polys) = LargeFieldSSS::<FieldType>::check_if_all_points_lie_on_degree_x_polynomial(all_points.clone(), vec![evaluations.clone()], degree);
+            prop_assert!(on_polynomial);
+            prop_assert_eq!(polys.unwrap()[0].evaluate(&extra_point), interpolated.evaluate(&extra_point));
+            evaluations[degree] = &evaluations[degree] + LargeField::one();
+            prop_assert!(!LargeFieldSSS::<FieldType>::check_if_all_points_lie_on_degree_x_polynomial(all_points, vec![evaluations], degree).0);
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,287 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    hash::Hash,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+/// Default bound on the bytes a dealer has in flight through AVID.
+pub const DEFAULT_AVID_OUTSTANDING_BYTES: usize = 64 << 20;
+
+/// A smoothed ratio of AVID latencies to their baselines above this factor is treated as congestion.
+const CONGESTION_FACTOR: f64 = 2.0;
+
+/// Latency baselines are replaced by the next sample after this period, so a path that became slower for good sets a new baseline.
+pub const BASELINE_EXPIRY: Duration = Duration::from_secs(10);
+
+static AVID_OUTSTANDING_BYTES: AtomicUsize = AtomicUsize::new(DEFAULT_AVID_OUTSTANDING_BYTES);
+
+/// Set the in-flight byte budget used by every service spawned after this call.
+pub fn set_avid_outstanding_bytes(bytes: usize) {
+    AVID_OUTSTANDING_BYTES.store(bytes.max(1), Ordering::Relaxed);
+}
+
+pub fn avid_outstanding_bytes() -> usize {
+    AVID_OUTSTANDING_BYTES.load(Ordering::Relaxed)
+}
+
+/// Congestion-aware scheduler for the AVID instances of a dealer.
+/// Instances are released while both the window and the byte budget allow it.
+/// The window grows by one instance per window of uncongested completions, and halves at most once per latency period when latencies exceed `CONGESTION_FACTOR` times their baseline.
+/// Instances of different sizes take different times without congestion, so each instance is compared against the lowest recent latency of instances of similar size.
+pub struct AvidThrottle<K: Eq + Hash + Clone, T> {
+    pending: VecDeque<(K, T, usize)>,
+    outstanding: HashMap<K, (Instant, usize)>,
+    outstanding_bytes: usize,
+    pub max_outstanding_bytes: usize,
+
+    window: usize,
+    pub min_window: usize,
+    pub max_window: usize,
+    /// Uncongested completions since the window last grew
+    acked: usize,
+    last_decrease: Option<Instant>,
+
+    /// Lowest recent latency per size class, the uncongested baselines
+    base_latencies: HashMap<u32, Baseline>,
+    /// Exponentially weighted moving averages of the latencies and of their ratios to the baselines
+    smoothed_latency: Option<Duration>,
+    smoothed_ratio: Option<f64>,
+}
+
+struct Baseline {
+    latency: Duration,
+    observed_at: Instant,
+}
+
+/// Instances within a factor of two in size share a baseline
+fn size_class(bytes: usize) -> u32 {
+    usize::BITS - bytes.leading_zeros()
+}
+
+impl<K: Eq + Hash + Clone, T> AvidThrottle<K, T> {
+    pub fn new(initial_window: usize) -> AvidThrottle<K, T> {
+        let initial_window = initial_window.max(1);
+        AvidThrottle {
+            pending: VecDeque::new(),
+            outstanding: HashMap::default(),
+            outstanding_bytes: 0,
+            max_outstanding_bytes: avid_outstanding_bytes(),
+
+            window: initial_window,
+            min_window: 1,
+            max_window: 16 * initial_window,
+            acked: 0,
+            last_decrease: None,
+
+            base_latencies: HashMap::default(),
+            smoothed_latency: None,
+            smoothed_ratio: None,
+        }
+    }
+
+    /// Queues an AVID instance carrying `bytes` bytes.
+    pub fn push(&mut self, key: K, item: T, bytes: usize) {
+        self.pending.push_back((key, item, bytes));
+    }
+
+    /// Returns the instances to start now.
+    pub fn release(&mut self) -> Vec<T> {
+        self.release_at(Instant::now())
+    }
+
+    pub fn release_at(&mut self, now: Instant) -> Vec<T> {
+        let mut released = Vec::new();
+        while let Some((_, _, bytes)) = self.pending.front() {
+            if self.outstanding.len() >= self.window {
+                break;
+            }
+            // A single instance larger than the budget still goes out once the link is idle
+            if !self.outstanding.is_empty() && self.outstanding_bytes + bytes > self.max_outstanding_bytes {
+                break;
+            }
+            let (key, item, bytes) = self.pending.pop_front().unwrap();
+            self.outstanding.insert(key, (now, bytes));
+            self.outstanding_bytes += bytes;
+            released.push(item);
+        }
+        released
+    }
+
+    /// Records the termination of an instance and adapts the window. Returns false if the instance was not outstanding.
+    pub fn complete(&mut self, key: &K) -> bool {
+        self.complete_at(key, Instant::now())
+    }
+
+    pub fn complete_at(&mut self, key: &K, now: Instant) -> bool {
+        let (started_at, bytes) = match self.outstanding.remove(key) {
+            Some(outstanding) => outstanding,
+            None => return false,
+        };
+        self.outstanding_bytes -= bytes;
+
+        let latency = now.duration_since(started_at);
+        let baseline = self.base_latencies.entry(size_class(bytes)).or_insert(Baseline { latency, observed_at: now });
+        if latency <= baseline.latency || now.duration_since(baseline.observed_at) > BASELINE_EXPIRY {
+            *baseline = Baseline { latency, observed_at: now };
+        }
+        let ratio = latency.as_nanos() as f64 / baseline.latency.as_nanos().max(1) as f64;
+        let base_latency = baseline.latency;
+        let smoothed_latency = match self.smoothed_latency {
+            Some(smoothed_latency) => (smoothed_latency * 7 + latency) / 8,
+            None => latency,
+        };
+        self.smoothed_latency = Some(smoothed_latency);
+        let smoothed_ratio = match self.smoothed_ratio {
+            Some(smoothed_ratio) => (smoothed_ratio * 7.0 + ratio) / 8.0,
+            None => ratio,
+        };
+        self.smoothed_ratio = Some(smoothed_ratio);
+
+        if smoothed_ratio > CONGESTION_FACTOR {
+            let recently_decreased = match self.last_decrease {
+                Some(last_decrease) => now.duration_since(last_decrease) < smoothed_latency,
+                None => false,
+            };
+            if !recently_decreased {
+                self.window = (self.window / 2).max(self.min_window);
+                self.acked = 0;
+                self.last_decrease = Some(now);
+                log::debug!("AVID latency {:?} over baseline {:?} of its size, shrinking window to {}", latency, base_latency, self.window);
+            }
+        }
+        else {
+            self.acked += 1;
+            if self.acked >= self.window {
+                self.window = (self.window + 1).min(self.max_window);
+                self.acked = 0;
+            }
+        }
+        true
+    }
+
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    pub fn outstanding(&self) -> usize {
+        self.outstanding.len()
+    }
+
+    pub fn outstanding_bytes(&self) -> usize {
+        self.outstanding_bytes
+    }
+
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_window_grows_without_congestion() {
+        let mut throttle: AvidThrottle<usize, usize> = AvidThrottle::new(2);
+        for instance in 0..100 {
+            throttle.push(instance, instance, 10);
+        }
+        let start = Instant::now();
+        let mut now = start;
+        let mut completed = 0;
+        while completed < 100 {
+            let released = throttle.release_at(now);
+            assert!(throttle.outstanding() <= throttle.window());
+            now += Duration::from_millis(10);
+            for instance in released {
+                assert!(throttle.complete_at(&instance, now));
+                completed += 1;
+            }
+        }
+        assert!(throttle.window() > 2);
+        assert_eq!(throttle.outstanding_bytes(), 0);
+    }
+
+    #[test]
+    fn test_window_shrinks_under_congestion() {
+        let mut throttle: AvidThrottle<usize, usize> = AvidThrottle::new(8);
+        for instance in 0..16 {
+            throttle.push(instance, instance, 10);
+        }
+        let start = Instant::now();
+        let released = throttle.release_at(start);
+        assert_eq!(released.len(), 8);
+        assert!(throttle.complete_at(&0, start + Duration::from_millis(10)));
+        // Latency jumps well above the baseline
+        for instance in 1..8 {
+            throttle.complete_at(&instance, start + Duration::from_millis(200));
+        }
+        assert_eq!(throttle.window(), 4);
+        assert!(!throttle.complete_at(&0, start + Duration::from_millis(300)));
+    }
+
+    #[test]
+    fn test_mixed_instance_sizes_are_not_congestion() {
+        // Small instances take 10ms and large ones 200ms, without any congestion
+        let small = 1 << 10;
+        let large = 1 << 20;
+        let mut throttle: AvidThrottle<usize, usize> = AvidThrottle::new(4);
+        for instance in 0..200 {
+            throttle.push(instance, instance, if instance % 2 == 0 { small } else { large });
+        }
+        let mut now = Instant::now();
+        let mut completed = 0;
+        while completed < 200 {
+            let released = throttle.release_at(now);
+            for instance in released.iter() {
+                let latency = if instance % 2 == 0 { 10 } else { 200 };
+                assert!(throttle.complete_at(instance, now + Duration::from_millis(latency)));
+                completed += 1;
+                assert!(throttle.window() >= 4, "window shrank without congestion");
+            }
+            now += Duration::from_millis(200);
+        }
+        assert!(throttle.window() > 4);
+    }
+
+    #[test]
+    fn test_baseline_expires() {
+        let mut throttle: AvidThrottle<usize, usize> = AvidThrottle::new(8);
+        let start = Instant::now();
+        throttle.push(0, 0, 10);
+        assert_eq!(throttle.release_at(start), vec![0]);
+        throttle.complete_at(&0, start + Duration::from_millis(10));
+        // The path became slower for good, the old baseline expires instead of marking every later instance as congested
+        let mut now = start + BASELINE_EXPIRY + Duration::from_secs(1);
+        for instance in 1..20 {
+            throttle.push(instance, instance, 10);
+            assert_eq!(throttle.release_at(now), vec![instance]);
+            now += Duration::from_millis(40);
+            assert!(throttle.complete_at(&instance, now));
+        }
+        assert!(throttle.window() >= 8);
+    }
+
+    #[test]
+    fn test_byte_budget_limits_release() {
+        let mut throttle: AvidThrottle<usize, usize> = AvidThrottle::new(8);
+        throttle.max_outstanding_bytes = 25;
+        for instance in 0..4 {
+            throttle.push(instance, instance, 10);
+        }
+        let now = Instant::now();
+        assert_eq!(throttle.release_at(now), vec![0, 1]);
+        assert_eq!(throttle.outstanding_bytes(), 20);
+        throttle.complete_at(&0, now);
+        assert_eq!(throttle.release_at(now), vec![2]);
+        assert_eq!(throttle.pending(), 1);
+
+        // Oversized instances are sent alone
+        let mut throttle: AvidThrottle<usize, usize> = AvidThrottle::new(8);
+        throttle.max_outstanding_bytes = 5;
+        throttle.push(0, 0, 10);
+        throttle.push(1, 1, 10);
+        assert_eq!(throttle.release_at(now), vec![0]);
+    }
+}
@@ -0,0 +1,147 @@
+use ha_crypto::hash::{do_hash, Hash};
+use lambdaworks_math::field::{element::FieldElement, traits::IsPrimeField};
+
+use crate::ProtocolField;
+
+/// Fiat-Shamir transcript.
+/// Every absorbed value is framed with its label and length, and every challenge depends on everything absorbed before it.
+/// Challenges are squeezed with rejection sampling, so they are uniform in the field and never fail to decode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transcript {
+    state: Hash,
+}
+
+impl Transcript {
+    /// Starts a transcript of the protocol identified by `domain`.
+    pub fn new(domain: &[u8]) -> Transcript {
+        let mut transcript = Transcript { state: [0u8; 32] };
+        transcript.append_message(b"domain", domain);
+        transcript
+    }
+
+    /// Resumes a transcript from its state.
+    /// The state stands in for the whole history, so transcripts can be passed through interfaces that carry commitments as hashes.
+    pub fn from_state(state: Hash) -> Transcript {
+        Transcript { state: state }
+    }
+
+    pub fn state(&self) -> Hash {
+        self.state
+    }
+
+    pub fn append_message(&mut self, label: &[u8], message: &[u8]) {
+        let mut input = Vec::with_capacity(self.state.len() + 16 + label.len() + message.len());
+        input.extend_from_slice(&self.state);
+        input.extend_from_slice(&(label.len() as u64).to_be_bytes());
+        input.extend_from_slice(label);
+        input.extend_from_slice(&(message.len() as u64).to_be_bytes());
+        input.extend_from_slice(message);
+        self.state = do_hash(input.as_slice());
+    }
+
+    pub fn append_hash(&mut self, label: &[u8], hash: &Hash) {
+        self.append_message(label, hash.as_slice());
+    }
+
+    pub fn append_u64(&mut self, label: &[u8], value: u64) {
+        self.append_message(label, &value.to_be_bytes());
+    }
+
+    pub fn append_field_element<F: ProtocolField>(&mut self, label: &[u8], element: &FieldElement<F>) {
+        self.append_message(label, F::to_ser(element).as_slice());
+    }
+
+    /// Squeezes a field element. Candidates are masked to the bit length of the modulus and rejected until one is canonical.
+    pub fn challenge_field_element<F: ProtocolField>(&mut self, label: &[u8]) -> FieldElement<F> {
+        self.append_message(b"challenge", label);
+        let mut counter: u64 = 0;
+        loop {
+            let mut candidate = Vec::with_capacity(F::SER_LEN + 32);
+            while candidate.len() < F::SER_LEN {
+                let mut input = self.state.to_vec();
+                input.extend_from_slice(&counter.to_be_bytes());
+                candidate.extend_from_slice(&do_hash(input.as_slice()));
+                counter += 1;
+            }
+            candidate.truncate(F::SER_LEN);
+
+            let mut excess_bits = (F::SER_LEN * 8).saturating_sub(F::field_bit_size());
+            let mut index = 0;
+            while excess_bits > 0 && index < candidate.len() {
+                let mask_bits = excess_bits.min(8);
+                candidate[index] &= 0xffu8.checked_shr(mask_bits as u32).unwrap_or(0);
+                excess_bits -= mask_bits;
+                index += 1;
+            }
+
+            if let Some(element) = F::from_ser(candidate.as_slice()) {
+                // Later challenges must differ from this one
+                self.append_message(b"challenge output", candidate.as_slice());
+                return element;
+            }
+        }
+    }
+
+    pub fn challenge_field_elements<F: ProtocolField>(&mut self, label: &[u8], count: usize) -> Vec<FieldElement<F>> {
+        (0..count).map(|_| self.challenge_field_element(label)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FieldType, GoldilocksField, Stark252Field};
+
+    fn transcript(instance_id: u64) -> Transcript {
+        let mut transcript = Transcript::new(b"test protocol");
+        transcript.append_u64(b"instance", instance_id);
+        transcript.append_hash(b"root", &[7u8; 32]);
+        transcript
+    }
+
+    #[test]
+    fn test_challenges_are_deterministic() {
+        let mut first = transcript(1);
+        let mut second = transcript(1);
+        let challenge: FieldElement<FieldType> = first.challenge_field_element(b"challenge");
+        assert_eq!(challenge, second.challenge_field_element(b"challenge"));
+
+        // Successive challenges differ, and a resumed transcript continues where the original left off
+        let mut resumed = Transcript::from_state(first.state());
+        let next: FieldElement<FieldType> = first.challenge_field_element(b"challenge");
+        assert_ne!(challenge, next);
+        assert_eq!(next, resumed.challenge_field_element(b"challenge"));
+    }
+
+    #[test]
+    fn test_challenges_are_domain_separated() {
+        let challenge: FieldElement<FieldType> = transcript(1).challenge_field_element(b"challenge");
+        assert_ne!(challenge, transcript(2).challenge_field_element(b"challenge"));
+        assert_ne!(challenge, transcript(1).challenge_field_element(b"other challenge"));
+
+        let mut other_domain = Transcript::new(b"other protocol");
+        other_domain.append_u64(b"instance", 1);
+        other_domain.append_hash(b"root", &[7u8; 32]);
+        assert_ne!(challenge, other_domain.challenge_field_element(b"challenge"));
+
+        // Labels and messages are framed, moving bytes between them changes the transcript
+        let mut first = Transcript::new(b"test protocol");
+        first.append_message(b"ab", b"c");
+        let mut second = Transcript::new(b"test protocol");
+        second.append_message(b"a", b"bc");
+        assert_ne!(first.state(), second.state());
+    }
+
+    #[test]
+    fn test_challenges_in_every_field() {
+        let mut transcript = transcript(3);
+        let stark: Vec<FieldElement<Stark252Field>> = transcript.challenge_field_elements(b"stark", 64);
+        let goldilocks: Vec<FieldElement<GoldilocksField>> = transcript.challenge_field_elements(b"goldilocks", 64);
+        for (index, element) in stark.iter().enumerate() {
+            assert!(!stark[index + 1..].contains(element));
+        }
+        for (index, element) in goldilocks.iter().enumerate() {
+            assert!(!goldilocks[index + 1..].contains(element));
+        }
+    }
+}
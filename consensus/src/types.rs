@@ -1,12 +1,26 @@
-use lambdaworks_math::field::{fields::{fft_friendly::stark_252_prime_field::{MontgomeryConfigStark252PrimeField}, montgomery_backed_prime_fields::MontgomeryBackendPrimeField}, element::FieldElement};
+use lambdaworks_math::field::element::FieldElement;
 
-pub type LargeField = FieldElement<MontgomeryBackendPrimeField<MontgomeryConfigStark252PrimeField, 4>>;
-pub type FieldType = MontgomeryBackendPrimeField<MontgomeryConfigStark252PrimeField, 4>;
+use crate::ProtocolField;
+
+/// Field used by the protocols. Stark252 unless another field is selected with a feature.
+#[cfg(not(any(feature = "bls12_381", feature = "goldilocks")))]
+pub type FieldType = crate::Stark252Field;
+#[cfg(feature = "bls12_381")]
+pub type FieldType = crate::Bls12381ScalarField;
+#[cfg(all(feature = "goldilocks", not(feature = "bls12_381")))]
+pub type FieldType = crate::GoldilocksField;
+
+pub type LargeField = FieldElement<FieldType>;
 
 //pub type LargeField = Secp256k1PrimeField;
 //pub type FieldType = Secp256k1PrimeField;
 
-pub type LargeFieldSer = [u8;32];
+pub type LargeFieldSer = [u8; <FieldType as ProtocolField>::SER_LEN];
+
+/// 64-bit prime field for throughput-oriented sharing, when per-element security margins of a 252-bit field are not needed.
+pub type SmallFieldType = crate::GoldilocksField;
+pub type SmallField = FieldElement<SmallFieldType>;
+pub type SmallFieldSer = [u8; <SmallFieldType as ProtocolField>::SER_LEN];
 
 // Shares, nonce polynomial, blinding_nonce polynomial
 pub type AvssShare =  (Vec<LargeFieldSer>, LargeFieldSer, LargeFieldSer);
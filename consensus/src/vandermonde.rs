@@ -0,0 +1,197 @@
+use std::ops::{Add, Mul, Sub};
+
+use lambdaworks_math::{field::element::FieldElement, polynomial::Polynomial};
+use rayon::prelude::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator};
+
+use crate::{cached_inverse_vandermonde, ProtocolField};
+
+// Dense interpolation over arbitrary evaluation points.
+// `LargeFieldSSS` exposes the same functions as associated functions for the protocol crates, and delegates to this module.
+
+pub fn check_if_all_points_lie_on_degree_x_polynomial<F: ProtocolField>(eval_points: Vec<FieldElement<F>>, polys_vector: Vec<Vec<FieldElement<F>>>, degree: usize) -> (bool,Option<Vec<Polynomial<FieldElement<F>>>>) where FieldElement<F>: Send + Sync{
+    //log::info!("Checking evaluations on points :{:?}, eval_points: {:?}", eval_points, polys_vector);
+    let inverse_vandermonde = cached_inverse_vandermonde(&eval_points[0..degree]);
+    let polys = polys_vector.into_par_iter().map(|points| {
+        let coeffs = matrix_vector_multiply(&inverse_vandermonde, &points[0..degree].to_vec());
+        let polynomial = Polynomial::new(&coeffs);
+        let all_points_match =  eval_points[degree..].iter().zip(points[degree..].iter()).map(|(eval_point, share)|{
+            return polynomial.evaluate(eval_point) == *share;
+        }).fold(true, |acc,x| acc && x);
+
+        if all_points_match{
+            Some(polynomial)
+        }
+        else{
+            None
+        }
+    }).fold(|| Vec::new(), |mut acc_vec, vec: Option<Polynomial<FieldElement<F>>>|{
+        acc_vec.push(vec);
+        acc_vec
+    }).reduce(|| Vec::new(), |mut acc_vec, vec: Vec<Option<Polynomial<FieldElement<F>>>>|{
+        acc_vec.extend(vec);
+        acc_vec
+    });
+    let all_polys_positive = polys.par_iter().all(|poly| poly.is_some());
+    if all_polys_positive{
+        let polys_vec = polys.into_iter().map(|x| x.unwrap()).collect();
+        (true, Some(polys_vec))
+    }
+    else{
+        (false, None)
+    }
+}
+
+
+/// Constructs the Vandermonde matrix for a given set of x-values.
+pub fn vandermonde_matrix<F: ProtocolField>(x_values: Vec<FieldElement<F>>) -> Vec<Vec<FieldElement<F>>> {
+    let n = x_values.len();
+    let mut matrix = vec![vec![FieldElement::<F>::zero(); n]; n];
+
+    for (row, x) in x_values.iter().enumerate() {
+        let mut value = FieldElement::<F>::one();
+        for col in 0..n {
+            matrix[row][col] = value.clone();
+            value = value.mul(x);
+        }
+    }
+
+    matrix
+}
+
+/// Computes the inverse of a Vandermonde matrix modulo prime using Gaussian elimination.
+pub fn inverse_vandermonde<F: ProtocolField>(matrix: Vec<Vec<FieldElement<F>>>) -> Vec<Vec<FieldElement<F>>> {
+    let n = matrix.len();
+    let mut augmented = matrix.clone();
+
+    // Extend the matrix with an identity matrix on the right
+    for i in 0..n {
+        augmented[i].extend((0..n).map(|j| if i == j { FieldElement::<F>::one() } else { FieldElement::<F>::zero() }));
+    }
+
+    // Perform Gaussian elimination
+    for col in 0..n {
+        // Normalize pivot row
+        let inv = &augmented[col][col].inv().unwrap();
+        for k in col..2 * n {
+            augmented[col][k] = augmented[col][k].mul(inv);
+        }
+
+        // Eliminate other rows
+        for row in 0..n {
+            if row != col {
+                let factor = augmented[row][col].clone();
+                for k in col..2 * n {
+                    augmented[row][k] = augmented[row][k].sub(factor.mul(augmented[col][k]));
+                }
+            }
+        }
+    }
+
+    // Extract the right half as the inverse
+    augmented
+        .into_iter()
+        .map(|row| row[n..2 * n].to_vec())
+        .collect()
+}
+
+pub fn matrix_vector_multiply<F: ProtocolField>(
+    matrix: &Vec<Vec<FieldElement<F>>>,
+    vector: &Vec<FieldElement<F>>,
+) -> Vec<FieldElement<F>> where FieldElement<F>: Send + Sync {
+    matrix
+        .par_iter()
+        .map(|row| {
+            row.iter()
+                .zip(vector)
+                .fold(FieldElement::<F>::zero(), |sum, (a, b)| sum.add(a.mul(b)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LargeField;
+    use proptest::{collection, prelude::*};
+
+    /// Between 1 and `max` distinct evaluation points
+    fn distinct_points(max: usize) -> impl Strategy<Value = Vec<LargeField>> {
+        collection::hash_set(any::<u64>(), 1..max + 1).prop_map(|points| points.into_iter().map(LargeField::from).collect())
+    }
+
+    fn field_elements(len: usize) -> impl Strategy<Value = Vec<LargeField>> {
+        collection::vec(any::<u64>(), len).prop_map(|values| values.into_iter().map(LargeField::from).collect())
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn prop_inverse_vandermonde_is_inverse(points in distinct_points(12)) {
+            let vandermonde = vandermonde_matrix(points.clone());
+            let inverse = inverse_vandermonde(vandermonde.clone());
+            for i in 0..points.len() {
+                let column: Vec<LargeField> = vandermonde.iter().map(|row| row[i].clone()).collect();
+                let mut unit = vec![LargeField::zero(); points.len()];
+                unit[i] = LargeField::one();
+                prop_assert_eq!(matrix_vector_multiply(&inverse, &column), unit);
+            }
+            prop_assert_eq!(cached_inverse_vandermonde(&points).as_ref(), &inverse);
+        }
+
+        #[test]
+        fn prop_vandermonde_interpolation_matches_lagrange(
+            (points, coefficients) in distinct_points(12).prop_flat_map(|points| {
+                let len = points.len();
+                (Just(points), field_elements(len))
+            })
+        ) {
+            let polynomial = Polynomial::new(&coefficients);
+            let values: Vec<LargeField> = points.iter().map(|point| polynomial.evaluate(point)).collect();
+            let interpolated = matrix_vector_multiply(&inverse_vandermonde(vandermonde_matrix(points.clone())), &values);
+            prop_assert_eq!(Polynomial::new(&interpolated), Polynomial::interpolate(&points, &values).unwrap());
+            prop_assert_eq!(Polynomial::new(&interpolated), polynomial);
+        }
+
+        #[test]
+        fn prop_points_on_low_degree_polynomials_are_accepted(
+            (degree, extra, points) in (1usize..8, 1usize..6).prop_flat_map(|(degree, extra)| {
+                (Just(degree), Just(extra), collection::hash_set(any::<u64>(), degree + extra))
+            }),
+            coefficients in collection::vec(field_elements(7), 3),
+            seed in any::<usize>(),
+        ) {
+            let points: Vec<LargeField> = points.into_iter().map(LargeField::from).collect();
+            let polynomials: Vec<Polynomial<LargeField>> = coefficients.iter().map(|coefficients| Polynomial::new(&coefficients[0..degree])).collect();
+            let evaluations: Vec<Vec<LargeField>> = polynomials.iter().map(|poly| points.iter().map(|point| poly.evaluate(point)).collect()).collect();
+
+            let (status, recovered) = check_if_all_points_lie_on_degree_x_polynomial(points.clone(), evaluations.clone(), degree);
+            prop_assert!(status);
+            prop_assert_eq!(recovered.unwrap(), polynomials);
+
+            // A single evaluation off the polynomial fails the whole batch
+            let mut corrupted = evaluations;
+            let index = degree + seed % extra;
+            corrupted[1][index] = corrupted[1][index].clone() + LargeField::one();
+            let (status, recovered) = check_if_all_points_lie_on_degree_x_polynomial(points, corrupted, degree);
+            prop_assert!(!status);
+            prop_assert!(recovered.is_none());
+        }
+
+        #[test]
+        fn prop_points_on_higher_degree_polynomials_are_rejected(
+            degree in 1usize..8,
+            coefficients in field_elements(8),
+            leading in 1u64..,
+        ) {
+            // Degree exactly `degree`, one above what the check allows
+            let mut coefficients = coefficients[0..degree].to_vec();
+            coefficients.push(LargeField::from(leading));
+            let polynomial = Polynomial::new(&coefficients);
+            let points: Vec<LargeField> = (1..(degree + 2) as u64 + 1).map(LargeField::from).collect();
+            let evaluations: Vec<LargeField> = points.iter().map(|point| polynomial.evaluate(point)).collect();
+            let (status, _) = check_if_all_points_lie_on_degree_x_polynomial(points, vec![evaluations], degree);
+            prop_assert!(!status);
+        }
+    }
+}
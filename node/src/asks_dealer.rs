@@ -0,0 +1,52 @@
+use asks::{ASKSOutput, ASKSService};
+use config::Node;
+use tokio::sync::oneshot;
+
+/// Runs standalone ASKS dealings. Every node deals `num_dealings` public dealings of `num_secrets` random secrets each.
+/// Terminated dealings are reconstructed to the client registered in the config if private output delivery is enabled, and to the committee otherwise.
+pub fn spawn_asks(config: Node, num_dealings: usize, num_secrets: usize) -> anyhow::Result<oneshot::Sender<()>> {
+    let to_client = consensus::client_output_key().is_some();
+    let (mut asks, asks_status) = ASKSService::spawn(config, false);
+    let asks_exit = asks_status?;
+    let (exit_tx, mut exit_rx) = oneshot::channel();
+    tokio::spawn(async move {
+        for id in 0..num_dealings {
+            if let Err(e) = asks.share(id, num_secrets, None, true).await {
+                log::error!("Unable to start ASKS dealing {}: {}", id, e);
+                return;
+            }
+        }
+        loop {
+            tokio::select! {
+                _exit = &mut exit_rx => {
+                    log::debug!("Termination signal received, stopping ASKS dealings");
+                    break;
+                },
+                output = asks.next_output() => {
+                    match output {
+                        Some(ASKSOutput::Shared { id, dealer }) => {
+                            let status = if to_client {
+                                asks.reconstruct_to_client(id, dealer, None).await
+                            } else {
+                                asks.reconstruct(id, dealer, None, None).await
+                            };
+                            if let Err(e) = status {
+                                log::error!("Unable to reconstruct ASKS dealing {} of party {}: {}", id, dealer, e);
+                                break;
+                            }
+                        },
+                        Some(ASKSOutput::Reconstructed { id, dealer, results }) => {
+                            log::info!("Reconstructed {} secrets of ASKS dealing {} of party {}", results.len(), id, dealer);
+                        },
+                        None => {
+                            log::error!("ASKS service has shut down");
+                            break;
+                        }
+                    }
+                },
+            }
+        }
+        let _ = asks_exit.send(());
+    });
+    Ok(exit_tx)
+}
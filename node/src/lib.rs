@@ -2,4 +2,7 @@ pub mod syncer;
 pub use syncer::*;
 
 pub mod sync_handler;
-pub use sync_handler::*;
\ No newline at end of file
+pub use sync_handler::*;
+
+pub mod asks_dealer;
+pub use asks_dealer::*;
@@ -2,12 +2,14 @@ use anyhow::{anyhow, Result};
 use clap::{load_yaml, App};
 use config::Node;
 use fnv::FnvHashMap;
-use node::Syncer;
+use node::{spawn_asks, Syncer};
 use signal_hook::{
     consts::{SIGINT, SIGTERM},
     iterator::Signals,
 };
-use std::{net::{SocketAddr, SocketAddrV4}};
+use crypto::aes_hash::HashState;
+use std::{collections::HashMap, net::{SocketAddr, SocketAddrV4}};
+use types::Replica;
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -45,6 +47,43 @@ async fn main() -> Result<()> {
         .value_of("ibft")
         .expect("Unable to parse per ibft")
         .parse::<bool>().unwrap();
+    let use_fft = match m.value_of("fft") {
+        Some(fft) => fft.parse::<bool>().expect("Unable to parse fft"),
+        None => false,
+    };
+    if let Some(gc) = m.value_of("gc") {
+        let retention = gc.parse::<u64>().expect("Unable to parse gc retention window");
+        consensus::set_gc_retention(std::time::Duration::from_millis(retention));
+    }
+    let ingress_queue = match m.value_of("queue") {
+        Some(q) => q.parse::<usize>().expect("Unable to parse ingress queue capacity"),
+        None => consensus::DEFAULT_INGRESS_PER_SENDER_CAPACITY,
+    };
+    let service_queue = match m.value_of("service_queue") {
+        Some(q) => q.parse::<usize>().expect("Unable to parse service queue capacity"),
+        None => consensus::DEFAULT_SERVICE_CHANNEL_CAPACITY,
+    };
+    consensus::set_channel_capacities(ingress_queue, service_queue);
+    if let Some(chunk) = m.value_of("chunk") {
+        let chunk_size = chunk.parse::<usize>().expect("Unable to parse dealing chunk size");
+        consensus::set_dealing_chunk_size(chunk_size);
+    }
+    if let Some(avid_bytes) = m.value_of("avid_bytes") {
+        let bytes = avid_bytes.parse::<usize>().expect("Unable to parse AVID in-flight byte budget");
+        consensus::set_avid_outstanding_bytes(bytes);
+    }
+    if let Some(rbc) = m.value_of("rbc") {
+        let backend = rbc.parse::<consensus::RBCBackend>().expect("Unable to parse reliable broadcast backend");
+        consensus::set_rbc_backend(backend);
+    }
+    if let Some(dzk) = m.value_of("dzk") {
+        let scheme = dzk.parse::<consensus::DZKScheme>().expect("Unable to parse DZK scheme");
+        consensus::set_dzk_scheme(scheme);
+    }
+    if let Some(dzk_batch) = m.value_of("dzk_batch") {
+        let batch_size = dzk_batch.parse::<usize>().expect("Unable to parse DZK batch size");
+        consensus::set_dzk_batch_size(batch_size);
+    }
 
     // let broadcast_msgs_file = m
     //     .value_of("bfile")
@@ -83,6 +122,34 @@ async fn main() -> Result<()> {
         log::info!("Logging the file f {}", f_str);
         config.update_config(util::io::file_to_ips(f.to_string()));
     }
+    let session_nonce = m
+        .value_of("session")
+        .expect("A fresh session nonce is required for every run")
+        .parse::<u64>()
+        .expect("Unable to parse session nonce");
+    let session_id = consensus::session_id_from_config(&config, session_nonce);
+    consensus::set_session_id(session_id);
+    log::info!("Running session {}", session_id);
+    if m.value_of("auth") == Some("signature") {
+        let keys = consensus::SignatureKeys::from_config(&config)
+            .map_err(|e| anyhow!("Unable to load signature keys: {}", e))?;
+        consensus::enable_signatures(keys);
+        log::info!("Authenticating messages with Ed25519 signatures");
+    }
+    if let Some(dir) = m.value_of("matrices") {
+        let loaded = consensus::load_precomputed_matrices::<consensus::FieldType>(std::path::Path::new(dir), config.num_nodes)
+            .map_err(|e| anyhow!("Unable to load precomputed matrices: {}", e))?;
+        log::info!("Loaded {} precomputed matrices from {}", loaded, dir);
+    }
+    let client_key = m.value_of("client_key");
+    if let Some(key_file) = client_key {
+        if vss_type != "sync" {
+            let key_bytes = std::fs::read(key_file).expect("Unable to read client key file");
+            let key: Vec<u8> = bincode::deserialize(&key_bytes).expect("Unable to parse client key file");
+            consensus::enable_client_output(key);
+            log::info!("Delivering outputs privately to the client");
+        }
+    }
     let config = config;
     // Start the Reliable Broadcast protocol
     let exit_tx;
@@ -94,6 +161,7 @@ async fn main() -> Result<()> {
                     per_batch, 
                     opt_pess,
                     lin_quad,
+                    use_fft,
                     ibft,
                     false
                 ).unwrap();
@@ -109,6 +177,9 @@ async fn main() -> Result<()> {
                     false
                 ).unwrap();
         },
+        "asks" => {
+            exit_tx = spawn_asks(config, batches, per_batch).unwrap();
+        },
         "sync" => {
             let f_str = syncer_file.to_string();
             log::info!("Logging the file f {}", f_str);
@@ -121,7 +192,20 @@ async fn main() -> Result<()> {
             }
             //let client_addr = net_map.get(&(net_map.len()-1)).unwrap();
             //exit_tx = Syncer::spawn(net_map, config.client_addr.clone(),broadcast_msgs_file.to_string()).unwrap();
-            exit_tx = Syncer::spawn(net_map, config.client_addr.clone()).unwrap();
+            // The syncer acts as the client of private output delivery
+            let client = client_key.map(|key_file| {
+                let key_bytes = std::fs::read(key_file).expect("Unable to read client key file");
+                let keys: HashMap<Replica, Vec<u8>> = bincode::deserialize(&key_bytes).expect("Unable to parse client key file");
+                // Keyed AES ciphers of the commitments
+                let hash_context = HashState::new([5u8; 16], [29u8; 16], [23u8; 16]);
+                let mut client = consensus::ClientReconstructor::new(config.num_nodes, config.num_faults, keys, hash_context);
+                // DPSS shares at the roots of unity under --fft, ASKS always shares at 1..n
+                if use_fft {
+                    client.set_evaluation_points("dpss", consensus::roots_of_unity::<consensus::FieldType>(config.num_nodes));
+                }
+                client
+            });
+            exit_tx = Syncer::spawn(net_map, config.client_addr.clone(), client).unwrap();
         }
         _ => {
             log::error!(
@@ -1,11 +1,12 @@
 use std::{collections::{HashSet, HashMap}, net::{SocketAddr,SocketAddrV4}, time::{SystemTime, UNIX_EPOCH, Duration}};
 
 use anyhow::{Result, anyhow};
+use consensus::{ClientOutputMsg, ClientReconstructor, listen_for_client_output, service_channel};
 use fnv::FnvHashMap;
 use network::{plaintcp::{TcpReceiver, TcpReliableSender, CancelHandler}, Acknowledgement};
 use serde::{Deserialize, Serialize};
-use tokio::{sync::{oneshot, mpsc::{unbounded_channel, UnboundedReceiver}}, time};
-use types::{Replica, SyncMsg, SyncState};
+use tokio::{sync::{oneshot, mpsc::{unbounded_channel, Receiver, UnboundedReceiver}}, time};
+use types::{Replica, SyncMsg, SyncState, WrapperMsg};
 //use std::fs::read_to_string;
 
 use crate::SyncHandler;
@@ -32,6 +33,10 @@ pub struct Syncer{
     
     pub rx_net: UnboundedReceiver<SyncMsg>,
     pub net_send: TcpReliableSender<Replica,SyncMsg,Acknowledgement>,
+
+    /// Reconstructs outputs delivered privately by the nodes, if enabled
+    pub client: Option<ClientReconstructor>,
+    pub rx_client_output: Receiver<WrapperMsg<ClientOutputMsg>>,
     
     exit_rx: oneshot::Receiver<()>,
     /// Cancel Handlers
@@ -42,6 +47,7 @@ impl Syncer{
     pub fn spawn(
         net_map: FnvHashMap<Replica,String>,
         cli_addr:SocketAddr,
+        client: Option<ClientReconstructor>,
         //filename: String
     )-> anyhow::Result<oneshot::Sender<()>>{
         let (exit_tx, exit_rx) = oneshot::channel();
@@ -52,6 +58,12 @@ impl Syncer{
             std::net::SocketAddr::V4(new_sock_address),
             SyncHandler::new(tx_net_to_server),
         );
+        // The branch reading outputs is disabled without a client, the receiver is only a placeholder then
+        let rx_client_output = if client.is_some() {
+            listen_for_client_output(&cli_addr)
+        } else {
+            service_channel().1
+        };
         //let broadcast_msgs = read_lines(&filename);
         let mut server_addrs :FnvHashMap<Replica,SocketAddr>= FnvHashMap::default();
         for (replica,address) in net_map.iter(){
@@ -81,6 +93,8 @@ impl Syncer{
                 cli_addr:cli_addr,
                 rx_net:rx_net_to_server,
                 net_send:net_send,
+                client:client,
+                rx_client_output:rx_client_output,
                 exit_rx:exit_rx,
                 cancel_handlers:Vec::new()
             };
@@ -162,6 +176,16 @@ impl Syncer{
                         _=>{}
                     }
                 },
+                client_msg = self.rx_client_output.recv(), if self.client.is_some() => {
+                    let client_msg = client_msg.ok_or_else(||
+                        anyhow!("Networking layer has closed")
+                    )?;
+                    if let Some(output) = self.client.as_mut().unwrap().process_msg(client_msg){
+                        let failed = output.results.iter().filter(|result| result.secret.is_none()).count();
+                        let faulty: HashSet<Replica> = output.results.iter().flat_map(|result| result.faulty_parties.iter().cloned()).collect();
+                        log::info!("Client reconstructed {} secrets of {} instance {}, {} failed, faulty parties {:?}", output.results.len(), output.protocol, output.instance, failed, faulty);
+                    }
+                },
                 _ = interval.tick() => {
                     if self.ready_for_broadcast{
                         // Initiate new broadcast
@@ -7,6 +7,10 @@ vals=(27000 27100 27200 27300)
 #rand=$(gshuf -i 1000-150000000 -n 1)
 TESTDIR=${TESTDIR:="testdata/hyb_16"}
 TYPE=${TYPE:="release"}
+# Fresh session identifier so that messages of earlier runs are rejected
+SESSION=${SESSION:=$(date +%s)}
+# Share at roots of unity with FFTs instead of Vandermonde matrices
+FFT=${FFT:="false"}
 
 # Run the syncer now
 ./target/$TYPE/node \
@@ -18,7 +22,9 @@ TYPE=${TYPE:="release"}
     --per $3 \
     --lin $4 \
     --opt $5 \
-    --ibft $6 > logs/syncer.log &
+    --ibft $6 \
+    --fft $FFT \
+    --session $SESSION > logs/syncer.log &
 
 for((i=0;i<16;i++)); do
 ./target/$TYPE/node \
@@ -30,7 +36,9 @@ for((i=0;i<16;i++)); do
     --per $3 \
     --lin $4 \
     --opt $5 \
-    --ibft $6 > logs/$i.log &
+    --ibft $6 \
+    --fft $FFT \
+    --session $SESSION > logs/$i.log &
 done
 
 # Kill all nodes sudo lsof -ti:7000-7015 | xargs kill -9
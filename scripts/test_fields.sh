@@ -0,0 +1,18 @@
+# Builds and tests the protocols over every supported field.
+# Protocols that derive challenges from 32-byte hashes must also run over Goldilocks, whose elements are 8 bytes.
+
+set -e
+CRATES=${CRATES:="consensus consensus/hacss consensus/acss_bv consensus/acss_ske consensus/dpss consensus/g_dpss consensus/asks"}
+
+for FIELD in default bls12_381 goldilocks; do
+    if [ "$FIELD" = "default" ]; then
+        FEATURES=""
+    else
+        FEATURES="--features $FIELD"
+    fi
+    for CRATE in $CRATES; do
+        echo "Testing $CRATE over the $FIELD field"
+        cargo test --manifest-path $CRATE/Cargo.toml $FEATURES
+    done
+    cargo build --manifest-path node/Cargo.toml $FEATURES
+done
@@ -6,6 +6,7 @@ use clap::{load_yaml, App};
 use rand::Rng;
 use types::Replica;
 use crypto::{Algorithm, SecretKey};
+use ed25519_dalek::SigningKey;
 use std::{error::Error, io::{BufWriter, Write}, fs::File};
 use util::io::*;
 use fnv::FnvHashMap as HashMap;
@@ -38,10 +39,12 @@ fn main() -> Result<(), Box<dyn Error>> {
         .expect("no client_base_port specified")
         .parse::<u16>()
         .expect("unable to parse client_base_port into an integer");
-    let t:Algorithm = m.value_of("algorithm")
+    let t:Algorithm = m.value_of("crypto")
         .unwrap_or("NOPKI")
         .parse::<Algorithm>()
         .unwrap_or(Algorithm::NOPKI);
+    // Ed25519 keys are generated in addition to the pairwise MAC keys
+    let sign = m.value_of("crypto") == Some("ED25519");
     let out = m.value_of("out_type")
         .unwrap_or("json");
     let target = m.value_of("target")
@@ -54,6 +57,8 @@ fn main() -> Result<(), Box<dyn Error>> {
         .unwrap_or("false")
         .parse()
         .unwrap();
+    // Keys shared by each node with the client, for private output delivery
+    let client_output = m.value_of("client_output") == Some("true");
     let c_rport:u16 = m.value_of("client_run_port")
         .expect("Client port expected")
         .parse::<u16>()
@@ -66,7 +71,7 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     let mut node:Vec<Node> = Vec::with_capacity(num_nodes);
 
-    let pk = HashMap::default();
+    let mut pk = HashMap::default();
     let mut ip = HashMap::default();
     
     //let (cert, privkey) = new_root_cert()?;
@@ -87,6 +92,15 @@ fn main() -> Result<(), Box<dyn Error>> {
             }
         }
     }
+    let mut sig_keys:Vec<Vec<u8>> = Vec::with_capacity(num_nodes);
+    if sign{
+        for i in 0..num_nodes{
+            let seed:[u8;32] = rand::thread_rng().gen();
+            let signing_key = SigningKey::from_bytes(&seed);
+            pk.insert(i as Replica, signing_key.verifying_key().to_bytes().to_vec());
+            sig_keys.push(seed.to_vec());
+        }
+    }
     for i in 0..num_nodes {
         node.push(Node::new());
 
@@ -108,6 +122,9 @@ fn main() -> Result<(), Box<dyn Error>> {
                 }
             }
         };
+        if sign{
+            node[i].secret_key_bytes = sig_keys[i].clone();
+        }
         ip.insert(i as Replica, 
         format!("{}:{}", "127.0.0.1", base_port+(i as u16))
         );
@@ -152,6 +169,15 @@ fn main() -> Result<(), Box<dyn Error>> {
         }
         //write_json(filename, &ip.clone());
     }
+    if client_output{
+        let mut client_keys = HashMap::default();
+        for i in 0..num_nodes{
+            let key = SecretKey::new().to_vec();
+            write_bin(format!("{}/client_output-{}.key",target,i), &key);
+            client_keys.insert(i as Replica, key);
+        }
+        write_bin(format!("{}/client_output.keys",target), &client_keys);
+    }
     let filename = format!("{}/syncer.json",target);
     write_json(filename, &client.net_map.clone());
     client.server_pk = pk;